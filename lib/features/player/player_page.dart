@@ -57,6 +57,7 @@ class _PlayerPageState extends State<PlayerPage> {
   StreamSubscription<String>? _playerErrorSub;
   StreamSubscription<bool>? _completedSub;
   StreamSubscription<ProxyAggregateStats>? _proxyStatsSub;
+  Timer? _positionReportTimer;
   String? _proxySessionId;
   Future<Map<String, String>?>? _pendingProxyAuthRecovery;
   bool _isRecoveringMediaKitAuth = false;
@@ -107,6 +108,7 @@ class _PlayerPageState extends State<PlayerPage> {
     _playerLogSub?.cancel();
     _playerErrorSub?.cancel();
     _proxyStatsSub?.cancel();
+    _positionReportTimer?.cancel();
     final sessionId = _proxySessionId;
     if (sessionId != null) {
       unawaited(ProxyController.instance.closeSession(sessionId));
@@ -187,6 +189,15 @@ class _PlayerPageState extends State<PlayerPage> {
       if (!_isAuthRejectedHttpMessage(message)) return;
       unawaited(_handleMediaKitAuthRejected(message));
     });
+    // The proxy prefetches ahead of what the player reports.
+    _positionReportTimer = Timer.periodic(const Duration(seconds: 1), (_) {
+      if (_proxySessionId == null || !player.state.playing) return;
+      ProxyController.instance.reportPlaybackPosition(
+        position: player.state.position,
+        bufferEnd: player.state.buffer,
+        rate: player.state.rate,
+      );
+    });
   }
 
   void _bindProxyStats() {
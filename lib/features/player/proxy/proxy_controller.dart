@@ -15,6 +15,8 @@ class ProxyController {
 
   static const int _chunkSize = 2 * 1024 * 1024;
   static const int _maxConcurrency = 8;
+  static const int _maxCacheBytes = 4 * 1024 * 1024 * 1024;
+  static const int _maxSessionCacheBytes = 2 * 1024 * 1024 * 1024;
 
   bool _engineReady = false;
   String? _activeSessionId;
@@ -44,6 +46,8 @@ class ProxyController {
         chunkSize: BigInt.from(_chunkSize),
         maxConcurrency: _maxConcurrency,
         cacheDir: proxyCache.path,
+        maxCacheBytes: BigInt.from(_maxCacheBytes),
+        maxSessionCacheBytes: BigInt.from(_maxSessionCacheBytes),
       ),
     );
     _engineReady = true;
@@ -151,6 +155,28 @@ class ProxyController {
   /// this always returns null — time-based seek from history takes over.
   int? getRestoredPosition(String sessionId) => null;
 
+  /// Tells the engine where playback of the active session is, so prefetch
+  /// and eviction follow the player. Call about once a second.
+  void reportPlaybackPosition({
+    required Duration position,
+    required Duration bufferEnd,
+    required double rate,
+  }) {
+    final sid = _activeSessionId;
+    if (sid == null) return;
+    try {
+      rust.reportPlaybackPosition(
+        sessionId: sid,
+        positionMs: BigInt.from(position.inMilliseconds),
+        bufferEndMs: BigInt.from(bufferEnd.inMilliseconds),
+        rate: rate,
+      );
+    } catch (e) {
+      // Session may have been closed.
+      _log('report position ignored id=$sid error=$e');
+    }
+  }
+
   /// Called by the player when the Rust proxy encounters an auth rejection.
   /// Refreshes credentials and pushes them back to Rust.
   Future<Map<String, String>?> handleAuthRejected() async {
@@ -4,12 +4,15 @@
 // ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
 
 import '../config.dart';
+import '../engine/policy.dart';
+import '../error.dart';
 import '../frb_generated.dart';
+import '../source/registry.dart';
 import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
 
-// These functions are ignored because they are not marked as `pub`: `compute_session_id`
+// These functions are ignored because they are not marked as `pub`: `compute_session_id`, `engine_cache_manager`, `new`
 // These types are ignored because they are neither used by any `pub` functions nor (for structs and enums) marked `#[frb(unignore)]`: `Engine`
-// These function are ignored because they are on traits that is not defined in current crate (put an empty `#[frb]` on it to unignore): `clone`, `clone`, `fmt`, `fmt`, `from`
+// These function are ignored because they are on traits that is not defined in current crate (put an empty `#[frb]` on it to unignore): `clone`, `clone`, `clone`, `clone`, `clone`, `clone`, `clone`, `clone`, `clone`, `clone`, `fmt`, `fmt`, `fmt`, `fmt`, `fmt`, `fmt`, `fmt`, `fmt`, `fmt`, `fmt`, `from`, `from`, `from`, `from`, `from`, `from`, `from`
 
 /// Initialize the proxy engine with the given configuration.
 ///
@@ -19,8 +22,7 @@ void initEngine({required EngineConfig config}) =>
 
 /// Create a new proxy session for the given source URL.
 ///
-/// Returns an existing session if one already exists with the same session ID.
-/// Otherwise clears previous sessions and creates a new one.
+/// Shorthand for [`create_session_with_source`] with an HTTP(S) descriptor.
 SessionInfo createSession({
   required String url,
   required Map<String, String> headers,
@@ -31,6 +33,26 @@ SessionInfo createSession({
   fileKey: fileKey,
 );
 
+/// Create a new proxy session for any source the registry can resolve.
+///
+/// `title` is stored with the cache so the settings page can name it.
+/// `cache_backend` overrides [`EngineConfig::cache_backend`] for this session,
+/// e.g. `Memory` for short clips.
+/// Returns an existing session if one already exists with the same session ID.
+/// Otherwise clears previous sessions and creates a new one.
+/// Failures read `<kind code>: <detail>`, as in [`SessionError`].
+SessionInfo createSessionWithSource({
+  required SourceDescriptor source,
+  required String fileKey,
+  String? title,
+  CacheBackend? cacheBackend,
+}) => RustLib.instance.api.crateApiProxyApiCreateSessionWithSource(
+  source: source,
+  fileKey: fileKey,
+  title: title,
+  cacheBackend: cacheBackend,
+);
+
 /// Close an existing proxy session and remove it from the map.
 void closeSession({required String sessionId}) =>
     RustLib.instance.api.crateApiProxyApiCloseSession(sessionId: sessionId);
@@ -53,9 +75,328 @@ void updateSessionAuth({
   newHeaders: newHeaders,
 );
 
+/// Return the session's most recent failure, cleared by
+/// `update_session_auth`. `AuthExpired` means fresh credentials are needed.
+SessionError? getSessionError({required String sessionId}) =>
+    RustLib.instance.api.crateApiProxyApiGetSessionError(sessionId: sessionId);
+
+/// Cap download bandwidth in bytes per second for one session, or for the
+/// whole engine when `session_id` is `None`. 0 removes the cap. Chunks the
+/// player is waiting on may borrow beyond it.
+void setBandwidthLimit({String? sessionId, required BigInt bytesPerSec}) =>
+    RustLib.instance.api.crateApiProxyApiSetBandwidthLimit(
+      sessionId: sessionId,
+      bytesPerSec: bytesPerSec,
+    );
+
+/// Report the player's position, buffered end and playback rate, about
+/// once a second. Prefetch, eviction and `buffered_bytes_ahead` follow it
+/// until reports stop for a few seconds.
+void reportPlaybackPosition({
+  required String sessionId,
+  required BigInt positionMs,
+  required BigInt bufferEndMs,
+  required double rate,
+}) => RustLib.instance.api.crateApiProxyApiReportPlaybackPosition(
+  sessionId: sessionId,
+  positionMs: positionMs,
+  bufferEndMs: bufferEndMs,
+  rate: rate,
+);
+
+/// Start fetching what a seek to `position_ms` will need, ahead of asking
+/// the player to seek. Returns the time of the keyframe decoding starts
+/// from, when the container has an index.
+BigInt? prepareSeek({required String sessionId, required BigInt positionMs}) =>
+    RustLib.instance.api.crateApiProxyApiPrepareSeek(
+      sessionId: sessionId,
+      positionMs: positionMs,
+    );
+
+/// Duration, bitrate and tracks of a session's file, parsed from its
+/// container headers before the player opens it, e.g. to warn about codecs
+/// the device cannot decode. MP4 and Matroska give full track details;
+/// MPEG-TS, AVI and FLV a subset. May wait on the network, so not `sync`.
+Future<MediaInfo> probeMediaInfo({required String sessionId}) =>
+    RustLib.instance.api.crateApiProxyApiProbeMediaInfo(sessionId: sessionId);
+
+/// Switch the engine-wide network policy, e.g. to `DataSaver` on cellular.
+/// Applies to running sessions from their next request. Returns the usage
+/// of the period that just ended.
+PolicyUsage setNetworkPolicy({required NetworkPolicy policy}) =>
+    RustLib.instance.api.crateApiProxyApiSetNetworkPolicy(policy: policy);
+
+/// Bytes downloaded since the current network policy was switched on.
+PolicyUsage getPolicyUsage() =>
+    RustLib.instance.api.crateApiProxyApiGetPolicyUsage();
+
+/// Cap requests per minute to a host (name or `host:port`) across all
+/// sessions, for drives that ban aggressive clients. 0 removes the cap.
+void setHostRequestBudget({
+  required String host,
+  required int requestsPerMinute,
+}) => RustLib.instance.api.crateApiProxyApiSetHostRequestBudget(
+  host: host,
+  requestsPerMinute: requestsPerMinute,
+);
+
+/// Play `magnet:` links through a TorrServer-compatible gateway at
+/// `gateway` (e.g. `http://127.0.0.1:8090`). An empty string turns magnet
+/// links off again; they are off until a gateway is set.
+void setMagnetGateway({required String gateway}) =>
+    RustLib.instance.api.crateApiProxyApiSetMagnetGateway(gateway: gateway);
+
+/// List persisted cache entries, most recently used first.
+List<CacheEntryInfo> listCacheEntries() =>
+    RustLib.instance.api.crateApiProxyApiListCacheEntries();
+
+/// Delete one cache entry. Fails if a live session is using it.
+void deleteCacheEntry({required String cacheKey}) =>
+    RustLib.instance.api.crateApiProxyApiDeleteCacheEntry(cacheKey: cacheKey);
+
+/// Delete all cache entries not used by a live session.
+///
+/// Pinned entries are kept unless `include_pinned` is set.
+/// Returns the number of entries removed.
+int clearCache({required bool includePinned}) =>
+    RustLib.instance.api.crateApiProxyApiClearCache(
+      includePinned: includePinned,
+    );
+
+/// Pin or unpin a cache entry against budget eviction.
+void pinCacheEntry({required String cacheKey, required bool pinned}) =>
+    RustLib.instance.api.crateApiProxyApiPinCacheEntry(
+      cacheKey: cacheKey,
+      pinned: pinned,
+    );
+
+/// Report total cache usage and free disk space.
+CacheUsage getCacheUsage() =>
+    RustLib.instance.api.crateApiProxyApiGetCacheUsage();
+
 /// Shut down the proxy engine and release all resources.
 void dispose() => RustLib.instance.api.crateApiProxyApiDispose();
 
+/// An audio track as described by the container.
+class AudioTrackInfo {
+  /// Short codec name: `aac`, `ac3`, `eac3`, `dts`, `truehd`, ...
+  final String codec;
+
+  /// 0 if unknown.
+  final int channels;
+
+  /// Hz; 0 if unknown.
+  final int sampleRate;
+
+  /// ISO 639-2 or BCP 47 code; empty if undetermined.
+  final String language;
+
+  final String title;
+
+  const AudioTrackInfo({
+    required this.codec,
+    required this.channels,
+    required this.sampleRate,
+    required this.language,
+    required this.title,
+  });
+
+  @override
+  int get hashCode =>
+      codec.hashCode ^
+      channels.hashCode ^
+      sampleRate.hashCode ^
+      language.hashCode ^
+      title.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is AudioTrackInfo &&
+          runtimeType == other.runtimeType &&
+          codec == other.codec &&
+          channels == other.channels &&
+          sampleRate == other.sampleRate &&
+          language == other.language &&
+          title == other.title;
+}
+
+/// A persisted cache file, as shown on the settings page.
+class CacheEntryInfo {
+  /// Cache key (the session ID the file belongs to).
+  final String cacheKey;
+
+  /// File key supplied when the session was created, if any.
+  final String fileKey;
+
+  /// Display title supplied when the session was created, if any.
+  final String title;
+
+  final BigInt sizeOnDisk;
+  final BigInt contentLength;
+
+  /// Downloaded share of the file, 0.0–100.0.
+  final double percentComplete;
+
+  /// Milliseconds since the Unix epoch.
+  final BigInt lastAccessAt;
+
+  final bool pinned;
+
+  /// Whether a live session is using this cache right now.
+  final bool active;
+
+  const CacheEntryInfo({
+    required this.cacheKey,
+    required this.fileKey,
+    required this.title,
+    required this.sizeOnDisk,
+    required this.contentLength,
+    required this.percentComplete,
+    required this.lastAccessAt,
+    required this.pinned,
+    required this.active,
+  });
+
+  @override
+  int get hashCode =>
+      cacheKey.hashCode ^
+      fileKey.hashCode ^
+      title.hashCode ^
+      sizeOnDisk.hashCode ^
+      contentLength.hashCode ^
+      percentComplete.hashCode ^
+      lastAccessAt.hashCode ^
+      pinned.hashCode ^
+      active.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is CacheEntryInfo &&
+          runtimeType == other.runtimeType &&
+          cacheKey == other.cacheKey &&
+          fileKey == other.fileKey &&
+          title == other.title &&
+          sizeOnDisk == other.sizeOnDisk &&
+          contentLength == other.contentLength &&
+          percentComplete == other.percentComplete &&
+          lastAccessAt == other.lastAccessAt &&
+          pinned == other.pinned &&
+          active == other.active;
+}
+
+/// Overall cache usage.
+class CacheUsage {
+  final BigInt totalBytes;
+  final int entryCount;
+
+  /// Configured engine budget; 0 means unlimited.
+  final BigInt maxCacheBytes;
+
+  /// Free space on the cache volume; 0 if the platform cannot report it.
+  final BigInt freeDiskBytes;
+
+  const CacheUsage({
+    required this.totalBytes,
+    required this.entryCount,
+    required this.maxCacheBytes,
+    required this.freeDiskBytes,
+  });
+
+  @override
+  int get hashCode =>
+      totalBytes.hashCode ^
+      entryCount.hashCode ^
+      maxCacheBytes.hashCode ^
+      freeDiskBytes.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is CacheUsage &&
+          runtimeType == other.runtimeType &&
+          totalBytes == other.totalBytes &&
+          entryCount == other.entryCount &&
+          maxCacheBytes == other.maxCacheBytes &&
+          freeDiskBytes == other.freeDiskBytes;
+}
+
+/// What a session's file contains, from its container headers.
+class MediaInfo {
+  /// Container name: `mp4`, `matroska`, `mpegts`, `avi`, `flv`, ...
+  final String container;
+
+  /// 0 if unknown.
+  final BigInt durationMs;
+
+  /// Average bits per second over the whole file; 0 if unknown.
+  final BigInt bitrate;
+
+  final List<VideoTrackInfo> videoTracks;
+  final List<AudioTrackInfo> audioTracks;
+  final List<SubtitleTrackInfo> subtitleTracks;
+
+  const MediaInfo({
+    required this.container,
+    required this.durationMs,
+    required this.bitrate,
+    required this.videoTracks,
+    required this.audioTracks,
+    required this.subtitleTracks,
+  });
+
+  @override
+  int get hashCode =>
+      container.hashCode ^
+      durationMs.hashCode ^
+      bitrate.hashCode ^
+      videoTracks.hashCode ^
+      audioTracks.hashCode ^
+      subtitleTracks.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is MediaInfo &&
+          runtimeType == other.runtimeType &&
+          container == other.container &&
+          durationMs == other.durationMs &&
+          bitrate == other.bitrate &&
+          videoTracks == other.videoTracks &&
+          audioTracks == other.audioTracks &&
+          subtitleTracks == other.subtitleTracks;
+}
+
+/// Bytes downloaded under one network policy.
+class PolicyUsage {
+  final NetworkPolicy policy;
+
+  /// Milliseconds since the Unix epoch when the policy was switched on.
+  final BigInt startedAt;
+
+  final BigInt downloadedBytes;
+
+  const PolicyUsage({
+    required this.policy,
+    required this.startedAt,
+    required this.downloadedBytes,
+  });
+
+  @override
+  int get hashCode =>
+      policy.hashCode ^ startedAt.hashCode ^ downloadedBytes.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is PolicyUsage &&
+          runtimeType == other.runtimeType &&
+          policy == other.policy &&
+          startedAt == other.startedAt &&
+          downloadedBytes == other.downloadedBytes;
+}
+
 /// Live statistics for a proxy session (or aggregated across all sessions).
 class ProxyStats {
   final BigInt downloadBps;
@@ -64,12 +405,17 @@ class ProxyStats {
   final int activeWorkers;
   final double cacheHitRate;
 
+  /// Playback bitrate in bits per second from container metadata or
+  /// observed progress; 0 while unknown.
+  final BigInt playbackBitrate;
+
   const ProxyStats({
     required this.downloadBps,
     required this.serveBps,
     required this.bufferedBytesAhead,
     required this.activeWorkers,
     required this.cacheHitRate,
+    required this.playbackBitrate,
   });
 
   @override
@@ -78,7 +424,8 @@ class ProxyStats {
       serveBps.hashCode ^
       bufferedBytesAhead.hashCode ^
       activeWorkers.hashCode ^
-      cacheHitRate.hashCode;
+      cacheHitRate.hashCode ^
+      playbackBitrate.hashCode;
 
   @override
   bool operator ==(Object other) =>
@@ -89,7 +436,29 @@ class ProxyStats {
           serveBps == other.serveBps &&
           bufferedBytesAhead == other.bufferedBytesAhead &&
           activeWorkers == other.activeWorkers &&
-          cacheHitRate == other.cacheHitRate;
+          cacheHitRate == other.cacheHitRate &&
+          playbackBitrate == other.playbackBitrate;
+}
+
+/// The most recent failure of a session, for the player UI.
+class SessionError {
+  final ProxyErrorKind kind;
+
+  /// Human-readable detail, starting with the kind's code.
+  final String message;
+
+  const SessionError({required this.kind, required this.message});
+
+  @override
+  int get hashCode => kind.hashCode ^ message.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is SessionError &&
+          runtimeType == other.runtimeType &&
+          kind == other.kind &&
+          message == other.message;
 }
 
 /// Information about an active proxy session.
@@ -123,3 +492,95 @@ class SessionInfo {
           contentLength == other.contentLength &&
           contentType == other.contentType;
 }
+
+/// A subtitle track as described by the container.
+class SubtitleTrackInfo {
+  /// Short codec name: `subrip`, `ass`, `pgs`, `mov_text`, ...
+  final String codec;
+
+  /// ISO 639-2 or BCP 47 code; empty if undetermined.
+  final String language;
+
+  final String title;
+  final bool forced;
+
+  const SubtitleTrackInfo({
+    required this.codec,
+    required this.language,
+    required this.title,
+    required this.forced,
+  });
+
+  @override
+  int get hashCode =>
+      codec.hashCode ^ language.hashCode ^ title.hashCode ^ forced.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is SubtitleTrackInfo &&
+          runtimeType == other.runtimeType &&
+          codec == other.codec &&
+          language == other.language &&
+          title == other.title &&
+          forced == other.forced;
+}
+
+/// A video track as described by the container.
+class VideoTrackInfo {
+  /// Short codec name: `h264`, `hevc`, `av1`, `vp9`, ...
+  final String codec;
+
+  /// e.g. `High`, `Main 10`; empty if unknown.
+  final String profile;
+
+  final int width;
+  final int height;
+
+  /// Frames per second; 0.0 if unknown.
+  final double frameRate;
+
+  /// Bits per sample; 0 if unknown.
+  final int bitDepth;
+
+  /// PQ (HDR10) or HLG transfer.
+  final bool hdr;
+
+  final bool dolbyVision;
+
+  const VideoTrackInfo({
+    required this.codec,
+    required this.profile,
+    required this.width,
+    required this.height,
+    required this.frameRate,
+    required this.bitDepth,
+    required this.hdr,
+    required this.dolbyVision,
+  });
+
+  @override
+  int get hashCode =>
+      codec.hashCode ^
+      profile.hashCode ^
+      width.hashCode ^
+      height.hashCode ^
+      frameRate.hashCode ^
+      bitDepth.hashCode ^
+      hdr.hashCode ^
+      dolbyVision.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is VideoTrackInfo &&
+          runtimeType == other.runtimeType &&
+          codec == other.codec &&
+          profile == other.profile &&
+          width == other.width &&
+          height == other.height &&
+          frameRate == other.frameRate &&
+          bitDepth == other.bitDepth &&
+          hdr == other.hdr &&
+          dolbyVision == other.dolbyVision;
+}
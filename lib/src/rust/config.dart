@@ -6,6 +6,19 @@
 import 'frb_generated.dart';
 import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
 
+/// Where a session keeps downloaded chunks.
+enum CacheBackend {
+  /// One sparse file mapped into memory.
+  mmap,
+
+  /// One file per chunk; no large mapping or sparse-file support needed.
+  chunkFiles,
+
+  /// RAM only, bounded and never persisted.
+  memory,
+  ;
+}
+
 /// Top-level configuration for the proxy engine.
 class EngineConfig {
   /// Size of each download chunk in bytes.
@@ -17,15 +30,33 @@ class EngineConfig {
   /// Directory used for on-disk cache files.
   final String cacheDir;
 
+  /// Upper bound for all cache files together; 0 means unlimited.
+  final BigInt maxCacheBytes;
+
+  /// Upper bound for a single session's cache file; 0 means unlimited.
+  final BigInt maxSessionCacheBytes;
+
+  /// Storage backend for session caches; `None` picks the platform
+  /// default ([`CacheBackend::default`]).
+  final CacheBackend? cacheBackend;
+
   const EngineConfig({
     required this.chunkSize,
     required this.maxConcurrency,
     required this.cacheDir,
+    required this.maxCacheBytes,
+    required this.maxSessionCacheBytes,
+    this.cacheBackend,
   });
 
   @override
   int get hashCode =>
-      chunkSize.hashCode ^ maxConcurrency.hashCode ^ cacheDir.hashCode;
+      chunkSize.hashCode ^
+      maxConcurrency.hashCode ^
+      cacheDir.hashCode ^
+      maxCacheBytes.hashCode ^
+      maxSessionCacheBytes.hashCode ^
+      cacheBackend.hashCode;
 
   @override
   bool operator ==(Object other) =>
@@ -34,5 +65,8 @@ class EngineConfig {
           runtimeType == other.runtimeType &&
           chunkSize == other.chunkSize &&
           maxConcurrency == other.maxConcurrency &&
-          cacheDir == other.cacheDir;
+          cacheDir == other.cacheDir &&
+          maxCacheBytes == other.maxCacheBytes &&
+          maxSessionCacheBytes == other.maxSessionCacheBytes &&
+          cacheBackend == other.cacheBackend;
 }
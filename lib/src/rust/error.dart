@@ -0,0 +1,38 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import 'frb_generated.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+
+/// What went wrong, coarse enough for a UI to act on.
+enum ProxyErrorKind {
+  /// Upstream rejected the credentials (HTTP 401/403/412); refresh them.
+  authExpired,
+
+  /// Upstream says the file does not exist (HTTP 404/410).
+  notFound,
+
+  /// Upstream is throttling us (HTTP 429).
+  rateLimited,
+
+  /// Upstream server error or another unexpected status.
+  upstream5xx,
+
+  /// Connection failed, timed out or was cut off.
+  network,
+
+  /// Reading or writing the local cache failed.
+  cacheIo,
+
+  /// The file changed upstream (validators, length or range no longer match).
+  sourceChanged,
+
+  /// The session or download was shut down.
+  cancelled,
+
+  /// A malformed request, unsupported input or an engine bug.
+  internal,
+  ;
+}
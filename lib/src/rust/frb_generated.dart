@@ -8,9 +8,12 @@ import 'api/simple.dart';
 import 'config.dart';
 import 'dart:async';
 import 'dart:convert';
+import 'engine/policy.dart';
+import 'error.dart';
 import 'frb_generated.dart';
 import 'frb_generated.io.dart'
     if (dart.library.js_interop) 'frb_generated.web.dart';
+import 'source/registry.dart';
 import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
 
 /// Main entrypoint of the Rust API
@@ -79,6 +82,8 @@ class RustLib extends BaseEntrypoint<RustLibApi, RustLibApiImpl, RustLibWire> {
 }
 
 abstract class RustLibApi extends BaseApi {
+  int crateApiProxyApiClearCache({required bool includePinned});
+
   void crateApiProxyApiCloseSession({required String sessionId});
 
   SessionInfo crateApiProxyApiCreateSession({
@@ -87,8 +92,23 @@ abstract class RustLibApi extends BaseApi {
     required String fileKey,
   });
 
+  SessionInfo crateApiProxyApiCreateSessionWithSource({
+    required SourceDescriptor source,
+    required String fileKey,
+    String? title,
+    CacheBackend? cacheBackend,
+  });
+
+  void crateApiProxyApiDeleteCacheEntry({required String cacheKey});
+
   void crateApiProxyApiDispose();
 
+  CacheUsage crateApiProxyApiGetCacheUsage();
+
+  PolicyUsage crateApiProxyApiGetPolicyUsage();
+
+  SessionError? crateApiProxyApiGetSessionError({required String sessionId});
+
   ProxyStats crateApiProxyApiGetStats({String? sessionId});
 
   String crateApiSimpleGreet({required String name});
@@ -97,6 +117,41 @@ abstract class RustLibApi extends BaseApi {
 
   void crateApiProxyApiInitEngine({required EngineConfig config});
 
+  List<CacheEntryInfo> crateApiProxyApiListCacheEntries();
+
+  void crateApiProxyApiPinCacheEntry({
+    required String cacheKey,
+    required bool pinned,
+  });
+
+  BigInt? crateApiProxyApiPrepareSeek({
+    required String sessionId,
+    required BigInt positionMs,
+  });
+
+  Future<MediaInfo> crateApiProxyApiProbeMediaInfo({required String sessionId});
+
+  void crateApiProxyApiReportPlaybackPosition({
+    required String sessionId,
+    required BigInt positionMs,
+    required BigInt bufferEndMs,
+    required double rate,
+  });
+
+  void crateApiProxyApiSetBandwidthLimit({
+    String? sessionId,
+    required BigInt bytesPerSec,
+  });
+
+  void crateApiProxyApiSetHostRequestBudget({
+    required String host,
+    required int requestsPerMinute,
+  });
+
+  void crateApiProxyApiSetMagnetGateway({required String gateway});
+
+  PolicyUsage crateApiProxyApiSetNetworkPolicy({required NetworkPolicy policy});
+
   void crateApiProxyApiUpdateSessionAuth({
     required String sessionId,
     required String newUrl,
@@ -112,6 +167,32 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     required super.portManager,
   });
 
+  @override
+  int crateApiProxyApiClearCache({required bool includePinned}) {
+    return handler.executeSync(
+      SyncTask(
+        callFfi: () {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_bool(includePinned, serializer);
+          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 1)!;
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_u_32,
+          decodeErrorData: sse_decode_AnyhowException,
+        ),
+        constMeta: kCrateApiProxyApiClearCacheConstMeta,
+        argValues: [includePinned],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyApiClearCacheConstMeta =>
+      const TaskConstMeta(
+        debugName: "clear_cache",
+        argNames: ["includePinned"],
+      );
+
   @override
   void crateApiProxyApiCloseSession({required String sessionId}) {
     return handler.executeSync(
@@ -119,7 +200,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         callFfi: () {
           final serializer = SseSerializer(generalizedFrbRustBinding);
           sse_encode_String(sessionId, serializer);
-          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 1)!;
+          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 2)!;
         },
         codec: SseCodec(
           decodeSuccessData: sse_decode_unit,
@@ -148,7 +229,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
           sse_encode_String(url, serializer);
           sse_encode_Map_String_String_None(headers, serializer);
           sse_encode_String(fileKey, serializer);
-          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 2)!;
+          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 3)!;
         },
         codec: SseCodec(
           decodeSuccessData: sse_decode_session_info,
@@ -167,13 +248,73 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         argNames: ["url", "headers", "fileKey"],
       );
 
+  @override
+  SessionInfo crateApiProxyApiCreateSessionWithSource({
+    required SourceDescriptor source,
+    required String fileKey,
+    String? title,
+    CacheBackend? cacheBackend,
+  }) {
+    return handler.executeSync(
+      SyncTask(
+        callFfi: () {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_box_autoadd_source_descriptor(source, serializer);
+          sse_encode_String(fileKey, serializer);
+          sse_encode_opt_String(title, serializer);
+          sse_encode_opt_box_autoadd_cache_backend(cacheBackend, serializer);
+          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 4)!;
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_session_info,
+          decodeErrorData: sse_decode_AnyhowException,
+        ),
+        constMeta: kCrateApiProxyApiCreateSessionWithSourceConstMeta,
+        argValues: [source, fileKey, title, cacheBackend],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyApiCreateSessionWithSourceConstMeta =>
+      const TaskConstMeta(
+        debugName: "create_session_with_source",
+        argNames: ["source", "fileKey", "title", "cacheBackend"],
+      );
+
+  @override
+  void crateApiProxyApiDeleteCacheEntry({required String cacheKey}) {
+    return handler.executeSync(
+      SyncTask(
+        callFfi: () {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_String(cacheKey, serializer);
+          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 5)!;
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_unit,
+          decodeErrorData: sse_decode_AnyhowException,
+        ),
+        constMeta: kCrateApiProxyApiDeleteCacheEntryConstMeta,
+        argValues: [cacheKey],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyApiDeleteCacheEntryConstMeta =>
+      const TaskConstMeta(
+        debugName: "delete_cache_entry",
+        argNames: ["cacheKey"],
+      );
+
   @override
   void crateApiProxyApiDispose() {
     return handler.executeSync(
       SyncTask(
         callFfi: () {
           final serializer = SseSerializer(generalizedFrbRustBinding);
-          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 3)!;
+          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 6)!;
         },
         codec: SseCodec(
           decodeSuccessData: sse_decode_unit,
@@ -189,6 +330,76 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
   TaskConstMeta get kCrateApiProxyApiDisposeConstMeta =>
       const TaskConstMeta(debugName: "dispose", argNames: []);
 
+  @override
+  CacheUsage crateApiProxyApiGetCacheUsage() {
+    return handler.executeSync(
+      SyncTask(
+        callFfi: () {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 7)!;
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_cache_usage,
+          decodeErrorData: sse_decode_AnyhowException,
+        ),
+        constMeta: kCrateApiProxyApiGetCacheUsageConstMeta,
+        argValues: [],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyApiGetCacheUsageConstMeta =>
+      const TaskConstMeta(debugName: "get_cache_usage", argNames: []);
+
+  @override
+  PolicyUsage crateApiProxyApiGetPolicyUsage() {
+    return handler.executeSync(
+      SyncTask(
+        callFfi: () {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 8)!;
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_policy_usage,
+          decodeErrorData: sse_decode_AnyhowException,
+        ),
+        constMeta: kCrateApiProxyApiGetPolicyUsageConstMeta,
+        argValues: [],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyApiGetPolicyUsageConstMeta =>
+      const TaskConstMeta(debugName: "get_policy_usage", argNames: []);
+
+  @override
+  SessionError? crateApiProxyApiGetSessionError({required String sessionId}) {
+    return handler.executeSync(
+      SyncTask(
+        callFfi: () {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_String(sessionId, serializer);
+          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 9)!;
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_opt_box_autoadd_session_error,
+          decodeErrorData: sse_decode_AnyhowException,
+        ),
+        constMeta: kCrateApiProxyApiGetSessionErrorConstMeta,
+        argValues: [sessionId],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyApiGetSessionErrorConstMeta =>
+      const TaskConstMeta(
+        debugName: "get_session_error",
+        argNames: ["sessionId"],
+      );
+
   @override
   ProxyStats crateApiProxyApiGetStats({String? sessionId}) {
     return handler.executeSync(
@@ -196,7 +407,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         callFfi: () {
           final serializer = SseSerializer(generalizedFrbRustBinding);
           sse_encode_opt_String(sessionId, serializer);
-          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 4)!;
+          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 10)!;
         },
         codec: SseCodec(
           decodeSuccessData: sse_decode_proxy_stats,
@@ -219,7 +430,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         callFfi: () {
           final serializer = SseSerializer(generalizedFrbRustBinding);
           sse_encode_String(name, serializer);
-          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 5)!;
+          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 11)!;
         },
         codec: SseCodec(
           decodeSuccessData: sse_decode_String,
@@ -244,7 +455,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
           pdeCallFfi(
             generalizedFrbRustBinding,
             serializer,
-            funcId: 6,
+            funcId: 12,
             port: port_,
           );
         },
@@ -269,7 +480,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         callFfi: () {
           final serializer = SseSerializer(generalizedFrbRustBinding);
           sse_encode_box_autoadd_engine_config(config, serializer);
-          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 7)!;
+          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 13)!;
         },
         codec: SseCodec(
           decodeSuccessData: sse_decode_unit,
@@ -286,89 +497,427 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
       const TaskConstMeta(debugName: "init_engine", argNames: ["config"]);
 
   @override
-  void crateApiProxyApiUpdateSessionAuth({
-    required String sessionId,
-    required String newUrl,
-    required Map<String, String> newHeaders,
+  List<CacheEntryInfo> crateApiProxyApiListCacheEntries() {
+    return handler.executeSync(
+      SyncTask(
+        callFfi: () {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 14)!;
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_list_cache_entry_info,
+          decodeErrorData: sse_decode_AnyhowException,
+        ),
+        constMeta: kCrateApiProxyApiListCacheEntriesConstMeta,
+        argValues: [],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyApiListCacheEntriesConstMeta =>
+      const TaskConstMeta(debugName: "list_cache_entries", argNames: []);
+
+  @override
+  void crateApiProxyApiPinCacheEntry({
+    required String cacheKey,
+    required bool pinned,
   }) {
     return handler.executeSync(
       SyncTask(
         callFfi: () {
           final serializer = SseSerializer(generalizedFrbRustBinding);
-          sse_encode_String(sessionId, serializer);
-          sse_encode_String(newUrl, serializer);
-          sse_encode_Map_String_String_None(newHeaders, serializer);
-          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 8)!;
+          sse_encode_String(cacheKey, serializer);
+          sse_encode_bool(pinned, serializer);
+          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 15)!;
         },
         codec: SseCodec(
           decodeSuccessData: sse_decode_unit,
           decodeErrorData: sse_decode_AnyhowException,
         ),
-        constMeta: kCrateApiProxyApiUpdateSessionAuthConstMeta,
-        argValues: [sessionId, newUrl, newHeaders],
+        constMeta: kCrateApiProxyApiPinCacheEntryConstMeta,
+        argValues: [cacheKey, pinned],
         apiImpl: this,
       ),
     );
   }
 
-  TaskConstMeta get kCrateApiProxyApiUpdateSessionAuthConstMeta =>
+  TaskConstMeta get kCrateApiProxyApiPinCacheEntryConstMeta =>
       const TaskConstMeta(
-        debugName: "update_session_auth",
-        argNames: ["sessionId", "newUrl", "newHeaders"],
+        debugName: "pin_cache_entry",
+        argNames: ["cacheKey", "pinned"],
       );
 
-  @protected
-  AnyhowException dco_decode_AnyhowException(dynamic raw) {
-    // Codec=Dco (DartCObject based), see doc to use other codecs
-    return AnyhowException(raw as String);
-  }
-
-  @protected
-  Map<String, String> dco_decode_Map_String_String_None(dynamic raw) {
-    // Codec=Dco (DartCObject based), see doc to use other codecs
-    return Map.fromEntries(
-      dco_decode_list_record_string_string(
-        raw,
-      ).map((e) => MapEntry(e.$1, e.$2)),
+  @override
+  BigInt? crateApiProxyApiPrepareSeek({
+    required String sessionId,
+    required BigInt positionMs,
+  }) {
+    return handler.executeSync(
+      SyncTask(
+        callFfi: () {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_String(sessionId, serializer);
+          sse_encode_u_64(positionMs, serializer);
+          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 16)!;
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_opt_box_autoadd_u_64,
+          decodeErrorData: sse_decode_AnyhowException,
+        ),
+        constMeta: kCrateApiProxyApiPrepareSeekConstMeta,
+        argValues: [sessionId, positionMs],
+        apiImpl: this,
+      ),
     );
   }
 
-  @protected
-  String dco_decode_String(dynamic raw) {
-    // Codec=Dco (DartCObject based), see doc to use other codecs
-    return raw as String;
-  }
-
-  @protected
-  EngineConfig dco_decode_box_autoadd_engine_config(dynamic raw) {
-    // Codec=Dco (DartCObject based), see doc to use other codecs
-    return dco_decode_engine_config(raw);
-  }
+  TaskConstMeta get kCrateApiProxyApiPrepareSeekConstMeta =>
+      const TaskConstMeta(
+        debugName: "prepare_seek",
+        argNames: ["sessionId", "positionMs"],
+      );
 
-  @protected
-  EngineConfig dco_decode_engine_config(dynamic raw) {
-    // Codec=Dco (DartCObject based), see doc to use other codecs
-    final arr = raw as List<dynamic>;
-    if (arr.length != 3)
-      throw Exception('unexpected arr length: expect 3 but see ${arr.length}');
-    return EngineConfig(
-      chunkSize: dco_decode_u_64(arr[0]),
-      maxConcurrency: dco_decode_u_32(arr[1]),
-      cacheDir: dco_decode_String(arr[2]),
+  @override
+  Future<MediaInfo> crateApiProxyApiProbeMediaInfo({
+    required String sessionId,
+  }) {
+    return handler.executeNormal(
+      NormalTask(
+        callFfi: (port_) {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_String(sessionId, serializer);
+          pdeCallFfi(
+            generalizedFrbRustBinding,
+            serializer,
+            funcId: 17,
+            port: port_,
+          );
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_media_info,
+          decodeErrorData: sse_decode_AnyhowException,
+        ),
+        constMeta: kCrateApiProxyApiProbeMediaInfoConstMeta,
+        argValues: [sessionId],
+        apiImpl: this,
+      ),
     );
   }
 
-  @protected
-  double dco_decode_f_64(dynamic raw) {
-    // Codec=Dco (DartCObject based), see doc to use other codecs
-    return raw as double;
-  }
-
-  @protected
-  Uint8List dco_decode_list_prim_u_8_strict(dynamic raw) {
-    // Codec=Dco (DartCObject based), see doc to use other codecs
-    return raw as Uint8List;
-  }
+  TaskConstMeta get kCrateApiProxyApiProbeMediaInfoConstMeta =>
+      const TaskConstMeta(
+        debugName: "probe_media_info",
+        argNames: ["sessionId"],
+      );
+
+  @override
+  void crateApiProxyApiReportPlaybackPosition({
+    required String sessionId,
+    required BigInt positionMs,
+    required BigInt bufferEndMs,
+    required double rate,
+  }) {
+    return handler.executeSync(
+      SyncTask(
+        callFfi: () {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_String(sessionId, serializer);
+          sse_encode_u_64(positionMs, serializer);
+          sse_encode_u_64(bufferEndMs, serializer);
+          sse_encode_f_64(rate, serializer);
+          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 18)!;
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_unit,
+          decodeErrorData: sse_decode_AnyhowException,
+        ),
+        constMeta: kCrateApiProxyApiReportPlaybackPositionConstMeta,
+        argValues: [sessionId, positionMs, bufferEndMs, rate],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyApiReportPlaybackPositionConstMeta =>
+      const TaskConstMeta(
+        debugName: "report_playback_position",
+        argNames: ["sessionId", "positionMs", "bufferEndMs", "rate"],
+      );
+
+  @override
+  void crateApiProxyApiSetBandwidthLimit({
+    String? sessionId,
+    required BigInt bytesPerSec,
+  }) {
+    return handler.executeSync(
+      SyncTask(
+        callFfi: () {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_opt_String(sessionId, serializer);
+          sse_encode_u_64(bytesPerSec, serializer);
+          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 19)!;
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_unit,
+          decodeErrorData: sse_decode_AnyhowException,
+        ),
+        constMeta: kCrateApiProxyApiSetBandwidthLimitConstMeta,
+        argValues: [sessionId, bytesPerSec],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyApiSetBandwidthLimitConstMeta =>
+      const TaskConstMeta(
+        debugName: "set_bandwidth_limit",
+        argNames: ["sessionId", "bytesPerSec"],
+      );
+
+  @override
+  void crateApiProxyApiSetHostRequestBudget({
+    required String host,
+    required int requestsPerMinute,
+  }) {
+    return handler.executeSync(
+      SyncTask(
+        callFfi: () {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_String(host, serializer);
+          sse_encode_u_32(requestsPerMinute, serializer);
+          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 20)!;
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_unit,
+          decodeErrorData: sse_decode_AnyhowException,
+        ),
+        constMeta: kCrateApiProxyApiSetHostRequestBudgetConstMeta,
+        argValues: [host, requestsPerMinute],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyApiSetHostRequestBudgetConstMeta =>
+      const TaskConstMeta(
+        debugName: "set_host_request_budget",
+        argNames: ["host", "requestsPerMinute"],
+      );
+
+  @override
+  void crateApiProxyApiSetMagnetGateway({required String gateway}) {
+    return handler.executeSync(
+      SyncTask(
+        callFfi: () {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_String(gateway, serializer);
+          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 21)!;
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_unit,
+          decodeErrorData: sse_decode_AnyhowException,
+        ),
+        constMeta: kCrateApiProxyApiSetMagnetGatewayConstMeta,
+        argValues: [gateway],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyApiSetMagnetGatewayConstMeta =>
+      const TaskConstMeta(
+        debugName: "set_magnet_gateway",
+        argNames: ["gateway"],
+      );
+
+  @override
+  PolicyUsage crateApiProxyApiSetNetworkPolicy({
+    required NetworkPolicy policy,
+  }) {
+    return handler.executeSync(
+      SyncTask(
+        callFfi: () {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_network_policy(policy, serializer);
+          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 22)!;
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_policy_usage,
+          decodeErrorData: sse_decode_AnyhowException,
+        ),
+        constMeta: kCrateApiProxyApiSetNetworkPolicyConstMeta,
+        argValues: [policy],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyApiSetNetworkPolicyConstMeta =>
+      const TaskConstMeta(
+        debugName: "set_network_policy",
+        argNames: ["policy"],
+      );
+
+  @override
+  void crateApiProxyApiUpdateSessionAuth({
+    required String sessionId,
+    required String newUrl,
+    required Map<String, String> newHeaders,
+  }) {
+    return handler.executeSync(
+      SyncTask(
+        callFfi: () {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_String(sessionId, serializer);
+          sse_encode_String(newUrl, serializer);
+          sse_encode_Map_String_String_None(newHeaders, serializer);
+          return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 23)!;
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_unit,
+          decodeErrorData: sse_decode_AnyhowException,
+        ),
+        constMeta: kCrateApiProxyApiUpdateSessionAuthConstMeta,
+        argValues: [sessionId, newUrl, newHeaders],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyApiUpdateSessionAuthConstMeta =>
+      const TaskConstMeta(
+        debugName: "update_session_auth",
+        argNames: ["sessionId", "newUrl", "newHeaders"],
+      );
+
+  @protected
+  AnyhowException dco_decode_AnyhowException(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return AnyhowException(raw as String);
+  }
+
+  @protected
+  Map<String, String> dco_decode_Map_String_String_None(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return Map.fromEntries(
+      dco_decode_list_record_string_string(
+        raw,
+      ).map((e) => MapEntry(e.$1, e.$2)),
+    );
+  }
+
+  @protected
+  String dco_decode_String(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return raw as String;
+  }
+
+  @protected
+  AudioTrackInfo dco_decode_audio_track_info(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    final arr = raw as List<dynamic>;
+    if (arr.length != 5)
+      throw Exception('unexpected arr length: expect 5 but see ${arr.length}');
+    return AudioTrackInfo(
+      codec: dco_decode_String(arr[0]),
+      channels: dco_decode_u_32(arr[1]),
+      sampleRate: dco_decode_u_32(arr[2]),
+      language: dco_decode_String(arr[3]),
+      title: dco_decode_String(arr[4]),
+    );
+  }
+
+  @protected
+  CacheBackend dco_decode_box_autoadd_cache_backend(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return dco_decode_cache_backend(raw);
+  }
+
+  @protected
+  CacheBackend dco_decode_cache_backend(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return CacheBackend.values[raw as int];
+  }
+
+  @protected
+  CacheEntryInfo dco_decode_cache_entry_info(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    final arr = raw as List<dynamic>;
+    if (arr.length != 9)
+      throw Exception('unexpected arr length: expect 9 but see ${arr.length}');
+    return CacheEntryInfo(
+      cacheKey: dco_decode_String(arr[0]),
+      fileKey: dco_decode_String(arr[1]),
+      title: dco_decode_String(arr[2]),
+      sizeOnDisk: dco_decode_u_64(arr[3]),
+      contentLength: dco_decode_u_64(arr[4]),
+      percentComplete: dco_decode_f_64(arr[5]),
+      lastAccessAt: dco_decode_u_64(arr[6]),
+      pinned: dco_decode_bool(arr[7]),
+      active: dco_decode_bool(arr[8]),
+    );
+  }
+
+  @protected
+  CacheUsage dco_decode_cache_usage(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    final arr = raw as List<dynamic>;
+    if (arr.length != 4)
+      throw Exception('unexpected arr length: expect 4 but see ${arr.length}');
+    return CacheUsage(
+      totalBytes: dco_decode_u_64(arr[0]),
+      entryCount: dco_decode_u_32(arr[1]),
+      maxCacheBytes: dco_decode_u_64(arr[2]),
+      freeDiskBytes: dco_decode_u_64(arr[3]),
+    );
+  }
+
+  @protected
+  EngineConfig dco_decode_box_autoadd_engine_config(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return dco_decode_engine_config(raw);
+  }
+
+  @protected
+  EngineConfig dco_decode_engine_config(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    final arr = raw as List<dynamic>;
+    if (arr.length != 6)
+      throw Exception('unexpected arr length: expect 6 but see ${arr.length}');
+    return EngineConfig(
+      chunkSize: dco_decode_u_64(arr[0]),
+      maxConcurrency: dco_decode_u_32(arr[1]),
+      cacheDir: dco_decode_String(arr[2]),
+      maxCacheBytes: dco_decode_u_64(arr[3]),
+      maxSessionCacheBytes: dco_decode_u_64(arr[4]),
+      cacheBackend: dco_decode_opt_box_autoadd_cache_backend(arr[5]),
+    );
+  }
+
+  @protected
+  double dco_decode_f_64(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return raw as double;
+  }
+
+  @protected
+  List<AudioTrackInfo> dco_decode_list_audio_track_info(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return (raw as List<dynamic>).map(dco_decode_audio_track_info).toList();
+  }
+
+  @protected
+  List<CacheEntryInfo> dco_decode_list_cache_entry_info(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return (raw as List<dynamic>).map(dco_decode_cache_entry_info).toList();
+  }
+
+  @protected
+  Uint8List dco_decode_list_prim_u_8_strict(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return raw as Uint8List;
+  }
 
   @protected
   List<(String, String)> dco_decode_list_record_string_string(dynamic raw) {
@@ -376,24 +925,96 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     return (raw as List<dynamic>).map(dco_decode_record_string_string).toList();
   }
 
+  @protected
+  List<SubtitleTrackInfo> dco_decode_list_subtitle_track_info(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return (raw as List<dynamic>).map(dco_decode_subtitle_track_info).toList();
+  }
+
+  @protected
+  List<VideoTrackInfo> dco_decode_list_video_track_info(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return (raw as List<dynamic>).map(dco_decode_video_track_info).toList();
+  }
+
+  @protected
+  MediaInfo dco_decode_media_info(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    final arr = raw as List<dynamic>;
+    if (arr.length != 6)
+      throw Exception('unexpected arr length: expect 6 but see ${arr.length}');
+    return MediaInfo(
+      container: dco_decode_String(arr[0]),
+      durationMs: dco_decode_u_64(arr[1]),
+      bitrate: dco_decode_u_64(arr[2]),
+      videoTracks: dco_decode_list_video_track_info(arr[3]),
+      audioTracks: dco_decode_list_audio_track_info(arr[4]),
+      subtitleTracks: dco_decode_list_subtitle_track_info(arr[5]),
+    );
+  }
+
+  @protected
+  NetworkPolicy dco_decode_network_policy(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return NetworkPolicy.values[raw as int];
+  }
+
   @protected
   String? dco_decode_opt_String(dynamic raw) {
     // Codec=Dco (DartCObject based), see doc to use other codecs
     return raw == null ? null : dco_decode_String(raw);
   }
 
+  @protected
+  CacheBackend? dco_decode_opt_box_autoadd_cache_backend(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return raw == null ? null : dco_decode_box_autoadd_cache_backend(raw);
+  }
+
+  @protected
+  SessionError? dco_decode_opt_box_autoadd_session_error(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return raw == null ? null : dco_decode_box_autoadd_session_error(raw);
+  }
+
+  @protected
+  BigInt? dco_decode_opt_box_autoadd_u_64(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return raw == null ? null : dco_decode_box_autoadd_u_64(raw);
+  }
+
+  @protected
+  PolicyUsage dco_decode_policy_usage(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    final arr = raw as List<dynamic>;
+    if (arr.length != 3)
+      throw Exception('unexpected arr length: expect 3 but see ${arr.length}');
+    return PolicyUsage(
+      policy: dco_decode_network_policy(arr[0]),
+      startedAt: dco_decode_u_64(arr[1]),
+      downloadedBytes: dco_decode_u_64(arr[2]),
+    );
+  }
+
+  @protected
+  ProxyErrorKind dco_decode_proxy_error_kind(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return ProxyErrorKind.values[raw as int];
+  }
+
   @protected
   ProxyStats dco_decode_proxy_stats(dynamic raw) {
     // Codec=Dco (DartCObject based), see doc to use other codecs
     final arr = raw as List<dynamic>;
-    if (arr.length != 5)
-      throw Exception('unexpected arr length: expect 5 but see ${arr.length}');
+    if (arr.length != 6)
+      throw Exception('unexpected arr length: expect 6 but see ${arr.length}');
     return ProxyStats(
       downloadBps: dco_decode_u_64(arr[0]),
       serveBps: dco_decode_u_64(arr[1]),
       bufferedBytesAhead: dco_decode_u_64(arr[2]),
       activeWorkers: dco_decode_u_32(arr[3]),
       cacheHitRate: dco_decode_f_64(arr[4]),
+      playbackBitrate: dco_decode_u_64(arr[5]),
     );
   }
 
@@ -407,6 +1028,24 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     return (dco_decode_String(arr[0]), dco_decode_String(arr[1]));
   }
 
+  @protected
+  SessionError dco_decode_box_autoadd_session_error(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return dco_decode_session_error(raw);
+  }
+
+  @protected
+  SessionError dco_decode_session_error(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    final arr = raw as List<dynamic>;
+    if (arr.length != 2)
+      throw Exception('unexpected arr length: expect 2 but see ${arr.length}');
+    return SessionError(
+      kind: dco_decode_proxy_error_kind(arr[0]),
+      message: dco_decode_String(arr[1]),
+    );
+  }
+
   @protected
   SessionInfo dco_decode_session_info(dynamic raw) {
     // Codec=Dco (DartCObject based), see doc to use other codecs
@@ -421,12 +1060,50 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     );
   }
 
+  @protected
+  SourceDescriptor dco_decode_box_autoadd_source_descriptor(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return dco_decode_source_descriptor(raw);
+  }
+
+  @protected
+  SourceDescriptor dco_decode_source_descriptor(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    final arr = raw as List<dynamic>;
+    if (arr.length != 2)
+      throw Exception('unexpected arr length: expect 2 but see ${arr.length}');
+    return SourceDescriptor(
+      uri: dco_decode_String(arr[0]),
+      headers: dco_decode_Map_String_String_None(arr[1]),
+    );
+  }
+
+  @protected
+  SubtitleTrackInfo dco_decode_subtitle_track_info(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    final arr = raw as List<dynamic>;
+    if (arr.length != 4)
+      throw Exception('unexpected arr length: expect 4 but see ${arr.length}');
+    return SubtitleTrackInfo(
+      codec: dco_decode_String(arr[0]),
+      language: dco_decode_String(arr[1]),
+      title: dco_decode_String(arr[2]),
+      forced: dco_decode_bool(arr[3]),
+    );
+  }
+
   @protected
   int dco_decode_u_32(dynamic raw) {
     // Codec=Dco (DartCObject based), see doc to use other codecs
     return raw as int;
   }
 
+  @protected
+  BigInt dco_decode_box_autoadd_u_64(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return dco_decode_u_64(raw);
+  }
+
   @protected
   BigInt dco_decode_u_64(dynamic raw) {
     // Codec=Dco (DartCObject based), see doc to use other codecs
@@ -446,87 +1123,346 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
   }
 
   @protected
-  AnyhowException sse_decode_AnyhowException(SseDeserializer deserializer) {
+  VideoTrackInfo dco_decode_video_track_info(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    final arr = raw as List<dynamic>;
+    if (arr.length != 8)
+      throw Exception('unexpected arr length: expect 8 but see ${arr.length}');
+    return VideoTrackInfo(
+      codec: dco_decode_String(arr[0]),
+      profile: dco_decode_String(arr[1]),
+      width: dco_decode_u_32(arr[2]),
+      height: dco_decode_u_32(arr[3]),
+      frameRate: dco_decode_f_64(arr[4]),
+      bitDepth: dco_decode_u_32(arr[5]),
+      hdr: dco_decode_bool(arr[6]),
+      dolbyVision: dco_decode_bool(arr[7]),
+    );
+  }
+
+  @protected
+  AnyhowException sse_decode_AnyhowException(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var inner = sse_decode_String(deserializer);
+    return AnyhowException(inner);
+  }
+
+  @protected
+  Map<String, String> sse_decode_Map_String_String_None(
+    SseDeserializer deserializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var inner = sse_decode_list_record_string_string(deserializer);
+    return Map.fromEntries(inner.map((e) => MapEntry(e.$1, e.$2)));
+  }
+
+  @protected
+  String sse_decode_String(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var inner = sse_decode_list_prim_u_8_strict(deserializer);
+    return utf8.decoder.convert(inner);
+  }
+
+  @protected
+  AudioTrackInfo sse_decode_audio_track_info(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var var_codec = sse_decode_String(deserializer);
+    var var_channels = sse_decode_u_32(deserializer);
+    var var_sampleRate = sse_decode_u_32(deserializer);
+    var var_language = sse_decode_String(deserializer);
+    var var_title = sse_decode_String(deserializer);
+    return AudioTrackInfo(
+      codec: var_codec,
+      channels: var_channels,
+      sampleRate: var_sampleRate,
+      language: var_language,
+      title: var_title,
+    );
+  }
+
+  @protected
+  CacheBackend sse_decode_box_autoadd_cache_backend(
+    SseDeserializer deserializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    return (sse_decode_cache_backend(deserializer));
+  }
+
+  @protected
+  EngineConfig sse_decode_box_autoadd_engine_config(
+    SseDeserializer deserializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    return (sse_decode_engine_config(deserializer));
+  }
+
+  @protected
+  SessionError sse_decode_box_autoadd_session_error(
+    SseDeserializer deserializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    return (sse_decode_session_error(deserializer));
+  }
+
+  @protected
+  SourceDescriptor sse_decode_box_autoadd_source_descriptor(
+    SseDeserializer deserializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    return (sse_decode_source_descriptor(deserializer));
+  }
+
+  @protected
+  BigInt sse_decode_box_autoadd_u_64(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    return (sse_decode_u_64(deserializer));
+  }
+
+  @protected
+  CacheBackend sse_decode_cache_backend(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var inner = sse_decode_i_32(deserializer);
+    return CacheBackend.values[inner];
+  }
+
+  @protected
+  CacheEntryInfo sse_decode_cache_entry_info(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var var_cacheKey = sse_decode_String(deserializer);
+    var var_fileKey = sse_decode_String(deserializer);
+    var var_title = sse_decode_String(deserializer);
+    var var_sizeOnDisk = sse_decode_u_64(deserializer);
+    var var_contentLength = sse_decode_u_64(deserializer);
+    var var_percentComplete = sse_decode_f_64(deserializer);
+    var var_lastAccessAt = sse_decode_u_64(deserializer);
+    var var_pinned = sse_decode_bool(deserializer);
+    var var_active = sse_decode_bool(deserializer);
+    return CacheEntryInfo(
+      cacheKey: var_cacheKey,
+      fileKey: var_fileKey,
+      title: var_title,
+      sizeOnDisk: var_sizeOnDisk,
+      contentLength: var_contentLength,
+      percentComplete: var_percentComplete,
+      lastAccessAt: var_lastAccessAt,
+      pinned: var_pinned,
+      active: var_active,
+    );
+  }
+
+  @protected
+  CacheUsage sse_decode_cache_usage(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var var_totalBytes = sse_decode_u_64(deserializer);
+    var var_entryCount = sse_decode_u_32(deserializer);
+    var var_maxCacheBytes = sse_decode_u_64(deserializer);
+    var var_freeDiskBytes = sse_decode_u_64(deserializer);
+    return CacheUsage(
+      totalBytes: var_totalBytes,
+      entryCount: var_entryCount,
+      maxCacheBytes: var_maxCacheBytes,
+      freeDiskBytes: var_freeDiskBytes,
+    );
+  }
+
+  @protected
+  EngineConfig sse_decode_engine_config(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var var_chunkSize = sse_decode_u_64(deserializer);
+    var var_maxConcurrency = sse_decode_u_32(deserializer);
+    var var_cacheDir = sse_decode_String(deserializer);
+    var var_maxCacheBytes = sse_decode_u_64(deserializer);
+    var var_maxSessionCacheBytes = sse_decode_u_64(deserializer);
+    var var_cacheBackend = sse_decode_opt_box_autoadd_cache_backend(
+      deserializer,
+    );
+    return EngineConfig(
+      chunkSize: var_chunkSize,
+      maxConcurrency: var_maxConcurrency,
+      cacheDir: var_cacheDir,
+      maxCacheBytes: var_maxCacheBytes,
+      maxSessionCacheBytes: var_maxSessionCacheBytes,
+      cacheBackend: var_cacheBackend,
+    );
+  }
+
+  @protected
+  double sse_decode_f_64(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    return deserializer.buffer.getFloat64();
+  }
+
+  @protected
+  List<AudioTrackInfo> sse_decode_list_audio_track_info(
+    SseDeserializer deserializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+
+    var len_ = sse_decode_i_32(deserializer);
+    var ans_ = <AudioTrackInfo>[];
+    for (var idx_ = 0; idx_ < len_; ++idx_) {
+      ans_.add(sse_decode_audio_track_info(deserializer));
+    }
+    return ans_;
+  }
+
+  @protected
+  List<CacheEntryInfo> sse_decode_list_cache_entry_info(
+    SseDeserializer deserializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+
+    var len_ = sse_decode_i_32(deserializer);
+    var ans_ = <CacheEntryInfo>[];
+    for (var idx_ = 0; idx_ < len_; ++idx_) {
+      ans_.add(sse_decode_cache_entry_info(deserializer));
+    }
+    return ans_;
+  }
+
+  @protected
+  Uint8List sse_decode_list_prim_u_8_strict(SseDeserializer deserializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
-    var inner = sse_decode_String(deserializer);
-    return AnyhowException(inner);
+    var len_ = sse_decode_i_32(deserializer);
+    return deserializer.buffer.getUint8List(len_);
   }
 
   @protected
-  Map<String, String> sse_decode_Map_String_String_None(
+  List<(String, String)> sse_decode_list_record_string_string(
     SseDeserializer deserializer,
   ) {
     // Codec=Sse (Serialization based), see doc to use other codecs
-    var inner = sse_decode_list_record_string_string(deserializer);
-    return Map.fromEntries(inner.map((e) => MapEntry(e.$1, e.$2)));
+
+    var len_ = sse_decode_i_32(deserializer);
+    var ans_ = <(String, String)>[];
+    for (var idx_ = 0; idx_ < len_; ++idx_) {
+      ans_.add(sse_decode_record_string_string(deserializer));
+    }
+    return ans_;
   }
 
   @protected
-  String sse_decode_String(SseDeserializer deserializer) {
+  List<SubtitleTrackInfo> sse_decode_list_subtitle_track_info(
+    SseDeserializer deserializer,
+  ) {
     // Codec=Sse (Serialization based), see doc to use other codecs
-    var inner = sse_decode_list_prim_u_8_strict(deserializer);
-    return utf8.decoder.convert(inner);
+
+    var len_ = sse_decode_i_32(deserializer);
+    var ans_ = <SubtitleTrackInfo>[];
+    for (var idx_ = 0; idx_ < len_; ++idx_) {
+      ans_.add(sse_decode_subtitle_track_info(deserializer));
+    }
+    return ans_;
   }
 
   @protected
-  EngineConfig sse_decode_box_autoadd_engine_config(
+  List<VideoTrackInfo> sse_decode_list_video_track_info(
     SseDeserializer deserializer,
   ) {
     // Codec=Sse (Serialization based), see doc to use other codecs
-    return (sse_decode_engine_config(deserializer));
+
+    var len_ = sse_decode_i_32(deserializer);
+    var ans_ = <VideoTrackInfo>[];
+    for (var idx_ = 0; idx_ < len_; ++idx_) {
+      ans_.add(sse_decode_video_track_info(deserializer));
+    }
+    return ans_;
   }
 
   @protected
-  EngineConfig sse_decode_engine_config(SseDeserializer deserializer) {
+  MediaInfo sse_decode_media_info(SseDeserializer deserializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
-    var var_chunkSize = sse_decode_u_64(deserializer);
-    var var_maxConcurrency = sse_decode_u_32(deserializer);
-    var var_cacheDir = sse_decode_String(deserializer);
-    return EngineConfig(
-      chunkSize: var_chunkSize,
-      maxConcurrency: var_maxConcurrency,
-      cacheDir: var_cacheDir,
+    var var_container = sse_decode_String(deserializer);
+    var var_durationMs = sse_decode_u_64(deserializer);
+    var var_bitrate = sse_decode_u_64(deserializer);
+    var var_videoTracks = sse_decode_list_video_track_info(deserializer);
+    var var_audioTracks = sse_decode_list_audio_track_info(deserializer);
+    var var_subtitleTracks = sse_decode_list_subtitle_track_info(deserializer);
+    return MediaInfo(
+      container: var_container,
+      durationMs: var_durationMs,
+      bitrate: var_bitrate,
+      videoTracks: var_videoTracks,
+      audioTracks: var_audioTracks,
+      subtitleTracks: var_subtitleTracks,
     );
   }
 
   @protected
-  double sse_decode_f_64(SseDeserializer deserializer) {
+  NetworkPolicy sse_decode_network_policy(SseDeserializer deserializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
-    return deserializer.buffer.getFloat64();
+    var inner = sse_decode_i_32(deserializer);
+    return NetworkPolicy.values[inner];
   }
 
   @protected
-  Uint8List sse_decode_list_prim_u_8_strict(SseDeserializer deserializer) {
+  String? sse_decode_opt_String(SseDeserializer deserializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
-    var len_ = sse_decode_i_32(deserializer);
-    return deserializer.buffer.getUint8List(len_);
+
+    if (sse_decode_bool(deserializer)) {
+      return (sse_decode_String(deserializer));
+    } else {
+      return null;
+    }
   }
 
   @protected
-  List<(String, String)> sse_decode_list_record_string_string(
+  CacheBackend? sse_decode_opt_box_autoadd_cache_backend(
     SseDeserializer deserializer,
   ) {
     // Codec=Sse (Serialization based), see doc to use other codecs
 
-    var len_ = sse_decode_i_32(deserializer);
-    var ans_ = <(String, String)>[];
-    for (var idx_ = 0; idx_ < len_; ++idx_) {
-      ans_.add(sse_decode_record_string_string(deserializer));
+    if (sse_decode_bool(deserializer)) {
+      return (sse_decode_box_autoadd_cache_backend(deserializer));
+    } else {
+      return null;
     }
-    return ans_;
   }
 
   @protected
-  String? sse_decode_opt_String(SseDeserializer deserializer) {
+  SessionError? sse_decode_opt_box_autoadd_session_error(
+    SseDeserializer deserializer,
+  ) {
     // Codec=Sse (Serialization based), see doc to use other codecs
 
     if (sse_decode_bool(deserializer)) {
-      return (sse_decode_String(deserializer));
+      return (sse_decode_box_autoadd_session_error(deserializer));
+    } else {
+      return null;
+    }
+  }
+
+  @protected
+  BigInt? sse_decode_opt_box_autoadd_u_64(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+
+    if (sse_decode_bool(deserializer)) {
+      return (sse_decode_box_autoadd_u_64(deserializer));
     } else {
       return null;
     }
   }
 
+  @protected
+  PolicyUsage sse_decode_policy_usage(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var var_policy = sse_decode_network_policy(deserializer);
+    var var_startedAt = sse_decode_u_64(deserializer);
+    var var_downloadedBytes = sse_decode_u_64(deserializer);
+    return PolicyUsage(
+      policy: var_policy,
+      startedAt: var_startedAt,
+      downloadedBytes: var_downloadedBytes,
+    );
+  }
+
+  @protected
+  ProxyErrorKind sse_decode_proxy_error_kind(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var inner = sse_decode_i_32(deserializer);
+    return ProxyErrorKind.values[inner];
+  }
+
   @protected
   ProxyStats sse_decode_proxy_stats(SseDeserializer deserializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -535,12 +1471,14 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     var var_bufferedBytesAhead = sse_decode_u_64(deserializer);
     var var_activeWorkers = sse_decode_u_32(deserializer);
     var var_cacheHitRate = sse_decode_f_64(deserializer);
+    var var_playbackBitrate = sse_decode_u_64(deserializer);
     return ProxyStats(
       downloadBps: var_downloadBps,
       serveBps: var_serveBps,
       bufferedBytesAhead: var_bufferedBytesAhead,
       activeWorkers: var_activeWorkers,
       cacheHitRate: var_cacheHitRate,
+      playbackBitrate: var_playbackBitrate,
     );
   }
 
@@ -554,6 +1492,17 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     return (var_field0, var_field1);
   }
 
+  @protected
+  SessionError sse_decode_session_error(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var var_kind = sse_decode_proxy_error_kind(deserializer);
+    var var_message = sse_decode_String(deserializer);
+    return SessionError(
+      kind: var_kind,
+      message: var_message,
+    );
+  }
+
   @protected
   SessionInfo sse_decode_session_info(SseDeserializer deserializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -569,6 +1518,34 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     );
   }
 
+  @protected
+  SourceDescriptor sse_decode_source_descriptor(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var var_uri = sse_decode_String(deserializer);
+    var var_headers = sse_decode_Map_String_String_None(deserializer);
+    return SourceDescriptor(
+      uri: var_uri,
+      headers: var_headers,
+    );
+  }
+
+  @protected
+  SubtitleTrackInfo sse_decode_subtitle_track_info(
+    SseDeserializer deserializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var var_codec = sse_decode_String(deserializer);
+    var var_language = sse_decode_String(deserializer);
+    var var_title = sse_decode_String(deserializer);
+    var var_forced = sse_decode_bool(deserializer);
+    return SubtitleTrackInfo(
+      codec: var_codec,
+      language: var_language,
+      title: var_title,
+      forced: var_forced,
+    );
+  }
+
   @protected
   int sse_decode_u_32(SseDeserializer deserializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -592,6 +1569,29 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     // Codec=Sse (Serialization based), see doc to use other codecs
   }
 
+  @protected
+  VideoTrackInfo sse_decode_video_track_info(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var var_codec = sse_decode_String(deserializer);
+    var var_profile = sse_decode_String(deserializer);
+    var var_width = sse_decode_u_32(deserializer);
+    var var_height = sse_decode_u_32(deserializer);
+    var var_frameRate = sse_decode_f_64(deserializer);
+    var var_bitDepth = sse_decode_u_32(deserializer);
+    var var_hdr = sse_decode_bool(deserializer);
+    var var_dolbyVision = sse_decode_bool(deserializer);
+    return VideoTrackInfo(
+      codec: var_codec,
+      profile: var_profile,
+      width: var_width,
+      height: var_height,
+      frameRate: var_frameRate,
+      bitDepth: var_bitDepth,
+      hdr: var_hdr,
+      dolbyVision: var_dolbyVision,
+    );
+  }
+
   @protected
   int sse_decode_i_32(SseDeserializer deserializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -631,6 +1631,28 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     sse_encode_list_prim_u_8_strict(utf8.encoder.convert(self), serializer);
   }
 
+  @protected
+  void sse_encode_audio_track_info(
+    AudioTrackInfo self,
+    SseSerializer serializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_String(self.codec, serializer);
+    sse_encode_u_32(self.channels, serializer);
+    sse_encode_u_32(self.sampleRate, serializer);
+    sse_encode_String(self.language, serializer);
+    sse_encode_String(self.title, serializer);
+  }
+
+  @protected
+  void sse_encode_box_autoadd_cache_backend(
+    CacheBackend self,
+    SseSerializer serializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_cache_backend(self, serializer);
+  }
+
   @protected
   void sse_encode_box_autoadd_engine_config(
     EngineConfig self,
@@ -640,12 +1662,71 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     sse_encode_engine_config(self, serializer);
   }
 
+  @protected
+  void sse_encode_box_autoadd_session_error(
+    SessionError self,
+    SseSerializer serializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_session_error(self, serializer);
+  }
+
+  @protected
+  void sse_encode_box_autoadd_source_descriptor(
+    SourceDescriptor self,
+    SseSerializer serializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_source_descriptor(self, serializer);
+  }
+
+  @protected
+  void sse_encode_box_autoadd_u_64(BigInt self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_u_64(self, serializer);
+  }
+
+  @protected
+  void sse_encode_cache_backend(CacheBackend self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_i_32(self.index, serializer);
+  }
+
+  @protected
+  void sse_encode_cache_entry_info(
+    CacheEntryInfo self,
+    SseSerializer serializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_String(self.cacheKey, serializer);
+    sse_encode_String(self.fileKey, serializer);
+    sse_encode_String(self.title, serializer);
+    sse_encode_u_64(self.sizeOnDisk, serializer);
+    sse_encode_u_64(self.contentLength, serializer);
+    sse_encode_f_64(self.percentComplete, serializer);
+    sse_encode_u_64(self.lastAccessAt, serializer);
+    sse_encode_bool(self.pinned, serializer);
+    sse_encode_bool(self.active, serializer);
+  }
+
+  @protected
+  void sse_encode_cache_usage(CacheUsage self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_u_64(self.totalBytes, serializer);
+    sse_encode_u_32(self.entryCount, serializer);
+    sse_encode_u_64(self.maxCacheBytes, serializer);
+    sse_encode_u_64(self.freeDiskBytes, serializer);
+  }
+
   @protected
   void sse_encode_engine_config(EngineConfig self, SseSerializer serializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
     sse_encode_u_64(self.chunkSize, serializer);
     sse_encode_u_32(self.maxConcurrency, serializer);
     sse_encode_String(self.cacheDir, serializer);
+    sse_encode_u_64(self.maxCacheBytes, serializer);
+    sse_encode_u_64(self.maxSessionCacheBytes, serializer);
+    sse_encode_opt_box_autoadd_cache_backend(self.cacheBackend, serializer);
   }
 
   @protected
@@ -654,6 +1735,30 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     serializer.buffer.putFloat64(self);
   }
 
+  @protected
+  void sse_encode_list_audio_track_info(
+    List<AudioTrackInfo> self,
+    SseSerializer serializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_i_32(self.length, serializer);
+    for (final item in self) {
+      sse_encode_audio_track_info(item, serializer);
+    }
+  }
+
+  @protected
+  void sse_encode_list_cache_entry_info(
+    List<CacheEntryInfo> self,
+    SseSerializer serializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_i_32(self.length, serializer);
+    for (final item in self) {
+      sse_encode_cache_entry_info(item, serializer);
+    }
+  }
+
   @protected
   void sse_encode_list_prim_u_8_strict(
     Uint8List self,
@@ -676,6 +1781,47 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     }
   }
 
+  @protected
+  void sse_encode_list_subtitle_track_info(
+    List<SubtitleTrackInfo> self,
+    SseSerializer serializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_i_32(self.length, serializer);
+    for (final item in self) {
+      sse_encode_subtitle_track_info(item, serializer);
+    }
+  }
+
+  @protected
+  void sse_encode_list_video_track_info(
+    List<VideoTrackInfo> self,
+    SseSerializer serializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_i_32(self.length, serializer);
+    for (final item in self) {
+      sse_encode_video_track_info(item, serializer);
+    }
+  }
+
+  @protected
+  void sse_encode_media_info(MediaInfo self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_String(self.container, serializer);
+    sse_encode_u_64(self.durationMs, serializer);
+    sse_encode_u_64(self.bitrate, serializer);
+    sse_encode_list_video_track_info(self.videoTracks, serializer);
+    sse_encode_list_audio_track_info(self.audioTracks, serializer);
+    sse_encode_list_subtitle_track_info(self.subtitleTracks, serializer);
+  }
+
+  @protected
+  void sse_encode_network_policy(NetworkPolicy self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_i_32(self.index, serializer);
+  }
+
   @protected
   void sse_encode_opt_String(String? self, SseSerializer serializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -686,6 +1832,59 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     }
   }
 
+  @protected
+  void sse_encode_opt_box_autoadd_cache_backend(
+    CacheBackend? self,
+    SseSerializer serializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+
+    sse_encode_bool(self != null, serializer);
+    if (self != null) {
+      sse_encode_box_autoadd_cache_backend(self, serializer);
+    }
+  }
+
+  @protected
+  void sse_encode_opt_box_autoadd_session_error(
+    SessionError? self,
+    SseSerializer serializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+
+    sse_encode_bool(self != null, serializer);
+    if (self != null) {
+      sse_encode_box_autoadd_session_error(self, serializer);
+    }
+  }
+
+  @protected
+  void sse_encode_opt_box_autoadd_u_64(BigInt? self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+
+    sse_encode_bool(self != null, serializer);
+    if (self != null) {
+      sse_encode_box_autoadd_u_64(self, serializer);
+    }
+  }
+
+  @protected
+  void sse_encode_policy_usage(PolicyUsage self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_network_policy(self.policy, serializer);
+    sse_encode_u_64(self.startedAt, serializer);
+    sse_encode_u_64(self.downloadedBytes, serializer);
+  }
+
+  @protected
+  void sse_encode_proxy_error_kind(
+    ProxyErrorKind self,
+    SseSerializer serializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_i_32(self.index, serializer);
+  }
+
   @protected
   void sse_encode_proxy_stats(ProxyStats self, SseSerializer serializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -694,6 +1893,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     sse_encode_u_64(self.bufferedBytesAhead, serializer);
     sse_encode_u_32(self.activeWorkers, serializer);
     sse_encode_f_64(self.cacheHitRate, serializer);
+    sse_encode_u_64(self.playbackBitrate, serializer);
   }
 
   @protected
@@ -706,6 +1906,13 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     sse_encode_String(self.$2, serializer);
   }
 
+  @protected
+  void sse_encode_session_error(SessionError self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_proxy_error_kind(self.kind, serializer);
+    sse_encode_String(self.message, serializer);
+  }
+
   @protected
   void sse_encode_session_info(SessionInfo self, SseSerializer serializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -715,6 +1922,28 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     sse_encode_String(self.contentType, serializer);
   }
 
+  @protected
+  void sse_encode_source_descriptor(
+    SourceDescriptor self,
+    SseSerializer serializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_String(self.uri, serializer);
+    sse_encode_Map_String_String_None(self.headers, serializer);
+  }
+
+  @protected
+  void sse_encode_subtitle_track_info(
+    SubtitleTrackInfo self,
+    SseSerializer serializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_String(self.codec, serializer);
+    sse_encode_String(self.language, serializer);
+    sse_encode_String(self.title, serializer);
+    sse_encode_bool(self.forced, serializer);
+  }
+
   @protected
   void sse_encode_u_32(int self, SseSerializer serializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -738,6 +1967,22 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     // Codec=Sse (Serialization based), see doc to use other codecs
   }
 
+  @protected
+  void sse_encode_video_track_info(
+    VideoTrackInfo self,
+    SseSerializer serializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_String(self.codec, serializer);
+    sse_encode_String(self.profile, serializer);
+    sse_encode_u_32(self.width, serializer);
+    sse_encode_u_32(self.height, serializer);
+    sse_encode_f_64(self.frameRate, serializer);
+    sse_encode_u_32(self.bitDepth, serializer);
+    sse_encode_bool(self.hdr, serializer);
+    sse_encode_bool(self.dolbyVision, serializer);
+  }
+
   @protected
   void sse_encode_i_32(int self, SseSerializer serializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
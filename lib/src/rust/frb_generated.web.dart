@@ -11,7 +11,10 @@ import 'api/simple.dart';
 import 'config.dart';
 import 'dart:async';
 import 'dart:convert';
+import 'engine/policy.dart';
+import 'error.dart';
 import 'frb_generated.dart';
+import 'source/registry.dart';
 import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated_web.dart';
 
 abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
@@ -31,6 +34,21 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   String dco_decode_String(dynamic raw);
 
+  @protected
+  AudioTrackInfo dco_decode_audio_track_info(dynamic raw);
+
+  @protected
+  CacheBackend dco_decode_box_autoadd_cache_backend(dynamic raw);
+
+  @protected
+  CacheBackend dco_decode_cache_backend(dynamic raw);
+
+  @protected
+  CacheEntryInfo dco_decode_cache_entry_info(dynamic raw);
+
+  @protected
+  CacheUsage dco_decode_cache_usage(dynamic raw);
+
   @protected
   EngineConfig dco_decode_box_autoadd_engine_config(dynamic raw);
 
@@ -40,27 +58,78 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   double dco_decode_f_64(dynamic raw);
 
+  @protected
+  List<AudioTrackInfo> dco_decode_list_audio_track_info(dynamic raw);
+
+  @protected
+  List<CacheEntryInfo> dco_decode_list_cache_entry_info(dynamic raw);
+
   @protected
   Uint8List dco_decode_list_prim_u_8_strict(dynamic raw);
 
   @protected
   List<(String, String)> dco_decode_list_record_string_string(dynamic raw);
 
+  @protected
+  List<SubtitleTrackInfo> dco_decode_list_subtitle_track_info(dynamic raw);
+
+  @protected
+  List<VideoTrackInfo> dco_decode_list_video_track_info(dynamic raw);
+
+  @protected
+  MediaInfo dco_decode_media_info(dynamic raw);
+
+  @protected
+  NetworkPolicy dco_decode_network_policy(dynamic raw);
+
   @protected
   String? dco_decode_opt_String(dynamic raw);
 
+  @protected
+  CacheBackend? dco_decode_opt_box_autoadd_cache_backend(dynamic raw);
+
+  @protected
+  SessionError? dco_decode_opt_box_autoadd_session_error(dynamic raw);
+
+  @protected
+  BigInt? dco_decode_opt_box_autoadd_u_64(dynamic raw);
+
+  @protected
+  PolicyUsage dco_decode_policy_usage(dynamic raw);
+
+  @protected
+  ProxyErrorKind dco_decode_proxy_error_kind(dynamic raw);
+
   @protected
   ProxyStats dco_decode_proxy_stats(dynamic raw);
 
   @protected
   (String, String) dco_decode_record_string_string(dynamic raw);
 
+  @protected
+  SessionError dco_decode_box_autoadd_session_error(dynamic raw);
+
+  @protected
+  SessionError dco_decode_session_error(dynamic raw);
+
   @protected
   SessionInfo dco_decode_session_info(dynamic raw);
 
+  @protected
+  SourceDescriptor dco_decode_box_autoadd_source_descriptor(dynamic raw);
+
+  @protected
+  SourceDescriptor dco_decode_source_descriptor(dynamic raw);
+
+  @protected
+  SubtitleTrackInfo dco_decode_subtitle_track_info(dynamic raw);
+
   @protected
   int dco_decode_u_32(dynamic raw);
 
+  @protected
+  BigInt dco_decode_box_autoadd_u_64(dynamic raw);
+
   @protected
   BigInt dco_decode_u_64(dynamic raw);
 
@@ -70,6 +139,9 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   void dco_decode_unit(dynamic raw);
 
+  @protected
+  VideoTrackInfo dco_decode_video_track_info(dynamic raw);
+
   @protected
   AnyhowException sse_decode_AnyhowException(SseDeserializer deserializer);
 
@@ -81,17 +153,57 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   String sse_decode_String(SseDeserializer deserializer);
 
+  @protected
+  AudioTrackInfo sse_decode_audio_track_info(SseDeserializer deserializer);
+
+  @protected
+  CacheBackend sse_decode_box_autoadd_cache_backend(
+    SseDeserializer deserializer,
+  );
+
   @protected
   EngineConfig sse_decode_box_autoadd_engine_config(
     SseDeserializer deserializer,
   );
 
+  @protected
+  SessionError sse_decode_box_autoadd_session_error(
+    SseDeserializer deserializer,
+  );
+
+  @protected
+  SourceDescriptor sse_decode_box_autoadd_source_descriptor(
+    SseDeserializer deserializer,
+  );
+
+  @protected
+  BigInt sse_decode_box_autoadd_u_64(SseDeserializer deserializer);
+
+  @protected
+  CacheBackend sse_decode_cache_backend(SseDeserializer deserializer);
+
+  @protected
+  CacheEntryInfo sse_decode_cache_entry_info(SseDeserializer deserializer);
+
+  @protected
+  CacheUsage sse_decode_cache_usage(SseDeserializer deserializer);
+
   @protected
   EngineConfig sse_decode_engine_config(SseDeserializer deserializer);
 
   @protected
   double sse_decode_f_64(SseDeserializer deserializer);
 
+  @protected
+  List<AudioTrackInfo> sse_decode_list_audio_track_info(
+    SseDeserializer deserializer,
+  );
+
+  @protected
+  List<CacheEntryInfo> sse_decode_list_cache_entry_info(
+    SseDeserializer deserializer,
+  );
+
   @protected
   Uint8List sse_decode_list_prim_u_8_strict(SseDeserializer deserializer);
 
@@ -100,9 +212,44 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
     SseDeserializer deserializer,
   );
 
+  @protected
+  List<SubtitleTrackInfo> sse_decode_list_subtitle_track_info(
+    SseDeserializer deserializer,
+  );
+
+  @protected
+  List<VideoTrackInfo> sse_decode_list_video_track_info(
+    SseDeserializer deserializer,
+  );
+
+  @protected
+  MediaInfo sse_decode_media_info(SseDeserializer deserializer);
+
+  @protected
+  NetworkPolicy sse_decode_network_policy(SseDeserializer deserializer);
+
   @protected
   String? sse_decode_opt_String(SseDeserializer deserializer);
 
+  @protected
+  CacheBackend? sse_decode_opt_box_autoadd_cache_backend(
+    SseDeserializer deserializer,
+  );
+
+  @protected
+  SessionError? sse_decode_opt_box_autoadd_session_error(
+    SseDeserializer deserializer,
+  );
+
+  @protected
+  BigInt? sse_decode_opt_box_autoadd_u_64(SseDeserializer deserializer);
+
+  @protected
+  PolicyUsage sse_decode_policy_usage(SseDeserializer deserializer);
+
+  @protected
+  ProxyErrorKind sse_decode_proxy_error_kind(SseDeserializer deserializer);
+
   @protected
   ProxyStats sse_decode_proxy_stats(SseDeserializer deserializer);
 
@@ -111,9 +258,20 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
     SseDeserializer deserializer,
   );
 
+  @protected
+  SessionError sse_decode_session_error(SseDeserializer deserializer);
+
   @protected
   SessionInfo sse_decode_session_info(SseDeserializer deserializer);
 
+  @protected
+  SourceDescriptor sse_decode_source_descriptor(SseDeserializer deserializer);
+
+  @protected
+  SubtitleTrackInfo sse_decode_subtitle_track_info(
+    SseDeserializer deserializer,
+  );
+
   @protected
   int sse_decode_u_32(SseDeserializer deserializer);
 
@@ -126,6 +284,9 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   void sse_decode_unit(SseDeserializer deserializer);
 
+  @protected
+  VideoTrackInfo sse_decode_video_track_info(SseDeserializer deserializer);
+
   @protected
   int sse_decode_i_32(SseDeserializer deserializer);
 
@@ -147,18 +308,69 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   void sse_encode_String(String self, SseSerializer serializer);
 
+  @protected
+  void sse_encode_audio_track_info(
+    AudioTrackInfo self,
+    SseSerializer serializer,
+  );
+
+  @protected
+  void sse_encode_box_autoadd_cache_backend(
+    CacheBackend self,
+    SseSerializer serializer,
+  );
+
   @protected
   void sse_encode_box_autoadd_engine_config(
     EngineConfig self,
     SseSerializer serializer,
   );
 
+  @protected
+  void sse_encode_box_autoadd_session_error(
+    SessionError self,
+    SseSerializer serializer,
+  );
+
+  @protected
+  void sse_encode_box_autoadd_source_descriptor(
+    SourceDescriptor self,
+    SseSerializer serializer,
+  );
+
+  @protected
+  void sse_encode_box_autoadd_u_64(BigInt self, SseSerializer serializer);
+
+  @protected
+  void sse_encode_cache_backend(CacheBackend self, SseSerializer serializer);
+
+  @protected
+  void sse_encode_cache_entry_info(
+    CacheEntryInfo self,
+    SseSerializer serializer,
+  );
+
+  @protected
+  void sse_encode_cache_usage(CacheUsage self, SseSerializer serializer);
+
   @protected
   void sse_encode_engine_config(EngineConfig self, SseSerializer serializer);
 
   @protected
   void sse_encode_f_64(double self, SseSerializer serializer);
 
+  @protected
+  void sse_encode_list_audio_track_info(
+    List<AudioTrackInfo> self,
+    SseSerializer serializer,
+  );
+
+  @protected
+  void sse_encode_list_cache_entry_info(
+    List<CacheEntryInfo> self,
+    SseSerializer serializer,
+  );
+
   @protected
   void sse_encode_list_prim_u_8_strict(
     Uint8List self,
@@ -171,9 +383,51 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
     SseSerializer serializer,
   );
 
+  @protected
+  void sse_encode_list_subtitle_track_info(
+    List<SubtitleTrackInfo> self,
+    SseSerializer serializer,
+  );
+
+  @protected
+  void sse_encode_list_video_track_info(
+    List<VideoTrackInfo> self,
+    SseSerializer serializer,
+  );
+
+  @protected
+  void sse_encode_media_info(MediaInfo self, SseSerializer serializer);
+
+  @protected
+  void sse_encode_network_policy(NetworkPolicy self, SseSerializer serializer);
+
   @protected
   void sse_encode_opt_String(String? self, SseSerializer serializer);
 
+  @protected
+  void sse_encode_opt_box_autoadd_cache_backend(
+    CacheBackend? self,
+    SseSerializer serializer,
+  );
+
+  @protected
+  void sse_encode_opt_box_autoadd_session_error(
+    SessionError? self,
+    SseSerializer serializer,
+  );
+
+  @protected
+  void sse_encode_opt_box_autoadd_u_64(BigInt? self, SseSerializer serializer);
+
+  @protected
+  void sse_encode_policy_usage(PolicyUsage self, SseSerializer serializer);
+
+  @protected
+  void sse_encode_proxy_error_kind(
+    ProxyErrorKind self,
+    SseSerializer serializer,
+  );
+
   @protected
   void sse_encode_proxy_stats(ProxyStats self, SseSerializer serializer);
 
@@ -183,9 +437,24 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
     SseSerializer serializer,
   );
 
+  @protected
+  void sse_encode_session_error(SessionError self, SseSerializer serializer);
+
   @protected
   void sse_encode_session_info(SessionInfo self, SseSerializer serializer);
 
+  @protected
+  void sse_encode_source_descriptor(
+    SourceDescriptor self,
+    SseSerializer serializer,
+  );
+
+  @protected
+  void sse_encode_subtitle_track_info(
+    SubtitleTrackInfo self,
+    SseSerializer serializer,
+  );
+
   @protected
   void sse_encode_u_32(int self, SseSerializer serializer);
 
@@ -198,6 +467,12 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   void sse_encode_unit(void self, SseSerializer serializer);
 
+  @protected
+  void sse_encode_video_track_info(
+    VideoTrackInfo self,
+    SseSerializer serializer,
+  );
+
   @protected
   void sse_encode_i_32(int self, SseSerializer serializer);
 
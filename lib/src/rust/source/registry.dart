@@ -0,0 +1,33 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../frb_generated.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+
+/// Description of where a session's bytes come from.
+///
+/// `uri` selects the backend by scheme (`https://…`, `file:///…`,
+/// `webdav://…`, `ftp://…`, `alist://…`, and `magnet:?…` once a gateway is
+/// set). Decorators are prefixed with `+`, outermost first, and take their
+/// parameters from the URI fragment, e.g.
+/// `iso+https://host/disc.iso#offset=1048576&length=734003200`. With more
+/// than one decorator, each key names its layer: `#zip.entry=…&iso.offset=…`.
+class SourceDescriptor {
+  final String uri;
+  final Map<String, String> headers;
+
+  const SourceDescriptor({required this.uri, required this.headers});
+
+  @override
+  int get hashCode => uri.hashCode ^ headers.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is SourceDescriptor &&
+          runtimeType == other.runtimeType &&
+          uri == other.uri &&
+          headers == other.headers;
+}
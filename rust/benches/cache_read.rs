@@ -0,0 +1,76 @@
+// Cache read throughput — copying reads vs. zero-copy views.
+//
+// Serves a fully cached file in player-sized slices, the way
+// `serve_range_stream` does, once through `read_range` (fresh `Vec` per
+// slice) and once through `read_bytes` (shared views where the store allows).
+// The socket write that follows is the same in both cases and is left out.
+//
+// Run with `cargo bench --bench cache_read`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rust_lib_ma_palyer::engine::cache::{CacheValidator, DiskCache};
+use rust_lib_ma_palyer::engine::store::CacheBackend;
+
+const MB: u64 = 1024 * 1024;
+const CONTENT_LENGTH: u64 = 64 * MB;
+const CHUNK_SIZE: u64 = 2 * MB;
+const SLICE: u64 = 512 * 1024;
+
+fn filled_cache(dir: &std::path::Path, backend: CacheBackend) -> DiskCache {
+    let cache = DiskCache::open_with_backend(
+        dir,
+        "bench",
+        CONTENT_LENGTH,
+        CHUNK_SIZE,
+        &CacheValidator::default(),
+        backend,
+    )
+    .unwrap();
+    for i in 0..cache.total_chunks() {
+        let data = vec![i as u8; cache.chunk_len(i)];
+        cache.put_chunk(i, &data).unwrap();
+    }
+    cache
+}
+
+fn bench_cache_read(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cache_read");
+    group.throughput(Throughput::Bytes(CONTENT_LENGTH));
+    group.sample_size(20);
+
+    for backend in [
+        CacheBackend::Mmap,
+        CacheBackend::ChunkFiles,
+        CacheBackend::Memory,
+    ] {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = filled_cache(dir.path(), backend);
+        let name = format!("{:?}", backend);
+
+        group.bench_function(BenchmarkId::new("copy", &name), |b| {
+            b.iter(|| {
+                let mut served = 0usize;
+                for start in (0..CONTENT_LENGTH).step_by(SLICE as usize) {
+                    let data = cache.read_range(start, start + SLICE).unwrap();
+                    served += black_box(&data).len();
+                }
+                served
+            })
+        });
+
+        group.bench_function(BenchmarkId::new("view", &name), |b| {
+            b.iter(|| {
+                let mut served = 0usize;
+                for start in (0..CONTENT_LENGTH).step_by(SLICE as usize) {
+                    let data = cache.read_bytes(start, start + SLICE).unwrap();
+                    served += black_box(&data).len();
+                }
+                served
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_cache_read);
+criterion_main!(benches);
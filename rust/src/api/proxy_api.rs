@@ -8,10 +8,21 @@ use parking_lot::Mutex;
 use tokio::runtime::Runtime;
 use tracing::{debug, info, warn};
 
-use crate::config::EngineConfig;
+use crate::config::{CacheBackend, EngineConfig, CONCURRENCY_STATE_FILE};
+use crate::detect::media_info::{AudioTrack, ProbedMedia, SubtitleTrack, VideoTrack};
+use crate::engine::bandwidth::global_bandwidth;
+use crate::engine::cache_manager::{CacheEntry, CacheManager};
+use crate::engine::concurrency::persist_learned_concurrency;
+use crate::engine::host_limiter::set_host_budget;
+pub use crate::engine::policy::NetworkPolicy;
+use crate::engine::policy::{self, PolicyPeriod};
+use crate::engine::position::PlaybackHint;
 use crate::engine::session::ProxySession;
 use crate::engine::stats::StatsSnapshot;
+use crate::error::ProxyError;
+pub use crate::error::ProxyErrorKind;
 use crate::server::handler::{ProxyServer, SessionMap};
+use crate::source::registry::{MagnetFactory, SourceDescriptor, SourceRegistry};
 
 // ---------------------------------------------------------------------------
 // Public data types
@@ -34,6 +45,206 @@ pub struct ProxyStats {
     pub buffered_bytes_ahead: u64,
     pub active_workers: u32,
     pub cache_hit_rate: f64,
+    /// Playback bitrate in bits per second from container metadata or
+    /// observed progress; 0 while unknown.
+    pub playback_bitrate: u64,
+}
+
+/// A persisted cache file, as shown on the settings page.
+#[derive(Debug, Clone)]
+pub struct CacheEntryInfo {
+    /// Cache key (the session ID the file belongs to).
+    pub cache_key: String,
+    /// File key supplied when the session was created, if any.
+    pub file_key: String,
+    /// Display title supplied when the session was created, if any.
+    pub title: String,
+    pub size_on_disk: u64,
+    pub content_length: u64,
+    /// Downloaded share of the file, 0.0–100.0.
+    pub percent_complete: f64,
+    /// Milliseconds since the Unix epoch.
+    pub last_access_at: u64,
+    pub pinned: bool,
+    /// Whether a live session is using this cache right now.
+    pub active: bool,
+}
+
+impl From<CacheEntry> for CacheEntryInfo {
+    fn from(e: CacheEntry) -> Self {
+        let percent_complete = if e.content_length > 0 {
+            e.size_bytes as f64 * 100.0 / e.content_length as f64
+        } else {
+            0.0
+        };
+        Self {
+            cache_key: e.key,
+            file_key: e.file_key.unwrap_or_default(),
+            title: e.title.unwrap_or_default(),
+            size_on_disk: e.size_bytes,
+            content_length: e.content_length,
+            percent_complete,
+            last_access_at: e.last_access_at,
+            pinned: e.pinned,
+            active: e.active,
+        }
+    }
+}
+
+/// The most recent failure of a session, for the player UI.
+#[derive(Debug, Clone)]
+pub struct SessionError {
+    pub kind: ProxyErrorKind,
+    /// Human-readable detail, starting with the kind's code.
+    pub message: String,
+}
+
+impl From<ProxyError> for SessionError {
+    fn from(e: ProxyError) -> Self {
+        Self {
+            kind: e.kind,
+            message: e.to_string(),
+        }
+    }
+}
+
+/// Bytes downloaded under one network policy.
+#[derive(Debug, Clone)]
+pub struct PolicyUsage {
+    pub policy: NetworkPolicy,
+    /// Milliseconds since the Unix epoch when the policy was switched on.
+    pub started_at: u64,
+    pub downloaded_bytes: u64,
+}
+
+impl From<PolicyPeriod> for PolicyUsage {
+    fn from(p: PolicyPeriod) -> Self {
+        Self {
+            policy: p.policy,
+            started_at: p.started_at,
+            downloaded_bytes: p.downloaded_bytes,
+        }
+    }
+}
+
+/// Overall cache usage.
+#[derive(Debug, Clone)]
+pub struct CacheUsage {
+    pub total_bytes: u64,
+    pub entry_count: u32,
+    /// Configured engine budget; 0 means unlimited.
+    pub max_cache_bytes: u64,
+    /// Free space on the cache volume; 0 if the platform cannot report it.
+    pub free_disk_bytes: u64,
+}
+
+/// A video track as described by the container.
+#[derive(Debug, Clone)]
+pub struct VideoTrackInfo {
+    /// Short codec name: `h264`, `hevc`, `av1`, `vp9`, ...
+    pub codec: String,
+    /// e.g. `High`, `Main 10`; empty if unknown.
+    pub profile: String,
+    pub width: u32,
+    pub height: u32,
+    /// Frames per second; 0.0 if unknown.
+    pub frame_rate: f64,
+    /// Bits per sample; 0 if unknown.
+    pub bit_depth: u32,
+    /// PQ (HDR10) or HLG transfer.
+    pub hdr: bool,
+    pub dolby_vision: bool,
+}
+
+impl From<VideoTrack> for VideoTrackInfo {
+    fn from(t: VideoTrack) -> Self {
+        Self {
+            codec: t.codec,
+            profile: t.profile.unwrap_or_default(),
+            width: t.width,
+            height: t.height,
+            frame_rate: t.frame_rate.unwrap_or(0.0),
+            bit_depth: t.bit_depth.map_or(0, u32::from),
+            hdr: t.hdr,
+            dolby_vision: t.dolby_vision,
+        }
+    }
+}
+
+/// An audio track as described by the container.
+#[derive(Debug, Clone)]
+pub struct AudioTrackInfo {
+    /// Short codec name: `aac`, `ac3`, `eac3`, `dts`, `truehd`, ...
+    pub codec: String,
+    /// 0 if unknown.
+    pub channels: u32,
+    /// Hz; 0 if unknown.
+    pub sample_rate: u32,
+    /// ISO 639-2 or BCP 47 code; empty if undetermined.
+    pub language: String,
+    pub title: String,
+}
+
+impl From<AudioTrack> for AudioTrackInfo {
+    fn from(t: AudioTrack) -> Self {
+        Self {
+            codec: t.codec,
+            channels: t.channels.unwrap_or(0),
+            sample_rate: t.sample_rate.unwrap_or(0),
+            language: t.language.unwrap_or_default(),
+            title: t.title.unwrap_or_default(),
+        }
+    }
+}
+
+/// A subtitle track as described by the container.
+#[derive(Debug, Clone)]
+pub struct SubtitleTrackInfo {
+    /// Short codec name: `subrip`, `ass`, `pgs`, `mov_text`, ...
+    pub codec: String,
+    /// ISO 639-2 or BCP 47 code; empty if undetermined.
+    pub language: String,
+    pub title: String,
+    pub forced: bool,
+}
+
+impl From<SubtitleTrack> for SubtitleTrackInfo {
+    fn from(t: SubtitleTrack) -> Self {
+        Self {
+            codec: t.codec,
+            language: t.language.unwrap_or_default(),
+            title: t.title.unwrap_or_default(),
+            forced: t.forced,
+        }
+    }
+}
+
+/// What a session's file contains, from its container headers.
+#[derive(Debug, Clone)]
+pub struct MediaInfo {
+    /// Container name: `mp4`, `matroska`, `mpegts`, `avi`, `flv`, ...
+    pub container: String,
+    /// 0 if unknown.
+    pub duration_ms: u64,
+    /// Average bits per second over the whole file; 0 if unknown.
+    pub bitrate: u64,
+    pub video_tracks: Vec<VideoTrackInfo>,
+    pub audio_tracks: Vec<AudioTrackInfo>,
+    pub subtitle_tracks: Vec<SubtitleTrackInfo>,
+}
+
+impl MediaInfo {
+    fn new(media: ProbedMedia, content_length: u64) -> Self {
+        let duration = media.duration_secs.filter(|&secs| secs > 0.0);
+        Self {
+            container: media.container.name().to_string(),
+            duration_ms: duration.map_or(0, |secs| (secs * 1000.0).round() as u64),
+            bitrate: duration.map_or(0, |secs| (content_length as f64 * 8.0 / secs) as u64),
+            video_tracks: media.video.into_iter().map(Into::into).collect(),
+            audio_tracks: media.audio.into_iter().map(Into::into).collect(),
+            subtitle_tracks: media.subtitles.into_iter().map(Into::into).collect(),
+        }
+    }
 }
 
 impl From<StatsSnapshot> for ProxyStats {
@@ -44,6 +255,7 @@ impl From<StatsSnapshot> for ProxyStats {
             buffered_bytes_ahead: s.buffered_bytes_ahead,
             active_workers: s.active_workers,
             cache_hit_rate: s.cache_hit_rate,
+            playback_bitrate: s.playback_bitrate,
         }
     }
 }
@@ -58,6 +270,7 @@ struct Engine {
     runtime: Arc<Runtime>,
     server: Option<ProxyServer>,
     sessions: SessionMap,
+    cache_manager: Arc<CacheManager>,
     config: EngineConfig,
 }
 
@@ -76,6 +289,14 @@ fn compute_session_id(url: &str, file_key: &str) -> String {
     format!("{:x}", digest)
 }
 
+fn engine_cache_manager() -> Result<Arc<CacheManager>> {
+    let guard = ENGINE.lock();
+    let engine = guard
+        .as_ref()
+        .ok_or_else(|| anyhow!("engine not initialized"))?;
+    Ok(engine.cache_manager.clone())
+}
+
 // ---------------------------------------------------------------------------
 // Public API functions
 // ---------------------------------------------------------------------------
@@ -107,10 +328,20 @@ pub fn init_engine(config: EngineConfig) -> Result<()> {
 
     info!("proxy engine initialized on port {}", server.port());
 
+    let cache_manager = Arc::new(CacheManager::new(
+        config.cache_dir.clone(),
+        config.max_cache_bytes,
+    ));
+    if let Err(e) = cache_manager.startup_scan() {
+        warn!("cache startup scan failed: {}", e);
+    }
+    persist_learned_concurrency(cache_manager.cache_dir().join(CONCURRENCY_STATE_FILE));
+
     *guard = Some(Engine {
         runtime,
         server: Some(server),
         sessions,
+        cache_manager,
         config,
     });
 
@@ -119,24 +350,41 @@ pub fn init_engine(config: EngineConfig) -> Result<()> {
 
 /// Create a new proxy session for the given source URL.
 ///
-/// Returns an existing session if one already exists with the same session ID.
-/// Otherwise clears previous sessions and creates a new one.
+/// Shorthand for [`create_session_with_source`] with an HTTP(S) descriptor.
 #[flutter_rust_bridge::frb(sync)]
 pub fn create_session(
     url: String,
     headers: HashMap<String, String>,
     file_key: String,
 ) -> Result<SessionInfo> {
-    let session_id = compute_session_id(&url, &file_key);
+    create_session_with_source(SourceDescriptor { uri: url, headers }, file_key, None, None)
+}
+
+/// Create a new proxy session for any source the registry can resolve.
+///
+/// `title` is stored with the cache so the settings page can name it.
+/// `cache_backend` overrides [`EngineConfig::cache_backend`] for this session,
+/// e.g. `Memory` for short clips.
+/// Returns an existing session if one already exists with the same session ID.
+/// Otherwise clears previous sessions and creates a new one.
+/// Failures read `<kind code>: <detail>`, as in [`SessionError`].
+#[flutter_rust_bridge::frb(sync)]
+pub fn create_session_with_source(
+    source: SourceDescriptor,
+    file_key: String,
+    title: Option<String>,
+    cache_backend: Option<CacheBackend>,
+) -> Result<SessionInfo> {
+    let session_id = compute_session_id(&source.uri, &file_key);
     info!(
         "create_session id={} file_key_present={} headers={}",
         session_id,
         !file_key.is_empty(),
-        headers.len()
+        source.headers.len()
     );
 
     // Extract what we need from the engine while holding the lock briefly.
-    let (runtime, sessions, config, port) = {
+    let (runtime, sessions, cache_manager, mut config, port) = {
         let guard = ENGINE.lock();
         let engine = guard
             .as_ref()
@@ -149,11 +397,16 @@ pub fn create_session(
         (
             engine.runtime.clone(),
             engine.sessions.clone(),
+            engine.cache_manager.clone(),
             engine.config.clone(),
             port,
         )
     };
 
+    if cache_backend.is_some() {
+        config.cache_backend = cache_backend;
+    }
+
     // Check if session already exists.
     {
         let map = sessions.read();
@@ -164,15 +417,15 @@ pub fn create_session(
                 session_id,
                 playback_url,
                 content_length: session.content_length(),
-                content_type: session.content_type().to_string(),
+                content_type: session.content_type(),
             });
         }
     }
 
     // Clear old sessions before creating a new one.
     // Shutdown downloaders explicitly before dropping sessions so that
-    // all in-flight workers release their Arc<DiskCache> (and mmap)
-    // before DiskCache::new truncates the file for the new session.
+    // all in-flight workers release their Arc<DiskCache> (and mmap) and
+    // the cache sidecar is written before another session reopens it.
     {
         let mut map = sessions.write();
         if !map.is_empty() {
@@ -190,23 +443,20 @@ pub fn create_session(
     // Create the new session (async, outside any engine lock).
     let session = runtime
         .block_on(async {
-            ProxySession::new(
-                session_id.clone(),
-                url,
-                headers,
-                &config.cache_dir,
-                config.chunk_size,
-                config.max_concurrency,
-            )
-            .await
+            let source = SourceRegistry::global().resolve(&source).await?;
+            ProxySession::new(session_id.clone(), source, cache_manager, &config).await
         })
         .map_err(|e| {
-            warn!("create_session failed id={} error={}", session_id, e);
-            e
+            // Surface the typed error so the message starts with its code.
+            let error = ProxyError::classify(&e);
+            warn!("create_session failed id={} error={:#}", session_id, e);
+            anyhow::Error::new(error)
         })?;
 
+    session.set_cache_label(Some(file_key), title);
+
     let content_length = session.content_length();
-    let content_type = session.content_type().to_string();
+    let content_type = session.content_type();
     let playback_url = format!("http://127.0.0.1:{}/stream/{}", port, session_id);
 
     // Insert into the session map.
@@ -273,6 +523,7 @@ pub fn get_stats(session_id: Option<String>) -> Result<ProxyStats> {
             buffered_bytes_ahead: 0,
             active_workers: 0,
             cache_hit_rate: 0.0,
+            playback_bitrate: 0,
         };
         let count = map.len();
         for session in map.values() {
@@ -282,6 +533,7 @@ pub fn get_stats(session_id: Option<String>) -> Result<ProxyStats> {
             total.buffered_bytes_ahead += snap.buffered_bytes_ahead;
             total.active_workers += snap.active_workers;
             total.cache_hit_rate += snap.cache_hit_rate;
+            total.playback_bitrate += snap.playback_bitrate;
         }
         if count > 0 {
             total.cache_hit_rate /= count as f64;
@@ -320,6 +572,246 @@ pub fn update_session_auth(
     Ok(())
 }
 
+/// Return the session's most recent failure, cleared by
+/// `update_session_auth`. `AuthExpired` means fresh credentials are needed.
+#[flutter_rust_bridge::frb(sync)]
+pub fn get_session_error(session_id: String) -> Result<Option<SessionError>> {
+    let sessions = {
+        let guard = ENGINE.lock();
+        let engine = guard
+            .as_ref()
+            .ok_or_else(|| anyhow!("engine not initialized"))?;
+        engine.sessions.clone()
+    };
+
+    let map = sessions.read();
+    let session = map
+        .get(&session_id)
+        .ok_or_else(|| anyhow!("session not found: {}", session_id))?;
+    Ok(session.last_error().map(SessionError::from))
+}
+
+/// Cap download bandwidth in bytes per second for one session, or for the
+/// whole engine when `session_id` is `None`. 0 removes the cap. Chunks the
+/// player is waiting on may borrow beyond it.
+#[flutter_rust_bridge::frb(sync)]
+pub fn set_bandwidth_limit(session_id: Option<String>, bytes_per_sec: u64) -> Result<()> {
+    info!(
+        "set_bandwidth_limit session={:?} bytes_per_sec={}",
+        session_id, bytes_per_sec
+    );
+    let Some(id) = session_id else {
+        global_bandwidth().set_limit(bytes_per_sec);
+        return Ok(());
+    };
+
+    let sessions = {
+        let guard = ENGINE.lock();
+        let engine = guard
+            .as_ref()
+            .ok_or_else(|| anyhow!("engine not initialized"))?;
+        engine.sessions.clone()
+    };
+    let map = sessions.read();
+    let session = map
+        .get(&id)
+        .ok_or_else(|| anyhow!("session not found: {}", id))?;
+    session.set_bandwidth_limit(bytes_per_sec);
+    Ok(())
+}
+
+/// Report the player's position, buffered end and playback rate, about
+/// once a second. Prefetch, eviction and `buffered_bytes_ahead` follow it
+/// until reports stop for a few seconds.
+#[flutter_rust_bridge::frb(sync)]
+pub fn report_playback_position(
+    session_id: String,
+    position_ms: u64,
+    buffer_end_ms: u64,
+    rate: f64,
+) -> Result<()> {
+    let sessions = {
+        let guard = ENGINE.lock();
+        let engine = guard
+            .as_ref()
+            .ok_or_else(|| anyhow!("engine not initialized"))?;
+        engine.sessions.clone()
+    };
+    let session = sessions
+        .read()
+        .get(&session_id)
+        .cloned()
+        .ok_or_else(|| anyhow!("session not found: {}", session_id))?;
+    session.report_playback(PlaybackHint {
+        position_secs: position_ms as f64 / 1000.0,
+        buffer_end_secs: buffer_end_ms as f64 / 1000.0,
+        rate,
+    });
+    Ok(())
+}
+
+/// Start fetching what a seek to `position_ms` will need, ahead of asking
+/// the player to seek. Returns the time of the keyframe decoding starts
+/// from, when the container has an index.
+#[flutter_rust_bridge::frb(sync)]
+pub fn prepare_seek(session_id: String, position_ms: u64) -> Result<Option<u64>> {
+    let sessions = {
+        let guard = ENGINE.lock();
+        let engine = guard
+            .as_ref()
+            .ok_or_else(|| anyhow!("engine not initialized"))?;
+        engine.sessions.clone()
+    };
+    let session = sessions
+        .read()
+        .get(&session_id)
+        .cloned()
+        .ok_or_else(|| anyhow!("session not found: {}", session_id))?;
+    let keyframe = session.prepare_seek(position_ms as f64 / 1000.0);
+    Ok(keyframe.map(|secs| (secs * 1000.0).round() as u64))
+}
+
+/// Duration, bitrate and tracks of a session's file, parsed from its
+/// container headers before the player opens it, e.g. to warn about codecs
+/// the device cannot decode. MP4 and Matroska give full track details;
+/// MPEG-TS, AVI and FLV a subset. May wait on the network, so not `sync`.
+pub fn probe_media_info(session_id: String) -> Result<MediaInfo> {
+    let (runtime, sessions) = {
+        let guard = ENGINE.lock();
+        let engine = guard
+            .as_ref()
+            .ok_or_else(|| anyhow!("engine not initialized"))?;
+        (engine.runtime.clone(), engine.sessions.clone())
+    };
+    let session = sessions
+        .read()
+        .get(&session_id)
+        .cloned()
+        .ok_or_else(|| anyhow!("session not found: {}", session_id))?;
+    let media = runtime.block_on(session.media_info())?;
+    Ok(MediaInfo::new(media, session.content_length()))
+}
+
+/// Switch the engine-wide network policy, e.g. to `DataSaver` on cellular.
+/// Applies to running sessions from their next request. Returns the usage
+/// of the period that just ended.
+#[flutter_rust_bridge::frb(sync)]
+pub fn set_network_policy(policy: NetworkPolicy) -> Result<PolicyUsage> {
+    Ok(policy::set_policy(policy).into())
+}
+
+/// Bytes downloaded since the current network policy was switched on.
+#[flutter_rust_bridge::frb(sync)]
+pub fn get_policy_usage() -> Result<PolicyUsage> {
+    Ok(policy::current_period().into())
+}
+
+/// Cap requests per minute to a host (name or `host:port`) across all
+/// sessions, for drives that ban aggressive clients. 0 removes the cap.
+#[flutter_rust_bridge::frb(sync)]
+pub fn set_host_request_budget(host: String, requests_per_minute: u32) -> Result<()> {
+    let host = host.trim();
+    if host.is_empty() {
+        return Err(anyhow!("host must not be empty"));
+    }
+    info!(
+        "set_host_request_budget host={} rpm={}",
+        host, requests_per_minute
+    );
+    set_host_budget(host, requests_per_minute);
+    Ok(())
+}
+
+/// Play `magnet:` links through a TorrServer-compatible gateway at
+/// `gateway` (e.g. `http://127.0.0.1:8090`). An empty string turns magnet
+/// links off again; they are off until a gateway is set.
+#[flutter_rust_bridge::frb(sync)]
+pub fn set_magnet_gateway(gateway: String) -> Result<()> {
+    let gateway = gateway.trim();
+    info!("set_magnet_gateway gateway={:?}", gateway);
+    let registry = SourceRegistry::global();
+    if gateway.is_empty() {
+        registry.unregister_factory("magnet");
+        return Ok(());
+    }
+    match reqwest::Url::parse(gateway) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+        _ => return Err(anyhow!("invalid magnet gateway: {}", gateway)),
+    }
+    registry.register_factory("magnet", Arc::new(MagnetFactory::new(gateway)));
+    Ok(())
+}
+
+/// List persisted cache entries, most recently used first.
+#[flutter_rust_bridge::frb(sync)]
+pub fn list_cache_entries() -> Result<Vec<CacheEntryInfo>> {
+    let cache_manager = engine_cache_manager()?;
+    Ok(cache_manager
+        .entries()
+        .into_iter()
+        .map(CacheEntryInfo::from)
+        .collect())
+}
+
+/// Delete one cache entry. Fails if a live session is using it.
+#[flutter_rust_bridge::frb(sync)]
+pub fn delete_cache_entry(cache_key: String) -> Result<()> {
+    let cache_manager = engine_cache_manager()?;
+    if cache_manager.is_active(&cache_key) {
+        return Err(anyhow!("cache entry in use: {}", cache_key));
+    }
+    cache_manager.remove(&cache_key)?;
+    info!("delete_cache_entry key={}", cache_key);
+    Ok(())
+}
+
+/// Delete all cache entries not used by a live session.
+///
+/// Pinned entries are kept unless `include_pinned` is set.
+/// Returns the number of entries removed.
+#[flutter_rust_bridge::frb(sync)]
+pub fn clear_cache(include_pinned: bool) -> Result<u32> {
+    let cache_manager = engine_cache_manager()?;
+    let removed = cache_manager.clear(include_pinned);
+    info!(
+        "clear_cache removed={} include_pinned={}",
+        removed, include_pinned
+    );
+    Ok(removed as u32)
+}
+
+/// Pin or unpin a cache entry against budget eviction.
+#[flutter_rust_bridge::frb(sync)]
+pub fn pin_cache_entry(cache_key: String, pinned: bool) -> Result<()> {
+    let (sessions, cache_manager) = {
+        let guard = ENGINE.lock();
+        let engine = guard
+            .as_ref()
+            .ok_or_else(|| anyhow!("engine not initialized"))?;
+        (engine.sessions.clone(), engine.cache_manager.clone())
+    };
+
+    // A live session owns its sidecar; route the change through it.
+    if let Some(session) = sessions.read().get(&cache_key) {
+        session.set_cache_pinned(pinned);
+        return Ok(());
+    }
+    cache_manager.set_pinned(&cache_key, pinned)
+}
+
+/// Report total cache usage and free disk space.
+#[flutter_rust_bridge::frb(sync)]
+pub fn get_cache_usage() -> Result<CacheUsage> {
+    let cache_manager = engine_cache_manager()?;
+    let entries = cache_manager.entries();
+    Ok(CacheUsage {
+        total_bytes: entries.iter().map(|e| e.size_bytes).sum(),
+        entry_count: entries.len() as u32,
+        max_cache_bytes: cache_manager.max_cache_bytes(),
+        free_disk_bytes: cache_manager.free_disk_bytes().unwrap_or(0),
+    })
+}
+
 /// Shut down the proxy engine and release all resources.
 #[flutter_rust_bridge::frb(sync)]
 pub fn dispose() -> Result<()> {
@@ -384,6 +876,25 @@ mod tests {
         assert_eq!(info.content_length, 1024);
     }
 
+    #[test]
+    fn test_cache_entry_info_percent() {
+        let entry = CacheEntry {
+            key: "k".to_string(),
+            size_bytes: 25,
+            content_length: 100,
+            last_access_at: 1,
+            file_key: Some("fid".to_string()),
+            title: None,
+            pinned: true,
+            active: false,
+        };
+        let info: CacheEntryInfo = entry.into();
+        assert!((info.percent_complete - 25.0).abs() < f64::EPSILON);
+        assert_eq!(info.file_key, "fid");
+        assert_eq!(info.title, "");
+        assert!(info.pinned);
+    }
+
     #[test]
     fn test_proxy_stats_from_snapshot() {
         let snap = StatsSnapshot {
@@ -392,6 +903,7 @@ mod tests {
             buffered_bytes_ahead: 300,
             active_workers: 4,
             cache_hit_rate: 0.75,
+            playback_bitrate: 8_000_000,
         };
         let stats: ProxyStats = snap.into();
         assert_eq!(stats.download_bps, 100);
@@ -399,5 +911,6 @@ mod tests {
         assert_eq!(stats.buffered_bytes_ahead, 300);
         assert_eq!(stats.active_workers, 4);
         assert!((stats.cache_hit_rate - 0.75).abs() < f64::EPSILON);
+        assert_eq!(stats.playback_bitrate, 8_000_000);
     }
 }
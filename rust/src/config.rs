@@ -1,8 +1,56 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// Number of seconds of content to keep buffered ahead of playback.
 pub const PRIORITY_BUFFER_SECONDS: u64 = 120;
 
+/// Seconds buffered ahead of playback in data-saver mode.
+pub const DATA_SAVER_BUFFER_SECONDS: u64 = 30;
+
+/// Chunks prefetched ahead before the bitrate is known, per policy.
+pub const DEFAULT_PREFETCH_CHUNKS: u64 = 20;
+pub const DATA_SAVER_PREFETCH_CHUNKS: u64 = 2;
+
+/// Bytes read from the head (and, for MPEG-TS, the tail) of a file to find
+/// its duration (64 KB).
+pub const BITRATE_PROBE_BYTES: u64 = 64 * 1024;
+
+/// Top-level MP4 boxes walked looking for `moov` before giving up.
+pub const MP4_MAX_TOP_LEVEL_BOXES: usize = 64;
+
+/// Top-level RIFF chunks walked looking for an AVI's `idx1`.
+pub const RIFF_MAX_TOP_LEVEL_CHUNKS: usize = 64;
+
+/// Tail read from an Ogg file, where the last page gives the duration
+/// (64 KB, the largest page).
+pub const OGG_TAIL_PROBE_BYTES: u64 = 64 * 1024;
+
+/// Bytes of the file head parsed for the container's layout (64 KB).
+pub const CONTAINER_HEAD_PROBE_BYTES: u64 = 64 * 1024;
+
+/// Largest Matroska Info, Tracks or Cues element prefetched and parsed
+/// (16 MB).
+pub const MKV_MAX_INDEX_ELEMENT_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Largest `moov` box read to build the MP4 keyframe index (64 MB).
+pub const MP4_MAX_MOOV_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Most samples walked in one MP4 track; a day of 60 fps video is ~5.2M.
+pub const MP4_MAX_TRACK_SAMPLES: u32 = 64 * 1024 * 1024;
+
+/// Index spacing for MP4 tracks where every sample is a sync sample.
+pub const MP4_ALL_SYNC_INDEX_SPACING_MS: u64 = 1000;
+
+/// Seconds of media fetched urgently from the keyframe before a seek target.
+pub const SEEK_PREFETCH_SECONDS: u64 = 5;
+
+/// Serving time before observed progress is trusted as a bitrate, while the
+/// container has not told us.
+pub const BITRATE_MIN_OBSERVE_SECS: u64 = 3;
+
+/// A player position report older than this no longer steers prefetch and
+/// eviction; request offsets take over again.
+pub const POSITION_HINT_STALE_SECS: u64 = 5;
+
 /// Maximum bytes allowed for an open-ended HTTP response (64 MB).
 pub const MAX_OPEN_ENDED_RESPONSE_BYTES: u64 = 64 * 1024 * 1024;
 
@@ -21,6 +69,123 @@ pub const SEEK_WARMUP_REQUESTS: u32 = 3;
 /// Number of sequential cache hits required to consider playback stable after a seek.
 pub const SEEK_STABLE_SEQUENTIAL_HITS: u32 = 2;
 
+/// Minimum interval between cache sidecar writes while chunks are landing.
+pub const CACHE_META_WRITE_INTERVAL_SECS: u64 = 5;
+
+/// Chunks within this distance behind the playback offset are never evicted
+/// by the per-session cache budget (8 MB).
+pub const CACHE_EVICT_KEEP_BEHIND_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Downloaded bytes are handed to the cache, and to readers waiting on the
+/// chunk, in pieces of at least this size (64 KB).
+pub const PROGRESSIVE_FLUSH_BYTES: u64 = 64 * 1024;
+
+/// Upper bound for a memory-only session cache (256 MB).
+pub const MEMORY_CACHE_MAX_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Download workers kept free for chunks the player is blocked on.
+pub const SCHEDULER_URGENT_WORKERS: usize = 2;
+
+/// Playback rate assumed for prefetch deadlines until the session has an
+/// estimate (1 MB/s, about 8 Mbit/s).
+pub const SCHEDULER_DEFAULT_BYTES_PER_SEC: u64 = 1024 * 1024;
+
+/// Deadline for warmup chunks (container head, tail and index) in seconds.
+pub const SCHEDULER_WARMUP_DEADLINE_SECS: u64 = 5;
+
+/// Deadline for queued prefetch chunks that playback has already passed.
+pub const SCHEDULER_BEHIND_DEADLINE_SECS: u64 = 600;
+
+/// Background workers a session starts with when nothing was learned for
+/// its host yet.
+pub const ADAPTIVE_INITIAL_WORKERS: usize = 2;
+
+/// File in the cache directory holding learned per-host worker counts.
+pub const CONCURRENCY_STATE_FILE: &str = "concurrency.json";
+
+/// How often the worker count is re-evaluated from measured throughput.
+pub const ADAPTIVE_SAMPLE_INTERVAL_MS: u64 = 2000;
+
+/// Relative throughput gain an extra worker must bring to be kept.
+pub const ADAPTIVE_MIN_GAIN: f64 = 0.1;
+
+/// Per-connection speed falling below this share of the previous sample
+/// counts as a collapse and halves the worker count.
+pub const ADAPTIVE_COLLAPSE_RATIO: f64 = 0.5;
+
+/// Samples to stay at a settled worker count before probing upward again.
+pub const ADAPTIVE_HOLD_SAMPLES: u32 = 5;
+
+/// Progress of an urgent chunk is checked over windows of this length.
+pub const HEDGE_PROGRESS_WINDOW_MS: u64 = 1000;
+
+/// An urgent chunk arriving slower than this gets a hedged request for its
+/// remainder (256 KB/s).
+pub const HEDGE_MIN_BYTES_PER_SEC: u64 = 256 * 1024;
+
+/// Most parallel sub-ranges a cold first chunk is split into.
+pub const FIRST_CHUNK_SPLIT_MAX_PARTS: usize = 4;
+
+/// Smallest sub-range worth its own request when splitting (256 KB).
+pub const FIRST_CHUNK_MIN_PART_BYTES: u64 = 256 * 1024;
+
+/// Most consecutive background chunks fetched with one ranged request.
+pub const MERGE_MAX_CHUNKS: usize = 4;
+
+/// Pause after a rate-limit response without `Retry-After`; doubles with
+/// each further failure.
+pub const RATE_LIMIT_BACKOFF_BASE_MS: u64 = 1000;
+
+/// Longest a host is paused, whatever it asks for.
+pub const RATE_LIMIT_MAX_PAUSE_SECS: u64 = 120;
+
+/// While a host is paused, urgent requests still go out this far apart.
+pub const RATE_LIMIT_URGENT_INTERVAL_MS: u64 = 1000;
+
+/// Consecutive upstream failures that open a host's circuit breaker.
+pub const CIRCUIT_BREAKER_FAILURES: u32 = 5;
+
+/// How long an open circuit keeps background requests away from the host.
+pub const CIRCUIT_BREAKER_OPEN_SECS: u64 = 30;
+
+/// Bandwidth a token bucket may save up while idle, as time at its rate.
+pub const BANDWIDTH_BURST_MS: u64 = 1000;
+
+/// Longest a throttled download sleeps before re-reading its limit, so
+/// runtime changes apply promptly.
+pub const BANDWIDTH_RECHECK_MS: u64 = 100;
+
+/// A tier with queued work is served after being passed over this many
+/// times in a row, whatever the deadlines say.
+pub const SCHEDULER_STARVATION_LIMIT: u32 = 4;
+
+/// Connect and reply timeout for FTP control and data connections.
+pub const FTP_TIMEOUT_SECS: u64 = 15;
+
+/// Where a session keeps downloaded chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CacheBackend {
+    /// One sparse file mapped into memory.
+    Mmap,
+    /// One file per chunk; no large mapping or sparse-file support needed.
+    ChunkFiles,
+    /// RAM only, bounded and never persisted.
+    Memory,
+}
+
+impl Default for CacheBackend {
+    /// 32-bit targets cannot map multi-gigabyte files, so they default to
+    /// chunk files.
+    fn default() -> Self {
+        if cfg!(target_pointer_width = "32") {
+            CacheBackend::ChunkFiles
+        } else {
+            CacheBackend::Mmap
+        }
+    }
+}
+
 /// Top-level configuration for the proxy engine.
 #[derive(Debug, Clone, Deserialize)]
 pub struct EngineConfig {
@@ -30,6 +195,24 @@ pub struct EngineConfig {
     pub max_concurrency: u32,
     /// Directory used for on-disk cache files.
     pub cache_dir: String,
+    /// Upper bound for all cache files together; 0 means unlimited.
+    #[serde(default = "default_max_cache_bytes")]
+    pub max_cache_bytes: u64,
+    /// Upper bound for a single session's cache file; 0 means unlimited.
+    #[serde(default = "default_max_session_cache_bytes")]
+    pub max_session_cache_bytes: u64,
+    /// Storage backend for session caches; `None` picks the platform
+    /// default ([`CacheBackend::default`]).
+    #[serde(default)]
+    pub cache_backend: Option<CacheBackend>,
+}
+
+fn default_max_cache_bytes() -> u64 {
+    4 * 1024 * 1024 * 1024 // 4 GB
+}
+
+fn default_max_session_cache_bytes() -> u64 {
+    2 * 1024 * 1024 * 1024 // 2 GB
 }
 
 impl Default for EngineConfig {
@@ -38,6 +221,29 @@ impl Default for EngineConfig {
             chunk_size: 2 * 1024 * 1024, // 2 MB
             max_concurrency: 6,
             cache_dir: String::new(),
+            max_cache_bytes: default_max_cache_bytes(),
+            max_session_cache_bytes: default_max_session_cache_bytes(),
+            cache_backend: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_without_cache_fields_uses_defaults() {
+        let config: EngineConfig = serde_json::from_str(
+            r#"{"chunk_size": 1048576, "max_concurrency": 4, "cache_dir": "/tmp/c"}"#,
+        )
+        .unwrap();
+        let defaults = EngineConfig::default();
+        assert_eq!(config.max_cache_bytes, defaults.max_cache_bytes);
+        assert_eq!(
+            config.max_session_cache_bytes,
+            defaults.max_session_cache_bytes
+        );
+        assert_eq!(config.cache_backend, None);
+    }
+}
@@ -0,0 +1,157 @@
+// Bitrate detection — average media bitrate from container metadata.
+//
+// MP4 reads the `mvhd` duration (fragmented MP4 the `sidx` total), Matroska `Segment/Info/Duration`, and MPEG-TS
+// compares program clock references near the head and the tail of the file.
+
+use anyhow::Result;
+
+use super::container::{box_header, detect_container, find_box, locate_moov, ContainerFormat};
+use super::fmp4::parse_sidx;
+use super::matroska::{parse_info, segment_layout};
+use crate::config::BITRATE_PROBE_BYTES;
+use crate::source::traits::MediaSource;
+
+/// Average bytes per second of the whole file, if its container says how
+/// long it plays.
+pub async fn estimate_bitrate(
+    source: &dyn MediaSource,
+    content_length: u64,
+) -> Result<Option<f64>> {
+    if content_length == 0 {
+        return Ok(None);
+    }
+    let probe = BITRATE_PROBE_BYTES.min(content_length);
+    let header = source.fetch_range(0, probe - 1).await?;
+
+    match detect_container(&header) {
+        ContainerFormat::FragmentedMp4 => {
+            // The segment index adds up every subsegment's duration; the
+            // `mvhd` of a fragmented file is often zero.
+            let Some((at, _)) = find_box(&header, b"sidx") else {
+                return Ok(None);
+            };
+            let secs = parse_sidx(&header[at as usize..], at)
+                .ok()
+                .and_then(|index| index.duration_secs);
+            Ok(secs.map(|secs| content_length as f64 / secs))
+        }
+        ContainerFormat::Mp4 => {
+            let Some((offset, size)) = locate_moov(source, &header, content_length).await? else {
+                return Ok(None);
+            };
+            // `mvhd` comes first in `moov` in practice.
+            let end = (offset + size.min(BITRATE_PROBE_BYTES)).min(content_length);
+            let moov = source.fetch_range(offset, end - 1).await?;
+            Ok(mp4_duration(&moov).map(|secs| content_length as f64 / secs))
+        }
+        ContainerFormat::Matroska => {
+            Ok(mkv_duration(&header).map(|secs| content_length as f64 / secs))
+        }
+        ContainerFormat::TransportStream => {
+            let Some((head_offset, pid, head_secs)) = first_pcr(&header) else {
+                return Ok(None);
+            };
+            let tail_start = content_length - probe;
+            let tail = source.fetch_range(tail_start, content_length - 1).await?;
+            let Some((tail_offset, tail_secs)) = last_pcr(&tail, pid) else {
+                return Ok(None);
+            };
+            let bytes = (tail_start + tail_offset as u64).saturating_sub(head_offset as u64);
+            let secs = tail_secs - head_secs;
+            // A clock wrap (every ~26.5 h) leaves no usable span.
+            Ok((secs > 0.0 && bytes > 0).then(|| bytes as f64 / secs))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Movie duration in seconds from the `mvhd` box of a `moov` box that
+/// starts at `moov[0]`.
+pub fn mp4_duration(moov: &[u8]) -> Option<f64> {
+    let (_, kind, header_len) = box_header(moov, moov.len() as u64)?;
+    if &kind != b"moov" {
+        return None;
+    }
+    let mut pos = header_len;
+    while pos + 8 <= moov.len() {
+        let (size, kind, header_len) = box_header(&moov[pos..], (moov.len() - pos) as u64)?;
+        if &kind == b"mvhd" {
+            return mvhd_duration(moov.get(pos + header_len..)?);
+        }
+        pos += size as usize;
+    }
+    None
+}
+
+fn mvhd_duration(body: &[u8]) -> Option<f64> {
+    let be32 = |at: usize| -> Option<u64> {
+        Some(u32::from_be_bytes(body.get(at..at + 4)?.try_into().ok()?) as u64)
+    };
+    let (timescale, duration) = match body.first()? {
+        0 => (be32(12)?, be32(16).filter(|&d| d != u32::MAX as u64)?),
+        1 => (
+            be32(20)?,
+            u64::from_be_bytes(body.get(24..32)?.try_into().ok()?),
+        ),
+        _ => return None,
+    };
+    (timescale > 0 && duration > 0 && duration != u64::MAX)
+        .then(|| duration as f64 / timescale as f64)
+}
+
+/// Segment duration in seconds from the Matroska/WebM header bytes. Info
+/// must come before the first Cluster.
+pub fn mkv_duration(buf: &[u8]) -> Option<f64> {
+    let info = segment_layout(buf)?.info?;
+    parse_info(buf.get(info as usize..)?)?.duration_secs
+}
+
+const TS_PACKET: usize = 188;
+
+/// Offset of the first packet boundary in `buf`, which may start mid-packet.
+fn ts_alignment(buf: &[u8]) -> Option<usize> {
+    (0..TS_PACKET.min(buf.len())).find(|&i| buf[i] == 0x47 && buf.get(i + TS_PACKET) == Some(&0x47))
+}
+
+/// PID and PCR in seconds of a packet that carries one.
+fn packet_pcr(packet: &[u8]) -> Option<(u16, f64)> {
+    if packet.len() < 12 || packet[0] != 0x47 {
+        return None;
+    }
+    let pid = (((packet[1] & 0x1F) as u16) << 8) | packet[2] as u16;
+    let has_adaptation = packet[3] & 0x20 != 0;
+    if !has_adaptation || packet[4] < 7 || packet[5] & 0x10 == 0 {
+        return None;
+    }
+    let p = &packet[6..12];
+    let base = ((p[0] as u64) << 25)
+        | ((p[1] as u64) << 17)
+        | ((p[2] as u64) << 9)
+        | ((p[3] as u64) << 1)
+        | ((p[4] as u64) >> 7);
+    let ext = (((p[4] & 0x01) as u64) << 8) | p[5] as u64;
+    Some((pid, (base * 300 + ext) as f64 / 27_000_000.0))
+}
+
+fn ts_packets(buf: &[u8]) -> impl DoubleEndedIterator<Item = (usize, &[u8])> {
+    let start = ts_alignment(buf).unwrap_or(buf.len());
+    buf[start..]
+        .chunks_exact(TS_PACKET)
+        .enumerate()
+        .map(move |(k, p)| (start + k * TS_PACKET, p))
+}
+
+/// First PCR in `buf`: (offset, PID, seconds).
+pub fn first_pcr(buf: &[u8]) -> Option<(usize, u16, f64)> {
+    ts_packets(buf).find_map(|(offset, p)| packet_pcr(p).map(|(pid, secs)| (offset, pid, secs)))
+}
+
+/// Last PCR of `pid` in `buf`: (offset, seconds).
+pub fn last_pcr(buf: &[u8], pid: u16) -> Option<(usize, f64)> {
+    ts_packets(buf)
+        .rev()
+        .find_map(|(offset, p)| match packet_pcr(p) {
+            Some((found, secs)) if found == pid => Some((offset, secs)),
+            _ => None,
+        })
+}
@@ -1,20 +1,131 @@
+use crate::config::{MP4_MAX_TOP_LEVEL_BOXES, RIFF_MAX_TOP_LEVEL_CHUNKS};
 use crate::source::traits::MediaSource;
 use anyhow::Result;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ContainerFormat {
     Mp4,
+    /// Fragmented MP4 (fMP4/CMAF): samples live in `moof`/`mdat` pairs.
+    FragmentedMp4,
     Matroska, // MKV/WebM
     TransportStream,
+    /// Blu-ray BDAV stream: TS packets behind a 4-byte timestamp (192 bytes).
+    M2ts,
+    /// MPEG program stream (`.mpg`, `.vob`).
+    MpegPs,
+    Avi,
+    Flv,
+    /// ASF: `.wmv`, `.wma`, `.asf`.
+    Asf,
+    /// RealMedia: `.rm`, `.rmvb`.
+    RealMedia,
+    Ogg,
+    Mp3,
+    /// Raw AAC in ADTS frames.
+    Aac,
+    Flac,
+    Wav,
     Iso9660,
     Udf,
     Unknown,
 }
 
+impl ContainerFormat {
+    /// Short lower-case name, as shown in file details.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Mp4 => "mp4",
+            Self::FragmentedMp4 => "fmp4",
+            Self::Matroska => "matroska",
+            Self::TransportStream => "mpegts",
+            Self::M2ts => "m2ts",
+            Self::MpegPs => "mpegps",
+            Self::Avi => "avi",
+            Self::Flv => "flv",
+            Self::Asf => "asf",
+            Self::RealMedia => "rm",
+            Self::Ogg => "ogg",
+            Self::Mp3 => "mp3",
+            Self::Aac => "aac",
+            Self::Flac => "flac",
+            Self::Wav => "wav",
+            Self::Iso9660 => "iso9660",
+            Self::Udf => "udf",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    /// MIME type served to the player, `None` when the bytes don't say.
+    pub fn mime_type(&self) -> Option<&'static str> {
+        self.mime_types().first().copied()
+    }
+
+    /// Whether an upstream `Content-Type` already names this format.
+    pub fn matches_content_type(&self, content_type: &str) -> bool {
+        let essence = content_type.split(';').next().unwrap_or("").trim();
+        self.mime_types()
+            .iter()
+            .any(|mime| mime.eq_ignore_ascii_case(essence))
+    }
+
+    /// Preferred MIME type first, then common aliases.
+    fn mime_types(&self) -> &'static [&'static str] {
+        match self {
+            Self::Mp4 | Self::FragmentedMp4 => &["video/mp4", "audio/mp4", "video/quicktime"],
+            Self::Matroska => &[
+                "video/x-matroska",
+                "video/webm",
+                "audio/webm",
+                "audio/x-matroska",
+            ],
+            Self::TransportStream | Self::M2ts => &["video/mp2t", "video/MP2T"],
+            Self::MpegPs => &["video/mpeg", "video/MP2P"],
+            Self::Avi => &["video/x-msvideo", "video/avi", "video/msvideo"],
+            Self::Flv => &["video/x-flv"],
+            Self::Asf => &[
+                "video/x-ms-asf",
+                "video/x-ms-wmv",
+                "audio/x-ms-wma",
+                "application/vnd.ms-asf",
+            ],
+            Self::RealMedia => &[
+                "application/vnd.rn-realmedia",
+                "application/vnd.rn-realmedia-vbr",
+                "audio/x-pn-realaudio",
+            ],
+            Self::Ogg => &["application/ogg", "video/ogg", "audio/ogg"],
+            Self::Mp3 => &["audio/mpeg", "audio/mp3"],
+            Self::Aac => &["audio/aac", "audio/aacp", "audio/x-aac"],
+            Self::Flac => &["audio/flac", "audio/x-flac"],
+            Self::Wav => &["audio/wav", "audio/x-wav", "audio/wave", "audio/vnd.wave"],
+            Self::Iso9660 | Self::Udf | Self::Unknown => &[],
+        }
+    }
+}
+
+/// ASF Header Object GUID, which opens every ASF file.
+const ASF_HEADER_GUID: [u8; 16] = [
+    0x30, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11, 0xA6, 0xD9, 0x00, 0xAA, 0x00, 0x62, 0xCE, 0x6C,
+];
+
+/// ASF Data Object GUID; index objects follow the data object.
+const ASF_DATA_GUID: [u8; 16] = [
+    0x36, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11, 0xA6, 0xD9, 0x00, 0xAA, 0x00, 0x62, 0xCE, 0x6C,
+];
+
+/// GUID plus 64-bit size in front of every ASF object.
+const ASF_OBJECT_HEADER: u64 = 24;
+
+/// ID3v1 tag size; the tag fills an MP3's last 128 bytes.
+pub const ID3V1_BYTES: u64 = 128;
+
 /// Detect container format from file header bytes (first few KB).
 pub fn detect_container(header: &[u8]) -> ContainerFormat {
     // MP4/MOV: bytes 4..8 == "ftyp"
     if header.len() >= 8 && &header[4..8] == b"ftyp" {
+        if is_fragmented(header) {
+            return ContainerFormat::FragmentedMp4;
+        }
         return ContainerFormat::Mp4;
     }
 
@@ -28,9 +139,89 @@ pub fn detect_container(header: &[u8]) -> ContainerFormat {
         return ContainerFormat::TransportStream;
     }
 
+    // RIFF: "RIFF" size form type — "AVI " or "WAVE"
+    if header.len() >= 12 && &header[0..4] == b"RIFF" {
+        match &header[8..12] {
+            b"AVI " => return ContainerFormat::Avi,
+            b"WAVE" => return ContainerFormat::Wav,
+            _ => {}
+        }
+    }
+
+    if header.starts_with(&ASF_HEADER_GUID) {
+        return ContainerFormat::Asf;
+    }
+
+    let magic: &[(&[u8], ContainerFormat)] = &[
+        (b"FLV\x01", ContainerFormat::Flv),
+        (b".RMF", ContainerFormat::RealMedia),
+        (b"OggS", ContainerFormat::Ogg),
+        (b"fLaC", ContainerFormat::Flac),
+        (b"ID3", ContainerFormat::Mp3),
+        // MPEG-PS pack header start code
+        (&[0x00, 0x00, 0x01, 0xBA], ContainerFormat::MpegPs),
+    ];
+    if let Some((_, format)) = magic.iter().find(|(m, _)| header.starts_with(m)) {
+        return *format;
+    }
+
+    // M2TS: the TS sync byte 4 bytes into each 192-byte packet. The 4-byte
+    // timestamp prefix is arbitrary, so only checked after the magic table.
+    if is_m2ts(header) {
+        return ContainerFormat::M2ts;
+    }
+
+    // Bare MPEG audio frames: 11-bit sync, then the layer tells ADTS
+    // (layer 0) from MP3 (layers 1-3). Two frames in a row rule out chance.
+    if header.len() >= 4 && header[0] == 0xFF && header[1] & 0xE0 == 0xE0 {
+        if header[1] & 0xF6 == 0xF0 {
+            return ContainerFormat::Aac;
+        }
+        if header[1] & 0x06 != 0 && mp3_frames_chain(header) {
+            return ContainerFormat::Mp3;
+        }
+    }
+
     ContainerFormat::Unknown
 }
 
+/// Packets checked for a sync byte before calling a head M2TS.
+const M2TS_PROBE_PACKETS: usize = 4;
+
+/// Whether every 192-byte packet in the head (at least two, up to
+/// `M2TS_PROBE_PACKETS`) carries the TS sync byte at offset 4.
+fn is_m2ts(header: &[u8]) -> bool {
+    let syncs: Vec<u8> = (0..M2TS_PROBE_PACKETS)
+        .filter_map(|i| header.get(4 + 192 * i).copied())
+        .collect();
+    syncs.len() >= 2 && syncs.iter().all(|&b| b == 0x47)
+}
+
+/// Whether the MP3 frame at the start of `header` is followed by another.
+fn mp3_frames_chain(header: &[u8]) -> bool {
+    const BITRATES_KBPS: [u32; 16] = [
+        0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 0,
+    ];
+    const SAMPLE_RATES: [u32; 4] = [44100, 48000, 32000, 0];
+    // MPEG-1 layer 3 only: the format of nearly every stored MP3.
+    if header[1] & 0x1E != 0x1A {
+        // Other versions or layers: accept the sync word alone.
+        return true;
+    }
+    let bitrate = BITRATES_KBPS[(header[2] >> 4) as usize] * 1000;
+    let sample_rate = SAMPLE_RATES[((header[2] >> 2) & 3) as usize];
+    if bitrate == 0 || sample_rate == 0 {
+        return false;
+    }
+    let padding = ((header[2] >> 1) & 1) as u32;
+    let next = (144 * bitrate / sample_rate + padding) as usize;
+    match header.get(next..next + 2) {
+        Some(sync) => sync[0] == 0xFF && sync[1] & 0xE0 == 0xE0,
+        // The probe ends first; trust the one frame.
+        None => true,
+    }
+}
+
 /// Detect ISO/UDF by checking bytes at offset 32768 (requires source fetch).
 /// ISO 9660 volume descriptor starts at sector 16 (32768 bytes).
 pub async fn detect_iso(source: &dyn MediaSource) -> Result<ContainerFormat> {
@@ -58,53 +249,177 @@ pub async fn detect_iso(source: &dyn MediaSource) -> Result<ContainerFormat> {
 /// For MP4 files, scan top-level atoms to find the moov box.
 /// Returns (offset, size) if found.
 pub fn find_moov_box(header: &[u8]) -> Option<(u64, u64)> {
+    find_box(header, b"moov")
+}
+
+/// Scan top-level atoms in `header` for the first of type `kind`.
+/// Returns (offset, size) if found.
+pub fn find_box(header: &[u8], kind: &[u8; 4]) -> Option<(u64, u64)> {
     let len = header.len() as u64;
     let mut offset: u64 = 0;
 
     while offset + 8 <= len {
-        let pos = offset as usize;
-        // Read 4-byte big-endian size
-        let size32 = u32::from_be_bytes([
-            header[pos],
-            header[pos + 1],
-            header[pos + 2],
-            header[pos + 3],
-        ]) as u64;
-
-        let atom_type = &header[pos + 4..pos + 8];
-
-        let atom_size = if size32 == 1 {
-            // 64-bit extended size in bytes 8..16
-            if offset + 16 > len {
-                break;
-            }
-            let ext = u64::from_be_bytes([
-                header[pos + 8],
-                header[pos + 9],
-                header[pos + 10],
-                header[pos + 11],
-                header[pos + 12],
-                header[pos + 13],
-                header[pos + 14],
-                header[pos + 15],
-            ]);
-            ext
-        } else if size32 == 0 {
-            // Atom extends to end of file
-            len - offset
-        } else {
-            size32
-        };
-
-        if atom_type == b"moov" {
+        let (atom_size, atom_type, _) = box_header(&header[offset as usize..], len - offset)?;
+        if &atom_type == kind {
             return Some((offset, atom_size));
         }
+        offset += atom_size;
+    }
+
+    None
+}
+
+/// Whether the MP4 head shows fragments: a `sidx`, `styp` or `moof` at the
+/// top level, or a `moov` announcing them with `mvex`.
+fn is_fragmented(header: &[u8]) -> bool {
+    if [b"sidx", b"styp", b"moof"]
+        .iter()
+        .any(|kind| find_box(header, kind).is_some())
+    {
+        return true;
+    }
+    let Some((offset, size)) = find_moov_box(header) else {
+        return false;
+    };
+    let offset = offset as usize;
+    let end = (offset as u64 + size).min(header.len() as u64) as usize;
+    let Some((_, _, header_len)) = box_header(&header[offset..end], size) else {
+        return false;
+    };
+    find_box(&header[offset + header_len..end], b"mvex").is_some()
+}
 
-        if atom_size == 0 {
+/// Walk top-level MP4 atoms until `moov` turns up, reading each header from
+/// `head` while it covers the offset and otherwise fetching just its 16
+/// bytes, so a `moov` behind a large `mdat` costs a few tiny requests.
+/// Returns (offset, size).
+pub async fn locate_moov(
+    source: &dyn MediaSource,
+    head: &[u8],
+    content_length: u64,
+) -> Result<Option<(u64, u64)>> {
+    let mut offset = 0u64;
+    for _ in 0..MP4_MAX_TOP_LEVEL_BOXES {
+        if offset + 8 > content_length {
             break;
         }
-        offset += atom_size;
+        let end = (offset + 16).min(content_length);
+        let parsed = match head.get(offset as usize..end as usize) {
+            Some(header) => box_header(header, content_length - offset),
+            None => box_header(
+                &source.fetch_range(offset, end - 1).await?,
+                content_length - offset,
+            ),
+        };
+        let Some((size, kind, _)) = parsed else {
+            break;
+        };
+        if &kind == b"moov" {
+            return Ok(Some((offset, size)));
+        }
+        offset += size;
     }
+    Ok(None)
+}
 
+/// Size, type and header length of the box at the start of `buf`. `rest`
+/// is what is left of the enclosing space, for size-0 boxes.
+pub fn box_header(buf: &[u8], rest: u64) -> Option<(u64, [u8; 4], usize)> {
+    let size32 = u32::from_be_bytes(buf.get(0..4)?.try_into().ok()?) as u64;
+    let kind: [u8; 4] = buf.get(4..8)?.try_into().ok()?;
+    let (size, header_len) = match size32 {
+        0 => (rest, 8),
+        1 => (u64::from_be_bytes(buf.get(8..16)?.try_into().ok()?), 16),
+        size => (size, 8),
+    };
+    (size >= header_len as u64).then_some((size, kind, header_len))
+}
+
+/// Walk the chunks of an AVI's RIFF list for `idx1`, the legacy index that
+/// follows `movi` at the end of the file. Headers come from `head` while it
+/// covers the offset and otherwise from an 8-byte fetch. Returns (offset,
+/// size) including the chunk header.
+pub async fn locate_avi_index(
+    source: &dyn MediaSource,
+    head: &[u8],
+    content_length: u64,
+) -> Result<Option<(u64, u64)>> {
+    let Some(riff_size) = head.get(4..8).map(|b| le32(b) as u64) else {
+        return Ok(None);
+    };
+    let riff_end = (8 + riff_size).min(content_length);
+    let mut offset = 12u64;
+    for _ in 0..RIFF_MAX_TOP_LEVEL_CHUNKS {
+        if offset + 8 > riff_end {
+            break;
+        }
+        let header = match head.get(offset as usize..offset as usize + 8) {
+            Some(header) => header.to_vec(),
+            None => source.fetch_range(offset, offset + 7).await?.to_vec(),
+        };
+        if header.len() < 8 {
+            break;
+        }
+        let size = le32(&header[4..8]) as u64;
+        if &header[0..4] == b"idx1" {
+            return Ok(Some((offset, 8 + size)));
+        }
+        // Chunks are padded to an even length.
+        offset += 8 + size + (size & 1);
+    }
+    Ok(None)
+}
+
+/// Offset of the index objects that follow an ASF file's data object, found
+/// from the header object's size in `head` and one 24-byte fetch.
+pub async fn locate_asf_index(
+    source: &dyn MediaSource,
+    head: &[u8],
+    content_length: u64,
+) -> Result<Option<u64>> {
+    let Some(header_size) = head.get(16..24).map(le64) else {
+        return Ok(None);
+    };
+    let data_at = header_size;
+    if data_at + ASF_OBJECT_HEADER > content_length {
+        return Ok(None);
+    }
+    let end = data_at + ASF_OBJECT_HEADER;
+    let data = match head.get(data_at as usize..end as usize) {
+        Some(data) => data.to_vec(),
+        None => source.fetch_range(data_at, end - 1).await?.to_vec(),
+    };
+    if data.len() < ASF_OBJECT_HEADER as usize || data[0..16] != ASF_DATA_GUID {
+        return Ok(None);
+    }
+    let index_at = data_at.saturating_add(le64(&data[16..24]));
+    Ok((index_at < content_length).then_some(index_at))
+}
+
+/// Index offset from the `PROP` chunk in a RealMedia head; the index runs
+/// from there to the end of the file.
+pub fn realmedia_index_offset(head: &[u8]) -> Option<u64> {
+    let mut offset = 0usize;
+    while let Some(chunk) = head.get(offset..offset + 8) {
+        let size = u32::from_be_bytes(chunk[4..8].try_into().ok()?) as usize;
+        if &chunk[0..4] == b"PROP" {
+            // id, size, version(2), then seven u32 fields before index_offset
+            let at = offset + 10 + 7 * 4;
+            let index = u32::from_be_bytes(head.get(at..at + 4)?.try_into().ok()?);
+            return (index != 0).then_some(index as u64);
+        }
+        if &chunk[0..4] == b"DATA" || size < 8 {
+            return None;
+        }
+        offset += size;
+    }
     None
 }
+
+fn le32(buf: &[u8]) -> u32 {
+    u32::from_le_bytes(buf[0..4].try_into().unwrap())
+}
+
+fn le64(buf: &[u8]) -> u64 {
+    u64::from_le_bytes(buf[0..8].try_into().unwrap())
+}
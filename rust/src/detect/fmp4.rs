@@ -0,0 +1,177 @@
+// Fragmented MP4 — fragment index from `sidx` or the trailing `mfra`.
+//
+// A segment index (`sidx`, near the head) lists every subsegment's size and
+// duration. Without one, the movie fragment random access box (`mfra`, found
+// through the `mfro` in the last 16 bytes) lists `moof` offsets by time.
+
+use anyhow::{anyhow, bail, Result};
+
+use super::container::box_header;
+use crate::source::traits::MediaSource;
+
+/// Size of the `mfro` box that ends a file carrying an `mfra`.
+pub const MFRO_BYTES: u64 = 16;
+
+/// Where the fragment for `time_secs` starts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fragment {
+    pub time_secs: f64,
+    pub offset: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FragmentIndex {
+    /// In time order.
+    pub fragments: Vec<Fragment>,
+    /// Total duration, when the index says.
+    pub duration_secs: Option<f64>,
+}
+
+fn be32(buf: &[u8], at: usize) -> Result<u32> {
+    buf.get(at..at + 4)
+        .map(|b| u32::from_be_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| anyhow!("box too short"))
+}
+
+fn be64(buf: &[u8], at: usize) -> Result<u64> {
+    buf.get(at..at + 8)
+        .map(|b| u64::from_be_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| anyhow!("box too short"))
+}
+
+/// Body of the box of type `kind` at the start of `buf`.
+fn box_body<'a>(buf: &'a [u8], kind: &[u8; 4]) -> Result<&'a [u8]> {
+    let (size, found, header_len) =
+        box_header(buf, buf.len() as u64).ok_or_else(|| anyhow!("truncated box header"))?;
+    if &found != kind {
+        bail!(
+            "expected {}, found {:?}",
+            String::from_utf8_lossy(kind),
+            String::from_utf8_lossy(&found)
+        );
+    }
+    buf.get(header_len..size as usize).ok_or_else(|| {
+        anyhow!(
+            "{} truncated: {} of {} bytes",
+            String::from_utf8_lossy(kind),
+            buf.len(),
+            size
+        )
+    })
+}
+
+/// Parse a `sidx` box that starts at `sidx[0]` and sits at file offset
+/// `sidx_offset`. Subsegments follow the box, `first_offset` bytes later.
+pub fn parse_sidx(sidx: &[u8], sidx_offset: u64) -> Result<FragmentIndex> {
+    let body = box_body(sidx, b"sidx")?;
+    let size = box_header(sidx, sidx.len() as u64).map_or(0, |(size, _, _)| size);
+
+    let timescale = be32(body, 8)? as f64;
+    if timescale == 0.0 {
+        bail!("sidx timescale 0");
+    }
+    let (earliest, first_offset, pos) = match body.first() {
+        Some(0) => (be32(body, 12)? as u64, be32(body, 16)? as u64, 20),
+        _ => (be64(body, 12)?, be64(body, 20)?, 28),
+    };
+    let count = (be32(body, pos)? & 0xFFFF) as usize;
+
+    let mut offset = sidx_offset + size + first_offset;
+    let mut time = earliest;
+    let mut fragments = Vec::with_capacity(count);
+    for k in 0..count {
+        let entry = pos + 4 + k * 12;
+        let referenced_size = (be32(body, entry)? & 0x7FFF_FFFF) as u64;
+        let duration = be32(body, entry + 4)? as u64;
+        fragments.push(Fragment {
+            time_secs: time as f64 / timescale,
+            offset,
+        });
+        offset += referenced_size;
+        time += duration;
+    }
+    Ok(FragmentIndex {
+        fragments,
+        duration_secs: Some((time - earliest) as f64 / timescale).filter(|&d| d > 0.0),
+    })
+}
+
+/// Offset and size of the `mfra` box, read through the `mfro` at the end
+/// of the file.
+pub async fn locate_mfra(
+    source: &dyn MediaSource,
+    content_length: u64,
+) -> Result<Option<(u64, u64)>> {
+    if content_length < MFRO_BYTES {
+        return Ok(None);
+    }
+    let tail = source
+        .fetch_range(content_length - MFRO_BYTES, content_length - 1)
+        .await?;
+    Ok(mfra_size(&tail)
+        .filter(|&size| size >= MFRO_BYTES && size <= content_length)
+        .map(|size| (content_length - size, size)))
+}
+
+/// Size of the `mfra` box from the `mfro` box in a file's last 16 bytes.
+pub fn mfra_size(tail: &[u8]) -> Option<u64> {
+    let body = box_body(tail, b"mfro").ok()?;
+    be32(body, 4).ok().map(|size| size as u64)
+}
+
+/// Fragments of `track_id` from an `mfra` box starting at `mfra[0]`, with
+/// times in that track's `timescale`.
+pub fn parse_mfra(mfra: &[u8], track_id: u32, timescale: u32) -> Result<FragmentIndex> {
+    if timescale == 0 {
+        bail!("track {} has timescale 0", track_id);
+    }
+    let body = box_body(mfra, b"mfra")?;
+    let mut pos = 0;
+    while pos + 8 <= body.len() {
+        let rest = &body[pos..];
+        let (size, kind, _) =
+            box_header(rest, rest.len() as u64).ok_or_else(|| anyhow!("bad box in mfra"))?;
+        if &kind == b"tfra" {
+            let tfra = box_body(&rest[..(size as usize).min(rest.len())], b"tfra")?;
+            if be32(tfra, 4)? == track_id {
+                return parse_tfra(tfra, timescale);
+            }
+        }
+        pos += size as usize;
+    }
+    bail!("no tfra for track {}", track_id)
+}
+
+fn parse_tfra(tfra: &[u8], timescale: u32) -> Result<FragmentIndex> {
+    let wide = tfra.first() == Some(&1);
+    let lengths = be32(tfra, 8)?;
+    let trailer =
+        ((lengths >> 4) & 3) as usize + ((lengths >> 2) & 3) as usize + (lengths & 3) as usize + 3;
+    let count = be32(tfra, 12)? as usize;
+    let entry_size = if wide { 16 } else { 8 } + trailer;
+    if tfra.len().saturating_sub(16) / entry_size < count {
+        bail!("tfra claims {} entries past its box", count);
+    }
+
+    let mut fragments: Vec<Fragment> = Vec::with_capacity(count);
+    for k in 0..count {
+        let entry = 16 + k * entry_size;
+        let (time, moof) = if wide {
+            (be64(tfra, entry)?, be64(tfra, entry + 8)?)
+        } else {
+            (be32(tfra, entry)? as u64, be32(tfra, entry + 4)? as u64)
+        };
+        // Several random access samples may share one fragment.
+        if fragments.last().is_some_and(|f| f.offset == moof) {
+            continue;
+        }
+        fragments.push(Fragment {
+            time_secs: time as f64 / timescale as f64,
+            offset: moof,
+        });
+    }
+    Ok(FragmentIndex {
+        fragments,
+        duration_secs: None,
+    })
+}
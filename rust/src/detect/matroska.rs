@@ -0,0 +1,276 @@
+// Matroska layout — EBML elements that locate a file's metadata and index.
+//
+// The `SeekHead` near the start of the `Segment` points at `Info`, `Tracks`
+// and `Cues`; `Cues` maps cue times to the clusters a seek reads from.
+
+pub const EBML_SEGMENT: u64 = 0x1853_8067;
+pub const EBML_SEEK_HEAD: u64 = 0x114D_9B74;
+pub const EBML_INFO: u64 = 0x1549_A966;
+pub const EBML_TRACKS: u64 = 0x1654_AE6B;
+pub const EBML_CUES: u64 = 0x1C53_BB6B;
+pub const EBML_CLUSTER: u64 = 0x1F43_B675;
+
+const EBML_SEEK: u64 = 0x4DBB;
+const EBML_SEEK_ID: u64 = 0x53AB;
+const EBML_SEEK_POSITION: u64 = 0x53AC;
+const EBML_TIMESTAMP_SCALE: u64 = 0x2A_D7B1;
+const EBML_DURATION: u64 = 0x4489;
+const EBML_TRACK_ENTRY: u64 = 0xAE;
+const EBML_TRACK_NUMBER: u64 = 0xD7;
+const EBML_TRACK_TYPE: u64 = 0x83;
+const EBML_CUE_POINT: u64 = 0xBB;
+const EBML_CUE_TIME: u64 = 0xB3;
+const EBML_CUE_TRACK_POSITIONS: u64 = 0xB7;
+const EBML_CUE_TRACK: u64 = 0xF7;
+const EBML_CUE_CLUSTER_POSITION: u64 = 0xF1;
+
+/// Longest element header: 4-byte ID plus 8-byte size.
+pub const EBML_MAX_HEADER: u64 = 12;
+
+/// Read an EBML variable-length integer. IDs keep their length marker;
+/// sizes drop it and report the all-ones "unknown" value as `None` in the
+/// second field.
+fn read_vint(buf: &[u8], pos: usize, keep_marker: bool) -> Option<(Option<u64>, usize)> {
+    let first = *buf.get(pos)?;
+    if first == 0 {
+        return None;
+    }
+    let len = first.leading_zeros() as usize + 1;
+    let bytes = buf.get(pos..pos + len)?;
+    let marker_mask = 0xFFu8.checked_shr(len as u32).unwrap_or(0);
+    let mut value = if keep_marker {
+        first as u64
+    } else {
+        (first & marker_mask) as u64
+    };
+    for &b in &bytes[1..] {
+        value = (value << 8) | b as u64;
+    }
+    let unknown = !keep_marker && value == (1u64 << (7 * len)) - 1;
+    Some(((!unknown).then_some(value), len))
+}
+
+/// ID, size (`None` if unknown) and body offset of the element at `pos`.
+pub fn element(buf: &[u8], pos: usize) -> Option<(u64, Option<u64>, usize)> {
+    let (id, id_len) = read_vint(buf, pos, true)?;
+    let (size, size_len) = read_vint(buf, pos + id_len, false)?;
+    Some((id?, size, pos + id_len + size_len))
+}
+
+/// Header plus body length of the element at the start of `buf`.
+pub fn element_len(buf: &[u8]) -> Option<u64> {
+    let (_, size, body) = element(buf, 0)?;
+    Some(body as u64 + size?)
+}
+
+/// (ID, body) of each child in `buf[pos..end]`, stopping at anything of
+/// unknown size or running past `end`.
+pub(crate) fn children(buf: &[u8], mut pos: usize, end: usize) -> Vec<(u64, &[u8])> {
+    let mut out = Vec::new();
+    while pos < end {
+        let Some((id, Some(size), body)) = element(buf, pos) else {
+            break;
+        };
+        let Some(data) = buf.get(body..body + size as usize) else {
+            break;
+        };
+        out.push((id, data));
+        pos = body + size as usize;
+    }
+    out
+}
+
+/// Children of the element at the start of `buf`.
+pub(crate) fn body_children(buf: &[u8]) -> Option<Vec<(u64, &[u8])>> {
+    let (_, size, body) = element(buf, 0)?;
+    let end = (body as u64 + size?).min(buf.len() as u64) as usize;
+    Some(children(buf, body, end))
+}
+
+pub(crate) fn uint(data: &[u8]) -> u64 {
+    data.iter().fold(0u64, |v, &b| (v << 8) | b as u64)
+}
+
+pub(crate) fn float(data: &[u8]) -> Option<f64> {
+    match data.len() {
+        4 => Some(f32::from_be_bytes(data.try_into().ok()?) as f64),
+        8 => Some(f64::from_be_bytes(data.try_into().ok()?)),
+        _ => None,
+    }
+}
+
+/// Absolute file offsets of the Segment's metadata elements.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SegmentLayout {
+    /// Start of the Segment body; SeekHead and Cues positions count from it.
+    pub data_start: u64,
+    pub info: Option<u64>,
+    pub tracks: Option<u64>,
+    pub cues: Option<u64>,
+}
+
+/// Find the Segment in the head of a file and where its Info, Tracks and
+/// Cues live, from the SeekHead or from the elements themselves when they
+/// come before the first Cluster.
+pub fn segment_layout(buf: &[u8]) -> Option<SegmentLayout> {
+    let mut pos = 0;
+    let (size, data_start) = loop {
+        let (id, size, body) = element(buf, pos)?;
+        if id == EBML_SEGMENT {
+            break (size, body);
+        }
+        pos = body + size? as usize;
+    };
+    let end = size.map_or(buf.len(), |s| {
+        (data_start as u64 + s).min(buf.len() as u64) as usize
+    });
+
+    let mut layout = SegmentLayout {
+        data_start: data_start as u64,
+        ..SegmentLayout::default()
+    };
+    let mut pos = data_start;
+    while pos < end {
+        let Some((id, size, body)) = element(buf, pos) else {
+            break;
+        };
+        let at = Some(pos as u64);
+        match id {
+            EBML_SEEK_HEAD => {
+                let head_end = (body as u64 + size?).min(buf.len() as u64) as usize;
+                for (id, seek) in children(buf, body, head_end) {
+                    if id == EBML_SEEK {
+                        layout.record_seek(seek);
+                    }
+                }
+            }
+            EBML_INFO => layout.info = at,
+            EBML_TRACKS => layout.tracks = at,
+            EBML_CUES => layout.cues = at,
+            // Media data starts; metadata after it is only found by seeking.
+            EBML_CLUSTER => break,
+            _ => {}
+        }
+        let Some(size) = size else {
+            break;
+        };
+        pos = body + size as usize;
+    }
+    Some(layout)
+}
+
+impl SegmentLayout {
+    fn record_seek(&mut self, seek: &[u8]) {
+        let mut target = None;
+        let mut position = None;
+        for (id, data) in children(seek, 0, seek.len()) {
+            match id {
+                EBML_SEEK_ID => target = Some(uint(data)),
+                EBML_SEEK_POSITION => position = Some(self.data_start + uint(data)),
+                _ => {}
+            }
+        }
+        let slot = match target {
+            Some(EBML_INFO) => &mut self.info,
+            Some(EBML_TRACKS) => &mut self.tracks,
+            Some(EBML_CUES) => &mut self.cues,
+            _ => return,
+        };
+        if let Some(position) = position {
+            slot.get_or_insert(position);
+        }
+    }
+}
+
+/// Segment timing from an Info element at the start of `buf`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SegmentInfo {
+    /// Nanoseconds per timestamp tick.
+    pub timestamp_scale: u64,
+    pub duration_secs: Option<f64>,
+}
+
+pub fn parse_info(buf: &[u8]) -> Option<SegmentInfo> {
+    let mut scale = 1_000_000u64;
+    let mut duration = None;
+    for (id, data) in body_children(buf)? {
+        match id {
+            EBML_TIMESTAMP_SCALE => scale = uint(data),
+            EBML_DURATION => duration = float(data),
+            _ => {}
+        }
+    }
+    Some(SegmentInfo {
+        timestamp_scale: scale,
+        duration_secs: duration
+            .map(|d| d * scale as f64 / 1e9)
+            .filter(|&secs| secs > 0.0),
+    })
+}
+
+/// Numbers of the video tracks in a Tracks element at the start of `buf`.
+pub fn video_tracks(buf: &[u8]) -> Vec<u64> {
+    let Some(entries) = body_children(buf) else {
+        return Vec::new();
+    };
+    entries
+        .into_iter()
+        .filter(|(id, _)| *id == EBML_TRACK_ENTRY)
+        .filter_map(|(_, entry)| {
+            let fields = children(entry, 0, entry.len());
+            let number = fields.iter().find(|(id, _)| *id == EBML_TRACK_NUMBER)?;
+            let kind = fields.iter().find(|(id, _)| *id == EBML_TRACK_TYPE)?;
+            (uint(kind.1) == 1).then(|| uint(number.1))
+        })
+        .collect()
+}
+
+/// A cue: where a seek to `time_secs` on `track` starts reading.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CuePoint {
+    pub time_secs: f64,
+    pub track: u64,
+    /// Absolute file offset of the cluster.
+    pub cluster_offset: u64,
+}
+
+/// Cue points from a Cues element at the start of `buf`.
+pub fn parse_cues(buf: &[u8], data_start: u64, timestamp_scale: u64) -> Vec<CuePoint> {
+    let Some(points) = body_children(buf) else {
+        return Vec::new();
+    };
+    let mut cues = Vec::new();
+    for (_, point) in points.into_iter().filter(|(id, _)| *id == EBML_CUE_POINT) {
+        let mut time = None;
+        let mut positions = Vec::new();
+        for (id, data) in children(point, 0, point.len()) {
+            match id {
+                EBML_CUE_TIME => time = Some(uint(data)),
+                EBML_CUE_TRACK_POSITIONS => positions.push(data),
+                _ => {}
+            }
+        }
+        let Some(time) = time else {
+            continue;
+        };
+        for position in positions {
+            let mut track = None;
+            let mut cluster = None;
+            for (id, data) in children(position, 0, position.len()) {
+                match id {
+                    EBML_CUE_TRACK => track = Some(uint(data)),
+                    EBML_CUE_CLUSTER_POSITION => cluster = Some(uint(data)),
+                    _ => {}
+                }
+            }
+            if let (Some(track), Some(cluster)) = (track, cluster) {
+                cues.push(CuePoint {
+                    time_secs: time as f64 * timestamp_scale as f64 / 1e9,
+                    track,
+                    cluster_offset: data_start + cluster,
+                });
+            }
+        }
+    }
+    cues
+}
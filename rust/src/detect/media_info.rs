@@ -0,0 +1,950 @@
+// Media info — tracks, codecs and timing from container headers, for the UI.
+//
+// MP4 sample descriptions and Matroska `Tracks` give the full picture: codec,
+// profile, resolution, frame rate and HDR/Dolby Vision signalling. MPEG-TS
+// program tables, AVI stream headers and FLV tags give a cheaper subset from
+// the head of the file alone.
+
+use anyhow::{anyhow, bail, Result};
+use tracing::debug;
+
+use super::bitrate::mp4_duration;
+use super::container::{box_header, ContainerFormat};
+use super::matroska::{self, body_children, float, parse_info, uint};
+use super::mp4::{self, child};
+
+const EBML_TRACK_ENTRY: u64 = 0xAE;
+const EBML_TRACK_TYPE: u64 = 0x83;
+const EBML_CODEC_ID: u64 = 0x86;
+const EBML_CODEC_PRIVATE: u64 = 0x63A2;
+const EBML_LANGUAGE: u64 = 0x22_B59C;
+const EBML_LANGUAGE_BCP47: u64 = 0x22_B59D;
+const EBML_NAME: u64 = 0x536E;
+const EBML_FLAG_FORCED: u64 = 0x55AA;
+const EBML_DEFAULT_DURATION: u64 = 0x23_E383;
+const EBML_VIDEO: u64 = 0xE0;
+const EBML_PIXEL_WIDTH: u64 = 0xB0;
+const EBML_PIXEL_HEIGHT: u64 = 0xBA;
+const EBML_COLOUR: u64 = 0x55B0;
+const EBML_BITS_PER_CHANNEL: u64 = 0x55B2;
+const EBML_TRANSFER_CHARACTERISTICS: u64 = 0x55BA;
+const EBML_AUDIO: u64 = 0xE1;
+const EBML_SAMPLING_FREQUENCY: u64 = 0xB5;
+const EBML_CHANNELS: u64 = 0x9F;
+const EBML_BLOCK_ADDITION_MAPPING: u64 = 0x41E4;
+const EBML_BLOCK_ADD_ID_TYPE: u64 = 0x41E7;
+
+/// Dolby Vision configuration boxes, also Matroska block addition types.
+const DOLBY_VISION_CONFIGS: [&[u8; 4]; 3] = [b"dvcC", b"dvvC", b"dvwC"];
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VideoTrack {
+    /// Short codec name: `h264`, `hevc`, `av1`, `vp9`, `mpeg4`, ...
+    pub codec: String,
+    /// e.g. `High`, `Main 10`.
+    pub profile: Option<String>,
+    pub width: u32,
+    pub height: u32,
+    pub frame_rate: Option<f64>,
+    pub bit_depth: Option<u8>,
+    /// PQ (HDR10) or HLG transfer.
+    pub hdr: bool,
+    pub dolby_vision: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AudioTrack {
+    /// Short codec name: `aac`, `ac3`, `eac3`, `dts`, `truehd`, `opus`, ...
+    pub codec: String,
+    pub channels: Option<u32>,
+    pub sample_rate: Option<u32>,
+    /// ISO 639-2 or BCP 47 code; `None` when undetermined.
+    pub language: Option<String>,
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SubtitleTrack {
+    /// Short codec name: `subrip`, `ass`, `pgs`, `mov_text`, ...
+    pub codec: String,
+    pub language: Option<String>,
+    pub title: Option<String>,
+    pub forced: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProbedMedia {
+    pub container: ContainerFormat,
+    pub duration_secs: Option<f64>,
+    pub video: Vec<VideoTrack>,
+    pub audio: Vec<AudioTrack>,
+    pub subtitles: Vec<SubtitleTrack>,
+}
+
+impl ProbedMedia {
+    pub fn new(container: ContainerFormat) -> Self {
+        Self {
+            container,
+            duration_secs: None,
+            video: Vec::new(),
+            audio: Vec::new(),
+            subtitles: Vec::new(),
+        }
+    }
+}
+
+fn be16(buf: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(buf.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn be32(buf: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(buf.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn le16(buf: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(buf.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn le32(buf: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(buf.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// Lower-case four-character code, trailing spaces dropped.
+fn fourcc_name(code: &[u8]) -> String {
+    String::from_utf8_lossy(code)
+        .trim_end_matches([' ', '\0'])
+        .to_ascii_lowercase()
+}
+
+/// A language code, or `None` for "undetermined".
+fn language(code: &str) -> Option<String> {
+    let code = code.trim_end_matches('\0').trim();
+    (!code.is_empty() && code != "und").then(|| code.to_string())
+}
+
+/// Transfer characteristics (ITU-T H.273) of HDR video: PQ or HLG.
+fn is_hdr_transfer(transfer: u64) -> bool {
+    transfer == 16 || transfer == 18
+}
+
+// ---------------------------------------------------------------------------
+// Codec configuration records, shared by MP4 and Matroska
+// ---------------------------------------------------------------------------
+
+/// Profile and bit depth from the codec's configuration record: `avcC`,
+/// `hvcC` or `av1C`, as an MP4 box body or Matroska `CodecPrivate`.
+fn apply_codec_config(track: &mut VideoTrack, config: &[u8]) {
+    match track.codec.as_str() {
+        "h264" => {
+            let (profile, depth) = match config.get(1) {
+                Some(66) => ("Baseline", Some(8)),
+                Some(77) => ("Main", Some(8)),
+                Some(88) => ("Extended", Some(8)),
+                Some(100) => ("High", Some(8)),
+                Some(110) => ("High 10", Some(10)),
+                Some(122) => ("High 4:2:2", None),
+                Some(244) => ("High 4:4:4 Predictive", None),
+                _ => return,
+            };
+            track.profile = Some(profile.to_string());
+            track.bit_depth = track.bit_depth.or(depth);
+        }
+        "hevc" => {
+            let profile = match config.get(1).map(|b| b & 0x1F) {
+                Some(1) => "Main",
+                Some(2) => "Main 10",
+                Some(3) => "Main Still Picture",
+                Some(4) => "Range Extensions",
+                _ => return,
+            };
+            track.profile = Some(profile.to_string());
+            if let Some(depth) = config.get(17) {
+                track.bit_depth = Some((depth & 0x07) + 8);
+            }
+        }
+        "av1" => {
+            let (Some(&b1), Some(&b2)) = (config.get(1), config.get(2)) else {
+                return;
+            };
+            let profile = match b1 >> 5 {
+                0 => "Main",
+                1 => "High",
+                2 => "Professional",
+                _ => return,
+            };
+            track.profile = Some(profile.to_string());
+            track.bit_depth = Some(match (b2 & 0x40 != 0, b2 & 0x20 != 0) {
+                (false, _) => 8,
+                (true, false) => 10,
+                (true, true) => 12,
+            });
+        }
+        _ => {}
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MP4
+// ---------------------------------------------------------------------------
+
+/// Tracks and duration from a complete `moov` box starting at `moov[0]`.
+pub fn mp4_media_info(moov: &[u8], container: ContainerFormat) -> Result<ProbedMedia> {
+    let (size, kind, header_len) =
+        box_header(moov, moov.len() as u64).ok_or_else(|| anyhow!("truncated box header"))?;
+    if &kind != b"moov" {
+        bail!("expected moov, found {:?}", String::from_utf8_lossy(&kind));
+    }
+    let body = moov
+        .get(header_len..size as usize)
+        .ok_or_else(|| anyhow!("moov truncated: {} of {} bytes", moov.len(), size))?;
+
+    let mut media = ProbedMedia::new(container);
+    media.duration_secs = mp4_duration(moov);
+    for (kind, trak) in mp4::children(body) {
+        if &kind == b"trak" {
+            if let Err(e) = mp4_track(trak, &mut media) {
+                debug!("skipping mp4 track: {}", e);
+            }
+        }
+    }
+    Ok(media)
+}
+
+fn mp4_track(trak: &[u8], media: &mut ProbedMedia) -> Result<()> {
+    let mdia = child(trak, b"mdia")?;
+    let mdhd = child(mdia, b"mdhd")?;
+    let wide = mdhd.first() == Some(&1);
+    let timescale = be32(mdhd, if wide { 20 } else { 12 }).unwrap_or(0);
+    let language = be16(mdhd, if wide { 32 } else { 20 }).and_then(mp4_language);
+    let handler: [u8; 4] = child(mdia, b"hdlr")?
+        .get(8..12)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| anyhow!("hdlr too short"))?;
+
+    let stbl = child(child(mdia, b"minf")?, b"stbl")?;
+    let stsd = child(stbl, b"stsd")?;
+    let entries = stsd.get(8..).ok_or_else(|| anyhow!("stsd too short"))?;
+    let (size, format, header_len) = box_header(entries, entries.len() as u64)
+        .ok_or_else(|| anyhow!("truncated sample entry"))?;
+    let entry = entries
+        .get(header_len..size as usize)
+        .ok_or_else(|| anyhow!("sample entry truncated"))?;
+
+    match &handler {
+        b"vide" => {
+            let mut track = mp4_video_entry(&format, entry);
+            track.frame_rate = child(stbl, b"stts")
+                .ok()
+                .and_then(|stts| stts_frame_rate(stts, timescale));
+            media.video.push(track);
+        }
+        b"soun" => media.audio.push(AudioTrack {
+            language,
+            ..mp4_audio_entry(&format, entry)
+        }),
+        b"subt" | b"text" | b"sbtl" | b"clcp" => media.subtitles.push(SubtitleTrack {
+            codec: match &format {
+                b"tx3g" | b"text" => "mov_text".to_string(),
+                b"wvtt" => "webvtt".to_string(),
+                b"stpp" => "ttml".to_string(),
+                b"c608" => "eia_608".to_string(),
+                other => fourcc_name(other),
+            },
+            language,
+            ..SubtitleTrack::default()
+        }),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// ISO 639-2/T code packed into three 5-bit letters; small values are
+/// QuickTime's Macintosh language numbers.
+fn mp4_language(packed: u16) -> Option<String> {
+    if packed < 0x400 {
+        return None;
+    }
+    let code: String = [10, 5, 0]
+        .iter()
+        .map(|shift| (((packed >> shift) & 0x1F) as u8 + 0x60) as char)
+        .collect();
+    language(&code)
+}
+
+/// Average frame rate from the decode time deltas.
+fn stts_frame_rate(stts: &[u8], timescale: u32) -> Option<f64> {
+    let count = be32(stts, 4)? as usize;
+    let (mut samples, mut ticks) = (0u64, 0u64);
+    for k in 0..count {
+        let n = be32(stts, 8 + k * 8)? as u64;
+        samples += n;
+        ticks += n * be32(stts, 12 + k * 8)? as u64;
+    }
+    (ticks > 0 && timescale > 0).then(|| samples as f64 * timescale as f64 / ticks as f64)
+}
+
+fn mp4_video_entry(format: &[u8; 4], entry: &[u8]) -> VideoTrack {
+    let codec = match format {
+        b"avc1" | b"avc3" | b"dva1" | b"dvav" => "h264".to_string(),
+        b"hvc1" | b"hev1" | b"dvh1" | b"dvhe" => "hevc".to_string(),
+        b"av01" | b"dav1" => "av1".to_string(),
+        b"vp09" => "vp9".to_string(),
+        b"mp4v" => "mpeg4".to_string(),
+        other => fourcc_name(other),
+    };
+    let mut track = VideoTrack {
+        codec,
+        width: be16(entry, 24).unwrap_or(0) as u32,
+        height: be16(entry, 26).unwrap_or(0) as u32,
+        dolby_vision: matches!(format, b"dva1" | b"dvav" | b"dvh1" | b"dvhe" | b"dav1"),
+        ..VideoTrack::default()
+    };
+    // Configuration boxes follow the 78-byte visual sample entry.
+    for (kind, body) in mp4::children(entry.get(78..).unwrap_or_default()) {
+        match &kind {
+            b"avcC" | b"hvcC" | b"av1C" => apply_codec_config(&mut track, body),
+            b"vpcC" => {
+                // Full box: profile, level, then bit depth in the high nibble.
+                if let Some(&profile) = body.get(4) {
+                    track.profile = Some(format!("Profile {}", profile));
+                }
+                track.bit_depth = body.get(6).map(|b| b >> 4);
+                track.hdr |= body.get(8).is_some_and(|&t| is_hdr_transfer(t as u64));
+            }
+            b"colr" if matches!(body.get(0..4), Some(b"nclx" | b"nclc")) => {
+                track.hdr |= be16(body, 6).is_some_and(|t| is_hdr_transfer(t as u64));
+            }
+            kind if DOLBY_VISION_CONFIGS.contains(&kind) => track.dolby_vision = true,
+            _ => {}
+        }
+    }
+    track
+}
+
+fn mp4_audio_entry(format: &[u8; 4], entry: &[u8]) -> AudioTrack {
+    // Sound sample entry; QuickTime versions 1 and 2 extend it.
+    let (channels, sample_rate, children_at) = match be16(entry, 8) {
+        Some(2) => (
+            be32(entry, 40),
+            entry
+                .get(32..40)
+                .and_then(|b| b.try_into().ok())
+                .map(|b| f64::from_be_bytes(b) as u32),
+            64,
+        ),
+        version => (
+            be16(entry, 16).map(u32::from),
+            be32(entry, 24).map(|rate| rate >> 16),
+            if version == Some(1) { 44 } else { 28 },
+        ),
+    };
+    let codec = match format {
+        b"mp4a" => mp4::children(entry.get(children_at..).unwrap_or_default())
+            .into_iter()
+            .find(|(kind, _)| kind == b"esds")
+            .and_then(|(_, esds)| esds_codec(esds))
+            .unwrap_or("aac")
+            .to_string(),
+        b"ac-3" => "ac3".to_string(),
+        b"ec-3" => "eac3".to_string(),
+        b"ac-4" => "ac4".to_string(),
+        b"Opus" => "opus".to_string(),
+        b"fLaC" => "flac".to_string(),
+        b"alac" => "alac".to_string(),
+        b".mp3" | b"mp3 " => "mp3".to_string(),
+        b"dtsc" | b"dtsh" | b"dtsl" | b"dtse" => "dts".to_string(),
+        b"mlpa" => "truehd".to_string(),
+        b"lpcm" | b"sowt" | b"twos" | b"ipcm" => "pcm".to_string(),
+        other => fourcc_name(other),
+    };
+    AudioTrack {
+        codec,
+        channels: channels.filter(|&c| c > 0),
+        sample_rate: sample_rate.filter(|&r| r > 0),
+        ..AudioTrack::default()
+    }
+}
+
+/// MPEG-4 descriptor at `pos`: tag, body offset and body length.
+fn descriptor(buf: &[u8], pos: usize) -> Option<(u8, usize, usize)> {
+    let tag = *buf.get(pos)?;
+    let mut len = 0usize;
+    for k in 1..=4 {
+        let b = *buf.get(pos + k)?;
+        len = (len << 7) | (b & 0x7F) as usize;
+        if b & 0x80 == 0 {
+            return Some((tag, pos + k + 1, len));
+        }
+    }
+    None
+}
+
+/// Codec named by the object type in an `esds` box's decoder config.
+fn esds_codec(esds: &[u8]) -> Option<&'static str> {
+    // Full box header, then ES_Descriptor (3) holding DecoderConfigDescriptor (4).
+    let (tag, body, _) = descriptor(esds, 4)?;
+    if tag != 3 {
+        return None;
+    }
+    let flags = *esds.get(body + 2)?;
+    let mut pos = body + 3;
+    if flags & 0x80 != 0 {
+        pos += 2;
+    }
+    if flags & 0x40 != 0 {
+        pos += 1 + *esds.get(pos)? as usize;
+    }
+    if flags & 0x20 != 0 {
+        pos += 2;
+    }
+    let (tag, body, _) = descriptor(esds, pos)?;
+    if tag != 4 {
+        return None;
+    }
+    match esds.get(body)? {
+        0x40 | 0x66..=0x68 => Some("aac"),
+        0x69 | 0x6B => Some("mp3"),
+        0xA5 => Some("ac3"),
+        0xA6 => Some("eac3"),
+        0xA9..=0xAC => Some("dts"),
+        0xAD => Some("opus"),
+        _ => None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Matroska
+// ---------------------------------------------------------------------------
+
+/// Tracks from a Matroska Tracks element at the start of `tracks`, and the
+/// duration from an Info element when given.
+pub fn mkv_media_info(info: Option<&[u8]>, tracks: Option<&[u8]>) -> ProbedMedia {
+    let mut media = ProbedMedia::new(ContainerFormat::Matroska);
+    media.duration_secs = info.and_then(parse_info).and_then(|i| i.duration_secs);
+    let entries = tracks.and_then(body_children).unwrap_or_default();
+    for (_, entry) in entries
+        .into_iter()
+        .filter(|(id, _)| *id == EBML_TRACK_ENTRY)
+    {
+        mkv_track(entry, &mut media);
+    }
+    media
+}
+
+fn mkv_text(data: &[u8]) -> String {
+    String::from_utf8_lossy(data)
+        .trim_end_matches('\0')
+        .to_string()
+}
+
+fn mkv_track(entry: &[u8], media: &mut ProbedMedia) {
+    let fields = matroska::children(entry, 0, entry.len());
+    let field = |id: u64| fields.iter().find(|(f, _)| *f == id).map(|(_, data)| *data);
+    let codec_id = field(EBML_CODEC_ID).map(mkv_text).unwrap_or_default();
+    let codec = mkv_codec(&codec_id);
+    // Matroska's default language is English.
+    let language = match field(EBML_LANGUAGE_BCP47).or(field(EBML_LANGUAGE)) {
+        Some(code) => language(&mkv_text(code)),
+        None => Some("eng".to_string()),
+    };
+    let title = field(EBML_NAME).map(mkv_text).filter(|t| !t.is_empty());
+
+    match field(EBML_TRACK_TYPE).map(uint) {
+        Some(1) => {
+            let mut track = VideoTrack {
+                codec,
+                frame_rate: field(EBML_DEFAULT_DURATION)
+                    .map(uint)
+                    .filter(|&ns| ns > 0)
+                    .map(|ns| 1e9 / ns as f64),
+                ..VideoTrack::default()
+            };
+            if let Some(config) = field(EBML_CODEC_PRIVATE) {
+                apply_codec_config(&mut track, config);
+            }
+            let video = field(EBML_VIDEO).unwrap_or_default();
+            for (id, data) in matroska::children(video, 0, video.len()) {
+                match id {
+                    EBML_PIXEL_WIDTH => track.width = uint(data) as u32,
+                    EBML_PIXEL_HEIGHT => track.height = uint(data) as u32,
+                    EBML_COLOUR => {
+                        for (id, data) in matroska::children(data, 0, data.len()) {
+                            match id {
+                                EBML_BITS_PER_CHANNEL if uint(data) > 0 => {
+                                    track.bit_depth = Some(uint(data) as u8)
+                                }
+                                EBML_TRANSFER_CHARACTERISTICS => {
+                                    track.hdr |= is_hdr_transfer(uint(data))
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            track.dolby_vision = fields
+                .iter()
+                .filter(|(id, _)| *id == EBML_BLOCK_ADDITION_MAPPING)
+                .flat_map(|(_, mapping)| matroska::children(mapping, 0, mapping.len()))
+                .any(|(id, data)| {
+                    id == EBML_BLOCK_ADD_ID_TYPE
+                        && DOLBY_VISION_CONFIGS
+                            .iter()
+                            .any(|config| uint(data) == u32::from_be_bytes(**config) as u64)
+                });
+            media.video.push(track);
+        }
+        Some(2) => {
+            let audio = field(EBML_AUDIO).unwrap_or_default();
+            let settings = matroska::children(audio, 0, audio.len());
+            let setting = |id: u64| settings.iter().find(|(s, _)| *s == id).map(|(_, d)| *d);
+            media.audio.push(AudioTrack {
+                codec,
+                channels: setting(EBML_CHANNELS).map(|d| uint(d) as u32),
+                sample_rate: setting(EBML_SAMPLING_FREQUENCY)
+                    .and_then(float)
+                    .map(|hz| hz as u32),
+                language,
+                title,
+            });
+        }
+        Some(17) => media.subtitles.push(SubtitleTrack {
+            codec,
+            language,
+            title,
+            forced: field(EBML_FLAG_FORCED).is_some_and(|d| uint(d) == 1),
+        }),
+        _ => {}
+    }
+}
+
+/// Short name for a Matroska codec ID.
+fn mkv_codec(codec_id: &str) -> String {
+    let name = match codec_id {
+        "V_MPEG4/ISO/AVC" => "h264",
+        "V_MPEGH/ISO/HEVC" => "hevc",
+        "V_AV1" => "av1",
+        "V_VP9" => "vp9",
+        "V_VP8" => "vp8",
+        "V_MPEG2" => "mpeg2video",
+        "V_MPEG4/ISO/ASP" | "V_MPEG4/ISO/SP" | "V_MPEG4/ISO/AP" => "mpeg4",
+        "V_MS/VFW/FOURCC" => "vfw",
+        "A_AC3" => "ac3",
+        "A_EAC3" => "eac3",
+        "A_DTS" | "A_DTS/EXPRESS" | "A_DTS/LOSSLESS" => "dts",
+        "A_TRUEHD" => "truehd",
+        "A_OPUS" => "opus",
+        "A_FLAC" => "flac",
+        "A_VORBIS" => "vorbis",
+        "A_MPEG/L3" => "mp3",
+        "A_MPEG/L2" => "mp2",
+        "S_TEXT/UTF8" => "subrip",
+        "S_TEXT/ASS" | "S_ASS" => "ass",
+        "S_TEXT/SSA" | "S_SSA" => "ssa",
+        "S_TEXT/WEBVTT" => "webvtt",
+        "S_HDMV/PGS" => "pgs",
+        "S_VOBSUB" => "vobsub",
+        "S_DVBSUB" => "dvb_subtitle",
+        id if id.starts_with("A_AAC") => "aac",
+        id if id.starts_with("A_PCM") => "pcm",
+        id => return id.to_ascii_lowercase(),
+    };
+    name.to_string()
+}
+
+// ---------------------------------------------------------------------------
+// MPEG-TS / M2TS
+// ---------------------------------------------------------------------------
+
+/// Streams of the first program whose PAT and PMT appear in `head`: codecs
+/// and languages only. M2TS packets carry a 4-byte prefix.
+pub fn ts_media_info(head: &[u8], container: ContainerFormat) -> ProbedMedia {
+    let mut media = ProbedMedia::new(container);
+    let packet_size = if container == ContainerFormat::M2ts {
+        192
+    } else {
+        188
+    };
+    let packets: Vec<&[u8]> = head
+        .chunks_exact(packet_size)
+        .map(|p| &p[packet_size - 188..])
+        .filter(|p| p[0] == 0x47)
+        .collect();
+    let section = |pid: u16, table_id: u8| {
+        packets
+            .iter()
+            .filter(|p| ts_pid(p) == pid)
+            .find_map(|p| psi_section(p).filter(|s| s.first() == Some(&table_id)))
+    };
+
+    let Some(pat) = section(0, 0x00) else {
+        return media;
+    };
+    let Some(pmt_pid) = psi_body(pat)
+        .chunks_exact(4)
+        .find(|entry| be16(entry, 0) != Some(0))
+        .map(|entry| be16(entry, 2).unwrap_or(0) & 0x1FFF)
+    else {
+        return media;
+    };
+    let Some(pmt) = section(pmt_pid, 0x02) else {
+        return media;
+    };
+
+    let body = psi_body(pmt);
+    let program_info = (be16(body, 2).unwrap_or(0) & 0x0FFF) as usize;
+    let mut pos = 4 + program_info;
+    while pos + 5 <= body.len() {
+        let stream_type = body[pos];
+        let info_len = (be16(body, pos + 3).unwrap_or(0) & 0x0FFF) as usize;
+        let descriptors = body.get(pos + 5..pos + 5 + info_len).unwrap_or_default();
+        ts_stream(stream_type, descriptors, &mut media);
+        pos += 5 + info_len;
+    }
+    media
+}
+
+fn ts_pid(packet: &[u8]) -> u16 {
+    (((packet[1] & 0x1F) as u16) << 8) | packet[2] as u16
+}
+
+/// The PSI section starting in `packet`, if its payload starts one.
+fn psi_section(packet: &[u8]) -> Option<&[u8]> {
+    if packet.len() < 5 || packet[1] & 0x40 == 0 {
+        return None;
+    }
+    let control = (packet[3] >> 4) & 0x03;
+    if control & 0x01 == 0 {
+        return None;
+    }
+    let payload = if control == 3 {
+        5 + *packet.get(4)? as usize
+    } else {
+        4
+    };
+    let pointer = *packet.get(payload)? as usize;
+    packet.get(payload + 1 + pointer..)
+}
+
+/// Table body of a long-form section: after the 8-byte header, before the CRC.
+fn psi_body(section: &[u8]) -> &[u8] {
+    let length = (be16(section, 1).unwrap_or(0) & 0x0FFF) as usize;
+    let end = (3 + length).saturating_sub(4).min(section.len());
+    section.get(8..end).unwrap_or_default()
+}
+
+fn ts_stream(stream_type: u8, descriptors: &[u8], media: &mut ProbedMedia) {
+    let mut tags = Vec::new();
+    let mut pos = 0;
+    while pos + 2 <= descriptors.len() {
+        let len = descriptors[pos + 1] as usize;
+        let data = descriptors.get(pos + 2..pos + 2 + len).unwrap_or_default();
+        tags.push((descriptors[pos], data));
+        pos += 2 + len;
+    }
+    let has = |tag: u8| tags.iter().any(|(t, _)| *t == tag);
+    // ISO 639 language, DVB subtitling and teletext descriptors lead with it.
+    let language = tags
+        .iter()
+        .find(|(t, d)| matches!(t, 0x0A | 0x59 | 0x56) && d.len() >= 3)
+        .and_then(|(_, d)| language(&String::from_utf8_lossy(&d[..3])));
+    let registration = tags
+        .iter()
+        .find(|(t, d)| *t == 0x05 && d.len() >= 4)
+        .map(|(_, d)| &d[..4]);
+
+    let video = |codec: &str| VideoTrack {
+        codec: codec.to_string(),
+        ..VideoTrack::default()
+    };
+    let audio = |codec: &str| AudioTrack {
+        codec: codec.to_string(),
+        language: language.clone(),
+        ..AudioTrack::default()
+    };
+    let subtitle = |codec: &str| SubtitleTrack {
+        codec: codec.to_string(),
+        language: language.clone(),
+        ..SubtitleTrack::default()
+    };
+    match stream_type {
+        0x01 => media.video.push(video("mpeg1video")),
+        0x02 => media.video.push(video("mpeg2video")),
+        0x10 => media.video.push(video("mpeg4")),
+        0x1B => media.video.push(video("h264")),
+        0x24 => media.video.push(video("hevc")),
+        0xEA => media.video.push(video("vc1")),
+        0x03 | 0x04 => media.audio.push(audio("mp2")),
+        0x0F => media.audio.push(audio("aac")),
+        0x11 => media.audio.push(audio("aac_latm")),
+        0x80 => media.audio.push(audio("pcm")),
+        0x81 => media.audio.push(audio("ac3")),
+        0x82 | 0x85 | 0x86 | 0xA2 => media.audio.push(audio("dts")),
+        0x83 => media.audio.push(audio("truehd")),
+        0x84 | 0x87 | 0xA1 => media.audio.push(audio("eac3")),
+        0x90 => media.subtitles.push(subtitle("pgs")),
+        0x92 => media.subtitles.push(subtitle("text")),
+        // PES private data: the descriptors say what it carries.
+        0x06 => {
+            if has(0x6A) || registration == Some(b"AC-3") {
+                media.audio.push(audio("ac3"));
+            } else if has(0x7A) {
+                media.audio.push(audio("eac3"));
+            } else if has(0x7B) {
+                media.audio.push(audio("dts"));
+            } else if registration == Some(b"Opus") {
+                media.audio.push(audio("opus"));
+            } else if has(0x59) {
+                media.subtitles.push(subtitle("dvb_subtitle"));
+            } else if has(0x56) {
+                media.subtitles.push(subtitle("dvb_teletext"));
+            }
+        }
+        _ => {}
+    }
+}
+
+// ---------------------------------------------------------------------------
+// AVI
+// ---------------------------------------------------------------------------
+
+/// (id, body) of each RIFF chunk in `buf`. A `LIST` body starts with its
+/// list type.
+fn riff_chunks(buf: &[u8]) -> Vec<([u8; 4], &[u8])> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+    while pos + 8 <= buf.len() {
+        let id: [u8; 4] = buf[pos..pos + 4].try_into().unwrap();
+        let size = le32(buf, pos + 4).unwrap_or(0) as usize;
+        let end = (pos + 8 + size).min(buf.len());
+        out.push((id, &buf[pos + 8..end]));
+        pos += 8 + size + (size & 1);
+    }
+    out
+}
+
+/// Streams from the `hdrl` list in the head of an AVI file.
+pub fn avi_media_info(head: &[u8]) -> ProbedMedia {
+    let mut media = ProbedMedia::new(ContainerFormat::Avi);
+    let Some(hdrl) = riff_chunks(head.get(12..).unwrap_or_default())
+        .into_iter()
+        .find(|(id, body)| id == b"LIST" && body.starts_with(b"hdrl"))
+        .map(|(_, body)| &body[4..])
+    else {
+        return media;
+    };
+
+    let mut frame_usecs = None;
+    let mut total_frames = None;
+    for (id, body) in riff_chunks(hdrl) {
+        if &id == b"avih" {
+            frame_usecs = le32(body, 0).filter(|&us| us > 0);
+            total_frames = le32(body, 16).filter(|&n| n > 0);
+        } else if &id == b"LIST" && body.starts_with(b"strl") {
+            avi_stream(&body[4..], &mut media);
+        }
+    }
+    if media.duration_secs.is_none() {
+        if let (Some(us), Some(frames)) = (frame_usecs, total_frames) {
+            media.duration_secs = Some(frames as f64 * us as f64 / 1e6);
+        }
+    }
+    media
+}
+
+fn avi_stream(strl: &[u8], media: &mut ProbedMedia) {
+    let chunks = riff_chunks(strl);
+    let chunk = |id: &[u8; 4]| chunks.iter().find(|(c, _)| c == id).map(|(_, b)| *b);
+    let (Some(strh), Some(strf)) = (chunk(b"strh"), chunk(b"strf")) else {
+        return;
+    };
+    let title = chunk(b"strn").map(mkv_text).filter(|t| !t.is_empty());
+    let scale = le32(strh, 20).unwrap_or(0);
+    let rate = le32(strh, 24).unwrap_or(0);
+    let length = le32(strh, 32).unwrap_or(0);
+
+    match strh.get(0..4) {
+        Some(b"vids") => {
+            if scale > 0 && rate > 0 && length > 0 {
+                media.duration_secs = Some(length as f64 * scale as f64 / rate as f64);
+            }
+            let codec = strf.get(16..20).unwrap_or_default();
+            media.video.push(VideoTrack {
+                codec: avi_video_codec(codec),
+                width: le32(strf, 4).unwrap_or(0),
+                height: le32(strf, 8).map_or(0, |h| (h as i32).unsigned_abs()),
+                frame_rate: (scale > 0 && rate > 0).then(|| rate as f64 / scale as f64),
+                ..VideoTrack::default()
+            });
+        }
+        Some(b"auds") => media.audio.push(AudioTrack {
+            codec: avi_audio_codec(le16(strf, 0).unwrap_or(0)),
+            channels: le16(strf, 2).map(u32::from).filter(|&c| c > 0),
+            sample_rate: le32(strf, 4).filter(|&r| r > 0),
+            title,
+            ..AudioTrack::default()
+        }),
+        Some(b"txts") => media.subtitles.push(SubtitleTrack {
+            codec: "text".to_string(),
+            title,
+            ..SubtitleTrack::default()
+        }),
+        _ => {}
+    }
+}
+
+fn avi_video_codec(fourcc: &[u8]) -> String {
+    let name = match fourcc.to_ascii_uppercase().as_slice() {
+        b"H264" | b"X264" | b"AVC1" | b"DAVC" => "h264",
+        b"HEVC" | b"H265" | b"HVC1" | b"X265" => "hevc",
+        b"XVID" | b"DIVX" | b"DX50" | b"FMP4" | b"MP4V" => "mpeg4",
+        b"DIV3" | b"MP43" => "msmpeg4v3",
+        b"MJPG" => "mjpeg",
+        b"WMV3" => "wmv3",
+        b"WVC1" => "vc1",
+        b"MPG2" => "mpeg2video",
+        _ => return fourcc_name(fourcc),
+    };
+    name.to_string()
+}
+
+fn avi_audio_codec(format_tag: u16) -> String {
+    let name = match format_tag {
+        0x0001 => "pcm",
+        0x0050 => "mp2",
+        0x0055 => "mp3",
+        0x00FF | 0x1610 | 0x706D => "aac",
+        0x0161 => "wmav2",
+        0x0162 => "wmapro",
+        0x2000 => "ac3",
+        0x2001 => "dts",
+        0x566F | 0x674F..=0x6771 => "vorbis",
+        tag => return format!("0x{:04x}", tag),
+    };
+    name.to_string()
+}
+
+// ---------------------------------------------------------------------------
+// FLV
+// ---------------------------------------------------------------------------
+
+/// Codecs from the first audio and video tags in the head of an FLV file,
+/// with duration, size and frame rate from `onMetaData`.
+pub fn flv_media_info(head: &[u8]) -> ProbedMedia {
+    let mut media = ProbedMedia::new(ContainerFormat::Flv);
+    let mut metadata: Option<&[u8]> = None;
+    let mut pos = be32(head, 5).unwrap_or(9) as usize + 4;
+    while pos + 11 <= head.len() && (media.video.is_empty() || media.audio.is_empty()) {
+        let size = (be32(head, pos).unwrap_or(0) & 0x00FF_FFFF) as usize;
+        let Some(data) = head.get(pos + 11..pos + 11 + size) else {
+            break;
+        };
+        match head[pos] & 0x1F {
+            8 if media.audio.is_empty() => media.audio.extend(flv_audio(data)),
+            9 if media.video.is_empty() => media.video.extend(flv_video(data)),
+            18 if metadata.is_none() => metadata = Some(data),
+            _ => {}
+        }
+        pos += 11 + size + 4;
+    }
+
+    if let Some(meta) = metadata {
+        media.duration_secs = amf_number(meta, "duration").filter(|&d| d > 0.0);
+        if let Some(video) = media.video.first_mut() {
+            video.width = amf_number(meta, "width").map_or(0, |w| w as u32);
+            video.height = amf_number(meta, "height").map_or(0, |h| h as u32);
+            video.frame_rate = amf_number(meta, "framerate").filter(|&r| r > 0.0);
+        }
+    }
+    media
+}
+
+fn flv_video(data: &[u8]) -> Option<VideoTrack> {
+    let first = *data.first()?;
+    // Enhanced RTMP: a FourCC follows the frame and packet type byte.
+    let (codec, config) = if first & 0x80 != 0 {
+        let codec = match data.get(1..5)? {
+            b"hvc1" => "hevc".to_string(),
+            b"av01" => "av1".to_string(),
+            b"vp09" => "vp9".to_string(),
+            b"avc1" => "h264".to_string(),
+            other => fourcc_name(other),
+        };
+        let sequence_start = first & 0x0F == 0;
+        (codec, data.get(5..).filter(|_| sequence_start))
+    } else {
+        let codec = match first & 0x0F {
+            2 => "flv1",
+            3 => "flashsv",
+            4 => "vp6f",
+            5 => "vp6a",
+            7 => "h264",
+            12 => "hevc",
+            _ => return None,
+        };
+        // AVC packet type 0 carries the decoder configuration record.
+        let sequence_header = data.get(1) == Some(&0);
+        (codec.to_string(), data.get(5..).filter(|_| sequence_header))
+    };
+    let mut track = VideoTrack {
+        codec,
+        ..VideoTrack::default()
+    };
+    if let Some(config) = config {
+        apply_codec_config(&mut track, config);
+    }
+    Some(track)
+}
+
+fn flv_audio(data: &[u8]) -> Option<AudioTrack> {
+    const RATES: [u32; 4] = [5512, 11025, 22050, 44100];
+    const AAC_RATES: [u32; 13] = [
+        96000, 88200, 64000, 48000, 44100, 32000, 24000, 22050, 16000, 12000, 11025, 8000, 7350,
+    ];
+    let first = *data.first()?;
+    let codec = match first >> 4 {
+        0 | 3 => "pcm",
+        1 => "adpcm_swf",
+        2 | 14 => "mp3",
+        4..=6 => "nellymoser",
+        7 => "pcm_alaw",
+        8 => "pcm_mulaw",
+        10 => "aac",
+        11 => "speex",
+        _ => return None,
+    };
+    let mut track = AudioTrack {
+        codec: codec.to_string(),
+        channels: Some(if first & 0x01 != 0 { 2 } else { 1 }),
+        sample_rate: Some(RATES[((first >> 2) & 0x03) as usize]),
+        ..AudioTrack::default()
+    };
+    // The AAC sequence header's AudioSpecificConfig has the real values.
+    if codec == "aac" && data.get(1) == Some(&0) {
+        if let Some(config) = be16(data, 2) {
+            let rate_index = ((config >> 7) & 0x0F) as usize;
+            let channels = ((config >> 3) & 0x0F) as u32;
+            if let Some(&rate) = AAC_RATES.get(rate_index) {
+                track.sample_rate = Some(rate);
+            }
+            if channels > 0 {
+                track.channels = Some(channels);
+            }
+        }
+    }
+    Some(track)
+}
+
+/// A number property of an AMF0 object: the key's length-prefixed name,
+/// the number marker, then a big-endian double.
+fn amf_number(data: &[u8], key: &str) -> Option<f64> {
+    let mut pattern = (key.len() as u16).to_be_bytes().to_vec();
+    pattern.extend_from_slice(key.as_bytes());
+    pattern.push(0x00);
+    let at = data
+        .windows(pattern.len())
+        .position(|window| window == pattern.as_slice())?
+        + pattern.len();
+    Some(f64::from_be_bytes(data.get(at..at + 8)?.try_into().ok()?))
+}
@@ -1,3 +1,8 @@
 // Container detection — identifies file format from magic bytes and extension.
 
+pub mod bitrate;
 pub mod container;
+pub mod fmp4;
+pub mod matroska;
+pub mod media_info;
+pub mod mp4;
@@ -0,0 +1,341 @@
+// MP4 sample tables — per-track keyframe index from a complete `moov` box.
+//
+// Decode times come from `stts`, sync samples from `stss` (every sample when
+// it is absent), sizes from `stsz`/`stz2` and chunk placement from `stsc` with
+// `stco`/`co64`. Walking them together gives the file offset of each keyframe.
+
+use anyhow::{anyhow, bail, Result};
+use tracing::debug;
+
+use super::bitrate::mp4_duration;
+use super::container::box_header;
+use crate::config::{MP4_ALL_SYNC_INDEX_SPACING_MS, MP4_MAX_TRACK_SAMPLES};
+
+/// A sync sample: where decoding can start.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Keyframe {
+    /// Decode time in seconds.
+    pub time_secs: f64,
+    pub offset: u64,
+    pub size: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Mp4Track {
+    pub track_id: u32,
+    /// `hdlr` handler type: `vide`, `soun`, `subt`, ...
+    pub handler: [u8; 4],
+    pub timescale: u32,
+    pub sample_count: u32,
+    /// Sync samples in time order. Tracks where every sample is a sync
+    /// sample (most audio) keep about one per second.
+    pub keyframes: Vec<Keyframe>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Mp4Index {
+    pub duration_secs: Option<f64>,
+    pub tracks: Vec<Mp4Track>,
+}
+
+impl Mp4Index {
+    /// The first video track with keyframes, else the first track with any.
+    pub fn primary_track(&self) -> Option<&Mp4Track> {
+        let indexed = || self.tracks.iter().filter(|t| !t.keyframes.is_empty());
+        indexed()
+            .find(|t| &t.handler == b"vide")
+            .or_else(|| indexed().next())
+    }
+}
+
+/// Parse a complete `moov` box starting at `moov[0]`.
+pub fn parse_moov(moov: &[u8]) -> Result<Mp4Index> {
+    let (size, kind, header_len) =
+        box_header(moov, moov.len() as u64).ok_or_else(|| anyhow!("truncated box header"))?;
+    if &kind != b"moov" {
+        bail!("expected moov, found {:?}", String::from_utf8_lossy(&kind));
+    }
+    let body = moov
+        .get(header_len..size as usize)
+        .ok_or_else(|| anyhow!("moov truncated: {} of {} bytes", moov.len(), size))?;
+
+    let mut tracks = Vec::new();
+    for (kind, trak) in children(body) {
+        if &kind != b"trak" {
+            continue;
+        }
+        match parse_trak(trak) {
+            Ok(track) => tracks.push(track),
+            Err(e) => debug!("skipping mp4 track: {}", e),
+        }
+    }
+    Ok(Mp4Index {
+        duration_secs: mp4_duration(moov),
+        tracks,
+    })
+}
+
+/// (type, body) of each box directly inside `body`.
+pub(crate) fn children(body: &[u8]) -> Vec<([u8; 4], &[u8])> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+    while pos + 8 <= body.len() {
+        let rest = &body[pos..];
+        let Some((size, kind, header_len)) = box_header(rest, rest.len() as u64) else {
+            break;
+        };
+        let Some(inner) = rest.get(header_len..size as usize) else {
+            break;
+        };
+        out.push((kind, inner));
+        pos += size as usize;
+    }
+    out
+}
+
+pub(crate) fn child<'a>(body: &'a [u8], kind: &[u8; 4]) -> Result<&'a [u8]> {
+    children(body)
+        .into_iter()
+        .find(|(k, _)| k == kind)
+        .map(|(_, inner)| inner)
+        .ok_or_else(|| anyhow!("missing {} box", String::from_utf8_lossy(kind)))
+}
+
+fn be32(buf: &[u8], at: usize) -> Result<u32> {
+    buf.get(at..at + 4)
+        .map(|b| u32::from_be_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| anyhow!("box too short"))
+}
+
+fn be64(buf: &[u8], at: usize) -> Result<u64> {
+    buf.get(at..at + 8)
+        .map(|b| u64::from_be_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| anyhow!("box too short"))
+}
+
+/// Entries of a full-box table with a 32-bit count at byte 4.
+fn table(body: &[u8], entry_size: usize) -> Result<Vec<&[u8]>> {
+    let count = be32(body, 4)? as usize;
+    let data = body
+        .get(8..)
+        .filter(|d| d.len() / entry_size >= count)
+        .ok_or_else(|| anyhow!("table claims {} entries past its box", count))?;
+    Ok(data.chunks_exact(entry_size).take(count).collect())
+}
+
+/// Sample sizes: one value for all, or one per sample.
+struct SampleSizes {
+    fixed: u32,
+    table: Vec<u32>,
+}
+
+impl SampleSizes {
+    fn get(&self, sample: usize) -> u32 {
+        if self.fixed != 0 {
+            self.fixed
+        } else {
+            self.table.get(sample).copied().unwrap_or(0)
+        }
+    }
+}
+
+/// Sizes and sample count from `stsz` or the compact `stz2`.
+fn sample_sizes(stbl: &[u8]) -> Result<(SampleSizes, u32)> {
+    if let Ok(stsz) = child(stbl, b"stsz") {
+        let fixed = be32(stsz, 4)?;
+        let count = be32(stsz, 8)?;
+        let table = if fixed == 0 {
+            let data = stsz
+                .get(12..)
+                .filter(|d| d.len() / 4 >= count as usize)
+                .ok_or_else(|| anyhow!("stsz claims {} samples past its box", count))?;
+            data.chunks_exact(4)
+                .take(count as usize)
+                .map(|b| u32::from_be_bytes(b.try_into().unwrap()))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        return Ok((SampleSizes { fixed, table }, count));
+    }
+
+    let stz2 = child(stbl, b"stz2")?;
+    let field_bits = *stz2.get(7).ok_or_else(|| anyhow!("stz2 too short"))? as usize;
+    let count = be32(stz2, 8)? as usize;
+    let data = stz2.get(12..).unwrap_or_default();
+    if !matches!(field_bits, 4 | 8 | 16) || data.len() * 8 / field_bits < count {
+        bail!("bad stz2: {} samples of {} bits", count, field_bits);
+    }
+    let table = (0..count)
+        .map(|i| match field_bits {
+            4 => (data[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) as u32 & 0x0F,
+            8 => data[i] as u32,
+            _ => u16::from_be_bytes([data[2 * i], data[2 * i + 1]]) as u32,
+        })
+        .collect();
+    Ok((SampleSizes { fixed: 0, table }, count as u32))
+}
+
+fn parse_trak(trak: &[u8]) -> Result<Mp4Track> {
+    let tkhd = child(trak, b"tkhd")?;
+    let track_id = be32(tkhd, if tkhd.first() == Some(&1) { 20 } else { 12 })?;
+
+    let mdia = child(trak, b"mdia")?;
+    let mdhd = child(mdia, b"mdhd")?;
+    let timescale = be32(mdhd, if mdhd.first() == Some(&1) { 20 } else { 12 })?;
+    if timescale == 0 {
+        bail!("track {} has timescale 0", track_id);
+    }
+    let handler: [u8; 4] = child(mdia, b"hdlr")?
+        .get(8..12)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| anyhow!("hdlr too short"))?;
+
+    let stbl = child(child(mdia, b"minf")?, b"stbl")?;
+    let stts: Vec<(u32, u32)> = table(child(stbl, b"stts")?, 8)?
+        .into_iter()
+        .map(|e| (be32(e, 0).unwrap(), be32(e, 4).unwrap()))
+        .collect();
+    let sync: Option<Vec<u32>> = match child(stbl, b"stss") {
+        Ok(stss) => Some(
+            table(stss, 4)?
+                .into_iter()
+                .map(|e| be32(e, 0).unwrap())
+                .collect(),
+        ),
+        Err(_) => None,
+    };
+    let stsc: Vec<(u32, u32)> = table(child(stbl, b"stsc")?, 12)?
+        .into_iter()
+        .map(|e| (be32(e, 0).unwrap(), be32(e, 4).unwrap()))
+        .collect();
+    let (sizes, sample_count) = sample_sizes(stbl)?;
+    let chunk_offsets: Vec<u64> = match child(stbl, b"stco") {
+        Ok(stco) => table(stco, 4)?
+            .into_iter()
+            .map(|e| be32(e, 0).unwrap() as u64)
+            .collect(),
+        Err(_) => table(child(stbl, b"co64")?, 8)?
+            .into_iter()
+            .map(|e| be64(e, 0).unwrap())
+            .collect(),
+    };
+    let capacity = chunk_table_capacity(chunk_offsets.len(), &stsc);
+    if sample_count as u64 > capacity || sample_count > MP4_MAX_TRACK_SAMPLES {
+        bail!(
+            "track {} claims {} samples; chunk tables hold {}",
+            track_id,
+            sample_count,
+            capacity
+        );
+    }
+
+    let keyframes = walk_samples(
+        &chunk_offsets,
+        &stsc,
+        &sizes,
+        sample_count,
+        &stts,
+        sync.as_deref(),
+        timescale,
+    );
+    Ok(Mp4Track {
+        track_id,
+        handler,
+        timescale,
+        sample_count,
+        keyframes,
+    })
+}
+
+/// Samples the chunk tables can place: each stsc run's samples per chunk
+/// times the chunks it covers.
+fn chunk_table_capacity(chunk_count: usize, stsc: &[(u32, u32)]) -> u64 {
+    let last_chunk = chunk_count as u64;
+    stsc.iter()
+        .enumerate()
+        .map(|(i, &(first, per_chunk))| {
+            // walk_samples applies the first run from chunk 1 onwards.
+            let first = if i == 0 { 1 } else { first as u64 };
+            let end = stsc.get(i + 1).map_or(last_chunk + 1, |&(next, _)| {
+                (next as u64).min(last_chunk + 1)
+            });
+            end.saturating_sub(first).saturating_mul(per_chunk as u64)
+        })
+        .fold(0, u64::saturating_add)
+}
+
+/// Visit samples chunk by chunk, tracking offset and decode time, and keep
+/// the sync samples.
+fn walk_samples(
+    chunk_offsets: &[u64],
+    stsc: &[(u32, u32)],
+    sizes: &SampleSizes,
+    sample_count: u32,
+    stts: &[(u32, u32)],
+    sync: Option<&[u32]>,
+    timescale: u32,
+) -> Vec<Keyframe> {
+    let spacing = MP4_ALL_SYNC_INDEX_SPACING_MS as f64 / 1000.0;
+    let mut keyframes: Vec<Keyframe> = Vec::new();
+    let mut sample = 0u32;
+    let mut dts = 0u64;
+    let (mut stts_entry, mut stts_left) = (0, stts.first().map_or(0, |e| e.0));
+    let mut stsc_entry = 0;
+    let mut sync_pos = 0;
+
+    'chunks: for (c, &chunk_offset) in chunk_offsets.iter().enumerate() {
+        let chunk_number = c as u32 + 1;
+        while stsc
+            .get(stsc_entry + 1)
+            .is_some_and(|&(first, _)| first <= chunk_number)
+        {
+            stsc_entry += 1;
+        }
+        let per_chunk = stsc.get(stsc_entry).map_or(0, |e| e.1);
+
+        let mut offset = chunk_offset;
+        for _ in 0..per_chunk {
+            if sample >= sample_count {
+                break 'chunks;
+            }
+            let size = sizes.get(sample as usize);
+            let is_sync = match sync {
+                None => true,
+                Some(list) => {
+                    while list.get(sync_pos).is_some_and(|&n| n < sample + 1) {
+                        sync_pos += 1;
+                    }
+                    list.get(sync_pos) == Some(&(sample + 1))
+                }
+            };
+            if is_sync {
+                let time_secs = dts as f64 / timescale as f64;
+                let thinned = sync.is_none()
+                    && keyframes
+                        .last()
+                        .is_some_and(|k| time_secs - k.time_secs < spacing);
+                if !thinned {
+                    keyframes.push(Keyframe {
+                        time_secs,
+                        offset,
+                        size,
+                    });
+                }
+            }
+
+            while stts_left == 0 && stts_entry + 1 < stts.len() {
+                stts_entry += 1;
+                stts_left = stts[stts_entry].0;
+            }
+            dts += stts.get(stts_entry).map_or(0, |e| e.1) as u64;
+            stts_left = stts_left.saturating_sub(1);
+            let Some(next) = offset.checked_add(size as u64) else {
+                break 'chunks;
+            };
+            offset = next;
+            sample += 1;
+        }
+    }
+    keyframes
+}
@@ -0,0 +1,142 @@
+// Bandwidth limiting — token buckets pacing downloads per session and engine-wide.
+//
+// Background transfers wait for tokens; urgent transfers take them at once
+// and may run the bucket into debt, which later background work repays.
+
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+use crate::config::{BANDWIDTH_BURST_MS, BANDWIDTH_RECHECK_MS};
+
+/// The engine-wide limiter shared by every session.
+pub fn global_bandwidth() -> &'static BandwidthLimiter {
+    static GLOBAL: OnceLock<BandwidthLimiter> = OnceLock::new();
+    GLOBAL.get_or_init(BandwidthLimiter::new)
+}
+
+struct Bucket {
+    /// Bytes per second; 0 means unlimited.
+    rate: u64,
+    /// Negative while urgent traffic has borrowed ahead.
+    tokens: f64,
+    last: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.last = now;
+        let burst = self.rate as f64 * BANDWIDTH_BURST_MS as f64 / 1000.0;
+        self.tokens = (self.tokens + elapsed * self.rate as f64).min(burst);
+    }
+
+    /// `None` once `bytes` are taken, otherwise how long until the debt is
+    /// paid off.
+    fn take(&mut self, bytes: u64, urgent: bool, now: Instant) -> Option<Duration> {
+        if self.rate == 0 {
+            return None;
+        }
+        self.refill(now);
+        if urgent || self.tokens >= 0.0 {
+            self.tokens -= bytes as f64;
+            return None;
+        }
+        Some(Duration::from_secs_f64(-self.tokens / self.rate as f64))
+    }
+}
+
+pub struct BandwidthLimiter {
+    bucket: Mutex<Bucket>,
+}
+
+impl Default for BandwidthLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BandwidthLimiter {
+    /// Unlimited until a limit is set.
+    pub fn new() -> Self {
+        Self {
+            bucket: Mutex::new(Bucket {
+                rate: 0,
+                tokens: 0.0,
+                last: Instant::now(),
+            }),
+        }
+    }
+
+    /// Bytes per second; 0 means unlimited.
+    pub fn limit(&self) -> u64 {
+        self.bucket.lock().rate
+    }
+
+    /// Change the limit; takes effect for transfers already waiting.
+    pub fn set_limit(&self, bytes_per_sec: u64) {
+        let mut bucket = self.bucket.lock();
+        bucket.refill(Instant::now());
+        bucket.rate = bytes_per_sec;
+        bucket.tokens = bucket.tokens.clamp(-(bytes_per_sec as f64), 0.0);
+    }
+
+    /// Account for `bytes` received, waiting first unless `urgent`.
+    pub async fn consume(&self, bytes: u64, urgent: bool) {
+        loop {
+            let Some(wait) = self.bucket.lock().take(bytes, urgent, Instant::now()) else {
+                return;
+            };
+            tokio::time::sleep(wait.min(Duration::from_millis(BANDWIDTH_RECHECK_MS))).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_background_waits_and_urgent_borrows() {
+        let now = Instant::now();
+        let mut bucket = Bucket {
+            rate: 1000,
+            tokens: 0.0,
+            last: now,
+        };
+        assert_eq!(bucket.take(500, false, now), None);
+        assert_eq!(
+            bucket.take(500, false, now),
+            Some(Duration::from_millis(500))
+        );
+
+        // Urgent traffic goes through and deepens the debt.
+        assert_eq!(bucket.take(1500, true, now), None);
+        assert_eq!(
+            bucket.take(100, false, now + Duration::from_millis(500)),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(bucket.take(100, false, now + Duration::from_secs(2)), None);
+
+        // Idle time saves up at most one burst.
+        assert_eq!(bucket.take(0, false, now + Duration::from_secs(60)), None);
+        assert_eq!(bucket.tokens, 1000.0);
+    }
+
+    #[test]
+    fn test_unlimited_by_default_and_limit_changes() {
+        let limiter = BandwidthLimiter::new();
+        assert_eq!(limiter.limit(), 0);
+        assert_eq!(
+            limiter.bucket.lock().take(u64::MAX, false, Instant::now()),
+            None
+        );
+
+        limiter.set_limit(2000);
+        limiter.bucket.lock().tokens = -1_000_000.0;
+        // A new limit forgives debt beyond one second at that rate.
+        limiter.set_limit(1000);
+        assert!(limiter.bucket.lock().tokens >= -1000.0);
+    }
+}
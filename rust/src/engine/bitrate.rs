@@ -0,0 +1,102 @@
+// Playback bitrate — how many bytes per second of the file playback consumes.
+//
+// The container's duration gives the real average. Until it is known, the
+// rate the player has been reading at stands in for it.
+
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+use crate::config::BITRATE_MIN_OBSERVE_SECS;
+
+struct State {
+    /// From container metadata.
+    container: Option<f64>,
+    /// When the player was first served, and bytes served since.
+    first_served: Option<Instant>,
+    served: u64,
+}
+
+pub struct BitrateEstimator {
+    state: Mutex<State>,
+}
+
+impl Default for BitrateEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BitrateEstimator {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(State {
+                container: None,
+                first_served: None,
+                served: 0,
+            }),
+        }
+    }
+
+    /// Average bytes per second derived from the container's duration.
+    pub fn set_container_rate(&self, bytes_per_sec: f64) {
+        if bytes_per_sec.is_finite() && bytes_per_sec > 0.0 {
+            self.state.lock().container = Some(bytes_per_sec);
+        }
+    }
+
+    /// The container-derived rate alone; serve progress is too bursty to
+    /// map media time to bytes.
+    pub fn container_rate(&self) -> Option<f64> {
+        self.state.lock().container
+    }
+
+    pub fn record_served(&self, bytes: u64, now: Instant) {
+        let mut state = self.state.lock();
+        state.first_served.get_or_insert(now);
+        state.served += bytes;
+    }
+
+    /// Bytes per second playback consumes: the container's figure, else
+    /// observed serve progress once it has run long enough, else unknown.
+    pub fn bytes_per_sec(&self, now: Instant) -> Option<f64> {
+        let state = self.state.lock();
+        if let Some(rate) = state.container {
+            return Some(rate);
+        }
+        let elapsed = now.duration_since(state.first_served?);
+        if elapsed < Duration::from_secs(BITRATE_MIN_OBSERVE_SECS) || state.served == 0 {
+            return None;
+        }
+        Some(state.served as f64 / elapsed.as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_observed_rate_until_container_known() {
+        let estimator = BitrateEstimator::new();
+        let start = Instant::now();
+        assert_eq!(estimator.bytes_per_sec(start), None);
+
+        estimator.record_served(1_000_000, start);
+        assert_eq!(
+            estimator.bytes_per_sec(start + Duration::from_secs(1)),
+            None
+        );
+        estimator.record_served(1_000_000, start + Duration::from_secs(4));
+        assert_eq!(
+            estimator.bytes_per_sec(start + Duration::from_secs(4)),
+            Some(500_000.0)
+        );
+
+        estimator.set_container_rate(250_000.0);
+        assert_eq!(
+            estimator.bytes_per_sec(start + Duration::from_secs(4)),
+            Some(250_000.0)
+        );
+    }
+}
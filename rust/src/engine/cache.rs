@@ -1,34 +1,213 @@
-// On-disk chunk cache backed by memory-mapped files and a bitvec completion map.
-
-use std::fs::{self, OpenOptions};
+// Chunk cache — a bitvec completion map over a pluggable `CacheStore`.
+//
+// Each persistent cache is the store's data (`{key}.cache` or `{key}.chunks/`)
+// plus a `{key}.json` sidecar holding the completion bitmap and the upstream
+// validators, so reopening the same file resumes from the chunks already on
+// disk instead of downloading them again. Memory-only caches skip the sidecar.
+//
+// A cache may also carry a byte budget: once exceeded, chunks far from the
+// playback offset are dropped and their disk blocks released (hole punching),
+// so a sparse file for a huge remux never holds more than the budget.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use anyhow::{anyhow, Result};
 use bitvec::prelude::*;
-use memmap2::MmapMut;
-use parking_lot::RwLock;
+use bytes::Bytes;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use super::store::{
+    cache_chunks_dir, remove_store_data, CacheBackend, CacheStore, ChunkFileStore, MemoryStore,
+    MmapStore,
+};
+use crate::config::{
+    CACHE_EVICT_KEEP_BEHIND_BYTES, CACHE_META_WRITE_INTERVAL_SECS, MEMORY_CACHE_MAX_BYTES,
+};
+
+pub use super::store::cache_data_path;
+
+const CACHE_META_VERSION: u32 = 1;
+
+/// Upstream identity a cache was filled from. Two validators conflict only
+/// when both sides carry the same kind of value and the values differ.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheValidator {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl CacheValidator {
+    fn matches(&self, other: &CacheValidator) -> bool {
+        if let (Some(a), Some(b)) = (&self.etag, &other.etag) {
+            return a == b;
+        }
+        if let (Some(a), Some(b)) = (&self.last_modified, &other.last_modified) {
+            return a == b;
+        }
+        true
+    }
+}
+
+/// Sidecar persisted next to the data file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheMeta {
+    pub version: u32,
+    pub content_length: u64,
+    pub chunk_size: u64,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// Milliseconds since the Unix epoch.
+    pub last_access_at: u64,
+    pub downloaded_chunks: Vec<usize>,
+    /// Bytes already written, from the chunk start, of chunks that were
+    /// still downloading.
+    #[serde(default)]
+    pub partial_chunks: BTreeMap<usize, u64>,
+    #[serde(default)]
+    pub file_key: Option<String>,
+    /// Display name for the settings page.
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Pinned caches are never evicted by the engine budget.
+    #[serde(default)]
+    pub pinned: bool,
+    /// Sidecars written before backends existed describe an mmap file.
+    #[serde(default = "legacy_backend")]
+    pub backend: CacheBackend,
+}
+
+fn legacy_backend() -> CacheBackend {
+    CacheBackend::Mmap
+}
+
+impl CacheMeta {
+    /// Read a sidecar, returning `None` if it is missing or unreadable.
+    pub fn load(path: &Path) -> Option<Self> {
+        let raw = fs::read(path).ok()?;
+        match serde_json::from_slice::<CacheMeta>(&raw) {
+            Ok(meta) if meta.version == CACHE_META_VERSION => Some(meta),
+            Ok(meta) => {
+                debug!("cache meta {} has version {}", path.display(), meta.version);
+                None
+            }
+            Err(e) => {
+                warn!("cache meta {} unreadable: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Write atomically via a temp file so a crash never leaves a torn sidecar.
+    pub fn store(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec(self)?)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+/// User-facing labels carried in the sidecar alongside the chunk map.
+#[derive(Debug, Clone, Default)]
+pub struct CacheLabel {
+    pub file_key: Option<String>,
+    pub title: Option<String>,
+    pub pinned: bool,
+}
+
+pub(crate) fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Path of the sidecar for a cache key.
+pub fn cache_meta_path(cache_dir: &Path, key: &str) -> PathBuf {
+    cache_dir.join(format!("{}.json", key))
+}
 
 pub struct DiskCache {
-    mmap: RwLock<MmapMut>,
+    store: Box<dyn CacheStore>,
     bitmap: RwLock<BitVec>,
     chunk_size: u64,
     content_length: u64,
     total_chunks: usize,
-    path: PathBuf,
+    /// `None` for stores that do not persist.
+    meta_path: Option<PathBuf>,
+    validator: CacheValidator,
+    label: Mutex<CacheLabel>,
+    last_meta_write: Mutex<Instant>,
     cached_bytes: AtomicU64,
+    /// Upper bound for `cached_bytes`; 0 means unlimited.
+    max_bytes: AtomicU64,
+    /// Latest playback offset, used to pick eviction victims.
+    playback_offset: AtomicU64,
+    /// Evicted chunks whose storage is released once no views are alive.
+    pending_release: Mutex<Vec<usize>>,
+    /// High-water mark of chunks still downloading: bytes from the chunk
+    /// start that are already in the store.
+    partial: Mutex<HashMap<usize, u64>>,
 }
 
 impl DiskCache {
-    /// Create a new disk cache backed by a temp file in `cache_dir`.
+    /// Create a disk cache in `cache_dir` with no upstream validators.
     ///
-    /// The file is truncated to `content_length` and memory-mapped.
-    /// A bitmap tracks which chunks have been written.
+    /// Equivalent to [`DiskCache::open`] with a default [`CacheValidator`].
     pub fn new(
         cache_dir: &Path,
         session_id: &str,
         content_length: u64,
         chunk_size: u64,
+    ) -> Result<Self> {
+        Self::open(
+            cache_dir,
+            session_id,
+            content_length,
+            chunk_size,
+            &CacheValidator::default(),
+        )
+    }
+
+    /// Open (or create) the cache for `key` on the platform default backend.
+    ///
+    /// If a sidecar from an earlier session matches `content_length`,
+    /// `chunk_size` and `validator`, its chunks are restored; otherwise the
+    /// data file is truncated and the cache starts empty.
+    pub fn open(
+        cache_dir: &Path,
+        key: &str,
+        content_length: u64,
+        chunk_size: u64,
+        validator: &CacheValidator,
+    ) -> Result<Self> {
+        Self::open_with_backend(
+            cache_dir,
+            key,
+            content_length,
+            chunk_size,
+            validator,
+            CacheBackend::default(),
+        )
+    }
+
+    /// Like [`DiskCache::open`], storing chunks in `backend`.
+    ///
+    /// If the mmap backend cannot map the file (e.g. it exceeds a 32-bit
+    /// address space), chunk files are used instead.
+    pub fn open_with_backend(
+        cache_dir: &Path,
+        key: &str,
+        content_length: u64,
+        chunk_size: u64,
+        validator: &CacheValidator,
+        backend: CacheBackend,
     ) -> Result<Self> {
         if content_length == 0 {
             return Err(anyhow!("content_length must be > 0"));
@@ -37,34 +216,168 @@ impl DiskCache {
             return Err(anyhow!("chunk_size must be > 0"));
         }
 
-        fs::create_dir_all(cache_dir)?;
-
-        let path = cache_dir.join(format!("{}.cache", session_id));
+        let total_chunks = ((content_length + chunk_size - 1) / chunk_size) as usize;
 
-        let file = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(&path)?;
+        // Decide before looking at the sidecar so a file too large to map
+        // still resumes from its chunk files on the next open.
+        let backend = if backend == CacheBackend::Mmap && usize::try_from(content_length).is_err() {
+            CacheBackend::ChunkFiles
+        } else {
+            backend
+        };
+
+        if backend == CacheBackend::Memory {
+            let store = MemoryStore::new(chunk_size, MEMORY_CACHE_MAX_BYTES);
+            return Ok(Self::with_store(
+                Box::new(store),
+                bitvec![0; total_chunks],
+                0,
+                chunk_size,
+                content_length,
+                None,
+                validator,
+                CacheLabel::default(),
+            ));
+        }
 
-        file.set_len(content_length)?;
+        fs::create_dir_all(cache_dir)?;
+        let meta_path = cache_meta_path(cache_dir, key);
+
+        // Labels (title, pin) survive even when the data is invalidated.
+        let previous = CacheMeta::load(&meta_path);
+        let label = previous
+            .as_ref()
+            .map(|meta| CacheLabel {
+                file_key: meta.file_key.clone(),
+                title: meta.title.clone(),
+                pinned: meta.pinned,
+            })
+            .unwrap_or_default();
+        let had_previous = previous.is_some();
+        let restored = previous.filter(|meta| {
+            let stored = CacheValidator {
+                etag: meta.etag.clone(),
+                last_modified: meta.last_modified.clone(),
+            };
+            meta.content_length == content_length
+                && meta.chunk_size == chunk_size
+                && meta.backend == backend
+                && stored.matches(validator)
+        });
+        if restored.is_none() {
+            if had_previous {
+                info!("cache {} invalidated, starting fresh", key);
+            }
+            // Also drops data another backend left under this key.
+            remove_store_data(cache_dir, key)?;
+        }
 
-        // SAFETY: we just created the file and own it exclusively.
-        let mmap = unsafe { MmapMut::map_mut(&file)? };
+        let keep = restored.is_some();
+        let store: Box<dyn CacheStore> = match backend {
+            CacheBackend::Mmap => match MmapStore::open(
+                &cache_data_path(cache_dir, key),
+                content_length,
+                chunk_size,
+                keep,
+            ) {
+                Ok(store) => Box::new(store),
+                Err(e) => {
+                    warn!("cache {} cannot use mmap ({}), using chunk files", key, e);
+                    let _ = remove_store_data(cache_dir, key);
+                    Box::new(ChunkFileStore::open(
+                        cache_chunks_dir(cache_dir, key),
+                        chunk_size,
+                        false,
+                    )?)
+                }
+            },
+            CacheBackend::ChunkFiles => Box::new(ChunkFileStore::open(
+                cache_chunks_dir(cache_dir, key),
+                chunk_size,
+                keep,
+            )?),
+            CacheBackend::Memory => unreachable!("handled above"),
+        };
+
+        let mut bitmap = bitvec![0; total_chunks];
+        let mut cached_bytes = 0u64;
+        let mut partial = HashMap::new();
+        if let Some(meta) = &restored {
+            for &i in &meta.downloaded_chunks {
+                if i >= total_chunks || bitmap[i] {
+                    continue;
+                }
+                let len = chunk_len_of(i, total_chunks, content_length, chunk_size);
+                if store.has_persisted(i, len) {
+                    bitmap.set(i, true);
+                    cached_bytes += len as u64;
+                }
+            }
+            for (&i, &mark) in &meta.partial_chunks {
+                if i >= total_chunks || bitmap[i] || mark == 0 {
+                    continue;
+                }
+                let len = chunk_len_of(i, total_chunks, content_length, chunk_size) as u64;
+                if mark <= len && store.has_partial(i, mark as usize) {
+                    partial.insert(i, mark);
+                }
+            }
+            info!(
+                "cache {} resumed: {}/{} chunks ({} bytes), {} partial",
+                key,
+                bitmap.count_ones(),
+                total_chunks,
+                cached_bytes,
+                partial.len()
+            );
+        } else {
+            let _ = fs::remove_file(&meta_path);
+        }
 
-        let total_chunks = ((content_length + chunk_size - 1) / chunk_size) as usize;
-        let bitmap = bitvec![0; total_chunks];
+        let cache = Self::with_store(
+            store,
+            bitmap,
+            cached_bytes,
+            chunk_size,
+            content_length,
+            Some(meta_path),
+            validator,
+            label,
+        );
+        *cache.partial.lock() = partial;
+        // Record the access time (and the new validators) right away.
+        cache.persist_meta()?;
+        Ok(cache)
+    }
 
-        Ok(Self {
-            mmap: RwLock::new(mmap),
+    #[allow(clippy::too_many_arguments)]
+    fn with_store(
+        store: Box<dyn CacheStore>,
+        bitmap: BitVec,
+        cached_bytes: u64,
+        chunk_size: u64,
+        content_length: u64,
+        meta_path: Option<PathBuf>,
+        validator: &CacheValidator,
+        label: CacheLabel,
+    ) -> Self {
+        let total_chunks = bitmap.len();
+        Self {
+            store,
             bitmap: RwLock::new(bitmap),
             chunk_size,
             content_length,
             total_chunks,
-            path,
-            cached_bytes: AtomicU64::new(0),
-        })
+            meta_path,
+            validator: validator.clone(),
+            label: Mutex::new(label),
+            last_meta_write: Mutex::new(Instant::now()),
+            cached_bytes: AtomicU64::new(cached_bytes),
+            max_bytes: AtomicU64::new(0),
+            playback_offset: AtomicU64::new(0),
+            pending_release: Mutex::new(Vec::new()),
+            partial: Mutex::new(HashMap::new()),
+        }
     }
 
     /// Write `data` into the cache at the given chunk index.
@@ -86,23 +399,118 @@ impl DiskCache {
             ));
         }
 
-        let offset = chunk_index as u64 * self.chunk_size;
+        // A deferred release of this index must not land after the write.
+        self.pending_release.lock().retain(|&i| i != chunk_index);
+        self.store.write_chunk(chunk_index, data)?;
+        self.mark_complete(chunk_index);
+        Ok(())
+    }
 
-        {
-            let mut mmap = self.mmap.write();
-            mmap[offset as usize..offset as usize + data.len()].copy_from_slice(data);
+    /// Write bytes of a chunk that is still downloading, `offset` bytes into
+    /// it. Pieces must arrive in order (`offset` at or below the current
+    /// high-water mark). Returns the new high-water mark.
+    pub fn write_partial(&self, chunk_index: usize, offset: u64, data: &[u8]) -> Result<u64> {
+        if chunk_index >= self.total_chunks {
+            return Err(anyhow!(
+                "chunk_index {} out of range (total {})",
+                chunk_index,
+                self.total_chunks
+            ));
+        }
+        let chunk_len = self.chunk_len(chunk_index) as u64;
+        if self.has_chunk(chunk_index) {
+            return Ok(chunk_len);
+        }
+        let end = offset + data.len() as u64;
+        if end > chunk_len {
+            return Err(anyhow!(
+                "partial write [{}, {}) exceeds chunk length {}",
+                offset,
+                end,
+                chunk_len
+            ));
+        }
+        let high_water = self.partial_len(chunk_index);
+        if offset > high_water {
+            return Err(anyhow!(
+                "partial write at {} leaves a gap after {}",
+                offset,
+                high_water
+            ));
         }
 
+        self.pending_release.lock().retain(|&i| i != chunk_index);
+        self.store
+            .write_partial(chunk_index, offset as usize, data)?;
+
+        // Publish only after the bytes are in the store.
+        let mark = {
+            let mut partial = self.partial.lock();
+            let mark = partial.entry(chunk_index).or_insert(0);
+            *mark = (*mark).max(end);
+            *mark
+        };
+        self.maybe_persist_meta();
+        Ok(mark)
+    }
+
+    /// Seal a chunk whose bytes all arrived through [`DiskCache::write_partial`].
+    pub fn finish_chunk(&self, chunk_index: usize) -> Result<()> {
+        let chunk_len = self.chunk_len(chunk_index);
+        let high_water = self.partial_len(chunk_index);
+        if high_water != chunk_len as u64 {
+            return Err(anyhow!(
+                "chunk {} incomplete: {} of {} bytes",
+                chunk_index,
+                high_water,
+                chunk_len
+            ));
+        }
+        self.store.complete_chunk(chunk_index, chunk_len)?;
+        self.mark_complete(chunk_index);
+        Ok(())
+    }
+
+    /// Contiguous bytes available from the start of a chunk: the full
+    /// length once cached, else the download's high-water mark.
+    pub fn available_in_chunk(&self, chunk_index: usize) -> u64 {
+        if self.has_chunk(chunk_index) {
+            return self.chunk_len(chunk_index) as u64;
+        }
+        self.partial_len(chunk_index)
+    }
+
+    /// Forget the bytes of a chunk still downloading so the next attempt
+    /// writes it again from its start. Used when a response turns out not
+    /// to be the range that was asked for.
+    pub fn discard_partial(&self, chunk_index: usize) {
+        if self.partial.lock().remove(&chunk_index).is_some() {
+            // Right away: a restart must not resume from the bad bytes.
+            if let Err(e) = self.persist_meta() {
+                warn!("cache meta write failed: {}", e);
+            }
+        }
+    }
+
+    fn partial_len(&self, chunk_index: usize) -> u64 {
+        self.partial.lock().get(&chunk_index).copied().unwrap_or(0)
+    }
+
+    fn mark_complete(&self, chunk_index: usize) {
         {
             let mut bitmap = self.bitmap.write();
             if !bitmap[chunk_index] {
                 bitmap.set(chunk_index, true);
                 self.cached_bytes
-                    .fetch_add(data.len() as u64, Ordering::Relaxed);
+                    .fetch_add(self.chunk_len(chunk_index) as u64, Ordering::Relaxed);
             }
+            self.partial.lock().remove(&chunk_index);
         }
 
-        Ok(())
+        if !self.enforce_budget(chunk_index) {
+            self.maybe_persist_meta();
+        }
+        self.release_pending();
     }
 
     /// Read a single chunk from the cache. Returns `None` if the chunk is not cached.
@@ -117,10 +525,14 @@ impl DiskCache {
         }
 
         let offset = chunk_index as u64 * self.chunk_size;
-        let len = self.chunk_len(chunk_index);
-
-        let mmap = self.mmap.read();
-        Some(mmap[offset as usize..offset as usize + len].to_vec())
+        let mut buf = vec![0u8; self.chunk_len(chunk_index)];
+        match self.store.read_into(offset, &mut buf) {
+            Ok(()) => Some(buf),
+            Err(e) => {
+                warn!("cache read chunk {} failed: {}", chunk_index, e);
+                None
+            }
+        }
     }
 
     /// Read an arbitrary byte range `[start, end)` from the cache.
@@ -133,18 +545,59 @@ impl DiskCache {
         let first_chunk = (start / self.chunk_size) as usize;
         let last_chunk = ((end - 1) / self.chunk_size) as usize;
 
-        // Check all required chunks are present.
-        {
-            let bitmap = self.bitmap.read();
-            for i in first_chunk..=last_chunk {
-                if !bitmap[i] {
-                    return None;
-                }
+        // Check all required chunks are present. The bitmap lock is held
+        // through the copy so eviction cannot punch the range mid-read.
+        let bitmap = self.bitmap.read();
+        if !bitmap[first_chunk..=last_chunk].all() {
+            return None;
+        }
+
+        let mut buf = vec![0u8; (end - start) as usize];
+        match self.store.read_into(start, &mut buf) {
+            Ok(()) => Some(buf),
+            Err(e) => {
+                warn!("cache read {}-{} failed: {}", start, end, e);
+                None
             }
         }
+    }
+
+    /// Read `[start, end)` as `Bytes` without copying where the store
+    /// allows it (mmap and memory backends hand out shared views).
+    /// Bytes below a downloading chunk's high-water mark count as present.
+    /// Returns `None` if any part of the range is missing.
+    pub fn read_bytes(&self, start: u64, end: u64) -> Option<Bytes> {
+        if start >= end || end > self.content_length {
+            return None;
+        }
+
+        let first_chunk = (start / self.chunk_size) as usize;
+        let last_chunk = ((end - 1) / self.chunk_size) as usize;
 
-        let mmap = self.mmap.read();
-        Some(mmap[start as usize..end as usize].to_vec())
+        // Evicted chunks are only released once no views into them remain,
+        // so the returned bytes stay valid after the lock is dropped.
+        let bitmap = self.bitmap.read();
+        let partial = self.partial.lock();
+        let covered = (first_chunk..=last_chunk).all(|i| {
+            if bitmap[i] {
+                return true;
+            }
+            let chunk_start = i as u64 * self.chunk_size;
+            let needed = end.min(chunk_start + self.chunk_size) - chunk_start;
+            partial.get(&i).is_some_and(|&mark| mark >= needed)
+        });
+        drop(partial);
+        if !covered {
+            return None;
+        }
+
+        match self.store.read_bytes(start, (end - start) as usize) {
+            Ok(bytes) => Some(bytes),
+            Err(e) => {
+                warn!("cache read {}-{} failed: {}", start, end, e);
+                None
+            }
+        }
     }
 
     /// Check whether a chunk is cached.
@@ -190,15 +643,211 @@ impl DiskCache {
 
     /// Byte length of the given chunk. The last chunk may be shorter than `chunk_size`.
     pub fn chunk_len(&self, chunk_index: usize) -> usize {
-        if chunk_index + 1 < self.total_chunks {
-            self.chunk_size as usize
-        } else {
-            // Last chunk — may be shorter.
-            let remainder = (self.content_length % self.chunk_size) as usize;
-            if remainder == 0 {
-                self.chunk_size as usize
-            } else {
-                remainder
+        chunk_len_of(
+            chunk_index,
+            self.total_chunks,
+            self.content_length,
+            self.chunk_size,
+        )
+    }
+
+    /// Limit the cache to `max_bytes` of chunk data (0 = unlimited).
+    /// Takes effect on the next chunk written.
+    pub fn set_byte_budget(&self, max_bytes: u64) {
+        self.max_bytes.store(max_bytes, Ordering::Relaxed);
+    }
+
+    /// Effective budget: the configured one, tightened by the store's capacity.
+    pub fn byte_budget(&self) -> u64 {
+        let configured = self.max_bytes.load(Ordering::Relaxed);
+        match self.store.capacity() {
+            Some(cap) if configured == 0 => cap,
+            Some(cap) => configured.min(cap),
+            None => configured,
+        }
+    }
+
+    /// Backend holding this cache's chunks.
+    pub fn backend(&self) -> CacheBackend {
+        self.store.backend()
+    }
+
+    /// Record where the player is reading so eviction keeps nearby chunks.
+    pub fn set_playback_offset(&self, offset: u64) {
+        self.playback_offset.store(offset, Ordering::Relaxed);
+    }
+
+    /// End (exclusive) of the chunks from the playback chunk on that fit in
+    /// the budget next to the cached ones eviction keeps behind it. Chunks
+    /// prefetched past it would be evicted again right away. `None` without
+    /// a budget.
+    pub fn budget_end_chunk(&self) -> Option<usize> {
+        let budget = self.byte_budget();
+        if budget == 0 {
+            return None;
+        }
+        let play_chunk = ((self.playback_offset.load(Ordering::Relaxed) / self.chunk_size)
+            as usize)
+            .min(self.total_chunks - 1);
+        let keep_behind = (CACHE_EVICT_KEEP_BEHIND_BYTES / self.chunk_size) as usize;
+        let keep_from = play_chunk.saturating_sub(keep_behind);
+        let kept: u64 = self.bitmap.read()[keep_from..play_chunk]
+            .iter_ones()
+            .map(|i| self.chunk_len(keep_from + i) as u64)
+            .sum();
+        let mut room = budget.saturating_sub(kept);
+        let mut end = play_chunk;
+        while end < self.total_chunks && room >= self.chunk_len(end) as u64 {
+            room -= self.chunk_len(end) as u64;
+            end += 1;
+        }
+        Some(end.max(play_chunk + 1))
+    }
+
+    /// Drop chunks until `cached_bytes` fits the budget. Victims are taken
+    /// from far behind the playback offset first, then from farthest ahead;
+    /// the chunk just written and the current playback chunk are kept.
+    ///
+    /// Returns `true` if anything was evicted (the sidecar is then already
+    /// up to date).
+    fn enforce_budget(&self, just_written: usize) -> bool {
+        let budget = self.byte_budget();
+        if budget == 0 || self.cached_bytes.load(Ordering::Relaxed) <= budget {
+            return false;
+        }
+
+        let play_chunk = ((self.playback_offset.load(Ordering::Relaxed) / self.chunk_size)
+            as usize)
+            .min(self.total_chunks - 1);
+        let keep_behind = (CACHE_EVICT_KEEP_BEHIND_BYTES / self.chunk_size) as usize;
+        let keep_from = play_chunk.saturating_sub(keep_behind);
+
+        let mut bitmap = self.bitmap.write();
+        let behind = bitmap[..keep_from].iter_ones().collect::<Vec<_>>();
+        let ahead = bitmap[play_chunk + 1..]
+            .iter_ones()
+            .rev()
+            .map(|i| i + play_chunk + 1)
+            .collect::<Vec<_>>();
+
+        let mut victims = Vec::new();
+        for i in behind.into_iter().chain(ahead) {
+            if self.cached_bytes.load(Ordering::Relaxed) <= budget {
+                break;
+            }
+            if i == just_written {
+                continue;
+            }
+            bitmap.set(i, false);
+            self.cached_bytes
+                .fetch_sub(self.chunk_len(i) as u64, Ordering::Relaxed);
+            victims.push(i);
+        }
+        if victims.is_empty() {
+            return false;
+        }
+
+        // The sidecar must stop listing victims before their data is gone,
+        // otherwise a crash would resurrect zero-filled chunks.
+        if let Some(meta_path) = &self.meta_path {
+            if let Err(e) = self.build_meta(&bitmap).store(meta_path) {
+                warn!("cache meta write failed: {}", e);
+            }
+            *self.last_meta_write.lock() = Instant::now();
+        }
+
+        self.pending_release.lock().extend(victims.iter().copied());
+        debug!(
+            "cache evicted {} chunk(s) around playback chunk {} (budget {})",
+            victims.len(),
+            play_chunk,
+            budget
+        );
+        true
+    }
+
+    /// Give evicted chunks' storage back. The store holds back chunks that
+    /// served views still point into until those views are dropped.
+    fn release_pending(&self) {
+        let mut pending = self.pending_release.lock();
+        for i in pending.drain(..) {
+            if let Err(e) = self.store.release_chunk(i, self.chunk_len(i)) {
+                debug!("release chunk {} failed: {}", i, e);
+            }
+        }
+    }
+
+    /// Attach the file key and display title shown by the cache API.
+    /// Empty values leave the stored ones untouched.
+    pub fn set_label(&self, file_key: Option<String>, title: Option<String>) {
+        {
+            let mut label = self.label.lock();
+            if let Some(key) = file_key.filter(|k| !k.is_empty()) {
+                label.file_key = Some(key);
+            }
+            if let Some(title) = title.filter(|t| !t.is_empty()) {
+                label.title = Some(title);
+            }
+        }
+        if let Err(e) = self.persist_meta() {
+            warn!("cache meta write failed: {}", e);
+        }
+    }
+
+    /// Protect (or unprotect) this cache from engine-level eviction.
+    pub fn set_pinned(&self, pinned: bool) {
+        self.label.lock().pinned = pinned;
+        if let Err(e) = self.persist_meta() {
+            warn!("cache meta write failed: {}", e);
+        }
+    }
+
+    pub fn label(&self) -> CacheLabel {
+        self.label.lock().clone()
+    }
+
+    fn build_meta(&self, bitmap: &BitVec) -> CacheMeta {
+        let label = self.label.lock().clone();
+        CacheMeta {
+            version: CACHE_META_VERSION,
+            content_length: self.content_length,
+            chunk_size: self.chunk_size,
+            etag: self.validator.etag.clone(),
+            last_modified: self.validator.last_modified.clone(),
+            last_access_at: now_millis(),
+            downloaded_chunks: bitmap.iter_ones().collect(),
+            partial_chunks: self
+                .partial
+                .lock()
+                .iter()
+                .map(|(&i, &mark)| (i, mark))
+                .collect(),
+            file_key: label.file_key,
+            title: label.title,
+            pinned: label.pinned,
+            backend: self.store.backend(),
+        }
+    }
+
+    /// Write the sidecar now. No-op for memory-only caches.
+    pub fn persist_meta(&self) -> Result<()> {
+        let Some(meta_path) = &self.meta_path else {
+            return Ok(());
+        };
+        let meta = self.build_meta(&self.bitmap.read());
+        *self.last_meta_write.lock() = Instant::now();
+        meta.store(meta_path)
+    }
+
+    /// Write the sidecar if the last write is older than the debounce interval.
+    fn maybe_persist_meta(&self) {
+        let due = {
+            let last = self.last_meta_write.lock();
+            last.elapsed() >= Duration::from_secs(CACHE_META_WRITE_INTERVAL_SECS)
+        };
+        if due {
+            if let Err(e) = self.persist_meta() {
+                warn!("cache meta write failed: {}", e);
             }
         }
     }
@@ -220,9 +869,34 @@ impl DiskCache {
     }
 }
 
+fn chunk_len_of(
+    chunk_index: usize,
+    total_chunks: usize,
+    content_length: u64,
+    chunk_size: u64,
+) -> usize {
+    if chunk_index + 1 < total_chunks {
+        chunk_size as usize
+    } else {
+        // Last chunk — may be shorter.
+        let remainder = (content_length % chunk_size) as usize;
+        if remainder == 0 {
+            chunk_size as usize
+        } else {
+            remainder
+        }
+    }
+}
+
 impl Drop for DiskCache {
     fn drop(&mut self) {
-        // Best-effort deletion of the backing file.
-        let _ = fs::remove_file(&self.path);
+        // Keep the data; flush it and record which chunks it holds.
+        self.release_pending();
+        if let Err(e) = self.store.flush() {
+            warn!("cache flush failed: {}", e);
+        }
+        if let Err(e) = self.persist_meta() {
+            warn!("cache meta write failed: {}", e);
+        }
     }
 }
@@ -0,0 +1,292 @@
+// Engine-wide cache budget — tracks every persisted cache file and evicts
+// whole files, least recently used first, to stay under `max_cache_bytes`.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use tracing::{debug, info, warn};
+
+use super::cache::{cache_meta_path, CacheMeta};
+use super::store::{cache_chunks_dir, cache_data_path, remove_store_data};
+use crate::config::CONCURRENCY_STATE_FILE;
+
+/// One persisted cache file as seen by the manager.
+#[derive(Debug, Clone)]
+pub struct CacheEntry {
+    pub key: String,
+    /// Bytes of downloaded chunks held in the data file.
+    pub size_bytes: u64,
+    pub content_length: u64,
+    /// Milliseconds since the Unix epoch.
+    pub last_access_at: u64,
+    pub file_key: Option<String>,
+    pub title: Option<String>,
+    pub pinned: bool,
+    pub active: bool,
+}
+
+pub struct CacheManager {
+    cache_dir: PathBuf,
+    max_cache_bytes: AtomicU64,
+    active: Mutex<HashSet<String>>,
+}
+
+/// Marks a cache key as in use; released when dropped.
+pub struct CacheLease {
+    manager: Arc<CacheManager>,
+    key: String,
+}
+
+impl Drop for CacheLease {
+    fn drop(&mut self) {
+        self.manager.active.lock().remove(&self.key);
+        debug!("cache lease released key={}", self.key);
+    }
+}
+
+impl CacheManager {
+    /// `max_cache_bytes == 0` disables the budget.
+    pub fn new(cache_dir: impl Into<PathBuf>, max_cache_bytes: u64) -> Self {
+        Self {
+            cache_dir: cache_dir.into(),
+            max_cache_bytes: AtomicU64::new(max_cache_bytes),
+            active: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache_dir
+    }
+
+    pub fn max_cache_bytes(&self) -> u64 {
+        self.max_cache_bytes.load(Ordering::Relaxed)
+    }
+
+    pub fn set_max_cache_bytes(&self, max_cache_bytes: u64) {
+        self.max_cache_bytes
+            .store(max_cache_bytes, Ordering::Relaxed);
+    }
+
+    /// Remove files a crash (or an older build) left behind: data files or
+    /// chunk directories without a readable sidecar, sidecars without data,
+    /// and temp files.
+    /// Returns the number of files removed.
+    pub fn startup_scan(&self) -> Result<usize> {
+        fs::create_dir_all(&self.cache_dir)?;
+        let mut removed = 0usize;
+        for entry in fs::read_dir(&self.cache_dir)? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let orphan = if name.ends_with(".json.tmp") {
+                true
+            } else if name == CONCURRENCY_STATE_FILE {
+                false
+            } else if let Some(key) = name.strip_suffix(".cache") {
+                CacheMeta::load(&cache_meta_path(&self.cache_dir, key)).is_none()
+            } else if let Some(key) = name.strip_suffix(".chunks") {
+                CacheMeta::load(&cache_meta_path(&self.cache_dir, key)).is_none()
+            } else if let Some(key) = name.strip_suffix(".json") {
+                !cache_data_path(&self.cache_dir, key).exists()
+                    && !cache_chunks_dir(&self.cache_dir, key).exists()
+            } else {
+                false
+            };
+            if orphan {
+                let result = if path.is_dir() {
+                    fs::remove_dir_all(&path)
+                } else {
+                    fs::remove_file(&path)
+                };
+                match result {
+                    Ok(()) => removed += 1,
+                    Err(e) => warn!("remove orphan {} failed: {}", path.display(), e),
+                }
+            }
+        }
+        if removed > 0 {
+            info!("cache startup scan removed {} orphaned file(s)", removed);
+        }
+        Ok(removed)
+    }
+
+    /// All cache files with a valid sidecar, most recently used first.
+    pub fn entries(&self) -> Vec<CacheEntry> {
+        let Ok(dir) = fs::read_dir(&self.cache_dir) else {
+            return Vec::new();
+        };
+        let active = self.active.lock().clone();
+        let mut entries: Vec<CacheEntry> = dir
+            .filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let name = e.file_name().to_str()?.to_string();
+                let key = name.strip_suffix(".json")?.to_string();
+                let meta = CacheMeta::load(&e.path())?;
+                Some(CacheEntry {
+                    size_bytes: meta_cached_bytes(&meta),
+                    content_length: meta.content_length,
+                    last_access_at: meta.last_access_at,
+                    file_key: meta.file_key,
+                    title: meta.title,
+                    pinned: meta.pinned,
+                    active: active.contains(&key),
+                    key,
+                })
+            })
+            .collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.last_access_at));
+        entries
+    }
+
+    /// Total bytes held by all cache files.
+    pub fn total_usage(&self) -> u64 {
+        self.entries().iter().map(|e| e.size_bytes).sum()
+    }
+
+    /// Whether a session currently holds a lease on `key`.
+    pub fn is_active(&self, key: &str) -> bool {
+        self.active.lock().contains(key)
+    }
+
+    /// Set the pinned flag of an inactive cache directly in its sidecar.
+    /// Active caches must be pinned through their live `DiskCache`, which
+    /// owns the sidecar while open.
+    pub fn set_pinned(&self, key: &str, pinned: bool) -> Result<()> {
+        let path = cache_meta_path(&self.cache_dir, key);
+        let mut meta =
+            CacheMeta::load(&path).ok_or_else(|| anyhow!("cache entry not found: {}", key))?;
+        meta.pinned = pinned;
+        meta.store(&path)
+    }
+
+    /// Remove every inactive, unpinned cache unless `include_pinned` is set.
+    /// Returns the number of entries removed.
+    pub fn clear(&self, include_pinned: bool) -> usize {
+        let mut removed = 0;
+        for entry in self.entries() {
+            if entry.active || (entry.pinned && !include_pinned) {
+                continue;
+            }
+            match self.remove(&entry.key) {
+                Ok(()) => removed += 1,
+                Err(e) => warn!("cache remove key={} failed: {}", entry.key, e),
+            }
+        }
+        removed
+    }
+
+    /// Free space on the filesystem holding the cache directory, if known.
+    pub fn free_disk_bytes(&self) -> Option<u64> {
+        free_disk_bytes(&self.cache_dir)
+    }
+
+    /// Delete the stored data and sidecar for `key`.
+    pub fn remove(&self, key: &str) -> Result<()> {
+        remove_store_data(&self.cache_dir, key)?;
+        match fs::remove_file(cache_meta_path(&self.cache_dir, key)) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Claim `key` for a session that may grow its cache to `expected_bytes`,
+    /// evicting least recently used inactive files until it fits.
+    ///
+    /// The budget is best-effort: if active sessions alone exceed it, the
+    /// lease is still granted so playback is never refused.
+    pub fn acquire(self: &Arc<Self>, key: &str, expected_bytes: u64) -> CacheLease {
+        self.active.lock().insert(key.to_string());
+        let lease = CacheLease {
+            manager: Arc::clone(self),
+            key: key.to_string(),
+        };
+
+        let budget = self.max_cache_bytes();
+        if budget == 0 {
+            return lease;
+        }
+
+        let entries = self.entries();
+        let already = entries
+            .iter()
+            .find(|e| e.key == key)
+            .map(|e| e.size_bytes)
+            .unwrap_or(0);
+        let mut usage: u64 = entries.iter().map(|e| e.size_bytes).sum::<u64>()
+            + expected_bytes.saturating_sub(already);
+
+        // Oldest first.
+        for entry in entries.iter().rev() {
+            if usage <= budget {
+                break;
+            }
+            if entry.active || entry.pinned {
+                continue;
+            }
+            match self.remove(&entry.key) {
+                Ok(()) => {
+                    usage = usage.saturating_sub(entry.size_bytes);
+                    info!(
+                        "cache evicted key={} bytes={} (budget {})",
+                        entry.key, entry.size_bytes, budget
+                    );
+                }
+                Err(e) => warn!("cache evict key={} failed: {}", entry.key, e),
+            }
+        }
+        if usage > budget {
+            warn!(
+                "cache budget exceeded by active sessions: usage={} budget={}",
+                usage, budget
+            );
+        }
+        lease
+    }
+}
+
+/// Bytes of downloaded chunks recorded in a sidecar.
+pub(crate) fn meta_cached_bytes(meta: &CacheMeta) -> u64 {
+    if meta.chunk_size == 0 {
+        return 0;
+    }
+    let total_chunks = meta.content_length.div_ceil(meta.chunk_size) as usize;
+    let last_len = meta.content_length - (total_chunks as u64 - 1) * meta.chunk_size;
+    meta.downloaded_chunks
+        .iter()
+        .filter(|&&i| i < total_chunks)
+        .map(|&i| {
+            if i + 1 == total_chunks {
+                last_len
+            } else {
+                meta.chunk_size
+            }
+        })
+        .sum()
+}
+
+#[cfg(unix)]
+fn free_disk_bytes(path: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    // SAFETY: `statvfs` only writes into the zeroed struct we pass in.
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    let ret = unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) };
+    if ret != 0 {
+        return None;
+    }
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+fn free_disk_bytes(_path: &Path) -> Option<u64> {
+    None
+}
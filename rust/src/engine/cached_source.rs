@@ -0,0 +1,48 @@
+// Cached source — a session's file read back through its cache.
+//
+// Container parsers take a `MediaSource`; handing them this one instead of
+// the upstream makes their reads warmup downloads, scheduled, host-limited
+// and paced like the rest, and leaves the bytes cached for the player.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use super::downloader::Downloader;
+use crate::source::traits::{MediaSource, SourceInfo};
+
+pub struct CachedSource {
+    downloader: Arc<Downloader>,
+    content_length: u64,
+}
+
+impl CachedSource {
+    pub fn new(downloader: Arc<Downloader>, content_length: u64) -> Self {
+        Self {
+            downloader,
+            content_length,
+        }
+    }
+}
+
+#[async_trait]
+impl MediaSource for CachedSource {
+    async fn probe(&self) -> Result<SourceInfo> {
+        Ok(SourceInfo {
+            content_length: self.content_length,
+            content_type: "application/octet-stream".to_string(),
+            supports_range: true,
+            etag: None,
+            last_modified: None,
+        })
+    }
+
+    async fn fetch_range(&self, start: u64, end: u64) -> Result<Bytes> {
+        if end < start {
+            return Err(anyhow!("invalid range: start={} end={}", start, end));
+        }
+        Ok(self.downloader.read_range(start, end + 1).await?)
+    }
+}
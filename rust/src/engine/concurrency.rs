@@ -0,0 +1,299 @@
+// Adaptive concurrency — tunes the background worker count from measured throughput.
+//
+// Additive increase while aggregate throughput keeps rising, multiplicative
+// decrease on fetch errors or when per-connection speed collapses. The count
+// a host settles at is remembered for the next session against that host,
+// and across restarts once the engine gives it a file next to the cache.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use anyhow::Result;
+use parking_lot::Mutex;
+use tracing::{debug, warn};
+
+use crate::config::{
+    ADAPTIVE_COLLAPSE_RATIO, ADAPTIVE_HOLD_SAMPLES, ADAPTIVE_INITIAL_WORKERS, ADAPTIVE_MIN_GAIN,
+};
+
+#[derive(Default)]
+struct Learned {
+    by_host: HashMap<String, usize>,
+    /// Where settled counts are saved, once the engine has set it.
+    path: Option<PathBuf>,
+}
+
+fn learned() -> &'static Mutex<Learned> {
+    static LEARNED: OnceLock<Mutex<Learned>> = OnceLock::new();
+    LEARNED.get_or_init(|| Mutex::new(Learned::default()))
+}
+
+/// Serializes writes of the learned file so the last one carries the
+/// newest counts; `learned()` itself is never held across file I/O.
+fn save_lock() -> &'static Mutex<()> {
+    static SAVE: OnceLock<Mutex<()>> = OnceLock::new();
+    SAVE.get_or_init(|| Mutex::new(()))
+}
+
+/// Worker count learned for a host by an earlier session, if any.
+pub fn learned_concurrency(host: &str) -> Option<usize> {
+    learned().lock().by_host.get(host).copied()
+}
+
+/// Keep learned counts in `path` from now on, starting from whatever an
+/// earlier run saved there. Counts learned in this run take precedence.
+pub fn persist_learned_concurrency(path: PathBuf) {
+    let saved = load_learned(&path);
+    let mut learned = learned().lock();
+    for (host, limit) in saved {
+        learned.by_host.entry(host).or_insert(limit);
+    }
+    learned.path = Some(path);
+}
+
+fn load_learned(path: &Path) -> HashMap<String, usize> {
+    fs::read(path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save_learned(path: &Path, by_host: &HashMap<String, usize>) -> Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_vec(by_host)?)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    limit: usize,
+    throughput: f64,
+}
+
+struct State {
+    limit: usize,
+    last_at: Instant,
+    last_bytes: u64,
+    last_errors: u64,
+    /// Previous saturated sample; `None` after a change that invalidates it.
+    prev: Option<Sample>,
+    /// Samples left before probing upward again.
+    hold: u32,
+}
+
+pub struct ConcurrencyController {
+    host: Option<String>,
+    ceiling: usize,
+    state: Mutex<State>,
+}
+
+impl ConcurrencyController {
+    /// Start from the host's learned count, else from a small default;
+    /// never above `ceiling`.
+    pub fn new(host: Option<String>, ceiling: usize) -> Self {
+        let ceiling = ceiling.max(1);
+        let initial = host
+            .as_deref()
+            .and_then(learned_concurrency)
+            .unwrap_or(ADAPTIVE_INITIAL_WORKERS)
+            .clamp(1, ceiling);
+        Self {
+            host,
+            ceiling,
+            state: Mutex::new(State {
+                limit: initial,
+                last_at: Instant::now(),
+                last_bytes: 0,
+                last_errors: 0,
+                prev: None,
+                hold: 0,
+            }),
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.state.lock().limit
+    }
+
+    /// Feed the running totals of downloaded bytes and failed fetches.
+    /// `saturated` says whether work was waiting for a worker, without which
+    /// throughput reflects demand rather than the worker count. Returns the
+    /// new limit.
+    pub fn sample(
+        &self,
+        total_bytes: u64,
+        total_errors: u64,
+        saturated: bool,
+        now: Instant,
+    ) -> usize {
+        let mut state = self.state.lock();
+        let secs = now.duration_since(state.last_at).as_secs_f64();
+        if secs <= 0.0 {
+            return state.limit;
+        }
+        let throughput = total_bytes.saturating_sub(state.last_bytes) as f64 / secs;
+        let errors = total_errors.saturating_sub(state.last_errors);
+        state.last_at = now;
+        state.last_bytes = total_bytes;
+        state.last_errors = total_errors;
+
+        if errors > 0 {
+            // A back-off, not a finding about the host: not remembered.
+            let limit = (state.limit / 2).max(1);
+            self.settle(&mut state, limit, "fetch errors");
+            return state.limit;
+        }
+        if !saturated {
+            state.prev = None;
+            return state.limit;
+        }
+
+        let current = Sample {
+            limit: state.limit,
+            throughput,
+        };
+        match state.prev {
+            // The last step added a worker: keep it only if it paid off.
+            Some(prev) if current.limit > prev.limit => {
+                if throughput >= prev.throughput * (1.0 + ADAPTIVE_MIN_GAIN) {
+                    state.prev = Some(current);
+                    state.limit = (current.limit + 1).min(self.ceiling);
+                } else {
+                    self.settle(&mut state, prev.limit, "no gain");
+                    self.remember(prev.limit);
+                }
+            }
+            Some(prev)
+                if current.limit == prev.limit
+                    && throughput < prev.throughput * ADAPTIVE_COLLAPSE_RATIO =>
+            {
+                let limit = (current.limit / 2).max(1);
+                self.settle(&mut state, limit, "per-connection speed collapsed");
+                self.remember(limit);
+            }
+            _ => {
+                state.prev = Some(current);
+                if state.hold > 0 {
+                    state.hold -= 1;
+                } else if state.limit < self.ceiling {
+                    state.limit += 1;
+                }
+            }
+        }
+        // Still gaining at the ceiling: that is as far as the host goes.
+        if state.limit == self.ceiling && current.limit < self.ceiling {
+            self.remember(self.ceiling);
+        }
+        state.limit
+    }
+
+    /// Fix the count and hold it for a while.
+    fn settle(&self, state: &mut State, limit: usize, reason: &str) {
+        debug!(
+            "concurrency {} -> {} ({}) host={:?}",
+            state.limit, limit, reason, self.host
+        );
+        state.limit = limit;
+        state.prev = None;
+        state.hold = ADAPTIVE_HOLD_SAMPLES;
+    }
+
+    /// Remember a settled count for the host, saving it if a file is set.
+    fn remember(&self, limit: usize) {
+        let Some(host) = &self.host else {
+            return;
+        };
+        let _save = save_lock().lock();
+        let (path, by_host) = {
+            let mut learned = learned().lock();
+            learned.by_host.insert(host.clone(), limit);
+            match &learned.path {
+                Some(path) => (path.clone(), learned.by_host.clone()),
+                None => return,
+            }
+        };
+        if let Err(e) = save_learned(&path, &by_host) {
+            warn!("saving learned concurrency failed: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const MB: f64 = 1024.0 * 1024.0;
+
+    /// Drive the controller against a link that scales up to `knee`
+    /// connections and then stays flat.
+    fn run(controller: &ConcurrencyController, knee: usize, rounds: usize) -> Vec<usize> {
+        let mut now = Instant::now();
+        let mut total = 0u64;
+        let mut limits = Vec::new();
+        for _ in 0..rounds {
+            now += Duration::from_secs(2);
+            total += (controller.limit().min(knee) as f64 * MB * 2.0) as u64;
+            limits.push(controller.sample(total, 0, true, now));
+        }
+        limits
+    }
+
+    #[test]
+    fn test_climbs_to_knee_and_remembers_it() {
+        let host = "climb.example:443".to_string();
+        let controller = ConcurrencyController::new(Some(host.clone()), 8);
+        assert_eq!(controller.limit(), ADAPTIVE_INITIAL_WORKERS);
+        let limits = run(&controller, 4, 12);
+        assert_eq!(*limits.iter().max().unwrap(), 5);
+        assert_eq!(controller.limit(), 4);
+        assert_eq!(learned_concurrency(&host), Some(4));
+
+        // The next session for the host starts at the optimum.
+        let next = ConcurrencyController::new(Some(host), 8);
+        assert_eq!(next.limit(), 4);
+    }
+
+    #[test]
+    fn test_learned_counts_round_trip_through_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("concurrency.json");
+        assert!(load_learned(&path).is_empty());
+
+        let by_host = HashMap::from([("a.example:443".to_string(), 3)]);
+        save_learned(&path, &by_host).unwrap();
+        assert_eq!(load_learned(&path), by_host);
+
+        std::fs::write(&path, b"not json").unwrap();
+        assert!(load_learned(&path).is_empty());
+    }
+
+    #[test]
+    fn test_gaining_up_to_ceiling_remembers_ceiling() {
+        let host = "ceiling.example:443".to_string();
+        let controller = ConcurrencyController::new(Some(host.clone()), 3);
+        run(&controller, 8, 6);
+        assert_eq!(controller.limit(), 3);
+        assert_eq!(learned_concurrency(&host), Some(3));
+    }
+
+    #[test]
+    fn test_errors_halve_and_idle_samples_change_nothing() {
+        let host = "errors.example:443".to_string();
+        let controller = ConcurrencyController::new(Some(host.clone()), 8);
+        run(&controller, 8, 4);
+        let before = controller.limit();
+        assert!(before > ADAPTIVE_INITIAL_WORKERS);
+
+        let later = Instant::now() + Duration::from_secs(60);
+        assert_eq!(controller.sample(0, 0, false, later), before);
+        let after = controller.sample(0, 3, true, later + Duration::from_secs(2));
+        assert_eq!(after, (before / 2).max(1));
+        // An error back-off is not what the host can take.
+        assert_eq!(learned_concurrency(&host), None);
+    }
+}
@@ -1,30 +1,74 @@
 // Multi-connection chunk downloader — fetches byte ranges from the source in parallel.
 
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
+use bytes::Bytes;
 use parking_lot::Mutex;
-use tokio::sync::{Notify, Semaphore};
+use tokio::sync::Notify;
+use tokio::task::AbortHandle;
+use tokio_stream::StreamExt;
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, warn};
 
+use super::bandwidth::{global_bandwidth, BandwidthLimiter};
 use super::cache::DiskCache;
+use super::concurrency::ConcurrencyController;
+use super::host_limiter::{host_limiter, HostLimiter};
+use super::policy;
+use super::scheduler::{Scheduler, Tier};
 use super::stats::StatsCollector;
-use crate::source::traits::MediaSource;
+use crate::config::{
+    ADAPTIVE_SAMPLE_INTERVAL_MS, FIRST_CHUNK_MIN_PART_BYTES, FIRST_CHUNK_SPLIT_MAX_PARTS,
+    HEDGE_MIN_BYTES_PER_SEC, HEDGE_PROGRESS_WINDOW_MS, MERGE_MAX_CHUNKS, PROGRESSIVE_FLUSH_BYTES,
+    SCHEDULER_URGENT_WORKERS,
+};
+use crate::error::{ProxyError, ProxyErrorKind};
+use crate::source::traits::{ByteStream, MediaSource};
 
 pub struct Downloader {
     source: Arc<dyn MediaSource>,
     cache: Arc<DiskCache>,
-    urgent_semaphore: Arc<Semaphore>,
-    background_semaphore: Arc<Semaphore>,
+    scheduler: Arc<Scheduler>,
     stats: Arc<StatsCollector>,
     chunk_notifiers: Arc<Mutex<Vec<Option<Arc<Notify>>>>>,
     cancel_tokens: Arc<Mutex<Vec<Option<CancellationToken>>>>,
+    /// Why the last download of each chunk failed, for its waiters.
+    chunk_errors: Arc<Mutex<Vec<Option<ProxyError>>>>,
+    /// Rate limits and circuit breaker shared with other sessions on the host.
+    limiter: Arc<HostLimiter>,
+    /// This session's bandwidth cap; the engine-wide one applies as well.
+    bandwidth: Arc<BandwidthLimiter>,
+    shutdown_token: CancellationToken,
+    max_retries: u32,
+}
+
+/// One download worker; pulls chunks from the scheduler until shutdown.
+#[derive(Clone)]
+struct Worker {
+    source: Arc<dyn MediaSource>,
+    cache: Arc<DiskCache>,
+    scheduler: Arc<Scheduler>,
+    stats: Arc<StatsCollector>,
+    chunk_notifiers: Arc<Mutex<Vec<Option<Arc<Notify>>>>>,
+    cancel_tokens: Arc<Mutex<Vec<Option<CancellationToken>>>>,
+    /// Why the last download of each chunk failed, for its waiters.
+    chunk_errors: Arc<Mutex<Vec<Option<ProxyError>>>>,
+    /// Rate limits and circuit breaker shared with other sessions on the host.
+    limiter: Arc<HostLimiter>,
+    /// This session's bandwidth cap; the engine-wide one applies as well.
+    bandwidth: Arc<BandwidthLimiter>,
     shutdown_token: CancellationToken,
     max_retries: u32,
 }
 
 impl Downloader {
+    /// Spawns the download workers, so it must run inside a Tokio runtime.
+    /// `max_concurrency` bounds the worker count; within it the count adapts
+    /// to measured throughput.
     pub fn new(
         source: Arc<dyn MediaSource>,
         cache: Arc<DiskCache>,
@@ -32,18 +76,78 @@ impl Downloader {
         stats: Arc<StatsCollector>,
     ) -> Self {
         let total_chunks = cache.total_chunks();
-        let urgent_permits = 2usize;
-        let background_permits = (max_concurrency as usize).saturating_sub(urgent_permits).max(1);
-        Self {
+        let background_workers = (max_concurrency as usize)
+            .saturating_sub(SCHEDULER_URGENT_WORKERS)
+            .max(1);
+        let host = source.host_key();
+        let limiter = match &host {
+            Some(host) => host_limiter(host),
+            None => Arc::new(HostLimiter::new(String::new(), None)),
+        };
+        let controller = ConcurrencyController::new(host, background_workers);
+        let downloader = Self {
             source,
+            scheduler: Arc::new(Scheduler::new(cache.chunk_size(), controller.limit())),
             cache,
-            urgent_semaphore: Arc::new(Semaphore::new(urgent_permits)),
-            background_semaphore: Arc::new(Semaphore::new(background_permits)),
             stats,
             chunk_notifiers: Arc::new(Mutex::new(vec![None; total_chunks])),
             cancel_tokens: Arc::new(Mutex::new(vec![None; total_chunks])),
+            chunk_errors: Arc::new(Mutex::new(vec![None; total_chunks])),
+            limiter,
+            bandwidth: Arc::new(BandwidthLimiter::new()),
             shutdown_token: CancellationToken::new(),
             max_retries: 3,
+        };
+
+        // Background chunks use at most `background_workers`; the rest stay
+        // free for chunks the player is blocked on.
+        let worker = Worker {
+            source: Arc::clone(&downloader.source),
+            cache: Arc::clone(&downloader.cache),
+            scheduler: Arc::clone(&downloader.scheduler),
+            stats: Arc::clone(&downloader.stats),
+            chunk_notifiers: Arc::clone(&downloader.chunk_notifiers),
+            cancel_tokens: Arc::clone(&downloader.cancel_tokens),
+            chunk_errors: Arc::clone(&downloader.chunk_errors),
+            limiter: Arc::clone(&downloader.limiter),
+            bandwidth: Arc::clone(&downloader.bandwidth),
+            shutdown_token: downloader.shutdown_token.clone(),
+            max_retries: downloader.max_retries,
+        };
+        for _ in 0..background_workers + SCHEDULER_URGENT_WORKERS {
+            tokio::spawn(worker.clone().run());
+        }
+        tokio::spawn(Self::tune_concurrency(
+            controller,
+            Arc::clone(&downloader.scheduler),
+            Arc::clone(&downloader.stats),
+            downloader.shutdown_token.clone(),
+        ));
+        downloader
+    }
+
+    /// Re-evaluate the background worker count every sample interval.
+    async fn tune_concurrency(
+        controller: ConcurrencyController,
+        scheduler: Arc<Scheduler>,
+        stats: Arc<StatsCollector>,
+        shutdown_token: CancellationToken,
+    ) {
+        let mut interval =
+            tokio::time::interval(Duration::from_millis(ADAPTIVE_SAMPLE_INTERVAL_MS));
+        interval.tick().await;
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown_token.cancelled() => return,
+            }
+            let limit = controller.sample(
+                stats.total_downloaded(),
+                stats.fetch_errors(),
+                scheduler.is_saturated(),
+                Instant::now(),
+            );
+            scheduler.set_background_limit(limit);
         }
     }
 
@@ -51,25 +155,73 @@ impl Downloader {
     pub fn shutdown(&self) {
         self.shutdown_token.cancel();
         // Also cancel all individual chunk tokens so in-flight fetches exit promptly.
-        let tokens = self.cancel_tokens.lock();
+        let mut tokens = self.cancel_tokens.lock();
         for slot in tokens.iter() {
             if let Some(token) = slot {
                 token.cancel();
             }
         }
+        // Queued chunks will never reach a worker; release their waiters.
+        for chunk_index in self.scheduler.drain() {
+            tokens[chunk_index] = None;
+            let notify = self.chunk_notifiers.lock()[chunk_index].take();
+            if let Some(notify) = notify {
+                notify.notify_waiters();
+            }
+        }
     }
 
-    /// Idempotent: start downloading a chunk with background priority.
+    /// Idempotent: queue a chunk ahead of playback; its deadline follows the
+    /// playback position.
     pub fn start_prefetch(&self, chunk_index: usize) {
-        self.start_download(chunk_index, Arc::clone(&self.background_semaphore), false);
+        self.start_download(chunk_index, Tier::Prefetch);
     }
 
-    /// Idempotent: start downloading a chunk with urgent priority (dedicated permits).
+    /// Idempotent: queue a chunk the player is blocked on.
     pub fn start_urgent_prefetch(&self, chunk_index: usize) {
-        self.start_download(chunk_index, Arc::clone(&self.urgent_semaphore), true);
+        self.start_download(chunk_index, Tier::Urgent);
+    }
+
+    /// Idempotent: queue a chunk needed to start playback (container head,
+    /// tail or index).
+    pub fn start_warmup(&self, chunk_index: usize) {
+        self.start_download(chunk_index, Tier::Warmup);
+    }
+
+    /// Forget queued prefetch at or past `end_chunk`; running downloads
+    /// finish.
+    pub fn trim_prefetch(&self, end_chunk: usize) {
+        let mut tokens = self.cancel_tokens.lock();
+        let dropped = self.scheduler.drop_prefetch_from(end_chunk);
+        if !dropped.is_empty() {
+            debug!("dropped {} queued prefetch chunks", dropped.len());
+        }
+        for chunk_index in dropped {
+            tokens[chunk_index] = None;
+            let notify = self.chunk_notifiers.lock()[chunk_index].take();
+            if let Some(notify) = notify {
+                notify.notify_waiters();
+            }
+        }
+    }
+
+    /// Cap this session's download rate in bytes per second; 0 removes the
+    /// cap. Urgent chunks may exceed it.
+    pub fn set_bandwidth_limit(&self, bytes_per_sec: u64) {
+        self.bandwidth.set_limit(bytes_per_sec);
     }
 
-    fn start_download(&self, chunk_index: usize, semaphore: Arc<Semaphore>, urgent: bool) {
+    pub fn bandwidth_limit(&self) -> u64 {
+        self.bandwidth.limit()
+    }
+
+    /// Tell the scheduler where playback is and how fast it moves. Queued
+    /// prefetch chunks are re-ordered, never cancelled.
+    pub fn set_playback(&self, offset: u64, bytes_per_sec: u64) {
+        self.scheduler.set_playback(offset, bytes_per_sec);
+    }
+
+    fn start_download(&self, chunk_index: usize, tier: Tier) {
         // Don't start new work if shutdown has been requested.
         if self.shutdown_token.is_cancelled() {
             return;
@@ -80,179 +232,454 @@ impl Downloader {
             return;
         }
 
-        // Check if already in-flight.
-        {
-            let tokens = self.cancel_tokens.lock();
-            if tokens[chunk_index].is_some() {
-                return;
-            }
+        let mut tokens = self.cancel_tokens.lock();
+        // Shutdown drains the queue under this lock; don't queue after it.
+        if self.shutdown_token.is_cancelled() {
+            return;
+        }
+        if tokens[chunk_index].is_some() {
+            // Queued or running; a queued chunk may still move up a tier.
+            self.scheduler.promote(chunk_index, tier);
+            return;
         }
 
-        // Set up notifier and cancel token.
-        let token = CancellationToken::new();
-        let notify = Arc::new(Notify::new());
+        // The slots are filled before queueing so the worker finds them.
+        tokens[chunk_index] = Some(CancellationToken::new());
+        self.chunk_notifiers.lock()[chunk_index] = Some(Arc::new(Notify::new()));
+        self.scheduler.push(chunk_index, tier);
+    }
+}
 
-        {
-            let mut tokens = self.cancel_tokens.lock();
-            // Double-check after acquiring lock.
-            if tokens[chunk_index].is_some() {
-                return;
+impl Worker {
+    async fn run(self) {
+        loop {
+            let (chunk_index, tier) = tokio::select! {
+                next = self.scheduler.next() => next,
+                _ = self.shutdown_token.cancelled() => return,
+            };
+            // Background chunks right behind this one ride along in the
+            // same upstream request.
+            let following = if tier == Tier::Urgent {
+                Vec::new()
+            } else {
+                self.scheduler
+                    .take_following(chunk_index, tier, MERGE_MAX_CHUNKS - 1)
+            };
+            if following.is_empty() {
+                self.download(chunk_index, tier).await;
+            } else {
+                let mut chunks = vec![chunk_index];
+                chunks.extend(following);
+                self.download_merged(chunks).await;
             }
-            tokens[chunk_index] = Some(token.clone());
-        }
-        {
-            let mut notifiers = self.chunk_notifiers.lock();
-            notifiers[chunk_index] = Some(notify.clone());
+            self.scheduler.finish(tier);
         }
+    }
 
-        let source = Arc::clone(&self.source);
-        let cache = Arc::clone(&self.cache);
-        let stats = Arc::clone(&self.stats);
-        let cancel_tokens = Arc::clone(&self.cancel_tokens);
-        let chunk_notifiers = Arc::clone(&self.chunk_notifiers);
-        let shutdown_token = self.shutdown_token.clone();
-        let max_retries = self.max_retries;
-        let chunk_size = cache.chunk_size();
+    async fn download(&self, chunk_index: usize, tier: Tier) {
+        let token = self.cancel_tokens.lock()[chunk_index].clone();
+        let notify = self.chunk_notifiers.lock()[chunk_index].clone();
+        let (Some(token), Some(notify)) = (token, notify) else {
+            return;
+        };
 
-        tokio::spawn(async move {
-            let _result = Self::download_chunk_task(
+        self.chunk_errors.lock()[chunk_index] = None;
+        if !token.is_cancelled() {
+            debug!("chunk {} started ({:?})", chunk_index, tier);
+            self.stats.increment_workers();
+            let result = Downloader::fetch_with_retry(
                 chunk_index,
-                source,
-                cache,
-                semaphore,
-                stats,
-                token,
-                shutdown_token,
-                max_retries,
-                chunk_size,
-                urgent,
+                &self.source,
+                &self.limiter,
+                &self.bandwidth,
+                &self.cache,
+                &self.stats,
+                &token,
+                &notify,
+                self.max_retries,
+                self.cache.chunk_size(),
+                tier == Tier::Urgent,
             )
             .await;
+            self.stats.decrement_workers();
+            if let Err(e) = result {
+                debug!("chunk {} gave up: {}", chunk_index, e);
+                self.chunk_errors.lock()[chunk_index] = Some(ProxyError::classify(&e));
+            }
+        }
 
-            // Notify waiters regardless of success/failure.
-            {
-                let notifiers = chunk_notifiers.lock();
-                if let Some(n) = &notifiers[chunk_index] {
-                    n.notify_waiters();
+        self.release(chunk_index, &notify);
+    }
+
+    /// Download consecutive chunks (the run of merged members starting
+    /// with the one the scheduler handed out) with one ranged request. Each
+    /// chunk is released to its waiters as soon as its bytes are in; chunks
+    /// the request did not complete fall back to per-chunk downloads at
+    /// their current tier. Members promoted to urgent before their first
+    /// byte are cut from the run and left to an urgent worker.
+    async fn download_merged(&self, mut chunks: Vec<usize>) {
+        let mut tokens = Vec::with_capacity(chunks.len());
+        let mut notifies = Vec::with_capacity(chunks.len());
+        for &i in &chunks {
+            let token = self.cancel_tokens.lock()[i].clone();
+            let notify = self.chunk_notifiers.lock()[i].clone();
+            if let (Some(token), Some(notify)) = (token, notify) {
+                tokens.push(token);
+                notifies.push(notify);
+            }
+        }
+        if tokens.len() != chunks.len() {
+            let tiers = self.scheduler.leave_run(&chunks);
+            for (&i, tier) in chunks.iter().zip(tiers) {
+                if let Some(tier) = tier {
+                    self.download(i, tier).await;
                 }
             }
+            return;
+        }
 
-            // Cleanup: remove token and notifier.
-            {
-                let mut tokens = cancel_tokens.lock();
-                tokens[chunk_index] = None;
+        debug!(
+            "chunks {}..={} merged into one request",
+            chunks[0],
+            chunks[chunks.len() - 1]
+        );
+        let mut done = 0;
+        self.stats.increment_workers();
+        let result = self
+            .stream_merged(&mut chunks, &tokens, &notifies, &mut done)
+            .await;
+        self.stats.decrement_workers();
+
+        // What is left of the run: not completed, not promoted out.
+        let rest: Vec<(usize, Tier, &Arc<Notify>)> = chunks[done..]
+            .iter()
+            .zip(self.scheduler.leave_run(&chunks[done..]))
+            .zip(&notifies[done..])
+            .filter_map(|((&i, tier), notify)| tier.map(|tier| (i, tier, notify)))
+            .collect();
+        match result {
+            Ok(true) => {}
+            Ok(false) => {
+                // Cancelled: nothing more will arrive for the rest.
+                for (i, _, notify) in rest {
+                    self.chunk_errors.lock()[i] = Some(ProxyError::cancelled());
+                    self.release(i, notify);
+                }
             }
-            {
-                let mut notifiers = chunk_notifiers.lock();
-                notifiers[chunk_index] = None;
+            Err(e) => {
+                warn!(
+                    "merged request for chunks {}..={} failed at chunk {}: {}",
+                    chunks[0],
+                    chunks[chunks.len() - 1],
+                    chunks[done],
+                    e
+                );
+                let error = ProxyError::classify(&e);
+                if error.kind == ProxyErrorKind::SourceChanged {
+                    // Bytes kept from before the run may be another version's.
+                    self.cache.discard_partial(chunks[done]);
+                }
+                let retry = error.kind.is_retryable() || error.kind == ProxyErrorKind::AuthExpired;
+                for (i, tier, notify) in rest {
+                    if retry {
+                        self.download(i, tier).await;
+                    } else {
+                        self.chunk_errors.lock()[i] = Some(error.clone());
+                        self.release(i, notify);
+                    }
+                }
             }
-        });
+        }
     }
 
-    async fn download_chunk_task(
-        chunk_index: usize,
-        source: Arc<dyn MediaSource>,
-        cache: Arc<DiskCache>,
-        semaphore: Arc<Semaphore>,
-        stats: Arc<StatsCollector>,
-        token: CancellationToken,
-        shutdown_token: CancellationToken,
-        max_retries: u32,
-        chunk_size: u64,
-        urgent: bool,
-    ) -> Result<()> {
-        // Check shutdown before waiting for semaphore.
-        if shutdown_token.is_cancelled() {
-            debug!("chunk {} skipped: shutdown in progress", chunk_index);
-            return Ok(());
-        }
+    /// Stream `chunks` (consecutive) from one request, splitting the body
+    /// at chunk boundaries. `done` counts the chunks completed and released.
+    /// The run is cut before a member that was promoted out of it, so the
+    /// request may end early. The source fails the request before any byte
+    /// is written unless it answers with the range asked for. Returns
+    /// `Ok(false)` if cancelled.
+    async fn stream_merged(
+        &self,
+        chunks: &mut Vec<usize>,
+        tokens: &[CancellationToken],
+        notifies: &[Arc<Notify>],
+        done: &mut usize,
+    ) -> Result<bool> {
+        let chunk_size = self.cache.chunk_size();
+        let first = chunks[0];
+        let last = chunks[chunks.len() - 1];
+        let mut written = self.cache.available_in_chunk(first);
+        let start = first as u64 * chunk_size + written;
+        let end = last as u64 * chunk_size + self.cache.chunk_len(last) as u64 - 1;
+
+        // Background requests wait out host pauses and open circuits; a
+        // member turning urgent meanwhile must not wait with them.
+        let request = self
+            .limiter
+            .request(false, self.source.fetch_range_stream(start, end));
+        tokio::pin!(request);
+        let mut stream = loop {
+            let promoted = self.scheduler.member_promoted();
+            tokio::select! {
+                stream = &mut request => break stream?,
+                _ = tokens[0].cancelled() => return Ok(false),
+                _ = promoted => {
+                    self.split_run(chunks, 0);
+                    if chunks.is_empty() {
+                        return Ok(true);
+                    }
+                }
+            }
+        };
 
-        let priority_label = if urgent { "urgent" } else { "background" };
+        let mut pending: Vec<u8> = Vec::new();
+        let mut entered = false;
+        loop {
+            if !entered {
+                if !self.scheduler.start_member(chunks[*done]) {
+                    self.split_run(chunks, *done);
+                    return Ok(true);
+                }
+                entered = true;
+            }
+            // Released chunks drop out of `cancel_tokens`; watch the one
+            // in progress.
+            let token = &tokens[*done];
+            let piece = tokio::select! {
+                piece = stream.next() => piece,
+                _ = token.cancelled() => return Ok(false),
+            };
+            let bytes = match piece {
+                Some(Ok(bytes)) => bytes,
+                Some(Err(e)) => {
+                    // Keep what arrived for the chunk in progress.
+                    if *done < chunks.len() && !pending.is_empty() {
+                        Downloader::flush_pending(
+                            chunks[*done],
+                            &mut written,
+                            &mut pending,
+                            &self.cache,
+                            &self.stats,
+                        )?;
+                        notifies[*done].notify_waiters();
+                    }
+                    return Err(e);
+                }
+                None => break,
+            };
+            // A member promoted while being written is paced as urgent.
+            let urgent = self.scheduler.member_tier(chunks[*done]) == Some(Tier::Urgent);
+            if !pace(&self.bandwidth, bytes.len() as u64, urgent, token).await {
+                return Ok(false);
+            }
+
+            let mut data = &bytes[..];
+            while !data.is_empty() {
+                if !entered {
+                    if !self.scheduler.start_member(chunks[*done]) {
+                        self.split_run(chunks, *done);
+                        return Ok(true);
+                    }
+                    entered = true;
+                }
+                let chunk_index = chunks[*done];
+                let chunk_len = self.cache.chunk_len(chunk_index) as u64;
+                let room = (chunk_len - written - pending.len() as u64) as usize;
+                let take = room.min(data.len());
+                pending.extend_from_slice(&data[..take]);
+                data = &data[take..];
 
-        // Acquire semaphore permit, but bail if shutdown fires while waiting.
-        let _permit = tokio::select! {
-            permit = semaphore.acquire() => {
-                permit.map_err(|e| anyhow::anyhow!("{}", e))?
+                let complete = written + pending.len() as u64 == chunk_len;
+                if complete || pending.len() as u64 >= PROGRESSIVE_FLUSH_BYTES {
+                    Downloader::flush_pending(
+                        chunk_index,
+                        &mut written,
+                        &mut pending,
+                        &self.cache,
+                        &self.stats,
+                    )?;
+                    notifies[*done].notify_waiters();
+                }
+                if complete {
+                    self.cache.finish_chunk(chunk_index).map_err(cache_io)?;
+                    self.scheduler.leave_run(&[chunk_index]);
+                    self.release(chunk_index, &notifies[*done]);
+                    *done += 1;
+                    written = 0;
+                    entered = false;
+                    if *done == chunks.len() {
+                        // Anything past a cut run is someone else's.
+                        return Ok(true);
+                    }
+                }
             }
-            _ = shutdown_token.cancelled() => {
-                debug!("chunk {} cancelled while waiting for {} semaphore", chunk_index, priority_label);
-                return Ok(());
+        }
+
+        if *done < chunks.len() {
+            if !pending.is_empty() {
+                Downloader::flush_pending(
+                    chunks[*done],
+                    &mut written,
+                    &mut pending,
+                    &self.cache,
+                    &self.stats,
+                )?;
+                notifies[*done].notify_waiters();
             }
-        };
+            return Err(ProxyError::new(
+                ProxyErrorKind::Network,
+                format!(
+                    "merged response for chunks {}..={} ended short",
+                    first, last
+                ),
+            )
+            .into());
+        }
+        Ok(true)
+    }
 
-        debug!("chunk {} acquired {} semaphore permit", chunk_index, priority_label);
-        stats.increment_workers();
-
-        let result = Self::fetch_with_retry(
-            chunk_index,
-            &source,
-            &cache,
-            &stats,
-            &token,
-            max_retries,
-            chunk_size,
-        )
-        .await;
-
-        stats.decrement_workers();
-        result
+    /// Cut `chunks` before the first member from `from` on that was
+    /// promoted out of the run; the members after it are queued again.
+    fn split_run(&self, chunks: &mut Vec<usize>, from: usize) {
+        let cut = chunks[from..]
+            .iter()
+            .position(|&i| self.scheduler.member_tier(i).is_none())
+            .map(|k| k + from);
+        if let Some(k) = cut {
+            debug!("chunk {} promoted out of its merged run", chunks[k]);
+            self.scheduler.requeue_members(&chunks[k + 1..]);
+            chunks.truncate(k);
+        }
     }
 
+    /// Clear a chunk's slots and wake its waiters.
+    fn release(&self, chunk_index: usize, notify: &Notify) {
+        // Cleanup first: a waiter that registers after this sees the
+        // empty slot, and one that registered before gets the wake-up.
+        {
+            let mut tokens = self.cancel_tokens.lock();
+            tokens[chunk_index] = None;
+        }
+        {
+            let mut notifiers = self.chunk_notifiers.lock();
+            notifiers[chunk_index] = None;
+        }
+
+        // Notify waiters regardless of success/failure.
+        notify.notify_waiters();
+    }
+}
+
+impl Downloader {
+    #[allow(clippy::too_many_arguments)]
     async fn fetch_with_retry(
         chunk_index: usize,
         source: &Arc<dyn MediaSource>,
+        limiter: &Arc<HostLimiter>,
+        bandwidth: &BandwidthLimiter,
         cache: &Arc<DiskCache>,
         stats: &Arc<StatsCollector>,
         token: &CancellationToken,
+        notify: &Notify,
         max_retries: u32,
         chunk_size: u64,
+        urgent: bool,
     ) -> Result<()> {
         let start = chunk_index as u64 * chunk_size;
         let end = start + cache.chunk_len(chunk_index) as u64 - 1;
 
-        for attempt in 0..=max_retries {
+        let mut attempt = 0;
+        let mut last_error = None;
+        while attempt <= max_retries {
             // Check cancellation before each attempt.
             if token.is_cancelled() {
                 debug!("chunk {} cancelled before fetch", chunk_index);
                 return Ok(());
             }
 
-            match source.fetch_range(start, end).await {
-                Ok(data) => {
-                    // Check cancellation after fetch.
-                    if token.is_cancelled() {
-                        debug!("chunk {} cancelled after fetch", chunk_index);
-                        return Ok(());
-                    }
-
-                    cache.put_chunk(chunk_index, &data)?;
-                    stats.record_downloaded(data.len() as u64);
-                    debug!("chunk {} downloaded ({} bytes)", chunk_index, data.len());
+            let before = cache.available_in_chunk(chunk_index);
+            match Self::stream_chunk(
+                chunk_index,
+                start,
+                end,
+                source,
+                limiter,
+                bandwidth,
+                cache,
+                stats,
+                token,
+                notify,
+                urgent,
+            )
+            .await
+            {
+                Ok(true) => {
+                    debug!(
+                        "chunk {} downloaded ({} bytes)",
+                        chunk_index,
+                        end - start + 1
+                    );
                     return Ok(());
                 }
+                Ok(false) => {
+                    debug!("chunk {} cancelled during fetch", chunk_index);
+                    return Err(ProxyError::cancelled().into());
+                }
                 Err(e) => {
-                    let msg = e.to_string();
-                    if msg.contains("auth_rejected") {
+                    // A cut-off or short response still moved the chunk
+                    // forward; resume from there without spending a retry.
+                    // Its range was checked when it started, so only a
+                    // network failure leaves bytes worth keeping.
+                    let error = ProxyError::classify(&e);
+                    let after = cache.available_in_chunk(chunk_index);
+                    if after > before && error.kind == ProxyErrorKind::Network {
                         warn!(
-                            "chunk {} auth rejected, refreshing auth (attempt {})",
-                            chunk_index, attempt
+                            "chunk {} interrupted at byte {}, resuming: {}",
+                            chunk_index, after, e
                         );
-                        if let Err(re) = source.refresh_auth().await {
-                            warn!("refresh_auth failed: {}", re);
-                        }
-                        // Retry after auth refresh.
                         continue;
                     }
+                    if error.kind == ProxyErrorKind::SourceChanged {
+                        // The offset or length no longer matches; what the
+                        // chunk holds may belong to another version.
+                        cache.discard_partial(chunk_index);
+                    }
+
+                    attempt += 1;
+                    match error.kind {
+                        ProxyErrorKind::AuthExpired => {
+                            warn!(
+                                "chunk {} auth rejected, refreshing auth (attempt {})",
+                                chunk_index, attempt
+                            );
+                            if let Err(re) = source.refresh_auth().await {
+                                warn!("refresh_auth failed: {}", re);
+                            }
+                            // Retry after auth refresh.
+                            last_error = Some(e);
+                            continue;
+                        }
+                        kind if !kind.is_retryable() => {
+                            warn!("chunk {} fetch failed, not retrying: {}", chunk_index, e);
+                            return Err(e);
+                        }
+                        _ => {}
+                    }
+                    stats.record_fetch_error();
 
-                    if attempt < max_retries {
+                    if attempt <= max_retries {
                         warn!(
                             "chunk {} fetch failed (attempt {}): {}",
-                            chunk_index, attempt, e
+                            chunk_index,
+                            attempt - 1,
+                            e
                         );
-                        tokio::time::sleep(std::time::Duration::from_millis(
-                            500 * (attempt as u64 + 1),
-                        ))
-                        .await;
+                        // Rate limits pause the whole host in the limiter;
+                        // other failures back off per chunk.
+                        if error.kind != ProxyErrorKind::RateLimited && error.retry_after.is_none()
+                        {
+                            tokio::time::sleep(Duration::from_millis(500 * attempt as u64)).await;
+                        }
                     } else {
                         warn!(
                             "chunk {} fetch failed after {} retries: {}",
@@ -264,40 +691,388 @@ impl Downloader {
             }
         }
 
-        Ok(())
+        // Only reached when auth refreshes used up the attempts.
+        Err(last_error.unwrap_or_else(|| anyhow::anyhow!("chunk {} not downloaded", chunk_index)))
     }
 
-    /// Wait until the chunk is cached. Returns `true` if available, `false` on timeout/failure.
-    pub async fn wait_for_chunk(&self, chunk_index: usize) -> bool {
-        if self.cache.has_chunk(chunk_index) {
-            return true;
+    /// One fetch attempt for the chunk `[start, end]`, written into the
+    /// cache piece by piece so readers can forward bytes before the chunk
+    /// completes. Bytes kept from earlier attempts are not requested again.
+    /// `urgent` chunks get a second request for the remainder raced against
+    /// a connection that stops making progress, and keep going while the
+    /// host is paused. Returns `Ok(false)` if cancelled.
+    #[allow(clippy::too_many_arguments)]
+    async fn stream_chunk(
+        chunk_index: usize,
+        start: u64,
+        end: u64,
+        source: &Arc<dyn MediaSource>,
+        limiter: &Arc<HostLimiter>,
+        bandwidth: &BandwidthLimiter,
+        cache: &Arc<DiskCache>,
+        stats: &Arc<StatsCollector>,
+        token: &CancellationToken,
+        notify: &Notify,
+        urgent: bool,
+    ) -> Result<bool> {
+        let chunk_len = end - start + 1;
+        let mut written = cache.available_in_chunk(chunk_index);
+        if written >= chunk_len {
+            // Every byte arrived before; only the seal is missing.
+            cache.finish_chunk(chunk_index).map_err(cache_io)?;
+            return Ok(true);
+        }
+        if written > 0 {
+            debug!("chunk {} resuming at byte {}", chunk_index, written);
         }
 
-        // Ensure the chunk is being fetched.
-        self.start_prefetch(chunk_index);
+        // A cold first chunk gates startup; spread it over the route pool.
+        let routes = source.route_count().min(FIRST_CHUNK_SPLIT_MAX_PARTS);
+        if chunk_index == 0 && written == 0 && routes > 1 {
+            let parts = routes.min((chunk_len / FIRST_CHUNK_MIN_PART_BYTES) as usize);
+            if parts > 1 {
+                return Self::fetch_split(
+                    chunk_index,
+                    start,
+                    end,
+                    parts,
+                    source,
+                    limiter,
+                    bandwidth,
+                    urgent,
+                    cache,
+                    stats,
+                    token,
+                    notify,
+                )
+                .await;
+            }
+        }
 
-        // Get the notifier.
-        let notify = {
-            let notifiers = self.chunk_notifiers.lock();
-            notifiers[chunk_index].clone()
+        let stream = tokio::select! {
+            stream = limiter.request(urgent, source.fetch_range_stream(start + written, end)) => stream?,
+            _ = token.cancelled() => return Ok(false),
         };
+        let mut primary = Some(Leg {
+            stream,
+            pos: written,
+        });
+        let mut hedge_leg: Option<Leg> = None;
+        let mut hedge_request: Option<HedgeRequest> = None;
+        let mut hedge_from = 0u64;
+        let mut hedged = !urgent;
+
+        let window = Duration::from_millis(HEDGE_PROGRESS_WINDOW_MS);
+        let window_min = HEDGE_MIN_BYTES_PER_SEC * HEDGE_PROGRESS_WINDOW_MS / 1000;
+        let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + window, window);
+        let mut window_start = written;
+
+        let mut pending: Vec<u8> = Vec::new();
+        let mut last_error = None;
+        loop {
+            let frontier = written + pending.len() as u64;
+            if frontier >= chunk_len
+                || (primary.is_none() && hedge_leg.is_none() && hedge_request.is_none())
+            {
+                break;
+            }
+
+            let event = tokio::select! {
+                piece = next_piece(&mut primary), if primary.is_some() => LegEvent::Piece(false, piece),
+                piece = next_piece(&mut hedge_leg), if hedge_leg.is_some() => LegEvent::Piece(true, piece),
+                stream = async { hedge_request.as_mut().expect("guarded").await }, if hedge_request.is_some() => {
+                    LegEvent::HedgeReady(stream)
+                }
+                _ = ticker.tick(), if !hedged => LegEvent::Tick,
+                _ = token.cancelled() => return Ok(false),
+            };
+
+            let mut received = 0;
+            match event {
+                LegEvent::Tick => {
+                    if frontier - window_start < window_min {
+                        // Slow connection: ask again for what is missing,
+                        // usually over another route, and keep both.
+                        debug!(
+                            "chunk {} slow ({} bytes in {} ms), hedging from byte {}",
+                            chunk_index,
+                            frontier - window_start,
+                            HEDGE_PROGRESS_WINDOW_MS,
+                            frontier
+                        );
+                        hedged = true;
+                        hedge_from = frontier;
+                        let source = Arc::clone(source);
+                        let limiter = Arc::clone(limiter);
+                        hedge_request = Some(Box::pin(async move {
+                            limiter
+                                .request(true, source.fetch_range_stream(start + hedge_from, end))
+                                .await
+                        }));
+                    }
+                    window_start = frontier;
+                }
+                LegEvent::HedgeReady(stream) => {
+                    hedge_request = None;
+                    match stream {
+                        Ok(stream) => {
+                            hedge_leg = Some(Leg {
+                                stream,
+                                pos: hedge_from,
+                            })
+                        }
+                        Err(e) => {
+                            warn!("chunk {} hedged request failed: {}", chunk_index, e);
+                            last_error = Some(e);
+                        }
+                    }
+                }
+                LegEvent::Piece(from_hedge, piece) => {
+                    let other_alive = if from_hedge {
+                        primary.is_some()
+                    } else {
+                        hedge_leg.is_some() || hedge_request.is_some()
+                    };
+                    let leg = if from_hedge {
+                        &mut hedge_leg
+                    } else {
+                        &mut primary
+                    };
+                    match piece {
+                        Some(Ok(bytes)) => {
+                            let Some(current) = leg.as_mut() else {
+                                continue;
+                            };
+                            let pos = current.pos;
+                            current.pos += bytes.len() as u64;
+                            received = bytes.len() as u64;
+                            if current.pos > chunk_len {
+                                // The bytes already flushed came from the
+                                // same response; none of them can be trusted.
+                                cache.discard_partial(chunk_index);
+                                return Err(ProxyError::new(
+                                    ProxyErrorKind::Upstream5xx,
+                                    format!(
+                                        "chunk {} response longer than {} bytes",
+                                        chunk_index, chunk_len
+                                    ),
+                                )
+                                .into());
+                            }
+                            // Both legs carry the same bytes; keep whatever
+                            // extends the frontier.
+                            if current.pos > frontier {
+                                pending.extend_from_slice(&bytes[(frontier - pos) as usize..]);
+                            }
+                        }
+                        Some(Err(e)) => {
+                            if other_alive {
+                                warn!("chunk {} dropped a failed leg: {}", chunk_index, e);
+                            }
+                            *leg = None;
+                            last_error = Some(e);
+                        }
+                        None => *leg = None,
+                    }
+                }
+            }
 
-        if let Some(notify) = notify {
-            notify.notified().await;
+            if received > 0 && !pace(bandwidth, received, urgent, token).await {
+                return Ok(false);
+            }
+
+            // Coalesce small network reads before touching the cache.
+            let frontier = written + pending.len() as u64;
+            if !pending.is_empty()
+                && (frontier >= chunk_len || pending.len() as u64 >= PROGRESSIVE_FLUSH_BYTES)
+            {
+                Self::flush_pending(chunk_index, &mut written, &mut pending, cache, stats)?;
+                notify.notify_waiters();
+            }
         }
 
-        self.cache.has_chunk(chunk_index)
+        // Whatever arrived counts, even if the response was cut short.
+        if !pending.is_empty() {
+            Self::flush_pending(chunk_index, &mut written, &mut pending, cache, stats)?;
+            notify.notify_waiters();
+        }
+        if written != chunk_len {
+            if let Some(e) = last_error {
+                return Err(e);
+            }
+            return Err(ProxyError::new(
+                ProxyErrorKind::Network,
+                format!(
+                    "chunk {} short response: {} of {} bytes",
+                    chunk_index, written, chunk_len
+                ),
+            )
+            .into());
+        }
+        cache.finish_chunk(chunk_index).map_err(cache_io)?;
+        Ok(true)
     }
 
-    /// Cancel all in-flight downloads outside the range [start_chunk, end_chunk).
-    pub fn abort_outside_window(&self, start_chunk: usize, end_chunk: usize) {
-        let tokens = self.cancel_tokens.lock();
-        for (i, slot) in tokens.iter().enumerate() {
-            if i >= start_chunk && i < end_chunk {
+    fn flush_pending(
+        chunk_index: usize,
+        written: &mut u64,
+        pending: &mut Vec<u8>,
+        cache: &DiskCache,
+        stats: &StatsCollector,
+    ) -> Result<()> {
+        cache
+            .write_partial(chunk_index, *written, pending)
+            .map_err(cache_io)?;
+        *written += pending.len() as u64;
+        stats.record_downloaded(pending.len() as u64);
+        pending.clear();
+        Ok(())
+    }
+
+    /// Fetch a cold chunk as `parts` parallel sub-ranges. The source rotates
+    /// routes per request, so the parts travel over different IPs. Parts are
+    /// written in order as soon as all earlier ones are in.
+    #[allow(clippy::too_many_arguments)]
+    async fn fetch_split(
+        chunk_index: usize,
+        start: u64,
+        end: u64,
+        parts: usize,
+        source: &Arc<dyn MediaSource>,
+        limiter: &Arc<HostLimiter>,
+        bandwidth: &BandwidthLimiter,
+        urgent: bool,
+        cache: &Arc<DiskCache>,
+        stats: &Arc<StatsCollector>,
+        token: &CancellationToken,
+        notify: &Notify,
+    ) -> Result<bool> {
+        let chunk_len = end - start + 1;
+        let part_len = chunk_len.div_ceil(parts as u64);
+        debug!(
+            "chunk {} split into {} parts of {} bytes",
+            chunk_index, parts, part_len
+        );
+        let handles: Vec<_> = (0..parts as u64)
+            .map(|k| {
+                let source = Arc::clone(source);
+                let limiter = Arc::clone(limiter);
+                let part_start = start + k * part_len;
+                let part_end = (part_start + part_len - 1).min(end);
+                tokio::spawn(async move {
+                    limiter
+                        .request(urgent, source.fetch_range(part_start, part_end))
+                        .await
+                })
+            })
+            .collect();
+        let _abort = AbortOnDrop(handles.iter().map(|h| h.abort_handle()).collect());
+
+        let mut written = 0u64;
+        for (k, handle) in handles.into_iter().enumerate() {
+            let data = tokio::select! {
+                joined = handle => joined.map_err(|e| anyhow::anyhow!("part {} task: {}", k, e))??,
+                _ = token.cancelled() => return Ok(false),
+            };
+            if !pace(bandwidth, data.len() as u64, urgent, token).await {
+                return Ok(false);
+            }
+            let expected = part_len.min(chunk_len - written);
+            if data.len() as u64 != expected {
+                return Err(ProxyError::new(
+                    ProxyErrorKind::Network,
+                    format!(
+                        "chunk {} part {} short response: {} of {} bytes",
+                        chunk_index,
+                        k,
+                        data.len(),
+                        expected
+                    ),
+                )
+                .into());
+            }
+            cache
+                .write_partial(chunk_index, written, &data)
+                .map_err(cache_io)?;
+            written += data.len() as u64;
+            stats.record_downloaded(data.len() as u64);
+            notify.notify_waiters();
+        }
+        cache.finish_chunk(chunk_index).map_err(cache_io)?;
+        Ok(true)
+    }
+
+    /// Wait until the chunk is cached, or return why its download failed.
+    pub async fn wait_for_chunk(&self, chunk_index: usize) -> Result<(), ProxyError> {
+        let chunk_len = self.cache.chunk_len(chunk_index) as u64;
+        let mut have = 0;
+        loop {
+            let available = self.wait_for_progress(chunk_index, have).await?;
+            if available >= chunk_len {
+                return Ok(());
+            }
+            have = available;
+        }
+    }
+
+    /// Wait until more than `have` bytes from the start of the chunk are
+    /// available, starting a download if none is in flight. Returns the
+    /// available byte count, or why the download ended without getting
+    /// further.
+    pub async fn wait_for_progress(
+        &self,
+        chunk_index: usize,
+        have: u64,
+    ) -> Result<u64, ProxyError> {
+        let mut started = false;
+        loop {
+            let available = self.cache.available_in_chunk(chunk_index);
+            if available > have || self.cache.has_chunk(chunk_index) {
+                return Ok(available);
+            }
+
+            let notify = {
+                let notifiers = self.chunk_notifiers.lock();
+                notifiers[chunk_index].clone()
+            };
+            let Some(notify) = notify else {
+                if self.shutdown_token.is_cancelled() {
+                    return Err(ProxyError::cancelled());
+                }
+                if started {
+                    let error = self.chunk_errors.lock()[chunk_index].clone();
+                    return Err(error.unwrap_or_else(|| {
+                        ProxyError::new(
+                            ProxyErrorKind::Network,
+                            format!("chunk {} download ended without data", chunk_index),
+                        )
+                    }));
+                }
+                // Ensure the chunk is being fetched; someone is waiting on it.
+                started = true;
+                self.start_urgent_prefetch(chunk_index);
                 continue;
+            };
+            started = true;
+
+            // Register before re-checking so a piece landing in between
+            // still wakes us.
+            let notified = notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let available = self.cache.available_in_chunk(chunk_index);
+            if available > have || self.cache.has_chunk(chunk_index) {
+                return Ok(available);
             }
-            if let Some(token) = slot {
-                token.cancel();
+            let still_running = {
+                let notifiers = self.chunk_notifiers.lock();
+                notifiers[chunk_index]
+                    .as_ref()
+                    .is_some_and(|n| Arc::ptr_eq(n, &notify))
+            };
+            if still_running {
+                notified.await;
             }
         }
     }
@@ -309,4 +1084,91 @@ impl Downloader {
             self.start_prefetch(i);
         }
     }
+
+    /// Queue warmup for all chunks in the range [start_chunk, end_chunk).
+    pub fn warmup_range(&self, start_chunk: usize, end_chunk: usize) {
+        let end = end_chunk.min(self.cache.total_chunks());
+        for i in start_chunk..end {
+            self.start_warmup(i);
+        }
+    }
+
+    /// Read bytes [start, end) through the cache, fetching missing chunks
+    /// at warmup priority. Used for container indexes the engine parses.
+    pub async fn read_range(&self, start: u64, end: u64) -> Result<Bytes, ProxyError> {
+        let end = end.min(self.cache.content_length());
+        if start >= end {
+            return Ok(Bytes::new());
+        }
+        let chunk_size = self.cache.chunk_size();
+        let first_chunk = (start / chunk_size) as usize;
+        let last_chunk = ((end - 1) / chunk_size) as usize;
+        self.warmup_range(first_chunk, last_chunk + 1);
+        for i in first_chunk..=last_chunk {
+            self.wait_for_chunk(i).await?;
+        }
+        self.cache.read_bytes(start, end).ok_or_else(|| {
+            ProxyError::new(
+                ProxyErrorKind::CacheIo,
+                format!("cache read failed for range [{}, {})", start, end),
+            )
+        })
+    }
+}
+
+/// One response body feeding a chunk; `pos` is the chunk-relative offset of
+/// its next byte.
+struct Leg {
+    stream: ByteStream,
+    pos: u64,
+}
+
+type HedgeRequest = Pin<Box<dyn Future<Output = Result<ByteStream>> + Send>>;
+
+enum LegEvent {
+    Piece(bool, Option<Result<Bytes>>),
+    HedgeReady(Result<ByteStream>),
+    Tick,
+}
+
+async fn next_piece(leg: &mut Option<Leg>) -> Option<Result<Bytes>> {
+    match leg {
+        Some(leg) => leg.stream.next().await,
+        None => None,
+    }
+}
+
+/// Aborts split-fetch tasks that are no longer awaited.
+struct AbortOnDrop(Vec<AbortHandle>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        for handle in &self.0 {
+            handle.abort();
+        }
+    }
+}
+
+/// Charge `bytes` to the session and engine-wide bandwidth caps, waiting
+/// for tokens unless `urgent`, and to the network policy's counter.
+/// Returns `false` if cancelled meanwhile.
+async fn pace(
+    bandwidth: &BandwidthLimiter,
+    bytes: u64,
+    urgent: bool,
+    token: &CancellationToken,
+) -> bool {
+    policy::record_downloaded(bytes);
+    tokio::select! {
+        _ = async {
+            bandwidth.consume(bytes, urgent).await;
+            global_bandwidth().consume(bytes, urgent).await;
+        } => true,
+        _ = token.cancelled() => false,
+    }
+}
+
+/// Mark a cache failure as such for the retry policy and the UI.
+fn cache_io(e: anyhow::Error) -> anyhow::Error {
+    ProxyError::new(ProxyErrorKind::CacheIo, e.to_string()).into()
 }
@@ -0,0 +1,316 @@
+// Host limiter — per-host rate limiting and circuit breaking shared by all sessions.
+//
+// A 429/503 with `Retry-After`, a run of rate-limit responses or a run of
+// server errors pauses background requests to the host; urgent requests
+// still trickle through so playback can continue. An optional
+// requests-per-minute budget keeps strict hosts from banning the client.
+
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use parking_lot::Mutex;
+use tracing::{debug, warn};
+
+use crate::config::{
+    CIRCUIT_BREAKER_FAILURES, CIRCUIT_BREAKER_OPEN_SECS, RATE_LIMIT_BACKOFF_BASE_MS,
+    RATE_LIMIT_MAX_PAUSE_SECS, RATE_LIMIT_URGENT_INTERVAL_MS,
+};
+use crate::error::{ProxyError, ProxyErrorKind};
+
+const BUDGET_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Default)]
+struct Registry {
+    limiters: HashMap<String, Arc<HostLimiter>>,
+    /// Requests-per-minute budgets by host, with or without port.
+    budgets: HashMap<String, u32>,
+}
+
+fn registry() -> &'static Mutex<Registry> {
+    static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(Registry::default()))
+}
+
+/// `host` without a `:port` suffix.
+fn host_name(key: &str) -> &str {
+    key.rsplit_once(':').map_or(key, |(host, _)| host)
+}
+
+fn budget_for(budgets: &HashMap<String, u32>, key: &str) -> Option<u32> {
+    budgets
+        .get(key)
+        .or_else(|| budgets.get(host_name(key)))
+        .copied()
+}
+
+/// The limiter shared by every session talking to `host_key`. Limiters no
+/// session holds are dropped once they have nothing left to enforce.
+pub fn host_limiter(host_key: &str) -> Arc<HostLimiter> {
+    let mut registry = registry().lock();
+    let now = Instant::now();
+    registry
+        .limiters
+        .retain(|_, limiter| Arc::strong_count(limiter) > 1 || !limiter.is_idle(now));
+    if let Some(limiter) = registry.limiters.get(host_key) {
+        return Arc::clone(limiter);
+    }
+    let limiter = Arc::new(HostLimiter::new(
+        host_key.to_string(),
+        budget_for(&registry.budgets, host_key),
+    ));
+    registry
+        .limiters
+        .insert(host_key.to_string(), Arc::clone(&limiter));
+    limiter
+}
+
+/// Cap requests to `host` (a host name, or `host:port`) per minute; 0
+/// removes the cap.
+pub fn set_host_budget(host: &str, requests_per_minute: u32) {
+    let mut registry = registry().lock();
+    if requests_per_minute == 0 {
+        registry.budgets.remove(host);
+    } else {
+        registry
+            .budgets
+            .insert(host.to_string(), requests_per_minute);
+    }
+    for (key, limiter) in &registry.limiters {
+        limiter.state.lock().budget = budget_for(&registry.budgets, key);
+    }
+}
+
+struct State {
+    /// Server-requested pause (`Retry-After`) or backoff after 429s.
+    paused_until: Option<Instant>,
+    /// Open circuit after repeated failures; closed by any success.
+    open_until: Option<Instant>,
+    /// Consecutive rate-limit or server failures.
+    failures: u32,
+    /// Start times of requests within the budget window.
+    recent: VecDeque<Instant>,
+    budget: Option<u32>,
+    /// Earliest next urgent request while the host is held back.
+    next_urgent: Option<Instant>,
+}
+
+impl State {
+    /// `None` if a request may start now (and counts it), otherwise how
+    /// long to wait before asking again.
+    fn delay(&mut self, urgent: bool, now: Instant) -> Option<Duration> {
+        while self
+            .recent
+            .front()
+            .is_some_and(|&t| now.duration_since(t) >= BUDGET_WINDOW)
+        {
+            self.recent.pop_front();
+        }
+        let budget_until = self.budget.and_then(|rpm| {
+            let rpm = rpm as usize;
+            (self.recent.len() >= rpm).then(|| self.recent[self.recent.len() - rpm] + BUDGET_WINDOW)
+        });
+        let held_until = [self.paused_until, self.open_until, budget_until]
+            .into_iter()
+            .flatten()
+            .max()
+            .filter(|&until| until > now);
+
+        let Some(held_until) = held_until else {
+            self.recent.push_back(now);
+            return None;
+        };
+        if !urgent {
+            return Some(held_until - now);
+        }
+        match self.next_urgent {
+            Some(next) if next > now => Some(next.min(held_until) - now),
+            _ => {
+                self.next_urgent = Some(now + Duration::from_millis(RATE_LIMIT_URGENT_INTERVAL_MS));
+                self.recent.push_back(now);
+                None
+            }
+        }
+    }
+}
+
+pub struct HostLimiter {
+    host: String,
+    state: Mutex<State>,
+}
+
+impl HostLimiter {
+    /// A limiter outside the shared registry, for sources without a host.
+    pub fn new(host: String, budget: Option<u32>) -> Self {
+        Self {
+            host,
+            state: Mutex::new(State {
+                paused_until: None,
+                open_until: None,
+                failures: 0,
+                recent: VecDeque::new(),
+                budget,
+                next_urgent: None,
+            }),
+        }
+    }
+
+    /// Wait for a slot, run `request` and learn from its outcome.
+    pub async fn request<T>(
+        &self,
+        urgent: bool,
+        request: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        self.acquire(urgent).await;
+        let result = request.await;
+        match &result {
+            Ok(_) => self.record_success(),
+            Err(e) => self.record_failure(&ProxyError::classify(e), Instant::now()),
+        }
+        result
+    }
+
+    /// Wait until a request to the host may start.
+    pub async fn acquire(&self, urgent: bool) {
+        loop {
+            let Some(wait) = self.state.lock().delay(urgent, Instant::now()) else {
+                return;
+            };
+            debug!(
+                "host {} held back for {:?} (urgent={})",
+                self.host, wait, urgent
+            );
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Whether forgetting the limiter would change nothing: no pause, no
+    /// open circuit, no failure streak and no request in the budget window.
+    fn is_idle(&self, now: Instant) -> bool {
+        let state = self.state.lock();
+        state.paused_until.is_none_or(|until| until <= now)
+            && state.open_until.is_none_or(|until| until <= now)
+            && state.failures == 0
+            && state
+                .recent
+                .back()
+                .is_none_or(|&t| now.duration_since(t) >= BUDGET_WINDOW)
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.state.lock();
+        state.failures = 0;
+        state.open_until = None;
+    }
+
+    pub fn record_failure(&self, error: &ProxyError, now: Instant) {
+        if !matches!(
+            error.kind,
+            ProxyErrorKind::RateLimited | ProxyErrorKind::Upstream5xx
+        ) {
+            return;
+        }
+        let mut state = self.state.lock();
+        state.failures += 1;
+        let max_pause = Duration::from_secs(RATE_LIMIT_MAX_PAUSE_SECS);
+
+        let pause = match error.retry_after {
+            Some(after) => Some(after.min(max_pause)),
+            None if error.kind == ProxyErrorKind::RateLimited => {
+                let doublings = (state.failures - 1).min(16);
+                Some(Duration::from_millis(RATE_LIMIT_BACKOFF_BASE_MS << doublings).min(max_pause))
+            }
+            None => None,
+        };
+        if let Some(pause) = pause {
+            let until = now + pause;
+            if state.paused_until.is_none_or(|current| current < until) {
+                warn!("host {} paused for {:?} ({})", self.host, pause, error);
+                state.paused_until = Some(until);
+            }
+        }
+        if state.failures >= CIRCUIT_BREAKER_FAILURES {
+            warn!(
+                "host {} circuit open after {} failures",
+                self.host, state.failures
+            );
+            state.open_until = Some(now + Duration::from_secs(CIRCUIT_BREAKER_OPEN_SECS));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate_limited(retry_after: Option<Duration>) -> ProxyError {
+        ProxyError::from_status(429).with_retry_after(retry_after)
+    }
+
+    #[test]
+    fn test_retry_after_pauses_background_but_not_urgent_trickle() {
+        let limiter = HostLimiter::new("pause.example:443".to_string(), None);
+        let now = Instant::now();
+        limiter.record_failure(&rate_limited(Some(Duration::from_secs(30))), now);
+
+        let mut state = limiter.state.lock();
+        assert_eq!(state.delay(false, now), Some(Duration::from_secs(30)));
+        assert_eq!(state.delay(true, now), None);
+        assert_eq!(
+            state.delay(true, now),
+            Some(Duration::from_millis(RATE_LIMIT_URGENT_INTERVAL_MS))
+        );
+        assert_eq!(state.delay(false, now + Duration::from_secs(30)), None);
+    }
+
+    #[test]
+    fn test_budget_and_circuit_breaker() {
+        let limiter = HostLimiter::new("strict.example:443".to_string(), Some(2));
+        let now = Instant::now();
+        {
+            let mut state = limiter.state.lock();
+            assert_eq!(state.delay(false, now), None);
+            assert_eq!(state.delay(false, now + Duration::from_secs(10)), None);
+            assert_eq!(
+                state.delay(false, now + Duration::from_secs(20)),
+                Some(Duration::from_secs(40))
+            );
+            assert_eq!(state.delay(false, now + Duration::from_secs(60)), None);
+        }
+
+        let limiter = HostLimiter::new("flaky.example:443".to_string(), None);
+        for _ in 0..CIRCUIT_BREAKER_FAILURES {
+            limiter.record_failure(&ProxyError::from_status(503), now);
+        }
+        assert!(limiter.state.lock().delay(false, now).is_some());
+        limiter.record_success();
+        assert_eq!(limiter.state.lock().delay(false, now), None);
+    }
+
+    #[test]
+    fn test_registry_drops_idle_unheld_limiters() {
+        let held = host_limiter("held.example:443");
+        let paused = host_limiter("paused.example:443");
+        paused.record_failure(&rate_limited(Some(Duration::from_secs(30))), Instant::now());
+        drop(paused);
+        drop(host_limiter("idle.example:443"));
+
+        host_limiter("other.example:443");
+        let registry = registry().lock();
+        assert!(registry.limiters.contains_key("held.example:443"));
+        assert!(registry.limiters.contains_key("paused.example:443"));
+        assert!(!registry.limiters.contains_key("idle.example:443"));
+        drop(held);
+    }
+
+    #[test]
+    fn test_budget_applies_to_all_ports_of_a_host() {
+        let limiter = host_limiter("budget.example:8443");
+        set_host_budget("budget.example", 30);
+        assert_eq!(limiter.state.lock().budget, Some(30));
+        set_host_budget("budget.example", 0);
+        assert_eq!(limiter.state.lock().budget, None);
+    }
+}
@@ -0,0 +1,176 @@
+// Media index — container seek indexes, read through the session cache.
+//
+// Index bytes (an MP4 `moov` or `sidx`/`mfra`, Matroska `Cues`) are fetched as warmup chunks,
+// so the player finds them cached when its demuxer reads the same region.
+
+use anyhow::Result;
+use bytes::Bytes;
+use tracing::debug;
+
+use super::downloader::Downloader;
+use super::position::TimeIndex;
+use crate::config::{CONTAINER_HEAD_PROBE_BYTES, MKV_MAX_INDEX_ELEMENT_BYTES, MP4_MAX_MOOV_BYTES};
+use crate::detect::container::{
+    detect_container, find_box, find_moov_box, locate_moov, ContainerFormat,
+};
+use crate::detect::fmp4::{mfra_size, parse_mfra, parse_sidx, MFRO_BYTES};
+use crate::detect::matroska::{
+    element_len, parse_cues, parse_info, segment_layout, video_tracks, EBML_MAX_HEADER,
+};
+use crate::detect::mp4::parse_moov;
+use crate::source::traits::MediaSource;
+
+/// Keyframe time → byte offset index for the file, if its container has one.
+pub async fn load_time_index(
+    source: &dyn MediaSource,
+    downloader: &Downloader,
+    content_length: u64,
+) -> Result<Option<TimeIndex>> {
+    let head = downloader.read_range(0, CONTAINER_HEAD_PROBE_BYTES).await?;
+    match detect_container(&head) {
+        ContainerFormat::Mp4 => mp4_time_index(source, downloader, &head, content_length).await,
+        ContainerFormat::FragmentedMp4 => fmp4_time_index(downloader, &head, content_length).await,
+        ContainerFormat::Matroska => mkv_time_index(downloader, &head).await,
+        _ => Ok(None),
+    }
+}
+
+async fn mp4_time_index(
+    source: &dyn MediaSource,
+    downloader: &Downloader,
+    head: &[u8],
+    content_length: u64,
+) -> Result<Option<TimeIndex>> {
+    let Some((offset, size)) = locate_moov(source, head, content_length).await? else {
+        return Ok(None);
+    };
+    if size > MP4_MAX_MOOV_BYTES {
+        debug!("moov of {} bytes is too large to index", size);
+        return Ok(None);
+    }
+    let moov = downloader.read_range(offset, offset + size).await?;
+    let index = parse_moov(&moov)?;
+    let Some(track) = index.primary_track() else {
+        return Ok(None);
+    };
+    debug!(
+        "mp4 track {} indexed: {} keyframes of {} samples",
+        track.track_id,
+        track.keyframes.len(),
+        track.sample_count
+    );
+    Ok(Some(TimeIndex::new(
+        track
+            .keyframes
+            .iter()
+            .map(|k| (k.time_secs, k.offset))
+            .collect(),
+    )))
+}
+
+/// Fragment start times from the head `sidx`, else from the `mfra` for the
+/// first video track.
+async fn fmp4_time_index(
+    downloader: &Downloader,
+    head: &[u8],
+    content_length: u64,
+) -> Result<Option<TimeIndex>> {
+    let index = match find_box(head, b"sidx") {
+        Some((at, size)) => parse_sidx(&downloader.read_range(at, at + size).await?, at)?,
+        None => {
+            let Some((moov_at, moov_size)) = find_moov_box(head) else {
+                return Ok(None);
+            };
+            let moov = parse_moov(&downloader.read_range(moov_at, moov_at + moov_size).await?)?;
+            let Some(track) = moov
+                .tracks
+                .iter()
+                .find(|t| &t.handler == b"vide")
+                .or(moov.tracks.first())
+            else {
+                return Ok(None);
+            };
+            let tail = downloader
+                .read_range(content_length.saturating_sub(MFRO_BYTES), content_length)
+                .await?;
+            let Some(size) = mfra_size(&tail).filter(|&size| size <= MP4_MAX_MOOV_BYTES) else {
+                debug!("fragmented mp4 has neither sidx nor mfra");
+                return Ok(None);
+            };
+            let mfra = downloader
+                .read_range(content_length.saturating_sub(size), content_length)
+                .await?;
+            parse_mfra(&mfra, track.track_id, track.timescale)?
+        }
+    };
+    debug!(
+        "fragmented mp4 indexed: {} fragments",
+        index.fragments.len()
+    );
+    Ok(Some(TimeIndex::new(
+        index
+            .fragments
+            .iter()
+            .map(|f| (f.time_secs, f.offset))
+            .collect(),
+    )))
+}
+
+/// Cue points of the first video track (else of whichever track has cues),
+/// pointing at the clusters a seek reads from.
+async fn mkv_time_index(downloader: &Downloader, head: &[u8]) -> Result<Option<TimeIndex>> {
+    let Some(layout) = segment_layout(head) else {
+        return Ok(None);
+    };
+    let Some(cues_at) = layout.cues else {
+        debug!("matroska file has no cues");
+        return Ok(None);
+    };
+    let timestamp_scale = match layout.info {
+        Some(at) => read_element(downloader, at)
+            .await?
+            .and_then(|info| parse_info(&info))
+            .map(|info| info.timestamp_scale),
+        None => None,
+    };
+    let video = match layout.tracks {
+        Some(at) => read_element(downloader, at)
+            .await?
+            .map(|tracks| video_tracks(&tracks))
+            .unwrap_or_default(),
+        None => Vec::new(),
+    };
+    let Some(cues) = read_element(downloader, cues_at).await? else {
+        return Ok(None);
+    };
+
+    let points = parse_cues(
+        &cues,
+        layout.data_start,
+        timestamp_scale.unwrap_or(1_000_000),
+    );
+    let Some(track) = video.first().copied().or(points.first().map(|c| c.track)) else {
+        return Ok(None);
+    };
+    debug!("matroska track {} indexed: {} cues", track, points.len());
+    Ok(Some(TimeIndex::new(
+        points
+            .iter()
+            .filter(|c| c.track == track)
+            .map(|c| (c.time_secs, c.cluster_offset))
+            .collect(),
+    )))
+}
+
+/// The whole EBML element at `at`, unless it is implausibly large.
+pub(super) async fn read_element(downloader: &Downloader, at: u64) -> Result<Option<Bytes>> {
+    let header = downloader.read_range(at, at + EBML_MAX_HEADER).await?;
+    let Some(len) = element_len(&header) else {
+        return Ok(None);
+    };
+    if len > MKV_MAX_INDEX_ELEMENT_BYTES {
+        debug!("matroska element of {} bytes at {} skipped", len, at);
+        return Ok(None);
+    }
+    Ok(Some(downloader.read_range(at, at + len).await?))
+}
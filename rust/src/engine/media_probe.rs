@@ -0,0 +1,65 @@
+// Media probe — tracks and codecs of a session's file, read through its cache.
+//
+// The regions parsed are the ones warmup already fetches (the head, an MP4
+// `moov`, Matroska Info and Tracks), so a probe costs little extra transfer
+// and leaves them cached for the player's demuxer.
+
+use anyhow::{anyhow, bail, Result};
+
+use super::downloader::Downloader;
+use super::media_index::read_element;
+use crate::config::{CONTAINER_HEAD_PROBE_BYTES, MP4_MAX_MOOV_BYTES};
+use crate::detect::container::{detect_container, find_box, locate_moov, ContainerFormat};
+use crate::detect::fmp4::parse_sidx;
+use crate::detect::matroska::segment_layout;
+use crate::detect::media_info::{
+    avi_media_info, flv_media_info, mkv_media_info, mp4_media_info, ts_media_info, ProbedMedia,
+};
+use crate::source::traits::MediaSource;
+
+/// Container, duration and tracks of the file, as far as its headers say.
+pub async fn probe_media(
+    source: &dyn MediaSource,
+    downloader: &Downloader,
+    content_length: u64,
+) -> Result<ProbedMedia> {
+    let head = downloader.read_range(0, CONTAINER_HEAD_PROBE_BYTES).await?;
+    let format = detect_container(&head);
+    let media = match format {
+        ContainerFormat::Mp4 | ContainerFormat::FragmentedMp4 => {
+            let Some((at, size)) = locate_moov(source, &head, content_length).await? else {
+                bail!("no moov box found");
+            };
+            if size > MP4_MAX_MOOV_BYTES {
+                bail!("moov of {} bytes is too large to probe", size);
+            }
+            let moov = downloader.read_range(at, at + size).await?;
+            let mut media = mp4_media_info(&moov, format)?;
+            // Fragmented files often leave `mvhd` empty; the sidx adds up.
+            if media.duration_secs.is_none() {
+                if let Some((at, size)) = find_box(&head, b"sidx") {
+                    let sidx = downloader.read_range(at, at + size).await?;
+                    media.duration_secs = parse_sidx(&sidx, at)?.duration_secs;
+                }
+            }
+            media
+        }
+        ContainerFormat::Matroska => {
+            let layout = segment_layout(&head).ok_or_else(|| anyhow!("no matroska segment"))?;
+            let info = match layout.info {
+                Some(at) => read_element(downloader, at).await?,
+                None => None,
+            };
+            let tracks = match layout.tracks {
+                Some(at) => read_element(downloader, at).await?,
+                None => None,
+            };
+            mkv_media_info(info.as_deref(), tracks.as_deref())
+        }
+        ContainerFormat::TransportStream | ContainerFormat::M2ts => ts_media_info(&head, format),
+        ContainerFormat::Avi => avi_media_info(&head),
+        ContainerFormat::Flv => flv_media_info(&head),
+        other => ProbedMedia::new(other),
+    };
+    Ok(media)
+}
@@ -1,7 +1,19 @@
 // Engine orchestration — session lifecycle and download coordination.
 
+pub mod bandwidth;
+pub mod bitrate;
 pub mod cache;
+pub mod cache_manager;
+pub mod cached_source;
+pub mod concurrency;
 pub mod downloader;
+pub mod host_limiter;
+pub mod media_index;
+pub mod media_probe;
+pub mod policy;
+pub mod position;
+pub mod scheduler;
 pub mod session;
 pub mod stats;
+pub mod store;
 pub mod warmup;
@@ -0,0 +1,123 @@
+// Network policy — how much speculative downloading the current network allows.
+//
+// Dart switches the mode as the device moves between Wi-Fi and cellular.
+// Each switch starts a new period with its own downloaded-bytes counter.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+
+use parking_lot::Mutex;
+use tracing::info;
+
+use super::cache::now_millis;
+use crate::config::{
+    DATA_SAVER_BUFFER_SECONDS, DATA_SAVER_PREFETCH_CHUNKS, DEFAULT_PREFETCH_CHUNKS,
+    PRIORITY_BUFFER_SECONDS,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NetworkPolicy {
+    /// Buffer far ahead and warm up head and tail speculatively.
+    #[default]
+    Normal,
+    /// Metered network: short buffer, only what the container needs.
+    DataSaver,
+}
+
+impl NetworkPolicy {
+    /// Seconds of content to keep buffered ahead of playback.
+    pub fn buffer_seconds(self) -> u64 {
+        match self {
+            NetworkPolicy::Normal => PRIORITY_BUFFER_SECONDS,
+            NetworkPolicy::DataSaver => DATA_SAVER_BUFFER_SECONDS,
+        }
+    }
+
+    /// Chunks to prefetch ahead while the bitrate is unknown.
+    pub fn default_prefetch_chunks(self) -> u64 {
+        match self {
+            NetworkPolicy::Normal => DEFAULT_PREFETCH_CHUNKS,
+            NetworkPolicy::DataSaver => DATA_SAVER_PREFETCH_CHUNKS,
+        }
+    }
+
+    /// Whether to fetch head/tail regions the container may not need, and
+    /// to keep queued prefetch past the buffer horizon.
+    pub fn speculative(self) -> bool {
+        self == NetworkPolicy::Normal
+    }
+}
+
+/// Bytes downloaded under one policy since it was switched on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PolicyPeriod {
+    pub policy: NetworkPolicy,
+    /// Milliseconds since the Unix epoch.
+    pub started_at: u64,
+    pub downloaded_bytes: u64,
+}
+
+/// Bytes downloaded in the current period. Counted without the period
+/// lock, which is only taken to switch policy or read the period.
+static DOWNLOADED: AtomicU64 = AtomicU64::new(0);
+
+/// Policy in effect and when it was switched on.
+fn period() -> &'static Mutex<(NetworkPolicy, u64)> {
+    static PERIOD: OnceLock<Mutex<(NetworkPolicy, u64)>> = OnceLock::new();
+    PERIOD.get_or_init(|| Mutex::new((NetworkPolicy::default(), now_millis())))
+}
+
+pub fn current_policy() -> NetworkPolicy {
+    period().lock().0
+}
+
+/// Switch the engine-wide policy and start a new period. Returns the
+/// period that just ended.
+pub fn set_policy(policy: NetworkPolicy) -> PolicyPeriod {
+    let mut period = period().lock();
+    let ended = PolicyPeriod {
+        policy: period.0,
+        started_at: period.1,
+        downloaded_bytes: DOWNLOADED.swap(0, Ordering::Relaxed),
+    };
+    *period = (policy, now_millis());
+    info!(
+        "network policy {:?} -> {:?} after {} bytes",
+        ended.policy, policy, ended.downloaded_bytes
+    );
+    ended
+}
+
+/// The period in progress.
+pub fn current_period() -> PolicyPeriod {
+    let period = period().lock();
+    PolicyPeriod {
+        policy: period.0,
+        started_at: period.1,
+        downloaded_bytes: DOWNLOADED.load(Ordering::Relaxed),
+    }
+}
+
+/// Count bytes downloaded from upstream against the current period.
+pub fn record_downloaded(bytes: u64) {
+    DOWNLOADED.fetch_add(bytes, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_switch_starts_new_period() {
+        set_policy(NetworkPolicy::DataSaver);
+        record_downloaded(1000);
+        assert_eq!(current_policy(), NetworkPolicy::DataSaver);
+        assert!(!current_policy().speculative());
+
+        let ended = set_policy(NetworkPolicy::Normal);
+        assert_eq!(ended.policy, NetworkPolicy::DataSaver);
+        assert!(ended.downloaded_bytes >= 1000);
+        assert_eq!(current_period().downloaded_bytes, 0);
+        assert!(current_period().started_at >= ended.started_at);
+    }
+}
@@ -0,0 +1,154 @@
+// Playback position — where the player really is, as reported by Dart.
+//
+// Players read tens of MB ahead, so the start of the last request says little
+// about what is on screen. Dart reports media time once a second; the session
+// maps it to byte offsets through a container time index when one is known,
+// otherwise linearly by the average bitrate.
+
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+use crate::config::POSITION_HINT_STALE_SECS;
+
+/// Media time to byte offset points, e.g. keyframes from a container index.
+#[derive(Debug, Clone, Default)]
+pub struct TimeIndex {
+    /// (seconds, byte offset), sorted by time.
+    points: Vec<(f64, u64)>,
+}
+
+impl TimeIndex {
+    pub fn new(mut points: Vec<(f64, u64)>) -> Self {
+        points.retain(|(secs, _)| secs.is_finite());
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        points.dedup_by(|b, a| a.0 == b.0);
+        Self { points }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// The last point at or before `secs`, e.g. the keyframe a seek there
+    /// decodes from.
+    pub fn point_at_or_before(&self, secs: f64) -> Option<(f64, u64)> {
+        let next = self.points.partition_point(|&(t, _)| t <= secs);
+        next.checked_sub(1).map(|i| self.points[i])
+    }
+
+    /// Byte offset playback reaches at `secs`, interpolated between the
+    /// surrounding points. `None` past the last point.
+    pub fn offset_at(&self, secs: f64) -> Option<u64> {
+        let next = self.points.partition_point(|&(t, _)| t <= secs);
+        if next == 0 {
+            return self.points.first().map(|&(_, offset)| offset);
+        }
+        let (t0, o0) = self.points[next - 1];
+        let (t1, o1) = *self.points.get(next)?;
+        let frac = (secs - t0) / (t1 - t0);
+        Some(o0 + (o1.saturating_sub(o0) as f64 * frac) as u64)
+    }
+}
+
+/// One report from the player, in media seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlaybackHint {
+    pub position_secs: f64,
+    /// End of what the player has buffered (read from the proxy).
+    pub buffer_end_secs: f64,
+    /// Playback speed; 1.0 is normal.
+    pub rate: f64,
+}
+
+/// The latest hint and when it arrived.
+pub struct PositionTracker {
+    last: Mutex<Option<(PlaybackHint, Instant)>>,
+}
+
+impl Default for PositionTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PositionTracker {
+    pub fn new() -> Self {
+        Self {
+            last: Mutex::new(None),
+        }
+    }
+
+    pub fn report(&self, hint: PlaybackHint, now: Instant) {
+        let rate = if hint.rate.is_finite() {
+            hint.rate.max(0.0)
+        } else {
+            1.0
+        };
+        let position_secs = hint.position_secs.max(0.0);
+        *self.last.lock() = Some((
+            PlaybackHint {
+                position_secs,
+                buffer_end_secs: hint.buffer_end_secs.max(position_secs),
+                rate,
+            },
+            now,
+        ));
+    }
+
+    /// The latest hint advanced to `now` at its rate, or `None` once Dart
+    /// has stopped reporting.
+    pub fn current(&self, now: Instant) -> Option<PlaybackHint> {
+        let (hint, at) = (*self.last.lock())?;
+        let elapsed = now.saturating_duration_since(at);
+        if elapsed > Duration::from_secs(POSITION_HINT_STALE_SECS) {
+            return None;
+        }
+        let position_secs = hint.position_secs + elapsed.as_secs_f64() * hint.rate;
+        Some(PlaybackHint {
+            position_secs,
+            buffer_end_secs: hint.buffer_end_secs.max(position_secs),
+            rate: hint.rate,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_interpolates_between_points() {
+        let index = TimeIndex::new(vec![(10.0, 5000), (0.0, 100), (20.0, 9000)]);
+        assert_eq!(index.offset_at(0.0), Some(100));
+        assert_eq!(index.offset_at(5.0), Some(2550));
+        assert_eq!(index.offset_at(15.0), Some(7000));
+        assert_eq!(index.offset_at(25.0), None);
+    }
+
+    #[test]
+    fn test_hint_advances_then_goes_stale() {
+        let tracker = PositionTracker::new();
+        let now = Instant::now();
+        assert_eq!(tracker.current(now), None);
+
+        tracker.report(
+            PlaybackHint {
+                position_secs: 10.0,
+                buffer_end_secs: 11.0,
+                rate: 2.0,
+            },
+            now,
+        );
+        let hint = tracker.current(now + Duration::from_secs(1)).unwrap();
+        assert_eq!(hint.position_secs, 12.0);
+        assert_eq!(hint.buffer_end_secs, 12.0);
+
+        let stale = now + Duration::from_secs(POSITION_HINT_STALE_SECS + 1);
+        assert_eq!(tracker.current(stale), None);
+    }
+}
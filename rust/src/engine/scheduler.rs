@@ -0,0 +1,503 @@
+// Download scheduler — orders queued chunk requests by deadline.
+//
+// Every request carries a deadline: urgent chunks are due now, warmup chunks
+// shortly, and prefetch chunks when playback is expected to reach them at the
+// estimated bitrate. Workers pull the earliest deadline next. Moving the
+// playback position recomputes prefetch deadlines instead of cancelling work,
+// and a tier that keeps losing to others is served after a few rounds.
+// Chunks that ride along in a merged request stay promotable: one that turns
+// urgent before its first byte goes back to the queue on its own.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use tokio::sync::futures::Notified;
+use tokio::sync::Notify;
+
+use crate::config::{
+    SCHEDULER_BEHIND_DEADLINE_SECS, SCHEDULER_DEFAULT_BYTES_PER_SEC, SCHEDULER_STARVATION_LIMIT,
+    SCHEDULER_WARMUP_DEADLINE_SECS,
+};
+
+/// Why a chunk is wanted, most urgent first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Tier {
+    /// The player is waiting on this chunk.
+    Urgent,
+    /// Container head, tail or index needed to start playback.
+    Warmup,
+    /// Ahead of the playback position.
+    Prefetch,
+}
+
+impl Tier {
+    const COUNT: usize = 3;
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    tier: Tier,
+    deadline: Instant,
+}
+
+/// Queued chunks plus the playback position their deadlines derive from.
+struct Queue {
+    entries: HashMap<usize, Entry>,
+    chunk_size: u64,
+    playback_offset: u64,
+    bytes_per_sec: u64,
+    /// Picks in a row each tier had work queued but lost.
+    skipped: [u32; Tier::COUNT],
+}
+
+impl Queue {
+    fn new(chunk_size: u64) -> Self {
+        Self {
+            entries: HashMap::new(),
+            chunk_size,
+            playback_offset: 0,
+            bytes_per_sec: SCHEDULER_DEFAULT_BYTES_PER_SEC,
+            skipped: [0; Tier::COUNT],
+        }
+    }
+
+    fn deadline(&self, chunk_index: usize, tier: Tier, now: Instant) -> Instant {
+        match tier {
+            Tier::Urgent => now,
+            Tier::Warmup => now + Duration::from_secs(SCHEDULER_WARMUP_DEADLINE_SECS),
+            Tier::Prefetch => {
+                let start = chunk_index as u64 * self.chunk_size;
+                if start + self.chunk_size <= self.playback_offset {
+                    return now + Duration::from_secs(SCHEDULER_BEHIND_DEADLINE_SECS);
+                }
+                let ahead = start.saturating_sub(self.playback_offset);
+                now + Duration::from_secs_f64(ahead as f64 / self.bytes_per_sec as f64)
+            }
+        }
+    }
+
+    /// Queue a chunk, or move a queued one to a more urgent tier.
+    /// Returns `true` if the queue changed.
+    fn push(&mut self, chunk_index: usize, tier: Tier, now: Instant) -> bool {
+        if let Some(entry) = self.entries.get(&chunk_index) {
+            if entry.tier <= tier {
+                return false;
+            }
+        }
+        let deadline = self.deadline(chunk_index, tier, now);
+        self.entries.insert(chunk_index, Entry { tier, deadline });
+        true
+    }
+
+    /// Move an already queued chunk to a more urgent tier.
+    fn promote(&mut self, chunk_index: usize, tier: Tier, now: Instant) -> bool {
+        self.entries.contains_key(&chunk_index) && self.push(chunk_index, tier, now)
+    }
+
+    /// Remove the run of queued non-urgent chunks directly after
+    /// `chunk_index`, at most `max` of them.
+    fn take_following(&mut self, chunk_index: usize, max: usize) -> Vec<(usize, Tier)> {
+        let mut taken = Vec::new();
+        for i in chunk_index + 1..=chunk_index + max {
+            match self.entries.get(&i) {
+                Some(entry) if entry.tier != Tier::Urgent => {
+                    taken.push((i, entry.tier));
+                    self.entries.remove(&i);
+                }
+                _ => break,
+            }
+        }
+        taken
+    }
+
+    /// Recompute prefetch deadlines for a new playback position.
+    fn set_playback(&mut self, offset: u64, bytes_per_sec: u64, now: Instant) {
+        self.playback_offset = offset;
+        self.bytes_per_sec = if bytes_per_sec == 0 {
+            SCHEDULER_DEFAULT_BYTES_PER_SEC
+        } else {
+            bytes_per_sec
+        };
+        let queued: Vec<usize> = self
+            .entries
+            .iter()
+            .filter(|(_, e)| e.tier == Tier::Prefetch)
+            .map(|(&i, _)| i)
+            .collect();
+        for i in queued {
+            let deadline = self.deadline(i, Tier::Prefetch, now);
+            if let Some(entry) = self.entries.get_mut(&i) {
+                entry.deadline = deadline;
+            }
+        }
+    }
+
+    /// Take the next chunk to download. Only urgent chunks are considered
+    /// when `allow_background` is false.
+    fn pop(&mut self, allow_background: bool) -> Option<(usize, Tier)> {
+        // Earliest entry of each tier, ties broken by chunk index.
+        let mut heads: [Option<(Instant, usize)>; Tier::COUNT] = [None; Tier::COUNT];
+        for (&i, entry) in &self.entries {
+            if !allow_background && entry.tier != Tier::Urgent {
+                continue;
+            }
+            let head = &mut heads[entry.tier.index()];
+            if head.is_none_or(|h| (entry.deadline, i) < h) {
+                *head = Some((entry.deadline, i));
+            }
+        }
+
+        let starved = (0..Tier::COUNT)
+            .find(|&t| heads[t].is_some() && self.skipped[t] >= SCHEDULER_STARVATION_LIMIT);
+        let chosen = match starved {
+            Some(t) => t,
+            None => {
+                (0..Tier::COUNT)
+                    .filter_map(|t| heads[t].map(|(deadline, i)| ((deadline, t, i), t)))
+                    .min()?
+                    .1
+            }
+        };
+
+        for (t, (skipped, head)) in self.skipped.iter_mut().zip(&heads).enumerate() {
+            if t == chosen || head.is_none() {
+                *skipped = 0;
+            } else {
+                *skipped += 1;
+            }
+        }
+        let (_, chunk_index) = heads[chosen]?;
+        let entry = self.entries.remove(&chunk_index)?;
+        Some((chunk_index, entry.tier))
+    }
+}
+
+/// A chunk handed out as part of a merged request.
+#[derive(Debug, Clone, Copy)]
+struct Member {
+    tier: Tier,
+    /// Bytes of it are being written; it can no longer leave the run.
+    started: bool,
+}
+
+/// Shared queue the download workers pull from.
+pub struct Scheduler {
+    state: Mutex<State>,
+    changed: Notify,
+    /// Signalled when a member of a merged run is promoted.
+    member_promoted: Notify,
+    background_limit: AtomicUsize,
+}
+
+struct State {
+    queue: Queue,
+    running_background: usize,
+    members: HashMap<usize, Member>,
+}
+
+impl Scheduler {
+    /// `background_limit` caps how many non-urgent chunks download at once.
+    pub fn new(chunk_size: u64, background_limit: usize) -> Self {
+        Self {
+            state: Mutex::new(State {
+                queue: Queue::new(chunk_size),
+                running_background: 0,
+                members: HashMap::new(),
+            }),
+            changed: Notify::new(),
+            member_promoted: Notify::new(),
+            background_limit: AtomicUsize::new(background_limit.max(1)),
+        }
+    }
+
+    /// Queue a chunk, or promote it if it is already queued with a less
+    /// urgent tier.
+    pub fn push(&self, chunk_index: usize, tier: Tier) {
+        let changed = self
+            .state
+            .lock()
+            .queue
+            .push(chunk_index, tier, Instant::now());
+        if changed {
+            self.changed.notify_waiters();
+        }
+    }
+
+    /// Promote a chunk that is still waiting for a worker. A merged-run
+    /// member that turns urgent before its first byte leaves the run and is
+    /// queued again; one already being written is paced as urgent. Other
+    /// chunks already handed out are left alone.
+    pub fn promote(&self, chunk_index: usize, tier: Tier) {
+        let now = Instant::now();
+        let mut state = self.state.lock();
+        let member = state.members.get(&chunk_index).copied();
+        let changed = match member {
+            Some(member) if tier < member.tier => {
+                if tier == Tier::Urgent && !member.started {
+                    state.members.remove(&chunk_index);
+                    state.queue.push(chunk_index, tier, now);
+                } else if let Some(member) = state.members.get_mut(&chunk_index) {
+                    member.tier = tier;
+                }
+                self.member_promoted.notify_waiters();
+                true
+            }
+            Some(_) => false,
+            None => state.queue.promote(chunk_index, tier, now),
+        };
+        drop(state);
+        if changed {
+            self.changed.notify_waiters();
+        }
+    }
+
+    /// Change how many non-urgent chunks may download at once. Transfers
+    /// above a lowered limit finish; no new ones start until below it.
+    pub fn set_background_limit(&self, limit: usize) {
+        self.background_limit.store(limit.max(1), Ordering::Relaxed);
+        self.changed.notify_waiters();
+    }
+
+    /// Whether background work is waiting because every background slot is
+    /// busy, i.e. throughput is limited by the worker count, not demand.
+    pub fn is_saturated(&self) -> bool {
+        let state = self.state.lock();
+        state.running_background >= self.background_limit.load(Ordering::Relaxed)
+            && state.queue.entries.values().any(|e| e.tier != Tier::Urgent)
+    }
+
+    /// Move the playback position; queued prefetch chunks are re-ordered
+    /// around it.
+    pub fn set_playback(&self, offset: u64, bytes_per_sec: u64) {
+        self.state
+            .lock()
+            .queue
+            .set_playback(offset, bytes_per_sec, Instant::now());
+        self.changed.notify_waiters();
+    }
+
+    /// Take the most urgent chunk a worker may start now.
+    pub fn try_next(&self) -> Option<(usize, Tier)> {
+        let mut state = self.state.lock();
+        let allow_background =
+            state.running_background < self.background_limit.load(Ordering::Relaxed);
+        let (chunk_index, tier) = state.queue.pop(allow_background)?;
+        if tier != Tier::Urgent {
+            state.running_background += 1;
+        }
+        Some((chunk_index, tier))
+    }
+
+    /// Wait for the most urgent chunk a worker may start.
+    pub async fn next(&self) -> (usize, Tier) {
+        loop {
+            // Register before checking so a push in between still wakes us.
+            let notified = self.changed.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if let Some(next) = self.try_next() {
+                return next;
+            }
+            notified.await;
+        }
+    }
+
+    /// Claim the queued background chunks right after one just handed out
+    /// at `tier`, so they can share its upstream request and worker slot.
+    /// The whole run is tracked as members until each leaves it.
+    pub fn take_following(&self, chunk_index: usize, tier: Tier, max: usize) -> Vec<usize> {
+        let mut state = self.state.lock();
+        let taken = state.queue.take_following(chunk_index, max);
+        if !taken.is_empty() {
+            let first = std::iter::once((chunk_index, tier));
+            for (i, tier) in first.chain(taken.iter().copied()) {
+                state.members.insert(
+                    i,
+                    Member {
+                        tier,
+                        started: false,
+                    },
+                );
+            }
+        }
+        taken.into_iter().map(|(i, _)| i).collect()
+    }
+
+    /// Mark a merged-run member as being written. Returns `false` if it was
+    /// promoted out of the run; the worker that picks it up owns it now.
+    pub fn start_member(&self, chunk_index: usize) -> bool {
+        match self.state.lock().members.get_mut(&chunk_index) {
+            Some(member) => {
+                member.started = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Current tier of a merged-run member, promotions included; `None`
+    /// once it left the run.
+    pub fn member_tier(&self, chunk_index: usize) -> Option<Tier> {
+        self.state
+            .lock()
+            .members
+            .get(&chunk_index)
+            .map(|member| member.tier)
+    }
+
+    /// Wait for a merged-run member to be promoted.
+    pub fn member_promoted(&self) -> Notified<'_> {
+        self.member_promoted.notified()
+    }
+
+    /// Take chunks out of their merged run. Returns each one's tier, or
+    /// `None` for chunks that were promoted out and queued again.
+    pub fn leave_run(&self, chunks: &[usize]) -> Vec<Option<Tier>> {
+        let mut state = self.state.lock();
+        chunks
+            .iter()
+            .map(|i| state.members.remove(i).map(|member| member.tier))
+            .collect()
+    }
+
+    /// Queue members that were cut from their run again at their tiers.
+    pub fn requeue_members(&self, chunks: &[usize]) {
+        let now = Instant::now();
+        {
+            let mut state = self.state.lock();
+            for &i in chunks {
+                if let Some(member) = state.members.remove(&i) {
+                    state.queue.push(i, member.tier, now);
+                }
+            }
+        }
+        self.changed.notify_waiters();
+    }
+
+    /// Report that a chunk handed out by `next` is done.
+    pub fn finish(&self, tier: Tier) {
+        if tier != Tier::Urgent {
+            let mut state = self.state.lock();
+            state.running_background = state.running_background.saturating_sub(1);
+        }
+        self.changed.notify_waiters();
+    }
+
+    /// Remove queued prefetch chunks at or past `end_chunk` and return
+    /// their indices.
+    pub fn drop_prefetch_from(&self, end_chunk: usize) -> Vec<usize> {
+        let mut state = self.state.lock();
+        let dropped: Vec<usize> = state
+            .queue
+            .entries
+            .iter()
+            .filter(|(&i, e)| e.tier == Tier::Prefetch && i >= end_chunk)
+            .map(|(&i, _)| i)
+            .collect();
+        for i in &dropped {
+            state.queue.entries.remove(i);
+        }
+        dropped
+    }
+
+    /// Remove every queued chunk and return their indices.
+    pub fn drain(&self) -> Vec<usize> {
+        let mut state = self.state.lock();
+        state.queue.entries.drain().map(|(i, _)| i).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MB: u64 = 1024 * 1024;
+
+    #[test]
+    fn test_prefetch_follows_playback_deadlines() {
+        let now = Instant::now();
+        let mut queue = Queue::new(MB);
+        for i in [10, 5, 8, 2] {
+            queue.push(i, Tier::Prefetch, now);
+        }
+        queue.set_playback(5 * MB + MB / 2, MB, now);
+        let order: Vec<usize> = std::iter::from_fn(|| queue.pop(true).map(|(i, _)| i)).collect();
+        // Chunk 5 still overlaps the playback position; chunk 2 is behind it.
+        assert_eq!(order, vec![5, 8, 10, 2]);
+    }
+
+    #[test]
+    fn test_drop_prefetch_keeps_other_tiers() {
+        let scheduler = Scheduler::new(MB, 1);
+        scheduler.push(2, Tier::Prefetch);
+        scheduler.push(6, Tier::Prefetch);
+        scheduler.push(7, Tier::Warmup);
+        let mut dropped = scheduler.drop_prefetch_from(3);
+        dropped.sort_unstable();
+        assert_eq!(dropped, vec![6]);
+        let mut rest = scheduler.drain();
+        rest.sort_unstable();
+        assert_eq!(rest, vec![2, 7]);
+    }
+
+    #[test]
+    fn test_promotion_and_background_gate() {
+        let now = Instant::now();
+        let mut queue = Queue::new(MB);
+        queue.push(3, Tier::Prefetch, now);
+        queue.push(4, Tier::Prefetch, now);
+        assert!(queue.pop(false).is_none());
+        assert!(queue.push(4, Tier::Urgent, now));
+        assert!(!queue.push(4, Tier::Prefetch, now));
+        assert!(!queue.promote(7, Tier::Urgent, now));
+        assert_eq!(queue.pop(false), Some((4, Tier::Urgent)));
+        assert_eq!(queue.pop(true), Some((3, Tier::Prefetch)));
+    }
+
+    #[test]
+    fn test_merged_members_stay_promotable() {
+        let scheduler = Scheduler::new(MB, 1);
+        for i in 0..4 {
+            scheduler.push(i, Tier::Prefetch);
+        }
+        let (head, tier) = scheduler.try_next().unwrap();
+        assert_eq!((head, tier), (0, Tier::Prefetch));
+        assert_eq!(scheduler.take_following(head, tier, 3), vec![1, 2, 3]);
+        assert!(scheduler.start_member(0));
+        assert!(scheduler.start_member(1));
+
+        // A member being written is only re-tiered; one not started yet
+        // goes back to the queue for an urgent worker.
+        scheduler.promote(1, Tier::Urgent);
+        scheduler.promote(3, Tier::Urgent);
+        assert_eq!(scheduler.member_tier(1), Some(Tier::Urgent));
+        assert_eq!(scheduler.member_tier(3), None);
+        assert!(!scheduler.start_member(3));
+        assert_eq!(scheduler.try_next(), Some((3, Tier::Urgent)));
+
+        assert_eq!(
+            scheduler.leave_run(&[1, 2, 3]),
+            vec![Some(Tier::Urgent), Some(Tier::Prefetch), None]
+        );
+    }
+
+    #[test]
+    fn test_starved_tier_gets_a_turn() {
+        let now = Instant::now();
+        let mut queue = Queue::new(MB);
+        queue.push(100, Tier::Prefetch, now);
+        let mut picks = Vec::new();
+        for i in 0..SCHEDULER_STARVATION_LIMIT as usize + 1 {
+            queue.push(i, Tier::Urgent, now);
+            picks.push(queue.pop(true).unwrap().1);
+        }
+        assert_eq!(picks.last(), Some(&Tier::Prefetch));
+        assert!(picks[..picks.len() - 1].iter().all(|&t| t == Tier::Urgent));
+    }
+}
@@ -1,7 +1,6 @@
 // Proxy session state machine — manages a single file's download and playback proxy.
 
 use std::collections::HashMap;
-use std::path::Path;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
@@ -10,17 +9,28 @@ use anyhow::{anyhow, Result};
 use bytes::Bytes;
 use parking_lot::Mutex;
 use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 use tracing::{debug, info};
 
-use super::cache::DiskCache;
+use super::bitrate::BitrateEstimator;
+use super::cache::{CacheValidator, DiskCache};
+use super::cache_manager::{CacheLease, CacheManager};
+use super::cached_source::CachedSource;
 use super::downloader::Downloader;
+use super::media_index::load_time_index;
+use super::media_probe::probe_media;
+use super::policy::current_policy;
+use super::position::{PlaybackHint, PositionTracker, TimeIndex};
 use super::stats::{StatsCollector, StatsSnapshot};
-use super::warmup::compute_warmup_ranges;
+use super::store::CacheBackend;
+use super::warmup::plan_warmup;
 use crate::config::{
-    PRIORITY_BUFFER_SECONDS, SEEK_STABLE_SEQUENTIAL_HITS, SEEK_THRESHOLD_BYTES,
+    EngineConfig, SEEK_PREFETCH_SECONDS, SEEK_STABLE_SEQUENTIAL_HITS, SEEK_THRESHOLD_BYTES,
     SEEK_WARMUP_REQUESTS, SEEK_WARMUP_SECONDS,
 };
-use crate::source::http_source::HttpSource;
+use crate::detect::bitrate::estimate_bitrate;
+use crate::detect::media_info::ProbedMedia;
+use crate::error::{ProxyError, ProxyErrorKind};
 use crate::source::traits::{MediaSource, SourceInfo};
 
 struct SeekState {
@@ -96,57 +106,105 @@ impl SeekState {
     }
 }
 
+/// A position report mapped to bytes.
+struct HintedWindow {
+    /// What is on screen; eviction keeps chunks around it.
+    position: u64,
+    /// End of the player's own buffer, where its next reads land.
+    read_offset: u64,
+    /// Prefetch target: the policy's buffer time past `read_offset`.
+    horizon: u64,
+    rate: f64,
+}
+
 pub struct ProxySession {
     pub session_id: String,
-    http_source: Arc<HttpSource>,
+    source: Arc<dyn MediaSource>,
+    /// The file read back through the cache, for container parsing.
+    reader: Arc<CachedSource>,
     cache: Arc<DiskCache>,
+    _cache_lease: CacheLease,
     downloader: Arc<Downloader>,
     stats: Arc<StatsCollector>,
     info: SourceInfo,
+    /// MIME type of the detected container, when the upstream's differs.
+    detected_type: Arc<Mutex<Option<&'static str>>>,
+    /// Start of the player's latest request.
     playback_offset: AtomicU64,
-    playback_bps: Mutex<f64>,
+    /// Position reports from Dart, when it sends them.
+    position: PositionTracker,
+    /// Container time index, for mapping reported times to bytes.
+    time_index: Arc<Mutex<Option<Arc<TimeIndex>>>>,
+    /// Bytes per second playback consumes, for the prefetch horizon.
+    bitrate: Arc<BitrateEstimator>,
     seek_state: Mutex<SeekState>,
     chunk_size: u64,
+    /// Most recent failure serving the player, until auth is updated.
+    last_error: Mutex<Option<ProxyError>>,
+    /// Background detection tasks, aborted on shutdown.
+    tasks: Mutex<Vec<JoinHandle<()>>>,
 }
 
 impl ProxySession {
     /// Create a new session: probe the source, create cache, start warmup prefetch.
+    ///
+    /// `source` is usually built by [`crate::source::registry::SourceRegistry`].
     pub async fn new(
         session_id: String,
-        url: String,
-        headers: HashMap<String, String>,
-        cache_dir: &str,
-        chunk_size: u64,
-        max_concurrency: u32,
+        source: Arc<dyn MediaSource>,
+        cache_manager: Arc<CacheManager>,
+        config: &EngineConfig,
     ) -> Result<Self> {
-        let http_source = Arc::new(HttpSource::new(url, headers));
+        let chunk_size = config.chunk_size;
+        let max_concurrency = config.max_concurrency;
 
         // Probe the source to get content info.
-        let info = http_source.probe().await?;
+        let info = source.probe().await?;
         if info.content_length == 0 {
-            return Err(anyhow!("source content_length is 0"));
+            return Err(
+                ProxyError::new(ProxyErrorKind::Upstream5xx, "source content_length is 0").into(),
+            );
         }
         if !info.supports_range {
-            return Err(anyhow!("source does not support range requests"));
+            return Err(ProxyError::new(
+                ProxyErrorKind::Upstream5xx,
+                "source does not support range requests",
+            )
+            .into());
         }
 
         info!(
             "session {} probed: {} bytes, type={}",
             session_id, info.content_length, info.content_type
         );
-        let effective_concurrency = http_source.effective_concurrency(max_concurrency).await;
+        let effective_concurrency = source.effective_concurrency(max_concurrency).await;
 
         // Auto-detect ISO/UDF and potentially wrap the source.
-        let source: Arc<dyn MediaSource> =
-            crate::source::iso_source::wrap_if_iso(http_source.clone() as Arc<dyn MediaSource>)
-                .await?;
-
-        let cache = Arc::new(DiskCache::new(
-            Path::new(cache_dir),
+        let source = crate::source::iso_source::wrap_if_iso(source).await?;
+
+        // Make room under the engine cache budget, then reopen any cache left
+        // by an earlier session for the same file.
+        // Memory-only caches take nothing from the disk budget.
+        let backend = config.cache_backend.unwrap_or_default();
+        let session_budget = match (backend, config.max_session_cache_bytes) {
+            (CacheBackend::Memory, _) => 0,
+            (_, 0) => info.content_length,
+            (_, budget) => budget.min(info.content_length),
+        };
+        let cache_lease = cache_manager.acquire(&session_id, session_budget);
+        let validator = CacheValidator {
+            etag: info.etag.clone(),
+            last_modified: info.last_modified.clone(),
+        };
+        let cache = Arc::new(DiskCache::open_with_backend(
+            cache_manager.cache_dir(),
             &session_id,
             info.content_length,
             chunk_size,
+            &validator,
+            backend,
         )?);
+        cache.set_byte_budget(config.max_session_cache_bytes);
 
         let stats = Arc::new(StatsCollector::new());
 
@@ -161,48 +219,106 @@ impl ProxySession {
             session_id, max_concurrency, effective_concurrency
         );
 
+        let reader = Arc::new(CachedSource::new(downloader.clone(), info.content_length));
         let session = Self {
             session_id,
-            http_source: http_source.clone(),
+            source: source.clone(),
+            reader: reader.clone(),
             cache: cache.clone(),
+            _cache_lease: cache_lease,
             downloader: downloader.clone(),
             stats: stats.clone(),
             info,
+            detected_type: Arc::new(Mutex::new(None)),
             playback_offset: AtomicU64::new(0),
-            playback_bps: Mutex::new(0.0),
+            position: PositionTracker::new(),
+            time_index: Arc::new(Mutex::new(None)),
+            bitrate: Arc::new(BitrateEstimator::new()),
             seek_state: Mutex::new(SeekState::new()),
             chunk_size,
+            last_error: Mutex::new(None),
+            tasks: Mutex::new(Vec::new()),
         };
 
         // Immediately prefetch head chunk (chunk 0) so the player's first
         // request doesn't have to wait.  Also prefetch the tail region
         // (last ~8 MB / 4 chunks) because MP4 moov atoms are commonly at
         // the end and the player will seek there right after reading the head.
+        // In data-saver mode the tail waits for format detection below.
         let total_chunks = cache.total_chunks();
-        downloader.start_prefetch(0);
-        {
+        let speculative = current_policy().speculative();
+        downloader.start_warmup(0);
+        if speculative {
             let tail_chunks = 4usize; // ~8 MB with 2 MB chunks
             let tail_start = total_chunks.saturating_sub(tail_chunks);
-            for i in tail_start..total_chunks {
-                downloader.start_prefetch(i);
-            }
+            downloader.warmup_range(tail_start, total_chunks);
         }
 
         // Kick off warmup prefetch in background (may add more ranges after
         // format detection, but the critical head+tail are already in-flight).
-        let warmup_source = source.clone();
+        // Detection reads through the cache, so it shares the downloader's
+        // scheduling and limits and its bytes stay cached for the player.
+        let warmup_source = reader.clone();
         let warmup_downloader = downloader.clone();
         let warmup_cache = cache.clone();
         let cs = chunk_size;
         let cl = session.info.content_length;
-        tokio::spawn(async move {
-            match compute_warmup_ranges(warmup_source.as_ref(), cl, cs).await {
-                Ok(ranges) => {
+        let upstream_type = session.info.content_type.clone();
+        let detected_type = session.detected_type.clone();
+        let warmup_session = session.session_id.clone();
+        let bitrate_source = reader.clone();
+        let bitrate = session.bitrate.clone();
+        let bitrate_session = session.session_id.clone();
+        let bitrate_task = tokio::spawn(async move {
+            match estimate_bitrate(bitrate_source.as_ref(), cl).await {
+                Ok(Some(rate)) => {
+                    info!(
+                        "session {} container bitrate {} bytes/s",
+                        bitrate_session, rate as u64
+                    );
+                    bitrate.set_container_rate(rate);
+                }
+                Ok(None) => debug!("session {} container has no duration", bitrate_session),
+                Err(e) => tracing::warn!("bitrate detection failed: {}", e),
+            }
+        });
+
+        let index_source = reader;
+        let index_downloader = downloader.clone();
+        let index_slot = session.time_index.clone();
+        let index_session = session.session_id.clone();
+        let index_task = tokio::spawn(async move {
+            match load_time_index(index_source.as_ref(), &index_downloader, cl).await {
+                Ok(Some(index)) => {
+                    info!(
+                        "session {} time index: {} points",
+                        index_session,
+                        index.len()
+                    );
+                    index_slot.lock().get_or_insert(Arc::new(index));
+                }
+                Ok(None) => debug!("session {} has no container index", index_session),
+                Err(e) => tracing::warn!("container index failed: {}", e),
+            }
+        });
+
+        let warmup_task = tokio::spawn(async move {
+            match plan_warmup(warmup_source.as_ref(), cl, cs, speculative).await {
+                Ok((format, ranges)) => {
+                    if let Some(mime) = format.mime_type() {
+                        if !format.matches_content_type(&upstream_type) {
+                            info!(
+                                "session {} detected {:?}: serving {} instead of {}",
+                                warmup_session, format, mime, upstream_type
+                            );
+                            *detected_type.lock() = Some(mime);
+                        }
+                    }
                     for (range_start, range_end) in ranges {
                         let start_chunk = (range_start / cs) as usize;
                         let end_chunk =
                             ((range_end / cs) + 1).min(warmup_cache.total_chunks() as u64) as usize;
-                        warmup_downloader.prefetch_range(start_chunk, end_chunk);
+                        warmup_downloader.warmup_range(start_chunk, end_chunk);
                     }
                 }
                 Err(e) => {
@@ -210,12 +326,13 @@ impl ProxySession {
                 }
             }
         });
+        *session.tasks.lock() = vec![bitrate_task, index_task, warmup_task];
 
         Ok(session)
     }
 
     /// Serve a byte range [start, end) to the player.
-    pub async fn serve_range(&self, start: u64, end: u64) -> Result<Vec<u8>> {
+    pub async fn serve_range(&self, start: u64, end: u64) -> Result<Bytes> {
         let t0 = Instant::now();
         let end = end.min(self.info.content_length);
         if start >= end {
@@ -235,16 +352,11 @@ impl ProxySession {
 
         if is_seek {
             debug!("seek detected at offset {}", start);
-            let start_chunk = (start / self.chunk_size) as usize;
-            // Keep a window of chunks around the seek target.
-            let window_chunks = 32usize; // ~64MB window with 2MB chunks
-            let window_end = (start_chunk + window_chunks).min(self.cache.total_chunks());
-            self.downloader
-                .abort_outside_window(start_chunk, window_end);
-
             let mut seek = self.seek_state.lock();
             seek.reset_warmup();
         }
+        // Queued prefetch is re-ordered around the new position, not dropped.
+        self.update_playback(start);
 
         // Calculate which chunks we need.
         let first_chunk = (start / self.chunk_size) as usize;
@@ -268,37 +380,25 @@ impl ProxySession {
 
         // Wait for required chunks.
         for i in first_chunk..=last_chunk {
-            if !self.downloader.wait_for_chunk(i).await {
-                return Err(anyhow!("failed to download chunk {}", i));
+            if let Err(e) = self.downloader.wait_for_chunk(i).await {
+                self.record_error(&e);
+                return Err(
+                    anyhow::Error::new(e).context(format!("failed to download chunk {}", i))
+                );
             }
         }
 
-        // Schedule prefetch ahead based on estimated playback bitrate.
-        let bps = {
-            let bps = self.playback_bps.lock();
-            *bps
-        };
-        let prefetch_bytes = if bps > 0.0 {
-            (bps * PRIORITY_BUFFER_SECONDS as f64) as u64
-        } else {
-            // Default: prefetch 20 chunks ahead.
-            self.chunk_size * 20
-        };
-        let prefetch_end_byte = (end + prefetch_bytes).min(self.info.content_length);
-        let prefetch_end_chunk =
-            ((prefetch_end_byte + self.chunk_size - 1) / self.chunk_size) as usize;
-        let prefetch_end_chunk = prefetch_end_chunk.min(self.cache.total_chunks());
-        let prefetch_start_chunk = last_chunk.saturating_add(1);
-        if prefetch_start_chunk < prefetch_end_chunk {
-            self.downloader
-                .prefetch_range(prefetch_start_chunk, prefetch_end_chunk);
-        }
+        self.prefetch_ahead(end, last_chunk + 1);
 
         // Read from cache.
-        let data = self
-            .cache
-            .read_range(start, end)
-            .ok_or_else(|| anyhow!("cache read failed for range [{}, {})", start, end))?;
+        let data = self.cache.read_bytes(start, end).ok_or_else(|| {
+            let e = ProxyError::new(
+                ProxyErrorKind::CacheIo,
+                format!("cache read failed for range [{}, {})", start, end),
+            );
+            self.record_error(&e);
+            anyhow::Error::new(e)
+        })?;
 
         // Update served stats and playback bitrate estimate.
         self.stats.record_served(data.len() as u64);
@@ -310,18 +410,8 @@ impl ProxySession {
             seek.update(start, was_sequential);
         }
 
-        // Simple bitrate estimation: use the serve rate as approximation.
-        // Update bps based on the range length (this is a rough heuristic).
-        if range_len > 0 {
-            let mut bps = self.playback_bps.lock();
-            if *bps == 0.0 {
-                // Initial estimate based on a common media bitrate.
-                *bps = range_len as f64 * 8.0;
-            } else {
-                // Exponential moving average.
-                *bps = *bps * 0.9 + range_len as f64 * 8.0 * 0.1;
-            }
-        }
+        self.bitrate
+            .record_served(data.len() as u64, Instant::now());
 
         debug!(
             "serve_range session={} range=[{}, {}) bytes={} elapsed_ms={}",
@@ -335,12 +425,12 @@ impl ProxySession {
         Ok(data)
     }
 
-    /// Serve a byte range [start, end) as a stream of Bytes chunks.
+    /// Serve a byte range [start, end) as a stream of Bytes pieces.
     ///
-    /// Returns a receiver that yields data piece-by-piece as each underlying
-    /// cache chunk becomes available.  The HTTP handler can start writing to
-    /// the socket as soon as the first piece arrives, preventing player
-    /// timeouts even when later chunks are still downloading.
+    /// Returns a receiver that yields data as soon as it is contiguous in the
+    /// cache, including bytes of chunks that are still downloading.  The HTTP
+    /// handler can start writing to the socket after roughly one upstream
+    /// round trip, even on a cold seek.
     pub fn serve_range_stream(
         self: &Arc<Self>,
         start: u64,
@@ -364,15 +454,11 @@ impl ProxySession {
 
         if is_seek {
             debug!("seek detected at offset {}", start);
-            let start_chunk = (start / self.chunk_size) as usize;
-            let window_chunks = 32usize;
-            let window_end = (start_chunk + window_chunks).min(self.cache.total_chunks());
-            self.downloader
-                .abort_outside_window(start_chunk, window_end);
-
             let mut seek = self.seek_state.lock();
             seek.reset_warmup();
         }
+        // Queued prefetch is re-ordered around the new position, not dropped.
+        self.update_playback(start);
 
         let first_chunk = (start / self.chunk_size) as usize;
         let last_chunk = ((end - 1) / self.chunk_size) as usize;
@@ -392,9 +478,9 @@ impl ProxySession {
             self.downloader.start_urgent_prefetch(i);
         }
 
-        // Channel with enough buffer for all chunks so sender doesn't block.
-        let chunk_count = last_chunk - first_chunk + 1;
-        let (tx, rx) = mpsc::channel::<Result<Bytes>>(chunk_count.max(1));
+        // Pieces are small while a chunk downloads; a short buffer is enough
+        // and lets a slow player apply backpressure.
+        let (tx, rx) = mpsc::channel::<Result<Bytes>>(16);
 
         let session = Arc::clone(self);
         let t0 = Instant::now();
@@ -403,14 +489,6 @@ impl ProxySession {
             let mut total_sent = 0u64;
 
             for i in first_chunk..=last_chunk {
-                // Wait for this specific chunk.
-                if !session.downloader.wait_for_chunk(i).await {
-                    let _ = tx
-                        .send(Err(anyhow!("failed to download chunk {}", i)))
-                        .await;
-                    return;
-                }
-
                 // Calculate the slice of this chunk that falls within [start, end).
                 let chunk_start_byte = i as u64 * session.chunk_size;
                 let chunk_end_byte =
@@ -418,54 +496,53 @@ impl ProxySession {
                 let slice_start = start.max(chunk_start_byte);
                 let slice_end = end.min(chunk_end_byte);
 
-                if slice_start >= slice_end {
-                    continue;
-                }
-
-                // Read just this slice from the mmap.
-                match session.cache.read_range(slice_start, slice_end) {
-                    Some(data) => {
-                        total_sent += data.len() as u64;
-                        if tx.send(Ok(Bytes::from(data))).await.is_err() {
-                            // Receiver dropped (client disconnected).
-                            debug!("stream receiver dropped at chunk {}", i);
+                // Forward the slice as it lands, one contiguous piece at a time.
+                let mut sent_to = slice_start;
+                while sent_to < slice_end {
+                    let have = sent_to - chunk_start_byte;
+                    let available = match session.downloader.wait_for_progress(i, have).await {
+                        Ok(available) => available,
+                        Err(e) => {
+                            session.record_error(&e);
+                            let _ = tx
+                                .send(Err(anyhow::Error::new(e)
+                                    .context(format!("failed to download chunk {}", i))))
+                                .await;
+                            return;
+                        }
+                    };
+                    let piece_end = (chunk_start_byte + available).min(slice_end);
+
+                    // Borrow just this piece; mmap and memory stores hand out
+                    // views, so nothing is copied on the way to the socket.
+                    match session.cache.read_bytes(sent_to, piece_end) {
+                        Some(data) => {
+                            total_sent += data.len() as u64;
+                            sent_to = piece_end;
+                            if tx.send(Ok(data)).await.is_err() {
+                                // Receiver dropped (client disconnected).
+                                debug!("stream receiver dropped at chunk {}", i);
+                                return;
+                            }
+                        }
+                        None => {
+                            let e = ProxyError::new(
+                                ProxyErrorKind::CacheIo,
+                                format!(
+                                    "cache read failed for chunk {} slice [{}, {})",
+                                    i, sent_to, piece_end
+                                ),
+                            );
+                            session.record_error(&e);
+                            let _ = tx.send(Err(e.into())).await;
                             return;
                         }
-                    }
-                    None => {
-                        let _ = tx
-                            .send(Err(anyhow!(
-                                "cache read failed for chunk {} slice [{}, {})",
-                                i,
-                                slice_start,
-                                slice_end
-                            )))
-                            .await;
-                        return;
                     }
                 }
             }
 
             // All chunks sent — schedule prefetch ahead.
-            let bps = {
-                let bps = session.playback_bps.lock();
-                *bps
-            };
-            let prefetch_bytes = if bps > 0.0 {
-                (bps * PRIORITY_BUFFER_SECONDS as f64) as u64
-            } else {
-                session.chunk_size * 20
-            };
-            let prefetch_end_byte = (end + prefetch_bytes).min(session.info.content_length);
-            let prefetch_end_chunk =
-                ((prefetch_end_byte + session.chunk_size - 1) / session.chunk_size) as usize;
-            let prefetch_end_chunk = prefetch_end_chunk.min(session.cache.total_chunks());
-            let prefetch_start_chunk = last_chunk.saturating_add(1);
-            if prefetch_start_chunk < prefetch_end_chunk {
-                session
-                    .downloader
-                    .prefetch_range(prefetch_start_chunk, prefetch_end_chunk);
-            }
+            session.prefetch_ahead(end, last_chunk + 1);
 
             // Update stats.
             session.stats.record_served(total_sent);
@@ -474,14 +551,7 @@ impl ProxySession {
                 let was_sequential = !is_seek;
                 seek.update(start, was_sequential);
             }
-            if range_len > 0 {
-                let mut bps = session.playback_bps.lock();
-                if *bps == 0.0 {
-                    *bps = range_len as f64 * 8.0;
-                } else {
-                    *bps = *bps * 0.9 + range_len as f64 * 8.0 * 0.1;
-                }
-            }
+            session.bitrate.record_served(total_sent, Instant::now());
 
             debug!(
                 "serve_range_stream session={} range=[{}, {}) bytes={} elapsed_ms={}",
@@ -496,21 +566,218 @@ impl ProxySession {
         Ok(rx)
     }
 
+    /// Queue prefetch from `first_chunk` up to the policy's buffer horizon:
+    /// past the player's reported buffer end when Dart sends positions,
+    /// else past `end` at the playback bitrate. The horizon stops where the
+    /// session budget would evict chunks again. In data-saver mode, or when
+    /// the budget cuts it short, queued chunks past the horizon are dropped.
+    fn prefetch_ahead(&self, end: u64, first_chunk: usize) {
+        let policy = current_policy();
+        let prefetch_end_byte = match self.hinted_window() {
+            Some(window) => window.horizon,
+            None => {
+                let prefetch_bytes = match self.bitrate.bytes_per_sec(Instant::now()) {
+                    Some(rate) => (rate * policy.buffer_seconds() as f64) as u64,
+                    None => self.chunk_size * policy.default_prefetch_chunks(),
+                };
+                end + prefetch_bytes
+            }
+        }
+        .min(self.info.content_length);
+        let prefetch_end_chunk =
+            ((prefetch_end_byte + self.chunk_size - 1) / self.chunk_size) as usize;
+        let mut prefetch_end_chunk = prefetch_end_chunk.min(self.cache.total_chunks());
+        let budget_end = self
+            .cache
+            .budget_end_chunk()
+            .filter(|&budget_end| budget_end < prefetch_end_chunk);
+        if let Some(budget_end) = budget_end {
+            prefetch_end_chunk = budget_end;
+        }
+        if !policy.speculative() || budget_end.is_some() {
+            self.downloader
+                .trim_prefetch(prefetch_end_chunk.max(first_chunk));
+        }
+        if first_chunk < prefetch_end_chunk {
+            self.downloader
+                .prefetch_range(first_chunk, prefetch_end_chunk);
+        }
+    }
+
+    fn record_error(&self, error: &ProxyError) {
+        if error.kind != ProxyErrorKind::Cancelled {
+            *self.last_error.lock() = Some(error.clone());
+        }
+    }
+
+    /// The most recent failure serving the player, if any since the last
+    /// auth update.
+    pub fn last_error(&self) -> Option<ProxyError> {
+        self.last_error.lock().clone()
+    }
+
+    /// Record a position report from the player and re-aim prefetch,
+    /// scheduling and eviction at it.
+    pub fn report_playback(&self, hint: PlaybackHint) {
+        self.position.report(hint, Instant::now());
+        self.update_playback(self.playback_offset.load(Ordering::Relaxed));
+        if let Some(window) = self.hinted_window() {
+            let read_chunk = (window.read_offset / self.chunk_size) as usize;
+            self.prefetch_ahead(window.read_offset, read_chunk);
+        }
+    }
+
+    /// Provide the container's time index for mapping reported positions
+    /// to bytes.
+    pub fn set_time_index(&self, index: TimeIndex) {
+        if !index.is_empty() {
+            *self.time_index.lock() = Some(Arc::new(index));
+        }
+    }
+
+    /// Container, duration and tracks of the file, from its headers. Without
+    /// a duration in the headers, it follows from the container bitrate.
+    pub async fn media_info(&self) -> Result<ProbedMedia> {
+        let cl = self.info.content_length;
+        let mut media = probe_media(self.reader.as_ref(), &self.downloader, cl).await?;
+        if media.duration_secs.is_none() {
+            let rate = match self.bitrate.container_rate() {
+                Some(rate) => Some(rate),
+                None => estimate_bitrate(self.reader.as_ref(), cl).await?,
+            };
+            media.duration_secs = rate.filter(|&r| r > 0.0).map(|r| cl as f64 / r);
+        }
+        Ok(media)
+    }
+
+    /// Urgently fetch what a seek to `position_secs` will read: from the
+    /// keyframe at or before it through a few seconds past. Returns the
+    /// keyframe's time when the container index has one.
+    pub fn prepare_seek(&self, position_secs: f64) -> Option<f64> {
+        let keyframe = self
+            .time_index
+            .lock()
+            .as_ref()
+            .and_then(|index| index.point_at_or_before(position_secs));
+        let (from_secs, start) = match keyframe {
+            Some(point) => point,
+            None => (position_secs, self.time_to_offset(position_secs)?),
+        };
+        if start >= self.info.content_length {
+            return None;
+        }
+        let end = self
+            .time_to_offset(from_secs + SEEK_PREFETCH_SECONDS as f64)
+            .unwrap_or(self.info.content_length)
+            .clamp(start + 1, self.info.content_length);
+        let first_chunk = (start / self.chunk_size) as usize;
+        let last_chunk = ((end - 1) / self.chunk_size) as usize;
+        for i in first_chunk..=last_chunk {
+            self.downloader.start_urgent_prefetch(i);
+        }
+        debug!(
+            "seek to {:.3}s prepared from {:.3}s, chunks {}..={}",
+            position_secs, from_secs, first_chunk, last_chunk
+        );
+        keyframe.map(|(secs, _)| secs)
+    }
+
+    /// Byte offset playback reaches at `secs` of media time.
+    fn time_to_offset(&self, secs: f64) -> Option<u64> {
+        let indexed = self
+            .time_index
+            .lock()
+            .as_ref()
+            .and_then(|index| index.offset_at(secs));
+        let offset = indexed.or_else(|| {
+            self.bitrate
+                .container_rate()
+                .map(|rate| (rate * secs) as u64)
+        })?;
+        Some(offset.min(self.info.content_length))
+    }
+
+    /// Byte offsets of the latest fresh position report, if the times can
+    /// be mapped.
+    fn hinted_window(&self) -> Option<HintedWindow> {
+        let hint = self.position.current(Instant::now())?;
+        let horizon_secs =
+            hint.buffer_end_secs + current_policy().buffer_seconds() as f64 * hint.rate;
+        let position = self.time_to_offset(hint.position_secs)?;
+        let read_offset = self.time_to_offset(hint.buffer_end_secs)?.max(position);
+        let horizon = self
+            .time_to_offset(horizon_secs)
+            .unwrap_or(self.info.content_length)
+            .max(read_offset);
+        Some(HintedWindow {
+            position,
+            read_offset,
+            horizon,
+            rate: hint.rate,
+        })
+    }
+
+    /// Aim the download scheduler and cache eviction at the player. A fresh
+    /// position report wins; otherwise `request_start` stands in for both.
+    fn update_playback(&self, request_start: u64) {
+        let bytes_per_sec = self.bitrate.bytes_per_sec(Instant::now()).unwrap_or(0.0);
+        match self.hinted_window() {
+            Some(window) => {
+                self.cache.set_playback_offset(window.position);
+                self.downloader
+                    .set_playback(window.read_offset, (bytes_per_sec * window.rate) as u64);
+            }
+            None => {
+                self.cache.set_playback_offset(request_start);
+                self.downloader
+                    .set_playback(request_start, bytes_per_sec as u64);
+            }
+        }
+    }
+
+    /// Where the player is: the reported position, else the last request.
+    fn playback_position(&self) -> u64 {
+        self.hinted_window()
+            .map(|window| window.position)
+            .unwrap_or_else(|| self.playback_offset.load(Ordering::Relaxed))
+    }
+
     /// Get a stats snapshot.
     pub fn snapshot(&self) -> StatsSnapshot {
-        let offset = self.playback_offset.load(Ordering::Relaxed);
-        let buffered = self.cache.buffered_bytes_ahead(offset);
-        self.stats.snapshot(buffered)
+        let buffered = self.cache.buffered_bytes_ahead(self.playback_position());
+        let bitrate = self.bitrate.bytes_per_sec(Instant::now()).unwrap_or(0.0) * 8.0;
+        self.stats.snapshot(buffered, bitrate as u64)
     }
 
     /// Update authentication credentials (new URL / headers from token refresh).
     pub fn update_auth(&self, new_url: String, new_headers: HashMap<String, String>) {
-        self.http_source.update_auth(new_url, new_headers);
+        self.source.update_auth(new_url, new_headers);
+        *self.last_error.lock() = None;
     }
 
-    /// Get the content type of the source.
-    pub fn content_type(&self) -> &str {
-        &self.info.content_type
+    /// Cap this session's download rate in bytes per second; 0 removes the
+    /// cap. Chunks the player is blocked on may exceed it.
+    pub fn set_bandwidth_limit(&self, bytes_per_sec: u64) {
+        self.downloader.set_bandwidth_limit(bytes_per_sec);
+    }
+
+    /// Label the session's cache for the cache management API.
+    pub fn set_cache_label(&self, file_key: Option<String>, title: Option<String>) {
+        self.cache.set_label(file_key, title);
+    }
+
+    /// Pin or unpin the session's cache against engine-level eviction.
+    pub fn set_cache_pinned(&self, pinned: bool) {
+        self.cache.set_pinned(pinned);
+    }
+
+    /// Content type served to the player: the detected container's when
+    /// the upstream reports something else, e.g. `application/octet-stream`.
+    pub fn content_type(&self) -> String {
+        match *self.detected_type.lock() {
+            Some(mime) => mime.to_string(),
+            None => self.info.content_type.clone(),
+        }
     }
 
     /// Get the total content length.
@@ -518,8 +785,11 @@ impl ProxySession {
         self.info.content_length
     }
 
-    /// Cancel all in-flight download workers.
+    /// Cancel all in-flight download workers and detection tasks.
     pub fn shutdown(&self) {
+        for task in self.tasks.lock().drain(..) {
+            task.abort();
+        }
         self.downloader.shutdown();
     }
 }
@@ -527,6 +797,6 @@ impl ProxySession {
 impl Drop for ProxySession {
     fn drop(&mut self) {
         debug!("ProxySession {} dropped, shutting down downloader", self.session_id);
-        self.downloader.shutdown();
+        self.shutdown();
     }
 }
@@ -18,6 +18,8 @@ pub struct StatsSnapshot {
     pub buffered_bytes_ahead: u64,
     pub active_workers: u32,
     pub cache_hit_rate: f64,
+    /// Playback bitrate in bits per second; 0 while unknown.
+    pub playback_bitrate: u64,
 }
 
 pub struct StatsCollector {
@@ -26,6 +28,7 @@ pub struct StatsCollector {
     active_workers: AtomicU32,
     requested_bytes: AtomicU64,
     cache_hit_bytes: AtomicU64,
+    fetch_errors: AtomicU64,
     last_sample: Mutex<StatsSample>,
 }
 
@@ -37,6 +40,7 @@ impl StatsCollector {
             active_workers: AtomicU32::new(0),
             requested_bytes: AtomicU64::new(0),
             cache_hit_bytes: AtomicU64::new(0),
+            fetch_errors: AtomicU64::new(0),
             last_sample: Mutex::new(StatsSample {
                 at: Instant::now(),
                 download_bytes: 0,
@@ -60,6 +64,15 @@ impl StatsCollector {
         self.cache_hit_bytes.fetch_add(cached, Ordering::Relaxed);
     }
 
+    /// Count a failed fetch attempt (one that made no progress).
+    pub fn record_fetch_error(&self) {
+        self.fetch_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn fetch_errors(&self) -> u64 {
+        self.fetch_errors.load(Ordering::Relaxed)
+    }
+
     pub fn increment_workers(&self) {
         self.active_workers.fetch_add(1, Ordering::Relaxed);
     }
@@ -68,7 +81,7 @@ impl StatsCollector {
         self.active_workers.fetch_sub(1, Ordering::Relaxed);
     }
 
-    pub fn snapshot(&self, buffered_bytes_ahead: u64) -> StatsSnapshot {
+    pub fn snapshot(&self, buffered_bytes_ahead: u64, playback_bitrate: u64) -> StatsSnapshot {
         let now = Instant::now();
         let current_download = self.download_bytes_total.load(Ordering::Relaxed);
         let current_serve = self.serve_bytes_total.load(Ordering::Relaxed);
@@ -107,6 +120,7 @@ impl StatsCollector {
             buffered_bytes_ahead,
             active_workers: self.active_workers.load(Ordering::Relaxed),
             cache_hit_rate,
+            playback_bitrate,
         }
     }
 
@@ -139,7 +153,7 @@ mod tests {
         stats.increment_workers();
         stats.decrement_workers();
 
-        let snap = stats.snapshot(4096);
+        let snap = stats.snapshot(4096, 0);
         assert_eq!(snap.buffered_bytes_ahead, 4096);
         assert_eq!(snap.active_workers, 1);
         assert!((snap.cache_hit_rate - 0.3).abs() < f64::EPSILON);
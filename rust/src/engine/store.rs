@@ -0,0 +1,692 @@
+// Cache storage backends — where chunk bytes live once downloaded.
+//
+// `DiskCache` owns the completion bitmap, sidecar and budget; a `CacheStore`
+// only holds the bytes. Three backends are provided:
+//
+// * `MmapStore`: one sparse `{key}.cache` file mapped over the full
+//   content length. Fastest, but needs address space for the whole file.
+// * `ChunkFileStore`: one file per chunk under `{key}.chunks/`. Works on
+//   32-bit devices and filesystems without sparse files.
+// * `MemoryStore`: chunks held in RAM, bounded, never persisted. Suits
+//   short clips that are not worth a disk round trip.
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use memmap2::{Mmap, MmapMut};
+use parking_lot::{Mutex, RwLock};
+use tracing::debug;
+
+pub use crate::config::CacheBackend;
+
+/// Byte storage underneath a `DiskCache`.
+///
+/// Callers guarantee that reads only cover chunks that were written and not
+/// released since; the store does not track completion itself.
+pub trait CacheStore: Send + Sync {
+    /// Which backend this is (recorded in the sidecar).
+    fn backend(&self) -> CacheBackend;
+
+    /// Whether `chunk_index` survived from an earlier run with `len` bytes.
+    fn has_persisted(&self, chunk_index: usize, len: usize) -> bool;
+
+    /// Whether the first `len` bytes of a partly downloaded chunk survived
+    /// from an earlier run.
+    fn has_partial(&self, _chunk_index: usize, _len: usize) -> bool {
+        false
+    }
+
+    /// Store a complete chunk.
+    fn write_chunk(&self, chunk_index: usize, data: &[u8]) -> Result<()>;
+
+    /// Store part of a chunk that is still downloading, `offset` bytes into it.
+    fn write_partial(&self, chunk_index: usize, offset: usize, data: &[u8]) -> Result<()>;
+
+    /// Seal a chunk built from `write_partial` calls once all `len` bytes
+    /// are in place.
+    fn complete_chunk(&self, _chunk_index: usize, _len: usize) -> Result<()> {
+        Ok(())
+    }
+
+    /// Fill `buf` with the bytes starting at absolute `offset`.
+    fn read_into(&self, offset: u64, buf: &mut [u8]) -> Result<()>;
+
+    /// Return `len` bytes at `offset`. Backends that can share their memory
+    /// hand out reference-counted views instead of copies.
+    fn read_bytes(&self, offset: u64, len: usize) -> Result<Bytes> {
+        let mut buf = vec![0u8; len];
+        self.read_into(offset, &mut buf)?;
+        Ok(Bytes::from(buf))
+    }
+
+    /// Drop a chunk's bytes and give its storage back. Backends whose
+    /// `read_bytes` views share storage defer this until the chunk's last
+    /// view is dropped, so readers never see the bytes change.
+    fn release_chunk(&self, chunk_index: usize, len: usize) -> Result<()>;
+
+    /// Push buffered writes to durable storage.
+    fn flush(&self) -> Result<()>;
+
+    /// Whether the bytes outlive the process, i.e. a sidecar is worth writing.
+    fn is_persistent(&self) -> bool {
+        true
+    }
+
+    /// Hard cap on bytes held, if the backend has one.
+    fn capacity(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// Path of the mmap data file for a cache key.
+pub fn cache_data_path(cache_dir: &Path, key: &str) -> PathBuf {
+    cache_dir.join(format!("{}.cache", key))
+}
+
+/// Path of the per-chunk directory for a cache key.
+pub fn cache_chunks_dir(cache_dir: &Path, key: &str) -> PathBuf {
+    cache_dir.join(format!("{}.chunks", key))
+}
+
+/// Delete whatever data any backend keeps on disk for `key`.
+pub fn remove_store_data(cache_dir: &Path, key: &str) -> std::io::Result<()> {
+    match fs::remove_file(cache_data_path(cache_dir, key)) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    match fs::remove_dir_all(cache_chunks_dir(cache_dir, key)) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// MmapStore
+// ---------------------------------------------------------------------------
+
+pub struct MmapStore {
+    /// Writable mapping used by `write_chunk`.
+    mmap: RwLock<MmapMut>,
+    /// Read-only mapping of the same file that `read_bytes` views borrow.
+    view: Arc<Mmap>,
+    views: Arc<ViewTracker>,
+    chunk_size: u64,
+    /// The data file was found intact at full size on open.
+    restored: bool,
+}
+
+impl MmapStore {
+    /// Map `path` at `content_length` bytes. With `keep`, existing data is
+    /// preserved if the file already has the right size.
+    pub fn open(path: &Path, content_length: u64, chunk_size: u64, keep: bool) -> Result<Self> {
+        if usize::try_from(content_length).is_err() {
+            return Err(anyhow!(
+                "content_length {} exceeds the address space",
+                content_length
+            ));
+        }
+
+        let data_len = fs::metadata(path).map(|m| m.len()).ok();
+        let restored = keep && data_len == Some(content_length);
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(!restored)
+            .open(path)?;
+        file.set_len(content_length)?;
+
+        // SAFETY: the file is owned by this cache; other handles to the same
+        // key only ever write identical chunk contents.
+        let mmap = unsafe { MmapMut::map_mut(&file)? };
+        // SAFETY: as above; both mappings share the page cache, so bytes
+        // written through `mmap` are visible here.
+        let view = unsafe { Mmap::map(&file)? };
+
+        let total_chunks = content_length.div_ceil(chunk_size) as usize;
+        Ok(Self {
+            mmap: RwLock::new(mmap),
+            view: Arc::new(view),
+            views: Arc::new(ViewTracker {
+                file,
+                chunk_size,
+                state: Mutex::new(ViewState {
+                    counts: vec![0; total_chunks],
+                    deferred: HashMap::new(),
+                }),
+            }),
+            chunk_size,
+            restored,
+        })
+    }
+}
+
+impl MmapStore {
+    /// Whether the first `len` bytes of a chunk are backed by data rather
+    /// than a hole: never written, or punched out by eviction.
+    fn has_data(&self, chunk_index: usize, len: usize) -> bool {
+        let offset = chunk_index as u64 * self.chunk_size;
+        match next_hole(&self.views.file, offset) {
+            Ok(hole) => hole >= offset + len as u64,
+            Err(e) => {
+                debug!("hole lookup for chunk {} failed: {}", chunk_index, e);
+                false
+            }
+        }
+    }
+}
+
+impl CacheStore for MmapStore {
+    fn backend(&self) -> CacheBackend {
+        CacheBackend::Mmap
+    }
+
+    fn has_persisted(&self, chunk_index: usize, len: usize) -> bool {
+        self.restored && self.has_data(chunk_index, len)
+    }
+
+    fn has_partial(&self, chunk_index: usize, len: usize) -> bool {
+        self.restored && self.has_data(chunk_index, len)
+    }
+
+    fn write_chunk(&self, chunk_index: usize, data: &[u8]) -> Result<()> {
+        self.views.cancel_release(chunk_index);
+        let offset = (chunk_index as u64 * self.chunk_size) as usize;
+        let mut mmap = self.mmap.write();
+        mmap[offset..offset + data.len()].copy_from_slice(data);
+        Ok(())
+    }
+
+    fn write_partial(&self, chunk_index: usize, offset: usize, data: &[u8]) -> Result<()> {
+        self.views.cancel_release(chunk_index);
+        let start = (chunk_index as u64 * self.chunk_size) as usize + offset;
+        let mut mmap = self.mmap.write();
+        mmap[start..start + data.len()].copy_from_slice(data);
+        Ok(())
+    }
+
+    fn read_into(&self, offset: u64, buf: &mut [u8]) -> Result<()> {
+        let offset = offset as usize;
+        let mmap = self.mmap.read();
+        buf.copy_from_slice(&mmap[offset..offset + buf.len()]);
+        Ok(())
+    }
+
+    fn read_bytes(&self, offset: u64, len: usize) -> Result<Bytes> {
+        let start = offset as usize;
+        let chunks = self.views.chunks_of(offset, len);
+        self.views.acquire(chunks.clone());
+        Ok(Bytes::from_owner(MmapView {
+            map: Arc::clone(&self.view),
+            views: Arc::clone(&self.views),
+            chunks,
+            start,
+            end: start + len,
+        }))
+    }
+
+    fn release_chunk(&self, chunk_index: usize, len: usize) -> Result<()> {
+        self.views.release_chunk(chunk_index, len)
+    }
+
+    fn flush(&self) -> Result<()> {
+        self.mmap.read().flush()?;
+        Ok(())
+    }
+}
+
+/// A slice of the read-only mapping that keeps the mapping alive and its
+/// chunks' storage allocated.
+struct MmapView {
+    map: Arc<Mmap>,
+    views: Arc<ViewTracker>,
+    chunks: Range<usize>,
+    start: usize,
+    end: usize,
+}
+
+impl AsRef<[u8]> for MmapView {
+    fn as_ref(&self) -> &[u8] {
+        &self.map[self.start..self.end]
+    }
+}
+
+impl Drop for MmapView {
+    fn drop(&mut self) {
+        self.views.drop_view(self.chunks.clone());
+    }
+}
+
+/// Live views per chunk, and evicted chunks whose hole punch waits for the
+/// last of them. Views may outlive the store, so this owns the file.
+struct ViewTracker {
+    file: File,
+    chunk_size: u64,
+    state: Mutex<ViewState>,
+}
+
+struct ViewState {
+    counts: Vec<u32>,
+    /// Evicted chunk index → byte length still to be punched.
+    deferred: HashMap<usize, usize>,
+}
+
+impl ViewTracker {
+    fn chunks_of(&self, offset: u64, len: usize) -> Range<usize> {
+        let first = (offset / self.chunk_size) as usize;
+        let end = (offset + len as u64).div_ceil(self.chunk_size) as usize;
+        first..end.max(first)
+    }
+
+    fn acquire(&self, chunks: Range<usize>) {
+        let mut state = self.state.lock();
+        for i in chunks {
+            state.counts[i] += 1;
+        }
+    }
+
+    fn drop_view(&self, chunks: Range<usize>) {
+        let mut state = self.state.lock();
+        for i in chunks {
+            state.counts[i] -= 1;
+            if state.counts[i] == 0 {
+                if let Some(len) = state.deferred.remove(&i) {
+                    if let Err(e) = self.punch(i, len) {
+                        debug!("deferred release of chunk {} failed: {}", i, e);
+                    }
+                }
+            }
+        }
+    }
+
+    fn release_chunk(&self, chunk_index: usize, len: usize) -> Result<()> {
+        let mut state = self.state.lock();
+        if state.counts[chunk_index] > 0 {
+            state.deferred.insert(chunk_index, len);
+            return Ok(());
+        }
+        self.punch(chunk_index, len)?;
+        Ok(())
+    }
+
+    /// The chunk is being written again; a deferred punch must not land on
+    /// the new bytes.
+    fn cancel_release(&self, chunk_index: usize) {
+        self.state.lock().deferred.remove(&chunk_index);
+    }
+
+    fn punch(&self, chunk_index: usize, len: usize) -> std::io::Result<()> {
+        punch_hole(&self.file, chunk_index as u64 * self.chunk_size, len as u64)
+    }
+}
+
+/// Release the disk blocks backing `[offset, offset + len)` while keeping
+/// the file size, so the range reads back as zeros.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn punch_hole(file: &File, offset: u64, len: u64) -> std::io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+    // SAFETY: plain syscall on a file descriptor we own.
+    let ret = unsafe {
+        libc::fallocate(
+            file.as_raw_fd(),
+            libc::FALLOC_FL_PUNCH_HOLE | libc::FALLOC_FL_KEEP_SIZE,
+            offset as libc::off_t,
+            len as libc::off_t,
+        )
+    };
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+fn punch_hole(file: &File, offset: u64, len: u64) -> std::io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+    let args = libc::fpunchhole_t {
+        fp_flags: 0,
+        reserved: 0,
+        fp_offset: offset as libc::off_t,
+        fp_length: len as libc::off_t,
+    };
+    // SAFETY: plain fcntl on a file descriptor we own with a valid argument.
+    let ret = unsafe { libc::fcntl(file.as_raw_fd(), libc::F_PUNCHHOLE, &args) };
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+/// No portable hole punching here; the bitmap still drops the chunk so the
+/// budget is honoured logically, but the blocks stay allocated.
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios"
+)))]
+fn punch_hole(_file: &File, _offset: u64, _len: u64) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        ErrorKind::Unsupported,
+        "hole punching not supported on this platform",
+    ))
+}
+
+/// Offset of the first hole at or after `offset`; the end of the file
+/// counts as one.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios"
+))]
+fn next_hole(file: &File, offset: u64) -> std::io::Result<u64> {
+    use std::os::unix::io::AsRawFd;
+    // SAFETY: plain syscall on a file descriptor we own; nothing else
+    // relies on its file position.
+    let ret = unsafe { libc::lseek(file.as_raw_fd(), offset as libc::off_t, libc::SEEK_HOLE) };
+    if ret >= 0 {
+        Ok(ret as u64)
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+/// Without hole lookup every byte counts as data, as without punching.
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios"
+)))]
+fn next_hole(file: &File, _offset: u64) -> std::io::Result<u64> {
+    Ok(file.metadata()?.len())
+}
+
+// ---------------------------------------------------------------------------
+// ChunkFileStore
+// ---------------------------------------------------------------------------
+
+pub struct ChunkFileStore {
+    dir: PathBuf,
+    chunk_size: u64,
+}
+
+impl ChunkFileStore {
+    /// Use `dir` for chunk files. Without `keep`, existing chunks are removed.
+    pub fn open(dir: PathBuf, chunk_size: u64, keep: bool) -> Result<Self> {
+        if !keep {
+            match fs::remove_dir_all(&dir) {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir, chunk_size })
+    }
+
+    fn chunk_path(&self, chunk_index: usize) -> PathBuf {
+        self.dir.join(format!("{:08}.bin", chunk_index))
+    }
+
+    /// File a chunk is assembled in while it downloads.
+    fn part_path(&self, chunk_index: usize) -> PathBuf {
+        self.chunk_path(chunk_index).with_extension("part")
+    }
+
+    /// Open a chunk for reading, whether sealed or still downloading.
+    fn open_for_read(&self, chunk_index: usize) -> std::io::Result<File> {
+        match File::open(self.chunk_path(chunk_index)) {
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                match File::open(self.part_path(chunk_index)) {
+                    // Sealed between the two opens.
+                    Err(e) if e.kind() == ErrorKind::NotFound => {
+                        File::open(self.chunk_path(chunk_index))
+                    }
+                    other => other,
+                }
+            }
+            other => other,
+        }
+    }
+}
+
+impl CacheStore for ChunkFileStore {
+    fn backend(&self) -> CacheBackend {
+        CacheBackend::ChunkFiles
+    }
+
+    fn has_persisted(&self, chunk_index: usize, len: usize) -> bool {
+        fs::metadata(self.chunk_path(chunk_index))
+            .map(|m| m.len() == len as u64)
+            .unwrap_or(false)
+    }
+
+    fn has_partial(&self, chunk_index: usize, len: usize) -> bool {
+        fs::metadata(self.part_path(chunk_index))
+            .map(|m| m.len() >= len as u64)
+            .unwrap_or(false)
+    }
+
+    fn write_chunk(&self, chunk_index: usize, data: &[u8]) -> Result<()> {
+        // Write-then-rename so a crash never leaves a short chunk file.
+        let path = self.chunk_path(chunk_index);
+        let tmp = path.with_extension("tmp");
+        {
+            let mut file = File::create(&tmp)?;
+            file.write_all(data)?;
+        }
+        fs::rename(&tmp, &path)?;
+        // Superseded by the complete chunk.
+        let _ = fs::remove_file(self.part_path(chunk_index));
+        Ok(())
+    }
+
+    fn write_partial(&self, chunk_index: usize, offset: usize, data: &[u8]) -> Result<()> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(self.part_path(chunk_index))?;
+        file.seek(SeekFrom::Start(offset as u64))?;
+        file.write_all(data)?;
+        Ok(())
+    }
+
+    fn complete_chunk(&self, chunk_index: usize, len: usize) -> Result<()> {
+        let part = self.part_path(chunk_index);
+        let actual = fs::metadata(&part)?.len();
+        if actual != len as u64 {
+            return Err(anyhow!(
+                "chunk {} part file has {} bytes, expected {}",
+                chunk_index,
+                actual,
+                len
+            ));
+        }
+        fs::rename(part, self.chunk_path(chunk_index))?;
+        Ok(())
+    }
+
+    fn read_into(&self, offset: u64, buf: &mut [u8]) -> Result<()> {
+        let mut pos = offset;
+        let mut filled = 0usize;
+        while filled < buf.len() {
+            let chunk_index = (pos / self.chunk_size) as usize;
+            let in_chunk = pos % self.chunk_size;
+            let take = ((self.chunk_size - in_chunk) as usize).min(buf.len() - filled);
+
+            let mut file = self.open_for_read(chunk_index)?;
+            file.seek(SeekFrom::Start(in_chunk))?;
+            file.read_exact(&mut buf[filled..filled + take])?;
+
+            filled += take;
+            pos += take as u64;
+        }
+        Ok(())
+    }
+
+    fn release_chunk(&self, chunk_index: usize, _len: usize) -> Result<()> {
+        match fs::remove_file(self.chunk_path(chunk_index)) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MemoryStore
+// ---------------------------------------------------------------------------
+
+pub struct MemoryStore {
+    chunks: RwLock<HashMap<usize, Bytes>>,
+    /// Chunks still downloading.
+    partial: RwLock<HashMap<usize, Vec<u8>>>,
+    chunk_size: u64,
+    capacity: u64,
+    held_bytes: AtomicU64,
+}
+
+impl MemoryStore {
+    pub fn new(chunk_size: u64, capacity: u64) -> Self {
+        Self {
+            chunks: RwLock::new(HashMap::new()),
+            partial: RwLock::new(HashMap::new()),
+            chunk_size,
+            capacity,
+            held_bytes: AtomicU64::new(0),
+        }
+    }
+
+    pub fn held_bytes(&self) -> u64 {
+        self.held_bytes.load(Ordering::Relaxed)
+    }
+}
+
+impl CacheStore for MemoryStore {
+    fn backend(&self) -> CacheBackend {
+        CacheBackend::Memory
+    }
+
+    fn has_persisted(&self, _chunk_index: usize, _len: usize) -> bool {
+        false
+    }
+
+    fn write_chunk(&self, chunk_index: usize, data: &[u8]) -> Result<()> {
+        let previous = self
+            .chunks
+            .write()
+            .insert(chunk_index, Bytes::copy_from_slice(data));
+        let old_len = previous.map(|p| p.len() as u64).unwrap_or(0);
+        self.held_bytes
+            .fetch_add(data.len() as u64 - old_len, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn write_partial(&self, chunk_index: usize, offset: usize, data: &[u8]) -> Result<()> {
+        let mut partial = self.partial.write();
+        let buf = partial.entry(chunk_index).or_default();
+        if buf.len() < offset + data.len() {
+            buf.resize(offset + data.len(), 0);
+        }
+        buf[offset..offset + data.len()].copy_from_slice(data);
+        Ok(())
+    }
+
+    fn complete_chunk(&self, chunk_index: usize, len: usize) -> Result<()> {
+        // Both locks are held for the move (chunks first, as in `read_into`)
+        // so readers never find the chunk missing from both maps.
+        let mut chunks = self.chunks.write();
+        let mut partial = self.partial.write();
+        let buf = partial
+            .remove(&chunk_index)
+            .ok_or_else(|| anyhow!("chunk {} has no partial data", chunk_index))?;
+        if buf.len() != len {
+            return Err(anyhow!(
+                "chunk {} has {} bytes, expected {}",
+                chunk_index,
+                buf.len(),
+                len
+            ));
+        }
+        self.held_bytes.fetch_add(len as u64, Ordering::Relaxed);
+        chunks.insert(chunk_index, Bytes::from(buf));
+        Ok(())
+    }
+
+    fn read_into(&self, offset: u64, buf: &mut [u8]) -> Result<()> {
+        // Lock order: chunks before partial, as in `complete_chunk`.
+        let chunks = self.chunks.read();
+        let partial = self.partial.read();
+        let mut pos = offset;
+        let mut filled = 0usize;
+        while filled < buf.len() {
+            let chunk_index = (pos / self.chunk_size) as usize;
+            let in_chunk = (pos % self.chunk_size) as usize;
+            let chunk: &[u8] = match chunks.get(&chunk_index) {
+                Some(chunk) => chunk,
+                None => partial
+                    .get(&chunk_index)
+                    .ok_or_else(|| anyhow!("chunk {} not in memory", chunk_index))?,
+            };
+            let take = (chunk.len() - in_chunk).min(buf.len() - filled);
+            buf[filled..filled + take].copy_from_slice(&chunk[in_chunk..in_chunk + take]);
+            filled += take;
+            pos += take as u64;
+        }
+        Ok(())
+    }
+
+    fn read_bytes(&self, offset: u64, len: usize) -> Result<Bytes> {
+        // A range inside one chunk is a view of it; spans are stitched.
+        let chunk_index = (offset / self.chunk_size) as usize;
+        let in_chunk = (offset % self.chunk_size) as usize;
+        if let Some(chunk) = self.chunks.read().get(&chunk_index) {
+            if in_chunk + len <= chunk.len() {
+                return Ok(chunk.slice(in_chunk..in_chunk + len));
+            }
+        }
+        let mut buf = vec![0u8; len];
+        self.read_into(offset, &mut buf)?;
+        Ok(Bytes::from(buf))
+    }
+
+    fn release_chunk(&self, chunk_index: usize, _len: usize) -> Result<()> {
+        if let Some(chunk) = self.chunks.write().remove(&chunk_index) {
+            self.held_bytes
+                .fetch_sub(chunk.len() as u64, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn is_persistent(&self) -> bool {
+        false
+    }
+
+    fn capacity(&self) -> Option<u64> {
+        Some(self.capacity)
+    }
+}
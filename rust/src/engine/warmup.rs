@@ -1,14 +1,34 @@
-use crate::detect::container::{detect_container, find_moov_box, ContainerFormat};
+use crate::config::{MKV_MAX_INDEX_ELEMENT_BYTES, OGG_TAIL_PROBE_BYTES};
+use crate::detect::container::{
+    detect_container, find_box, find_moov_box, locate_asf_index, locate_avi_index, locate_moov,
+    realmedia_index_offset, ContainerFormat, ID3V1_BYTES,
+};
+use crate::detect::fmp4::locate_mfra;
+use crate::detect::matroska::{element_len, segment_layout, EBML_MAX_HEADER};
 use crate::source::traits::MediaSource;
 use anyhow::Result;
 
 /// Determine which byte ranges to prefetch for fast playback start.
-/// Returns a list of (start, end) inclusive ranges.
+/// Returns a list of (start, end) inclusive ranges. Without `speculative`,
+/// only regions the detected container is known to need are included.
 pub async fn compute_warmup_ranges(
     source: &dyn MediaSource,
     content_length: u64,
     chunk_size: u64,
+    speculative: bool,
 ) -> Result<Vec<(u64, u64)>> {
+    let (_, ranges) = plan_warmup(source, content_length, chunk_size, speculative).await?;
+    Ok(ranges)
+}
+
+/// Detect the container and plan its warmup ranges, as
+/// [`compute_warmup_ranges`] does, also returning the detected format.
+pub async fn plan_warmup(
+    source: &dyn MediaSource,
+    content_length: u64,
+    chunk_size: u64,
+    speculative: bool,
+) -> Result<(ContainerFormat, Vec<(u64, u64)>)> {
     // Fetch first min(chunk_size, 32KB) for format detection
     let probe_size = chunk_size.min(32 * 1024).min(content_length);
     let header = source.fetch_range(0, probe_size.saturating_sub(1)).await?;
@@ -19,25 +39,123 @@ pub async fn compute_warmup_ranges(
 
     match format {
         ContainerFormat::Mp4 => {
-            // Scan for moov box in the header we already have
-            if let Some((moov_offset, moov_size)) = find_moov_box(&header) {
-                // moov is in the header area — prefetch from start through end of moov
-                let moov_end = moov_offset + moov_size - 1;
-                let end = moov_end.max(chunk_size - 1).min(content_length - 1);
-                ranges.push((0, end));
-            } else {
-                // moov likely at end of file — prefetch head + tail.
-                // Use 4 chunks (~8 MB) for the tail because moov atoms on
-                // large files can be several MB and may sit a few MB before EOF.
-                let tail_window = chunk_size * 4;
-                ranges.push((0, chunk_size.min(content_length) - 1));
-                if content_length > tail_window {
-                    let tail_start = content_length.saturating_sub(tail_window);
-                    ranges.push((tail_start, content_length - 1));
+            let head_end = chunk_size.min(content_length);
+            match locate_moov(source, &header, content_length).await? {
+                Some((moov_offset, moov_size)) => {
+                    let moov_end = (moov_offset + moov_size).min(content_length) - 1;
+                    if moov_offset < head_end {
+                        // moov follows ftyp — prefetch from start through end of moov
+                        ranges.push((0, moov_end.max(head_end - 1)));
+                    } else {
+                        // moov after the media data — head chunk plus exactly moov
+                        ranges.push((0, head_end - 1));
+                        ranges.push((moov_offset, moov_end));
+                    }
+                }
+                None => {
+                    // The atom walk broke down — prefetch head + tail.
+                    // Use 4 chunks (~8 MB) for the tail because moov atoms on
+                    // large files can be several MB and may sit a few MB before EOF.
+                    let tail_window = chunk_size * 4;
+                    ranges.push((0, head_end - 1));
+                    if content_length > tail_window {
+                        let tail_start = content_length.saturating_sub(tail_window);
+                        ranges.push((tail_start, content_length - 1));
+                    }
+                }
+            }
+        }
+        ContainerFormat::FragmentedMp4 => {
+            // The init segment and any sidx sit at the head; without a
+            // sidx, the mfra at the end is the fragment index.
+            let head_end = chunk_size.min(content_length);
+            ranges.push((0, head_end - 1));
+            let sidx = find_box(&header, b"sidx");
+            for (at, size) in [find_moov_box(&header), sidx].into_iter().flatten() {
+                let end = (at + size).min(content_length);
+                if end > head_end {
+                    ranges.push((at.max(head_end), end - 1));
+                }
+            }
+            if sidx.is_none() {
+                if let Some((at, _)) = locate_mfra(source, content_length).await? {
+                    ranges.push((at.max(head_end), content_length - 1));
+                }
+            }
+        }
+        ContainerFormat::Matroska => {
+            // Head chunk, plus the Info, Tracks and Cues elements the
+            // SeekHead places elsewhere (Cues usually at the end).
+            let head_end = chunk_size.min(content_length);
+            ranges.push((0, head_end - 1));
+            if let Some(layout) = segment_layout(&header) {
+                for at in [layout.info, layout.tracks, layout.cues]
+                    .into_iter()
+                    .flatten()
+                {
+                    let Some(len) = mkv_element_len(source, &header, at, content_length).await?
+                    else {
+                        continue;
+                    };
+                    let end = (at + len).min(content_length);
+                    if end > head_end {
+                        ranges.push((at.max(head_end), end - 1));
+                    }
+                }
+            }
+        }
+        ContainerFormat::Avi => {
+            // Headers at the head; the idx1 index after the movi list at the end.
+            let head_end = chunk_size.min(content_length);
+            ranges.push((0, head_end - 1));
+            if let Some((at, size)) = locate_avi_index(source, &header, content_length).await? {
+                let end = (at + size).min(content_length);
+                if end > head_end {
+                    ranges.push((at.max(head_end), end - 1));
                 }
             }
         }
-        ContainerFormat::Matroska | ContainerFormat::TransportStream => {
+        ContainerFormat::Asf => {
+            // Header object at the head; simple index objects after the data object.
+            let head_end = chunk_size.min(content_length);
+            ranges.push((0, head_end - 1));
+            if let Some(at) = locate_asf_index(source, &header, content_length).await? {
+                ranges.push((at.max(head_end), content_length - 1));
+            }
+        }
+        ContainerFormat::RealMedia => {
+            // PROP/MDPR headers at the head; the INDX chunks run to EOF.
+            let head_end = chunk_size.min(content_length);
+            ranges.push((0, head_end - 1));
+            if let Some(at) = realmedia_index_offset(&header).filter(|&at| at < content_length) {
+                ranges.push((at.max(head_end), content_length - 1));
+            }
+        }
+        ContainerFormat::Ogg => {
+            // Players read the last page for the duration.
+            let head_end = chunk_size.min(content_length);
+            ranges.push((0, head_end - 1));
+            let tail_start = content_length.saturating_sub(OGG_TAIL_PROBE_BYTES);
+            if tail_start >= head_end {
+                ranges.push((tail_start, content_length - 1));
+            }
+        }
+        ContainerFormat::Mp3 => {
+            // Players check the last 128 bytes for an ID3v1 tag.
+            let head_end = chunk_size.min(content_length);
+            ranges.push((0, head_end - 1));
+            let tail_start = content_length.saturating_sub(ID3V1_BYTES);
+            if tail_start >= head_end {
+                ranges.push((tail_start, content_length - 1));
+            }
+        }
+        ContainerFormat::TransportStream
+        | ContainerFormat::M2ts
+        | ContainerFormat::MpegPs
+        | ContainerFormat::Flv
+        | ContainerFormat::Aac
+        | ContainerFormat::Flac
+        | ContainerFormat::Wav => {
             // Sequential formats — just the head chunk
             ranges.push((0, chunk_size.min(content_length) - 1));
         }
@@ -45,12 +163,31 @@ pub async fn compute_warmup_ranges(
             // Unknown format — head + tail as a safe default
             let tail_window = chunk_size * 4;
             ranges.push((0, chunk_size.min(content_length) - 1));
-            if content_length > tail_window {
+            if speculative && content_length > tail_window {
                 let tail_start = content_length.saturating_sub(tail_window);
                 ranges.push((tail_start, content_length - 1));
             }
         }
     }
 
-    Ok(ranges)
+    Ok((format, ranges))
+}
+
+/// Length of the Matroska element at `at`, reading its header from `head`
+/// when it lies inside. `None` for unreadable or oversized elements.
+async fn mkv_element_len(
+    source: &dyn MediaSource,
+    head: &[u8],
+    at: u64,
+    content_length: u64,
+) -> Result<Option<u64>> {
+    if at >= content_length {
+        return Ok(None);
+    }
+    let header_end = (at + EBML_MAX_HEADER).min(content_length);
+    let len = match head.get(at as usize..header_end as usize) {
+        Some(header) => element_len(header),
+        None => element_len(&source.fetch_range(at, header_end - 1).await?),
+    };
+    Ok(len.filter(|&len| len <= MKV_MAX_INDEX_ELEMENT_BYTES))
 }
@@ -0,0 +1,163 @@
+// Error model — typed failures shared by sources, the downloader, the HTTP handler and the API.
+//
+// Errors still travel as `anyhow::Error`; a `ProxyError` inside the chain
+// says what kind of failure it was, so retry policy, HTTP status codes and
+// the Dart UI can react without matching on message text.
+
+use std::fmt;
+use std::time::Duration;
+
+/// What went wrong, coarse enough for a UI to act on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProxyErrorKind {
+    /// Upstream rejected the credentials (HTTP 401/403/412); refresh them.
+    AuthExpired,
+    /// Upstream says the file does not exist (HTTP 404/410).
+    NotFound,
+    /// Upstream is throttling us (HTTP 429).
+    RateLimited,
+    /// Upstream server error or another unexpected status.
+    Upstream5xx,
+    /// Connection failed, timed out or was cut off.
+    Network,
+    /// Reading or writing the local cache failed.
+    CacheIo,
+    /// The file changed upstream (validators, length or range no longer match).
+    SourceChanged,
+    /// The session or download was shut down.
+    Cancelled,
+    /// A malformed request, unsupported input or an engine bug.
+    Internal,
+}
+
+impl ProxyErrorKind {
+    /// Stable snake_case code; error messages start with it.
+    pub fn code(self) -> &'static str {
+        match self {
+            ProxyErrorKind::AuthExpired => "auth_expired",
+            ProxyErrorKind::NotFound => "not_found",
+            ProxyErrorKind::RateLimited => "rate_limited",
+            ProxyErrorKind::Upstream5xx => "upstream_5xx",
+            ProxyErrorKind::Network => "network",
+            ProxyErrorKind::CacheIo => "cache_io",
+            ProxyErrorKind::SourceChanged => "source_changed",
+            ProxyErrorKind::Cancelled => "cancelled",
+            ProxyErrorKind::Internal => "internal",
+        }
+    }
+
+    /// Whether repeating the same request later can succeed.
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            ProxyErrorKind::RateLimited | ProxyErrorKind::Upstream5xx | ProxyErrorKind::Network
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyError {
+    pub kind: ProxyErrorKind,
+    pub detail: String,
+    /// How long upstream asked us to wait (`Retry-After`), if it said.
+    pub retry_after: Option<Duration>,
+}
+
+impl ProxyError {
+    pub fn new(kind: ProxyErrorKind, detail: impl Into<String>) -> Self {
+        Self {
+            kind,
+            detail: detail.into(),
+            retry_after: None,
+        }
+    }
+
+    pub fn with_retry_after(mut self, retry_after: Option<Duration>) -> Self {
+        self.retry_after = retry_after;
+        self
+    }
+
+    pub fn cancelled() -> Self {
+        Self::new(ProxyErrorKind::Cancelled, "download cancelled")
+    }
+
+    /// Classify a non-success HTTP status from upstream.
+    pub fn from_status(status: u16) -> Self {
+        let kind = match status {
+            401 | 403 | 412 => ProxyErrorKind::AuthExpired,
+            404 | 410 => ProxyErrorKind::NotFound,
+            416 => ProxyErrorKind::SourceChanged,
+            429 => ProxyErrorKind::RateLimited,
+            _ => ProxyErrorKind::Upstream5xx,
+        };
+        Self::new(kind, format!("HTTP {}", status))
+    }
+
+    /// Find the typed error in an `anyhow` chain, or infer one from the
+    /// underlying I/O or HTTP client error. Bare I/O errors, a missing
+    /// chunk file included, come from the cache; sources type their own.
+    /// Anything untyped is `Internal`.
+    pub fn classify(err: &anyhow::Error) -> Self {
+        for cause in err.chain() {
+            if let Some(e) = cause.downcast_ref::<ProxyError>() {
+                return e.clone();
+            }
+            if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+                return match e.status() {
+                    Some(status) => Self::from_status(status.as_u16()),
+                    None => Self::new(ProxyErrorKind::Network, e.to_string()),
+                };
+            }
+            if let Some(e) = cause.downcast_ref::<std::io::Error>() {
+                return Self::new(ProxyErrorKind::CacheIo, e.to_string());
+            }
+        }
+        Self::new(ProxyErrorKind::Internal, err.to_string())
+    }
+}
+
+impl fmt::Display for ProxyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind.code(), self.detail)
+    }
+}
+
+impl std::error::Error for ProxyError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_status_mapping_and_classify_through_context() {
+        assert_eq!(
+            ProxyError::from_status(403).kind,
+            ProxyErrorKind::AuthExpired
+        );
+        assert_eq!(ProxyError::from_status(410).kind, ProxyErrorKind::NotFound);
+        assert_eq!(
+            ProxyError::from_status(429).kind,
+            ProxyErrorKind::RateLimited
+        );
+        assert_eq!(
+            ProxyError::from_status(503).kind,
+            ProxyErrorKind::Upstream5xx
+        );
+
+        let err = Err::<(), _>(ProxyError::from_status(401))
+            .context("chunk 3")
+            .unwrap_err();
+        assert_eq!(ProxyError::classify(&err).kind, ProxyErrorKind::AuthExpired);
+        assert!(err.root_cause().to_string().starts_with("auth_expired"));
+
+        let io = anyhow::Error::from(std::io::Error::other("disk full"));
+        assert_eq!(ProxyError::classify(&io).kind, ProxyErrorKind::CacheIo);
+        let missing = anyhow::Error::from(std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert_eq!(ProxyError::classify(&missing).kind, ProxyErrorKind::CacheIo);
+        let plain = anyhow::anyhow!("unexpected state");
+        let error = ProxyError::classify(&plain);
+        assert_eq!(error.kind, ProxyErrorKind::Internal);
+        assert!(!error.kind.is_retryable());
+    }
+}
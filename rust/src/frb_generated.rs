@@ -45,6 +45,38 @@ flutter_rust_bridge::frb_generated_default_handler!();
 
 // Section: wire_funcs
 
+fn wire__crate__api__proxy_api__clear_cache_impl(
+    ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
+    rust_vec_len_: i32,
+    data_len_: i32,
+) -> flutter_rust_bridge::for_generated::WireSyncRust2DartSse {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap_sync::<flutter_rust_bridge::for_generated::SseCodec, _>(
+        flutter_rust_bridge::for_generated::TaskInfo {
+            debug_name: "clear_cache",
+            port: None,
+            mode: flutter_rust_bridge::for_generated::FfiCallMode::Sync,
+        },
+        move || {
+            let message = unsafe {
+                flutter_rust_bridge::for_generated::Dart2RustMessageSse::from_wire(
+                    ptr_,
+                    rust_vec_len_,
+                    data_len_,
+                )
+            };
+            let mut deserializer =
+                flutter_rust_bridge::for_generated::SseDeserializer::new(message);
+            let api_include_pinned = <bool>::sse_decode(&mut deserializer);
+            deserializer.end();
+            transform_result_sse::<_, flutter_rust_bridge::for_generated::anyhow::Error>(
+                (move || {
+                    let output_ok = crate::api::proxy_api::clear_cache(api_include_pinned)?;
+                    Ok(output_ok)
+                })(),
+            )
+        },
+    )
+}
 fn wire__crate__api__proxy_api__close_session_impl(
     ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
     rust_vec_len_: i32,
@@ -113,6 +145,80 @@ fn wire__crate__api__proxy_api__create_session_impl(
         },
     )
 }
+fn wire__crate__api__proxy_api__create_session_with_source_impl(
+    ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
+    rust_vec_len_: i32,
+    data_len_: i32,
+) -> flutter_rust_bridge::for_generated::WireSyncRust2DartSse {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap_sync::<flutter_rust_bridge::for_generated::SseCodec, _>(
+        flutter_rust_bridge::for_generated::TaskInfo {
+            debug_name: "create_session_with_source",
+            port: None,
+            mode: flutter_rust_bridge::for_generated::FfiCallMode::Sync,
+        },
+        move || {
+            let message = unsafe {
+                flutter_rust_bridge::for_generated::Dart2RustMessageSse::from_wire(
+                    ptr_,
+                    rust_vec_len_,
+                    data_len_,
+                )
+            };
+            let mut deserializer =
+                flutter_rust_bridge::for_generated::SseDeserializer::new(message);
+            let api_source =
+                <crate::source::registry::SourceDescriptor>::sse_decode(&mut deserializer);
+            let api_file_key = <String>::sse_decode(&mut deserializer);
+            let api_title = <Option<String>>::sse_decode(&mut deserializer);
+            let api_cache_backend =
+                <Option<crate::config::CacheBackend>>::sse_decode(&mut deserializer);
+            deserializer.end();
+            transform_result_sse::<_, flutter_rust_bridge::for_generated::anyhow::Error>(
+                (move || {
+                    let output_ok = crate::api::proxy_api::create_session_with_source(
+                        api_source,
+                        api_file_key,
+                        api_title,
+                        api_cache_backend,
+                    )?;
+                    Ok(output_ok)
+                })(),
+            )
+        },
+    )
+}
+fn wire__crate__api__proxy_api__delete_cache_entry_impl(
+    ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
+    rust_vec_len_: i32,
+    data_len_: i32,
+) -> flutter_rust_bridge::for_generated::WireSyncRust2DartSse {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap_sync::<flutter_rust_bridge::for_generated::SseCodec, _>(
+        flutter_rust_bridge::for_generated::TaskInfo {
+            debug_name: "delete_cache_entry",
+            port: None,
+            mode: flutter_rust_bridge::for_generated::FfiCallMode::Sync,
+        },
+        move || {
+            let message = unsafe {
+                flutter_rust_bridge::for_generated::Dart2RustMessageSse::from_wire(
+                    ptr_,
+                    rust_vec_len_,
+                    data_len_,
+                )
+            };
+            let mut deserializer =
+                flutter_rust_bridge::for_generated::SseDeserializer::new(message);
+            let api_cache_key = <String>::sse_decode(&mut deserializer);
+            deserializer.end();
+            transform_result_sse::<_, flutter_rust_bridge::for_generated::anyhow::Error>(
+                (move || {
+                    let output_ok = crate::api::proxy_api::delete_cache_entry(api_cache_key)?;
+                    Ok(output_ok)
+                })(),
+            )
+        },
+    )
+}
 fn wire__crate__api__proxy_api__dispose_impl(
     ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
     rust_vec_len_: i32,
@@ -144,6 +250,100 @@ fn wire__crate__api__proxy_api__dispose_impl(
         },
     )
 }
+fn wire__crate__api__proxy_api__get_cache_usage_impl(
+    ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
+    rust_vec_len_: i32,
+    data_len_: i32,
+) -> flutter_rust_bridge::for_generated::WireSyncRust2DartSse {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap_sync::<flutter_rust_bridge::for_generated::SseCodec, _>(
+        flutter_rust_bridge::for_generated::TaskInfo {
+            debug_name: "get_cache_usage",
+            port: None,
+            mode: flutter_rust_bridge::for_generated::FfiCallMode::Sync,
+        },
+        move || {
+            let message = unsafe {
+                flutter_rust_bridge::for_generated::Dart2RustMessageSse::from_wire(
+                    ptr_,
+                    rust_vec_len_,
+                    data_len_,
+                )
+            };
+            let mut deserializer =
+                flutter_rust_bridge::for_generated::SseDeserializer::new(message);
+            deserializer.end();
+            transform_result_sse::<_, flutter_rust_bridge::for_generated::anyhow::Error>(
+                (move || {
+                    let output_ok = crate::api::proxy_api::get_cache_usage()?;
+                    Ok(output_ok)
+                })(),
+            )
+        },
+    )
+}
+fn wire__crate__api__proxy_api__get_policy_usage_impl(
+    ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
+    rust_vec_len_: i32,
+    data_len_: i32,
+) -> flutter_rust_bridge::for_generated::WireSyncRust2DartSse {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap_sync::<flutter_rust_bridge::for_generated::SseCodec, _>(
+        flutter_rust_bridge::for_generated::TaskInfo {
+            debug_name: "get_policy_usage",
+            port: None,
+            mode: flutter_rust_bridge::for_generated::FfiCallMode::Sync,
+        },
+        move || {
+            let message = unsafe {
+                flutter_rust_bridge::for_generated::Dart2RustMessageSse::from_wire(
+                    ptr_,
+                    rust_vec_len_,
+                    data_len_,
+                )
+            };
+            let mut deserializer =
+                flutter_rust_bridge::for_generated::SseDeserializer::new(message);
+            deserializer.end();
+            transform_result_sse::<_, flutter_rust_bridge::for_generated::anyhow::Error>(
+                (move || {
+                    let output_ok = crate::api::proxy_api::get_policy_usage()?;
+                    Ok(output_ok)
+                })(),
+            )
+        },
+    )
+}
+fn wire__crate__api__proxy_api__get_session_error_impl(
+    ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
+    rust_vec_len_: i32,
+    data_len_: i32,
+) -> flutter_rust_bridge::for_generated::WireSyncRust2DartSse {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap_sync::<flutter_rust_bridge::for_generated::SseCodec, _>(
+        flutter_rust_bridge::for_generated::TaskInfo {
+            debug_name: "get_session_error",
+            port: None,
+            mode: flutter_rust_bridge::for_generated::FfiCallMode::Sync,
+        },
+        move || {
+            let message = unsafe {
+                flutter_rust_bridge::for_generated::Dart2RustMessageSse::from_wire(
+                    ptr_,
+                    rust_vec_len_,
+                    data_len_,
+                )
+            };
+            let mut deserializer =
+                flutter_rust_bridge::for_generated::SseDeserializer::new(message);
+            let api_session_id = <String>::sse_decode(&mut deserializer);
+            deserializer.end();
+            transform_result_sse::<_, flutter_rust_bridge::for_generated::anyhow::Error>(
+                (move || {
+                    let output_ok = crate::api::proxy_api::get_session_error(api_session_id)?;
+                    Ok(output_ok)
+                })(),
+            )
+        },
+    )
+}
 fn wire__crate__api__proxy_api__get_stats_impl(
     ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
     rust_vec_len_: i32,
@@ -272,14 +472,14 @@ fn wire__crate__api__proxy_api__init_engine_impl(
         },
     )
 }
-fn wire__crate__api__proxy_api__update_session_auth_impl(
+fn wire__crate__api__proxy_api__list_cache_entries_impl(
     ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
     rust_vec_len_: i32,
     data_len_: i32,
 ) -> flutter_rust_bridge::for_generated::WireSyncRust2DartSse {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap_sync::<flutter_rust_bridge::for_generated::SseCodec, _>(
         flutter_rust_bridge::for_generated::TaskInfo {
-            debug_name: "update_session_auth",
+            debug_name: "list_cache_entries",
             port: None,
             mode: flutter_rust_bridge::for_generated::FfiCallMode::Sync,
         },
@@ -293,43 +493,353 @@ fn wire__crate__api__proxy_api__update_session_auth_impl(
             };
             let mut deserializer =
                 flutter_rust_bridge::for_generated::SseDeserializer::new(message);
-            let api_session_id = <String>::sse_decode(&mut deserializer);
-            let api_new_url = <String>::sse_decode(&mut deserializer);
-            let api_new_headers =
-                <std::collections::HashMap<String, String>>::sse_decode(&mut deserializer);
             deserializer.end();
             transform_result_sse::<_, flutter_rust_bridge::for_generated::anyhow::Error>(
                 (move || {
-                    let output_ok = crate::api::proxy_api::update_session_auth(
-                        api_session_id,
-                        api_new_url,
-                        api_new_headers,
-                    )?;
+                    let output_ok = crate::api::proxy_api::list_cache_entries()?;
                     Ok(output_ok)
                 })(),
             )
         },
     )
 }
-
-// Section: dart2rust
-
-impl SseDecode for flutter_rust_bridge::for_generated::anyhow::Error {
-    // Codec=Sse (Serialization based), see doc to use other codecs
-    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
-        let mut inner = <String>::sse_decode(deserializer);
-        return flutter_rust_bridge::for_generated::anyhow::anyhow!("{}", inner);
-    }
+fn wire__crate__api__proxy_api__pin_cache_entry_impl(
+    ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
+    rust_vec_len_: i32,
+    data_len_: i32,
+) -> flutter_rust_bridge::for_generated::WireSyncRust2DartSse {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap_sync::<flutter_rust_bridge::for_generated::SseCodec, _>(
+        flutter_rust_bridge::for_generated::TaskInfo {
+            debug_name: "pin_cache_entry",
+            port: None,
+            mode: flutter_rust_bridge::for_generated::FfiCallMode::Sync,
+        },
+        move || {
+            let message = unsafe {
+                flutter_rust_bridge::for_generated::Dart2RustMessageSse::from_wire(
+                    ptr_,
+                    rust_vec_len_,
+                    data_len_,
+                )
+            };
+            let mut deserializer =
+                flutter_rust_bridge::for_generated::SseDeserializer::new(message);
+            let api_cache_key = <String>::sse_decode(&mut deserializer);
+            let api_pinned = <bool>::sse_decode(&mut deserializer);
+            deserializer.end();
+            transform_result_sse::<_, flutter_rust_bridge::for_generated::anyhow::Error>(
+                (move || {
+                    let output_ok =
+                        crate::api::proxy_api::pin_cache_entry(api_cache_key, api_pinned)?;
+                    Ok(output_ok)
+                })(),
+            )
+        },
+    )
 }
-
-impl SseDecode for std::collections::HashMap<String, String> {
-    // Codec=Sse (Serialization based), see doc to use other codecs
-    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
-        let mut inner = <Vec<(String, String)>>::sse_decode(deserializer);
-        return inner.into_iter().collect();
-    }
+fn wire__crate__api__proxy_api__prepare_seek_impl(
+    ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
+    rust_vec_len_: i32,
+    data_len_: i32,
+) -> flutter_rust_bridge::for_generated::WireSyncRust2DartSse {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap_sync::<flutter_rust_bridge::for_generated::SseCodec, _>(
+        flutter_rust_bridge::for_generated::TaskInfo {
+            debug_name: "prepare_seek",
+            port: None,
+            mode: flutter_rust_bridge::for_generated::FfiCallMode::Sync,
+        },
+        move || {
+            let message = unsafe {
+                flutter_rust_bridge::for_generated::Dart2RustMessageSse::from_wire(
+                    ptr_,
+                    rust_vec_len_,
+                    data_len_,
+                )
+            };
+            let mut deserializer =
+                flutter_rust_bridge::for_generated::SseDeserializer::new(message);
+            let api_session_id = <String>::sse_decode(&mut deserializer);
+            let api_position_ms = <u64>::sse_decode(&mut deserializer);
+            deserializer.end();
+            transform_result_sse::<_, flutter_rust_bridge::for_generated::anyhow::Error>(
+                (move || {
+                    let output_ok =
+                        crate::api::proxy_api::prepare_seek(api_session_id, api_position_ms)?;
+                    Ok(output_ok)
+                })(),
+            )
+        },
+    )
 }
-
+fn wire__crate__api__proxy_api__probe_media_info_impl(
+    port_: flutter_rust_bridge::for_generated::MessagePort,
+    ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
+    rust_vec_len_: i32,
+    data_len_: i32,
+) {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap_normal::<flutter_rust_bridge::for_generated::SseCodec, _, _>(
+        flutter_rust_bridge::for_generated::TaskInfo {
+            debug_name: "probe_media_info",
+            port: Some(port_),
+            mode: flutter_rust_bridge::for_generated::FfiCallMode::Normal,
+        },
+        move || {
+            let message = unsafe {
+                flutter_rust_bridge::for_generated::Dart2RustMessageSse::from_wire(
+                    ptr_,
+                    rust_vec_len_,
+                    data_len_,
+                )
+            };
+            let mut deserializer =
+                flutter_rust_bridge::for_generated::SseDeserializer::new(message);
+            let api_session_id = <String>::sse_decode(&mut deserializer);
+            deserializer.end();
+            move |context| {
+                transform_result_sse::<_, flutter_rust_bridge::for_generated::anyhow::Error>(
+                    (move || {
+                        let output_ok = crate::api::proxy_api::probe_media_info(api_session_id)?;
+                        Ok(output_ok)
+                    })(),
+                )
+            }
+        },
+    )
+}
+fn wire__crate__api__proxy_api__report_playback_position_impl(
+    ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
+    rust_vec_len_: i32,
+    data_len_: i32,
+) -> flutter_rust_bridge::for_generated::WireSyncRust2DartSse {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap_sync::<flutter_rust_bridge::for_generated::SseCodec, _>(
+        flutter_rust_bridge::for_generated::TaskInfo {
+            debug_name: "report_playback_position",
+            port: None,
+            mode: flutter_rust_bridge::for_generated::FfiCallMode::Sync,
+        },
+        move || {
+            let message = unsafe {
+                flutter_rust_bridge::for_generated::Dart2RustMessageSse::from_wire(
+                    ptr_,
+                    rust_vec_len_,
+                    data_len_,
+                )
+            };
+            let mut deserializer =
+                flutter_rust_bridge::for_generated::SseDeserializer::new(message);
+            let api_session_id = <String>::sse_decode(&mut deserializer);
+            let api_position_ms = <u64>::sse_decode(&mut deserializer);
+            let api_buffer_end_ms = <u64>::sse_decode(&mut deserializer);
+            let api_rate = <f64>::sse_decode(&mut deserializer);
+            deserializer.end();
+            transform_result_sse::<_, flutter_rust_bridge::for_generated::anyhow::Error>(
+                (move || {
+                    let output_ok = crate::api::proxy_api::report_playback_position(
+                        api_session_id,
+                        api_position_ms,
+                        api_buffer_end_ms,
+                        api_rate,
+                    )?;
+                    Ok(output_ok)
+                })(),
+            )
+        },
+    )
+}
+fn wire__crate__api__proxy_api__set_bandwidth_limit_impl(
+    ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
+    rust_vec_len_: i32,
+    data_len_: i32,
+) -> flutter_rust_bridge::for_generated::WireSyncRust2DartSse {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap_sync::<flutter_rust_bridge::for_generated::SseCodec, _>(
+        flutter_rust_bridge::for_generated::TaskInfo {
+            debug_name: "set_bandwidth_limit",
+            port: None,
+            mode: flutter_rust_bridge::for_generated::FfiCallMode::Sync,
+        },
+        move || {
+            let message = unsafe {
+                flutter_rust_bridge::for_generated::Dart2RustMessageSse::from_wire(
+                    ptr_,
+                    rust_vec_len_,
+                    data_len_,
+                )
+            };
+            let mut deserializer =
+                flutter_rust_bridge::for_generated::SseDeserializer::new(message);
+            let api_session_id = <Option<String>>::sse_decode(&mut deserializer);
+            let api_bytes_per_sec = <u64>::sse_decode(&mut deserializer);
+            deserializer.end();
+            transform_result_sse::<_, flutter_rust_bridge::for_generated::anyhow::Error>(
+                (move || {
+                    let output_ok = crate::api::proxy_api::set_bandwidth_limit(
+                        api_session_id,
+                        api_bytes_per_sec,
+                    )?;
+                    Ok(output_ok)
+                })(),
+            )
+        },
+    )
+}
+fn wire__crate__api__proxy_api__set_host_request_budget_impl(
+    ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
+    rust_vec_len_: i32,
+    data_len_: i32,
+) -> flutter_rust_bridge::for_generated::WireSyncRust2DartSse {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap_sync::<flutter_rust_bridge::for_generated::SseCodec, _>(
+        flutter_rust_bridge::for_generated::TaskInfo {
+            debug_name: "set_host_request_budget",
+            port: None,
+            mode: flutter_rust_bridge::for_generated::FfiCallMode::Sync,
+        },
+        move || {
+            let message = unsafe {
+                flutter_rust_bridge::for_generated::Dart2RustMessageSse::from_wire(
+                    ptr_,
+                    rust_vec_len_,
+                    data_len_,
+                )
+            };
+            let mut deserializer =
+                flutter_rust_bridge::for_generated::SseDeserializer::new(message);
+            let api_host = <String>::sse_decode(&mut deserializer);
+            let api_requests_per_minute = <u32>::sse_decode(&mut deserializer);
+            deserializer.end();
+            transform_result_sse::<_, flutter_rust_bridge::for_generated::anyhow::Error>(
+                (move || {
+                    let output_ok = crate::api::proxy_api::set_host_request_budget(
+                        api_host,
+                        api_requests_per_minute,
+                    )?;
+                    Ok(output_ok)
+                })(),
+            )
+        },
+    )
+}
+fn wire__crate__api__proxy_api__set_magnet_gateway_impl(
+    ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
+    rust_vec_len_: i32,
+    data_len_: i32,
+) -> flutter_rust_bridge::for_generated::WireSyncRust2DartSse {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap_sync::<flutter_rust_bridge::for_generated::SseCodec, _>(
+        flutter_rust_bridge::for_generated::TaskInfo {
+            debug_name: "set_magnet_gateway",
+            port: None,
+            mode: flutter_rust_bridge::for_generated::FfiCallMode::Sync,
+        },
+        move || {
+            let message = unsafe {
+                flutter_rust_bridge::for_generated::Dart2RustMessageSse::from_wire(
+                    ptr_,
+                    rust_vec_len_,
+                    data_len_,
+                )
+            };
+            let mut deserializer =
+                flutter_rust_bridge::for_generated::SseDeserializer::new(message);
+            let api_gateway = <String>::sse_decode(&mut deserializer);
+            deserializer.end();
+            transform_result_sse::<_, flutter_rust_bridge::for_generated::anyhow::Error>(
+                (move || {
+                    let output_ok = crate::api::proxy_api::set_magnet_gateway(api_gateway)?;
+                    Ok(output_ok)
+                })(),
+            )
+        },
+    )
+}
+fn wire__crate__api__proxy_api__set_network_policy_impl(
+    ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
+    rust_vec_len_: i32,
+    data_len_: i32,
+) -> flutter_rust_bridge::for_generated::WireSyncRust2DartSse {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap_sync::<flutter_rust_bridge::for_generated::SseCodec, _>(
+        flutter_rust_bridge::for_generated::TaskInfo {
+            debug_name: "set_network_policy",
+            port: None,
+            mode: flutter_rust_bridge::for_generated::FfiCallMode::Sync,
+        },
+        move || {
+            let message = unsafe {
+                flutter_rust_bridge::for_generated::Dart2RustMessageSse::from_wire(
+                    ptr_,
+                    rust_vec_len_,
+                    data_len_,
+                )
+            };
+            let mut deserializer =
+                flutter_rust_bridge::for_generated::SseDeserializer::new(message);
+            let api_policy = <crate::engine::policy::NetworkPolicy>::sse_decode(&mut deserializer);
+            deserializer.end();
+            transform_result_sse::<_, flutter_rust_bridge::for_generated::anyhow::Error>(
+                (move || {
+                    let output_ok = crate::api::proxy_api::set_network_policy(api_policy)?;
+                    Ok(output_ok)
+                })(),
+            )
+        },
+    )
+}
+fn wire__crate__api__proxy_api__update_session_auth_impl(
+    ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
+    rust_vec_len_: i32,
+    data_len_: i32,
+) -> flutter_rust_bridge::for_generated::WireSyncRust2DartSse {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap_sync::<flutter_rust_bridge::for_generated::SseCodec, _>(
+        flutter_rust_bridge::for_generated::TaskInfo {
+            debug_name: "update_session_auth",
+            port: None,
+            mode: flutter_rust_bridge::for_generated::FfiCallMode::Sync,
+        },
+        move || {
+            let message = unsafe {
+                flutter_rust_bridge::for_generated::Dart2RustMessageSse::from_wire(
+                    ptr_,
+                    rust_vec_len_,
+                    data_len_,
+                )
+            };
+            let mut deserializer =
+                flutter_rust_bridge::for_generated::SseDeserializer::new(message);
+            let api_session_id = <String>::sse_decode(&mut deserializer);
+            let api_new_url = <String>::sse_decode(&mut deserializer);
+            let api_new_headers =
+                <std::collections::HashMap<String, String>>::sse_decode(&mut deserializer);
+            deserializer.end();
+            transform_result_sse::<_, flutter_rust_bridge::for_generated::anyhow::Error>(
+                (move || {
+                    let output_ok = crate::api::proxy_api::update_session_auth(
+                        api_session_id,
+                        api_new_url,
+                        api_new_headers,
+                    )?;
+                    Ok(output_ok)
+                })(),
+            )
+        },
+    )
+}
+
+// Section: dart2rust
+
+impl SseDecode for flutter_rust_bridge::for_generated::anyhow::Error {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut inner = <String>::sse_decode(deserializer);
+        return flutter_rust_bridge::for_generated::anyhow::anyhow!("{}", inner);
+    }
+}
+
+impl SseDecode for std::collections::HashMap<String, String> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut inner = <Vec<(String, String)>>::sse_decode(deserializer);
+        return inner.into_iter().collect();
+    }
+}
+
 impl SseDecode for String {
     // Codec=Sse (Serialization based), see doc to use other codecs
     fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
@@ -338,16 +848,95 @@ impl SseDecode for String {
     }
 }
 
+impl SseDecode for crate::api::proxy_api::AudioTrackInfo {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut var_codec = <String>::sse_decode(deserializer);
+        let mut var_channels = <u32>::sse_decode(deserializer);
+        let mut var_sampleRate = <u32>::sse_decode(deserializer);
+        let mut var_language = <String>::sse_decode(deserializer);
+        let mut var_title = <String>::sse_decode(deserializer);
+        return crate::api::proxy_api::AudioTrackInfo {
+            codec: var_codec,
+            channels: var_channels,
+            sample_rate: var_sampleRate,
+            language: var_language,
+            title: var_title,
+        };
+    }
+}
+
+impl SseDecode for crate::config::CacheBackend {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut inner = <i32>::sse_decode(deserializer);
+        return match inner {
+            0 => crate::config::CacheBackend::Mmap,
+            1 => crate::config::CacheBackend::ChunkFiles,
+            2 => crate::config::CacheBackend::Memory,
+            _ => unreachable!("Invalid variant for CacheBackend: {}", inner),
+        };
+    }
+}
+
+impl SseDecode for crate::api::proxy_api::CacheEntryInfo {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut var_cacheKey = <String>::sse_decode(deserializer);
+        let mut var_fileKey = <String>::sse_decode(deserializer);
+        let mut var_title = <String>::sse_decode(deserializer);
+        let mut var_sizeOnDisk = <u64>::sse_decode(deserializer);
+        let mut var_contentLength = <u64>::sse_decode(deserializer);
+        let mut var_percentComplete = <f64>::sse_decode(deserializer);
+        let mut var_lastAccessAt = <u64>::sse_decode(deserializer);
+        let mut var_pinned = <bool>::sse_decode(deserializer);
+        let mut var_active = <bool>::sse_decode(deserializer);
+        return crate::api::proxy_api::CacheEntryInfo {
+            cache_key: var_cacheKey,
+            file_key: var_fileKey,
+            title: var_title,
+            size_on_disk: var_sizeOnDisk,
+            content_length: var_contentLength,
+            percent_complete: var_percentComplete,
+            last_access_at: var_lastAccessAt,
+            pinned: var_pinned,
+            active: var_active,
+        };
+    }
+}
+
+impl SseDecode for crate::api::proxy_api::CacheUsage {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut var_totalBytes = <u64>::sse_decode(deserializer);
+        let mut var_entryCount = <u32>::sse_decode(deserializer);
+        let mut var_maxCacheBytes = <u64>::sse_decode(deserializer);
+        let mut var_freeDiskBytes = <u64>::sse_decode(deserializer);
+        return crate::api::proxy_api::CacheUsage {
+            total_bytes: var_totalBytes,
+            entry_count: var_entryCount,
+            max_cache_bytes: var_maxCacheBytes,
+            free_disk_bytes: var_freeDiskBytes,
+        };
+    }
+}
+
 impl SseDecode for crate::config::EngineConfig {
     // Codec=Sse (Serialization based), see doc to use other codecs
     fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
         let mut var_chunkSize = <u64>::sse_decode(deserializer);
         let mut var_maxConcurrency = <u32>::sse_decode(deserializer);
         let mut var_cacheDir = <String>::sse_decode(deserializer);
+        let mut var_maxCacheBytes = <u64>::sse_decode(deserializer);
+        let mut var_maxSessionCacheBytes = <u64>::sse_decode(deserializer);
+        let mut var_cacheBackend = <Option<crate::config::CacheBackend>>::sse_decode(deserializer);
         return crate::config::EngineConfig {
             chunk_size: var_chunkSize,
             max_concurrency: var_maxConcurrency,
             cache_dir: var_cacheDir,
+            max_cache_bytes: var_maxCacheBytes,
+            max_session_cache_bytes: var_maxSessionCacheBytes,
+            cache_backend: var_cacheBackend,
         };
     }
 }
@@ -359,6 +948,34 @@ impl SseDecode for f64 {
     }
 }
 
+impl SseDecode for Vec<crate::api::proxy_api::AudioTrackInfo> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut len_ = <i32>::sse_decode(deserializer);
+        let mut ans_ = vec![];
+        for idx_ in 0..len_ {
+            ans_.push(<crate::api::proxy_api::AudioTrackInfo>::sse_decode(
+                deserializer,
+            ));
+        }
+        return ans_;
+    }
+}
+
+impl SseDecode for Vec<crate::api::proxy_api::CacheEntryInfo> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut len_ = <i32>::sse_decode(deserializer);
+        let mut ans_ = vec![];
+        for idx_ in 0..len_ {
+            ans_.push(<crate::api::proxy_api::CacheEntryInfo>::sse_decode(
+                deserializer,
+            ));
+        }
+        return ans_;
+    }
+}
+
 impl SseDecode for Vec<u8> {
     // Codec=Sse (Serialization based), see doc to use other codecs
     fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
@@ -383,6 +1000,69 @@ impl SseDecode for Vec<(String, String)> {
     }
 }
 
+impl SseDecode for Vec<crate::api::proxy_api::SubtitleTrackInfo> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut len_ = <i32>::sse_decode(deserializer);
+        let mut ans_ = vec![];
+        for idx_ in 0..len_ {
+            ans_.push(<crate::api::proxy_api::SubtitleTrackInfo>::sse_decode(
+                deserializer,
+            ));
+        }
+        return ans_;
+    }
+}
+
+impl SseDecode for Vec<crate::api::proxy_api::VideoTrackInfo> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut len_ = <i32>::sse_decode(deserializer);
+        let mut ans_ = vec![];
+        for idx_ in 0..len_ {
+            ans_.push(<crate::api::proxy_api::VideoTrackInfo>::sse_decode(
+                deserializer,
+            ));
+        }
+        return ans_;
+    }
+}
+
+impl SseDecode for crate::api::proxy_api::MediaInfo {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut var_container = <String>::sse_decode(deserializer);
+        let mut var_durationMs = <u64>::sse_decode(deserializer);
+        let mut var_bitrate = <u64>::sse_decode(deserializer);
+        let mut var_videoTracks =
+            <Vec<crate::api::proxy_api::VideoTrackInfo>>::sse_decode(deserializer);
+        let mut var_audioTracks =
+            <Vec<crate::api::proxy_api::AudioTrackInfo>>::sse_decode(deserializer);
+        let mut var_subtitleTracks =
+            <Vec<crate::api::proxy_api::SubtitleTrackInfo>>::sse_decode(deserializer);
+        return crate::api::proxy_api::MediaInfo {
+            container: var_container,
+            duration_ms: var_durationMs,
+            bitrate: var_bitrate,
+            video_tracks: var_videoTracks,
+            audio_tracks: var_audioTracks,
+            subtitle_tracks: var_subtitleTracks,
+        };
+    }
+}
+
+impl SseDecode for crate::engine::policy::NetworkPolicy {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut inner = <i32>::sse_decode(deserializer);
+        return match inner {
+            0 => crate::engine::policy::NetworkPolicy::Normal,
+            1 => crate::engine::policy::NetworkPolicy::DataSaver,
+            _ => unreachable!("Invalid variant for NetworkPolicy: {}", inner),
+        };
+    }
+}
+
 impl SseDecode for Option<String> {
     // Codec=Sse (Serialization based), see doc to use other codecs
     fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
@@ -394,6 +1074,74 @@ impl SseDecode for Option<String> {
     }
 }
 
+impl SseDecode for Option<crate::config::CacheBackend> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        if (<bool>::sse_decode(deserializer)) {
+            return Some(<crate::config::CacheBackend>::sse_decode(deserializer));
+        } else {
+            return None;
+        }
+    }
+}
+
+impl SseDecode for Option<crate::api::proxy_api::SessionError> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        if (<bool>::sse_decode(deserializer)) {
+            return Some(<crate::api::proxy_api::SessionError>::sse_decode(
+                deserializer,
+            ));
+        } else {
+            return None;
+        }
+    }
+}
+
+impl SseDecode for Option<u64> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        if (<bool>::sse_decode(deserializer)) {
+            return Some(<u64>::sse_decode(deserializer));
+        } else {
+            return None;
+        }
+    }
+}
+
+impl SseDecode for crate::api::proxy_api::PolicyUsage {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut var_policy = <crate::engine::policy::NetworkPolicy>::sse_decode(deserializer);
+        let mut var_startedAt = <u64>::sse_decode(deserializer);
+        let mut var_downloadedBytes = <u64>::sse_decode(deserializer);
+        return crate::api::proxy_api::PolicyUsage {
+            policy: var_policy,
+            started_at: var_startedAt,
+            downloaded_bytes: var_downloadedBytes,
+        };
+    }
+}
+
+impl SseDecode for crate::error::ProxyErrorKind {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut inner = <i32>::sse_decode(deserializer);
+        return match inner {
+            0 => crate::error::ProxyErrorKind::AuthExpired,
+            1 => crate::error::ProxyErrorKind::NotFound,
+            2 => crate::error::ProxyErrorKind::RateLimited,
+            3 => crate::error::ProxyErrorKind::Upstream5xx,
+            4 => crate::error::ProxyErrorKind::Network,
+            5 => crate::error::ProxyErrorKind::CacheIo,
+            6 => crate::error::ProxyErrorKind::SourceChanged,
+            7 => crate::error::ProxyErrorKind::Cancelled,
+            8 => crate::error::ProxyErrorKind::Internal,
+            _ => unreachable!("Invalid variant for ProxyErrorKind: {}", inner),
+        };
+    }
+}
+
 impl SseDecode for crate::api::proxy_api::ProxyStats {
     // Codec=Sse (Serialization based), see doc to use other codecs
     fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
@@ -402,12 +1150,14 @@ impl SseDecode for crate::api::proxy_api::ProxyStats {
         let mut var_bufferedBytesAhead = <u64>::sse_decode(deserializer);
         let mut var_activeWorkers = <u32>::sse_decode(deserializer);
         let mut var_cacheHitRate = <f64>::sse_decode(deserializer);
+        let mut var_playbackBitrate = <u64>::sse_decode(deserializer);
         return crate::api::proxy_api::ProxyStats {
             download_bps: var_downloadBps,
             serve_bps: var_serveBps,
             buffered_bytes_ahead: var_bufferedBytesAhead,
             active_workers: var_activeWorkers,
             cache_hit_rate: var_cacheHitRate,
+            playback_bitrate: var_playbackBitrate,
         };
     }
 }
@@ -421,6 +1171,18 @@ impl SseDecode for (String, String) {
     }
 }
 
+impl SseDecode for crate::api::proxy_api::SessionError {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut var_kind = <crate::error::ProxyErrorKind>::sse_decode(deserializer);
+        let mut var_message = <String>::sse_decode(deserializer);
+        return crate::api::proxy_api::SessionError {
+            kind: var_kind,
+            message: var_message,
+        };
+    }
+}
+
 impl SseDecode for crate::api::proxy_api::SessionInfo {
     // Codec=Sse (Serialization based), see doc to use other codecs
     fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
@@ -437,6 +1199,34 @@ impl SseDecode for crate::api::proxy_api::SessionInfo {
     }
 }
 
+impl SseDecode for crate::source::registry::SourceDescriptor {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut var_uri = <String>::sse_decode(deserializer);
+        let mut var_headers = <std::collections::HashMap<String, String>>::sse_decode(deserializer);
+        return crate::source::registry::SourceDescriptor {
+            uri: var_uri,
+            headers: var_headers,
+        };
+    }
+}
+
+impl SseDecode for crate::api::proxy_api::SubtitleTrackInfo {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut var_codec = <String>::sse_decode(deserializer);
+        let mut var_language = <String>::sse_decode(deserializer);
+        let mut var_title = <String>::sse_decode(deserializer);
+        let mut var_forced = <bool>::sse_decode(deserializer);
+        return crate::api::proxy_api::SubtitleTrackInfo {
+            codec: var_codec,
+            language: var_language,
+            title: var_title,
+            forced: var_forced,
+        };
+    }
+}
+
 impl SseDecode for u32 {
     // Codec=Sse (Serialization based), see doc to use other codecs
     fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
@@ -463,6 +1253,30 @@ impl SseDecode for () {
     fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {}
 }
 
+impl SseDecode for crate::api::proxy_api::VideoTrackInfo {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut var_codec = <String>::sse_decode(deserializer);
+        let mut var_profile = <String>::sse_decode(deserializer);
+        let mut var_width = <u32>::sse_decode(deserializer);
+        let mut var_height = <u32>::sse_decode(deserializer);
+        let mut var_frameRate = <f64>::sse_decode(deserializer);
+        let mut var_bitDepth = <u32>::sse_decode(deserializer);
+        let mut var_hdr = <bool>::sse_decode(deserializer);
+        let mut var_dolbyVision = <bool>::sse_decode(deserializer);
+        return crate::api::proxy_api::VideoTrackInfo {
+            codec: var_codec,
+            profile: var_profile,
+            width: var_width,
+            height: var_height,
+            frame_rate: var_frameRate,
+            bit_depth: var_bitDepth,
+            hdr: var_hdr,
+            dolby_vision: var_dolbyVision,
+        };
+    }
+}
+
 impl SseDecode for i32 {
     // Codec=Sse (Serialization based), see doc to use other codecs
     fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
@@ -476,42 +1290,159 @@ impl SseDecode for bool {
         deserializer.cursor.read_u8().unwrap() != 0
     }
 }
-
-fn pde_ffi_dispatcher_primary_impl(
-    func_id: i32,
-    port: flutter_rust_bridge::for_generated::MessagePort,
-    ptr: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
-    rust_vec_len: i32,
-    data_len: i32,
-) {
-    // Codec=Pde (Serialization + dispatch), see doc to use other codecs
-    match func_id {
-        6 => wire__crate__api__simple__init_app_impl(port, ptr, rust_vec_len, data_len),
-        _ => unreachable!(),
+
+fn pde_ffi_dispatcher_primary_impl(
+    func_id: i32,
+    port: flutter_rust_bridge::for_generated::MessagePort,
+    ptr: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
+    rust_vec_len: i32,
+    data_len: i32,
+) {
+    // Codec=Pde (Serialization + dispatch), see doc to use other codecs
+    match func_id {
+        12 => wire__crate__api__simple__init_app_impl(port, ptr, rust_vec_len, data_len),
+        17 => wire__crate__api__proxy_api__probe_media_info_impl(port, ptr, rust_vec_len, data_len),
+        _ => unreachable!(),
+    }
+}
+
+fn pde_ffi_dispatcher_sync_impl(
+    func_id: i32,
+    ptr: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
+    rust_vec_len: i32,
+    data_len: i32,
+) -> flutter_rust_bridge::for_generated::WireSyncRust2DartSse {
+    // Codec=Pde (Serialization + dispatch), see doc to use other codecs
+    match func_id {
+        1 => wire__crate__api__proxy_api__clear_cache_impl(ptr, rust_vec_len, data_len),
+        2 => wire__crate__api__proxy_api__close_session_impl(ptr, rust_vec_len, data_len),
+        3 => wire__crate__api__proxy_api__create_session_impl(ptr, rust_vec_len, data_len),
+        4 => wire__crate__api__proxy_api__create_session_with_source_impl(
+            ptr,
+            rust_vec_len,
+            data_len,
+        ),
+        5 => wire__crate__api__proxy_api__delete_cache_entry_impl(ptr, rust_vec_len, data_len),
+        6 => wire__crate__api__proxy_api__dispose_impl(ptr, rust_vec_len, data_len),
+        7 => wire__crate__api__proxy_api__get_cache_usage_impl(ptr, rust_vec_len, data_len),
+        8 => wire__crate__api__proxy_api__get_policy_usage_impl(ptr, rust_vec_len, data_len),
+        9 => wire__crate__api__proxy_api__get_session_error_impl(ptr, rust_vec_len, data_len),
+        10 => wire__crate__api__proxy_api__get_stats_impl(ptr, rust_vec_len, data_len),
+        11 => wire__crate__api__simple__greet_impl(ptr, rust_vec_len, data_len),
+        13 => wire__crate__api__proxy_api__init_engine_impl(ptr, rust_vec_len, data_len),
+        14 => wire__crate__api__proxy_api__list_cache_entries_impl(ptr, rust_vec_len, data_len),
+        15 => wire__crate__api__proxy_api__pin_cache_entry_impl(ptr, rust_vec_len, data_len),
+        16 => wire__crate__api__proxy_api__prepare_seek_impl(ptr, rust_vec_len, data_len),
+        18 => {
+            wire__crate__api__proxy_api__report_playback_position_impl(ptr, rust_vec_len, data_len)
+        }
+        19 => wire__crate__api__proxy_api__set_bandwidth_limit_impl(ptr, rust_vec_len, data_len),
+        20 => {
+            wire__crate__api__proxy_api__set_host_request_budget_impl(ptr, rust_vec_len, data_len)
+        }
+        21 => wire__crate__api__proxy_api__set_magnet_gateway_impl(ptr, rust_vec_len, data_len),
+        22 => wire__crate__api__proxy_api__set_network_policy_impl(ptr, rust_vec_len, data_len),
+        23 => wire__crate__api__proxy_api__update_session_auth_impl(ptr, rust_vec_len, data_len),
+        _ => unreachable!(),
+    }
+}
+
+// Section: rust2dart
+
+// Codec=Dco (DartCObject based), see doc to use other codecs
+impl flutter_rust_bridge::IntoDart for crate::api::proxy_api::AudioTrackInfo {
+    fn into_dart(self) -> flutter_rust_bridge::for_generated::DartAbi {
+        [
+            self.codec.into_into_dart().into_dart(),
+            self.channels.into_into_dart().into_dart(),
+            self.sample_rate.into_into_dart().into_dart(),
+            self.language.into_into_dart().into_dart(),
+            self.title.into_into_dart().into_dart(),
+        ]
+        .into_dart()
+    }
+}
+impl flutter_rust_bridge::for_generated::IntoDartExceptPrimitive
+    for crate::api::proxy_api::AudioTrackInfo
+{
+}
+impl flutter_rust_bridge::IntoIntoDart<crate::api::proxy_api::AudioTrackInfo>
+    for crate::api::proxy_api::AudioTrackInfo
+{
+    fn into_into_dart(self) -> crate::api::proxy_api::AudioTrackInfo {
+        self
+    }
+}
+// Codec=Dco (DartCObject based), see doc to use other codecs
+impl flutter_rust_bridge::IntoDart for crate::config::CacheBackend {
+    fn into_dart(self) -> flutter_rust_bridge::for_generated::DartAbi {
+        match self {
+            Self::Mmap => 0.into_dart(),
+            Self::ChunkFiles => 1.into_dart(),
+            Self::Memory => 2.into_dart(),
+            _ => unreachable!(),
+        }
+    }
+}
+impl flutter_rust_bridge::for_generated::IntoDartExceptPrimitive for crate::config::CacheBackend {}
+impl flutter_rust_bridge::IntoIntoDart<crate::config::CacheBackend>
+    for crate::config::CacheBackend
+{
+    fn into_into_dart(self) -> crate::config::CacheBackend {
+        self
+    }
+}
+// Codec=Dco (DartCObject based), see doc to use other codecs
+impl flutter_rust_bridge::IntoDart for crate::api::proxy_api::CacheEntryInfo {
+    fn into_dart(self) -> flutter_rust_bridge::for_generated::DartAbi {
+        [
+            self.cache_key.into_into_dart().into_dart(),
+            self.file_key.into_into_dart().into_dart(),
+            self.title.into_into_dart().into_dart(),
+            self.size_on_disk.into_into_dart().into_dart(),
+            self.content_length.into_into_dart().into_dart(),
+            self.percent_complete.into_into_dart().into_dart(),
+            self.last_access_at.into_into_dart().into_dart(),
+            self.pinned.into_into_dart().into_dart(),
+            self.active.into_into_dart().into_dart(),
+        ]
+        .into_dart()
+    }
+}
+impl flutter_rust_bridge::for_generated::IntoDartExceptPrimitive
+    for crate::api::proxy_api::CacheEntryInfo
+{
+}
+impl flutter_rust_bridge::IntoIntoDart<crate::api::proxy_api::CacheEntryInfo>
+    for crate::api::proxy_api::CacheEntryInfo
+{
+    fn into_into_dart(self) -> crate::api::proxy_api::CacheEntryInfo {
+        self
+    }
+}
+// Codec=Dco (DartCObject based), see doc to use other codecs
+impl flutter_rust_bridge::IntoDart for crate::api::proxy_api::CacheUsage {
+    fn into_dart(self) -> flutter_rust_bridge::for_generated::DartAbi {
+        [
+            self.total_bytes.into_into_dart().into_dart(),
+            self.entry_count.into_into_dart().into_dart(),
+            self.max_cache_bytes.into_into_dart().into_dart(),
+            self.free_disk_bytes.into_into_dart().into_dart(),
+        ]
+        .into_dart()
     }
 }
-
-fn pde_ffi_dispatcher_sync_impl(
-    func_id: i32,
-    ptr: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
-    rust_vec_len: i32,
-    data_len: i32,
-) -> flutter_rust_bridge::for_generated::WireSyncRust2DartSse {
-    // Codec=Pde (Serialization + dispatch), see doc to use other codecs
-    match func_id {
-        1 => wire__crate__api__proxy_api__close_session_impl(ptr, rust_vec_len, data_len),
-        2 => wire__crate__api__proxy_api__create_session_impl(ptr, rust_vec_len, data_len),
-        3 => wire__crate__api__proxy_api__dispose_impl(ptr, rust_vec_len, data_len),
-        4 => wire__crate__api__proxy_api__get_stats_impl(ptr, rust_vec_len, data_len),
-        5 => wire__crate__api__simple__greet_impl(ptr, rust_vec_len, data_len),
-        7 => wire__crate__api__proxy_api__init_engine_impl(ptr, rust_vec_len, data_len),
-        8 => wire__crate__api__proxy_api__update_session_auth_impl(ptr, rust_vec_len, data_len),
-        _ => unreachable!(),
+impl flutter_rust_bridge::for_generated::IntoDartExceptPrimitive
+    for crate::api::proxy_api::CacheUsage
+{
+}
+impl flutter_rust_bridge::IntoIntoDart<crate::api::proxy_api::CacheUsage>
+    for crate::api::proxy_api::CacheUsage
+{
+    fn into_into_dart(self) -> crate::api::proxy_api::CacheUsage {
+        self
     }
 }
-
-// Section: rust2dart
-
 // Codec=Dco (DartCObject based), see doc to use other codecs
 impl flutter_rust_bridge::IntoDart for crate::config::EngineConfig {
     fn into_dart(self) -> flutter_rust_bridge::for_generated::DartAbi {
@@ -519,6 +1450,9 @@ impl flutter_rust_bridge::IntoDart for crate::config::EngineConfig {
             self.chunk_size.into_into_dart().into_dart(),
             self.max_concurrency.into_into_dart().into_dart(),
             self.cache_dir.into_into_dart().into_dart(),
+            self.max_cache_bytes.into_into_dart().into_dart(),
+            self.max_session_cache_bytes.into_into_dart().into_dart(),
+            self.cache_backend.into_into_dart().into_dart(),
         ]
         .into_dart()
     }
@@ -532,6 +1466,99 @@ impl flutter_rust_bridge::IntoIntoDart<crate::config::EngineConfig>
     }
 }
 // Codec=Dco (DartCObject based), see doc to use other codecs
+impl flutter_rust_bridge::IntoDart for crate::api::proxy_api::MediaInfo {
+    fn into_dart(self) -> flutter_rust_bridge::for_generated::DartAbi {
+        [
+            self.container.into_into_dart().into_dart(),
+            self.duration_ms.into_into_dart().into_dart(),
+            self.bitrate.into_into_dart().into_dart(),
+            self.video_tracks.into_into_dart().into_dart(),
+            self.audio_tracks.into_into_dart().into_dart(),
+            self.subtitle_tracks.into_into_dart().into_dart(),
+        ]
+        .into_dart()
+    }
+}
+impl flutter_rust_bridge::for_generated::IntoDartExceptPrimitive
+    for crate::api::proxy_api::MediaInfo
+{
+}
+impl flutter_rust_bridge::IntoIntoDart<crate::api::proxy_api::MediaInfo>
+    for crate::api::proxy_api::MediaInfo
+{
+    fn into_into_dart(self) -> crate::api::proxy_api::MediaInfo {
+        self
+    }
+}
+// Codec=Dco (DartCObject based), see doc to use other codecs
+impl flutter_rust_bridge::IntoDart for crate::engine::policy::NetworkPolicy {
+    fn into_dart(self) -> flutter_rust_bridge::for_generated::DartAbi {
+        match self {
+            Self::Normal => 0.into_dart(),
+            Self::DataSaver => 1.into_dart(),
+            _ => unreachable!(),
+        }
+    }
+}
+impl flutter_rust_bridge::for_generated::IntoDartExceptPrimitive
+    for crate::engine::policy::NetworkPolicy
+{
+}
+impl flutter_rust_bridge::IntoIntoDart<crate::engine::policy::NetworkPolicy>
+    for crate::engine::policy::NetworkPolicy
+{
+    fn into_into_dart(self) -> crate::engine::policy::NetworkPolicy {
+        self
+    }
+}
+// Codec=Dco (DartCObject based), see doc to use other codecs
+impl flutter_rust_bridge::IntoDart for crate::api::proxy_api::PolicyUsage {
+    fn into_dart(self) -> flutter_rust_bridge::for_generated::DartAbi {
+        [
+            self.policy.into_into_dart().into_dart(),
+            self.started_at.into_into_dart().into_dart(),
+            self.downloaded_bytes.into_into_dart().into_dart(),
+        ]
+        .into_dart()
+    }
+}
+impl flutter_rust_bridge::for_generated::IntoDartExceptPrimitive
+    for crate::api::proxy_api::PolicyUsage
+{
+}
+impl flutter_rust_bridge::IntoIntoDart<crate::api::proxy_api::PolicyUsage>
+    for crate::api::proxy_api::PolicyUsage
+{
+    fn into_into_dart(self) -> crate::api::proxy_api::PolicyUsage {
+        self
+    }
+}
+// Codec=Dco (DartCObject based), see doc to use other codecs
+impl flutter_rust_bridge::IntoDart for crate::error::ProxyErrorKind {
+    fn into_dart(self) -> flutter_rust_bridge::for_generated::DartAbi {
+        match self {
+            Self::AuthExpired => 0.into_dart(),
+            Self::NotFound => 1.into_dart(),
+            Self::RateLimited => 2.into_dart(),
+            Self::Upstream5xx => 3.into_dart(),
+            Self::Network => 4.into_dart(),
+            Self::CacheIo => 5.into_dart(),
+            Self::SourceChanged => 6.into_dart(),
+            Self::Cancelled => 7.into_dart(),
+            Self::Internal => 8.into_dart(),
+            _ => unreachable!(),
+        }
+    }
+}
+impl flutter_rust_bridge::for_generated::IntoDartExceptPrimitive for crate::error::ProxyErrorKind {}
+impl flutter_rust_bridge::IntoIntoDart<crate::error::ProxyErrorKind>
+    for crate::error::ProxyErrorKind
+{
+    fn into_into_dart(self) -> crate::error::ProxyErrorKind {
+        self
+    }
+}
+// Codec=Dco (DartCObject based), see doc to use other codecs
 impl flutter_rust_bridge::IntoDart for crate::api::proxy_api::ProxyStats {
     fn into_dart(self) -> flutter_rust_bridge::for_generated::DartAbi {
         [
@@ -540,6 +1567,7 @@ impl flutter_rust_bridge::IntoDart for crate::api::proxy_api::ProxyStats {
             self.buffered_bytes_ahead.into_into_dart().into_dart(),
             self.active_workers.into_into_dart().into_dart(),
             self.cache_hit_rate.into_into_dart().into_dart(),
+            self.playback_bitrate.into_into_dart().into_dart(),
         ]
         .into_dart()
     }
@@ -556,6 +1584,27 @@ impl flutter_rust_bridge::IntoIntoDart<crate::api::proxy_api::ProxyStats>
     }
 }
 // Codec=Dco (DartCObject based), see doc to use other codecs
+impl flutter_rust_bridge::IntoDart for crate::api::proxy_api::SessionError {
+    fn into_dart(self) -> flutter_rust_bridge::for_generated::DartAbi {
+        [
+            self.kind.into_into_dart().into_dart(),
+            self.message.into_into_dart().into_dart(),
+        ]
+        .into_dart()
+    }
+}
+impl flutter_rust_bridge::for_generated::IntoDartExceptPrimitive
+    for crate::api::proxy_api::SessionError
+{
+}
+impl flutter_rust_bridge::IntoIntoDart<crate::api::proxy_api::SessionError>
+    for crate::api::proxy_api::SessionError
+{
+    fn into_into_dart(self) -> crate::api::proxy_api::SessionError {
+        self
+    }
+}
+// Codec=Dco (DartCObject based), see doc to use other codecs
 impl flutter_rust_bridge::IntoDart for crate::api::proxy_api::SessionInfo {
     fn into_dart(self) -> flutter_rust_bridge::for_generated::DartAbi {
         [
@@ -578,6 +1627,77 @@ impl flutter_rust_bridge::IntoIntoDart<crate::api::proxy_api::SessionInfo>
         self
     }
 }
+// Codec=Dco (DartCObject based), see doc to use other codecs
+impl flutter_rust_bridge::IntoDart for crate::source::registry::SourceDescriptor {
+    fn into_dart(self) -> flutter_rust_bridge::for_generated::DartAbi {
+        [
+            self.uri.into_into_dart().into_dart(),
+            self.headers.into_into_dart().into_dart(),
+        ]
+        .into_dart()
+    }
+}
+impl flutter_rust_bridge::for_generated::IntoDartExceptPrimitive
+    for crate::source::registry::SourceDescriptor
+{
+}
+impl flutter_rust_bridge::IntoIntoDart<crate::source::registry::SourceDescriptor>
+    for crate::source::registry::SourceDescriptor
+{
+    fn into_into_dart(self) -> crate::source::registry::SourceDescriptor {
+        self
+    }
+}
+// Codec=Dco (DartCObject based), see doc to use other codecs
+impl flutter_rust_bridge::IntoDart for crate::api::proxy_api::SubtitleTrackInfo {
+    fn into_dart(self) -> flutter_rust_bridge::for_generated::DartAbi {
+        [
+            self.codec.into_into_dart().into_dart(),
+            self.language.into_into_dart().into_dart(),
+            self.title.into_into_dart().into_dart(),
+            self.forced.into_into_dart().into_dart(),
+        ]
+        .into_dart()
+    }
+}
+impl flutter_rust_bridge::for_generated::IntoDartExceptPrimitive
+    for crate::api::proxy_api::SubtitleTrackInfo
+{
+}
+impl flutter_rust_bridge::IntoIntoDart<crate::api::proxy_api::SubtitleTrackInfo>
+    for crate::api::proxy_api::SubtitleTrackInfo
+{
+    fn into_into_dart(self) -> crate::api::proxy_api::SubtitleTrackInfo {
+        self
+    }
+}
+// Codec=Dco (DartCObject based), see doc to use other codecs
+impl flutter_rust_bridge::IntoDart for crate::api::proxy_api::VideoTrackInfo {
+    fn into_dart(self) -> flutter_rust_bridge::for_generated::DartAbi {
+        [
+            self.codec.into_into_dart().into_dart(),
+            self.profile.into_into_dart().into_dart(),
+            self.width.into_into_dart().into_dart(),
+            self.height.into_into_dart().into_dart(),
+            self.frame_rate.into_into_dart().into_dart(),
+            self.bit_depth.into_into_dart().into_dart(),
+            self.hdr.into_into_dart().into_dart(),
+            self.dolby_vision.into_into_dart().into_dart(),
+        ]
+        .into_dart()
+    }
+}
+impl flutter_rust_bridge::for_generated::IntoDartExceptPrimitive
+    for crate::api::proxy_api::VideoTrackInfo
+{
+}
+impl flutter_rust_bridge::IntoIntoDart<crate::api::proxy_api::VideoTrackInfo>
+    for crate::api::proxy_api::VideoTrackInfo
+{
+    fn into_into_dart(self) -> crate::api::proxy_api::VideoTrackInfo {
+        self
+    }
+}
 
 impl SseEncode for flutter_rust_bridge::for_generated::anyhow::Error {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -600,12 +1720,68 @@ impl SseEncode for String {
     }
 }
 
+impl SseEncode for crate::api::proxy_api::AudioTrackInfo {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <String>::sse_encode(self.codec, serializer);
+        <u32>::sse_encode(self.channels, serializer);
+        <u32>::sse_encode(self.sample_rate, serializer);
+        <String>::sse_encode(self.language, serializer);
+        <String>::sse_encode(self.title, serializer);
+    }
+}
+
+impl SseEncode for crate::config::CacheBackend {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <i32>::sse_encode(
+            match self {
+                crate::config::CacheBackend::Mmap => 0,
+                crate::config::CacheBackend::ChunkFiles => 1,
+                crate::config::CacheBackend::Memory => 2,
+                _ => {
+                    unimplemented!("");
+                }
+            },
+            serializer,
+        );
+    }
+}
+
+impl SseEncode for crate::api::proxy_api::CacheEntryInfo {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <String>::sse_encode(self.cache_key, serializer);
+        <String>::sse_encode(self.file_key, serializer);
+        <String>::sse_encode(self.title, serializer);
+        <u64>::sse_encode(self.size_on_disk, serializer);
+        <u64>::sse_encode(self.content_length, serializer);
+        <f64>::sse_encode(self.percent_complete, serializer);
+        <u64>::sse_encode(self.last_access_at, serializer);
+        <bool>::sse_encode(self.pinned, serializer);
+        <bool>::sse_encode(self.active, serializer);
+    }
+}
+
+impl SseEncode for crate::api::proxy_api::CacheUsage {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <u64>::sse_encode(self.total_bytes, serializer);
+        <u32>::sse_encode(self.entry_count, serializer);
+        <u64>::sse_encode(self.max_cache_bytes, serializer);
+        <u64>::sse_encode(self.free_disk_bytes, serializer);
+    }
+}
+
 impl SseEncode for crate::config::EngineConfig {
     // Codec=Sse (Serialization based), see doc to use other codecs
     fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
         <u64>::sse_encode(self.chunk_size, serializer);
         <u32>::sse_encode(self.max_concurrency, serializer);
         <String>::sse_encode(self.cache_dir, serializer);
+        <u64>::sse_encode(self.max_cache_bytes, serializer);
+        <u64>::sse_encode(self.max_session_cache_bytes, serializer);
+        <Option<crate::config::CacheBackend>>::sse_encode(self.cache_backend, serializer);
     }
 }
 
@@ -616,6 +1792,26 @@ impl SseEncode for f64 {
     }
 }
 
+impl SseEncode for Vec<crate::api::proxy_api::AudioTrackInfo> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <i32>::sse_encode(self.len() as _, serializer);
+        for item in self {
+            <crate::api::proxy_api::AudioTrackInfo>::sse_encode(item, serializer);
+        }
+    }
+}
+
+impl SseEncode for Vec<crate::api::proxy_api::CacheEntryInfo> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <i32>::sse_encode(self.len() as _, serializer);
+        for item in self {
+            <crate::api::proxy_api::CacheEntryInfo>::sse_encode(item, serializer);
+        }
+    }
+}
+
 impl SseEncode for Vec<u8> {
     // Codec=Sse (Serialization based), see doc to use other codecs
     fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
@@ -636,6 +1832,57 @@ impl SseEncode for Vec<(String, String)> {
     }
 }
 
+impl SseEncode for Vec<crate::api::proxy_api::SubtitleTrackInfo> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <i32>::sse_encode(self.len() as _, serializer);
+        for item in self {
+            <crate::api::proxy_api::SubtitleTrackInfo>::sse_encode(item, serializer);
+        }
+    }
+}
+
+impl SseEncode for Vec<crate::api::proxy_api::VideoTrackInfo> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <i32>::sse_encode(self.len() as _, serializer);
+        for item in self {
+            <crate::api::proxy_api::VideoTrackInfo>::sse_encode(item, serializer);
+        }
+    }
+}
+
+impl SseEncode for crate::api::proxy_api::MediaInfo {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <String>::sse_encode(self.container, serializer);
+        <u64>::sse_encode(self.duration_ms, serializer);
+        <u64>::sse_encode(self.bitrate, serializer);
+        <Vec<crate::api::proxy_api::VideoTrackInfo>>::sse_encode(self.video_tracks, serializer);
+        <Vec<crate::api::proxy_api::AudioTrackInfo>>::sse_encode(self.audio_tracks, serializer);
+        <Vec<crate::api::proxy_api::SubtitleTrackInfo>>::sse_encode(
+            self.subtitle_tracks,
+            serializer,
+        );
+    }
+}
+
+impl SseEncode for crate::engine::policy::NetworkPolicy {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <i32>::sse_encode(
+            match self {
+                crate::engine::policy::NetworkPolicy::Normal => 0,
+                crate::engine::policy::NetworkPolicy::DataSaver => 1,
+                _ => {
+                    unimplemented!("");
+                }
+            },
+            serializer,
+        );
+    }
+}
+
 impl SseEncode for Option<String> {
     // Codec=Sse (Serialization based), see doc to use other codecs
     fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
@@ -646,6 +1893,68 @@ impl SseEncode for Option<String> {
     }
 }
 
+impl SseEncode for Option<crate::config::CacheBackend> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <bool>::sse_encode(self.is_some(), serializer);
+        if let Some(value) = self {
+            <crate::config::CacheBackend>::sse_encode(value, serializer);
+        }
+    }
+}
+
+impl SseEncode for Option<crate::api::proxy_api::SessionError> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <bool>::sse_encode(self.is_some(), serializer);
+        if let Some(value) = self {
+            <crate::api::proxy_api::SessionError>::sse_encode(value, serializer);
+        }
+    }
+}
+
+impl SseEncode for Option<u64> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <bool>::sse_encode(self.is_some(), serializer);
+        if let Some(value) = self {
+            <u64>::sse_encode(value, serializer);
+        }
+    }
+}
+
+impl SseEncode for crate::api::proxy_api::PolicyUsage {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <crate::engine::policy::NetworkPolicy>::sse_encode(self.policy, serializer);
+        <u64>::sse_encode(self.started_at, serializer);
+        <u64>::sse_encode(self.downloaded_bytes, serializer);
+    }
+}
+
+impl SseEncode for crate::error::ProxyErrorKind {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <i32>::sse_encode(
+            match self {
+                crate::error::ProxyErrorKind::AuthExpired => 0,
+                crate::error::ProxyErrorKind::NotFound => 1,
+                crate::error::ProxyErrorKind::RateLimited => 2,
+                crate::error::ProxyErrorKind::Upstream5xx => 3,
+                crate::error::ProxyErrorKind::Network => 4,
+                crate::error::ProxyErrorKind::CacheIo => 5,
+                crate::error::ProxyErrorKind::SourceChanged => 6,
+                crate::error::ProxyErrorKind::Cancelled => 7,
+                crate::error::ProxyErrorKind::Internal => 8,
+                _ => {
+                    unimplemented!("");
+                }
+            },
+            serializer,
+        );
+    }
+}
+
 impl SseEncode for crate::api::proxy_api::ProxyStats {
     // Codec=Sse (Serialization based), see doc to use other codecs
     fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
@@ -654,6 +1963,7 @@ impl SseEncode for crate::api::proxy_api::ProxyStats {
         <u64>::sse_encode(self.buffered_bytes_ahead, serializer);
         <u32>::sse_encode(self.active_workers, serializer);
         <f64>::sse_encode(self.cache_hit_rate, serializer);
+        <u64>::sse_encode(self.playback_bitrate, serializer);
     }
 }
 
@@ -665,6 +1975,14 @@ impl SseEncode for (String, String) {
     }
 }
 
+impl SseEncode for crate::api::proxy_api::SessionError {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <crate::error::ProxyErrorKind>::sse_encode(self.kind, serializer);
+        <String>::sse_encode(self.message, serializer);
+    }
+}
+
 impl SseEncode for crate::api::proxy_api::SessionInfo {
     // Codec=Sse (Serialization based), see doc to use other codecs
     fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
@@ -675,6 +1993,24 @@ impl SseEncode for crate::api::proxy_api::SessionInfo {
     }
 }
 
+impl SseEncode for crate::source::registry::SourceDescriptor {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <String>::sse_encode(self.uri, serializer);
+        <std::collections::HashMap<String, String>>::sse_encode(self.headers, serializer);
+    }
+}
+
+impl SseEncode for crate::api::proxy_api::SubtitleTrackInfo {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <String>::sse_encode(self.codec, serializer);
+        <String>::sse_encode(self.language, serializer);
+        <String>::sse_encode(self.title, serializer);
+        <bool>::sse_encode(self.forced, serializer);
+    }
+}
+
 impl SseEncode for u32 {
     // Codec=Sse (Serialization based), see doc to use other codecs
     fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
@@ -701,6 +2037,20 @@ impl SseEncode for () {
     fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {}
 }
 
+impl SseEncode for crate::api::proxy_api::VideoTrackInfo {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <String>::sse_encode(self.codec, serializer);
+        <String>::sse_encode(self.profile, serializer);
+        <u32>::sse_encode(self.width, serializer);
+        <u32>::sse_encode(self.height, serializer);
+        <f64>::sse_encode(self.frame_rate, serializer);
+        <u32>::sse_encode(self.bit_depth, serializer);
+        <bool>::sse_encode(self.hdr, serializer);
+        <bool>::sse_encode(self.dolby_vision, serializer);
+    }
+}
+
 impl SseEncode for i32 {
     // Codec=Sse (Serialization based), see doc to use other codecs
     fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
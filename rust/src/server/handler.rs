@@ -15,10 +15,12 @@ use axum::{
 use parking_lot::RwLock;
 use tokio::net::TcpListener;
 use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
 use tracing::{debug, error};
 
 use crate::config::{MAX_OPEN_ENDED_RESPONSE_BYTES, STARTUP_PROBE_CLAMP_BYTES};
 use crate::engine::session::ProxySession;
+use crate::error::{ProxyError, ProxyErrorKind};
 
 pub type SessionMap = Arc<RwLock<HashMap<String, Arc<ProxySession>>>>;
 
@@ -144,7 +146,6 @@ async fn stream_handler(
     };
 
     let total = session.content_length();
-    let content_type = session.content_type().to_string();
 
     let range = headers
         .get(header::RANGE)
@@ -208,18 +209,30 @@ async fn stream_handler(
 
     let body_len = end - start;
 
-    // Set up the streaming body — data is sent chunk-by-chunk as each
-    // cache chunk becomes available, so the player receives first bytes
-    // immediately and never times out waiting for the full range.
-    let rx = match session.serve_range_stream(start, end) {
+    // Set up the streaming body — data is sent piece-by-piece as soon as it
+    // is contiguous in the cache, even mid-chunk, so the player receives
+    // first bytes after about one upstream round trip.
+    let mut rx = match session.serve_range_stream(start, end) {
         Ok(rx) => rx,
         Err(e) => {
             error!("serve_range_stream error: {}", e);
-            return (StatusCode::INTERNAL_SERVER_ERROR, format!("error: {}", e)).into_response();
+            return error_response(&e);
         }
     };
 
-    let stream = ReceiverStream::new(rx);
+    // Wait for the first piece so a failure before any data is reported
+    // with a status the player can act on, not as a truncated 206.
+    let first = match rx.recv().await {
+        Some(Ok(bytes)) => bytes,
+        Some(Err(e)) => {
+            error!("stream error before first byte: {:#}", e);
+            return error_response(&e);
+        }
+        None => return error_response(&ProxyError::cancelled().into()),
+    };
+    // Read after the first piece, by which time the container is detected.
+    let content_type = session.content_type();
+    let stream = tokio_stream::once(Ok(first)).chain(ReceiverStream::new(rx));
     let body = Body::from_stream(stream);
 
     let status = if is_partial {
@@ -244,6 +257,28 @@ async fn stream_handler(
     (status, resp_headers, body).into_response()
 }
 
+/// Map a failure to the HTTP status a player understands, with the error
+/// code in `x-proxy-error`.
+fn error_response(err: &anyhow::Error) -> Response {
+    let error = ProxyError::classify(err);
+    let status = match error.kind {
+        ProxyErrorKind::AuthExpired => StatusCode::UNAUTHORIZED,
+        ProxyErrorKind::NotFound => StatusCode::NOT_FOUND,
+        ProxyErrorKind::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+        ProxyErrorKind::Upstream5xx => StatusCode::BAD_GATEWAY,
+        ProxyErrorKind::Network => StatusCode::GATEWAY_TIMEOUT,
+        ProxyErrorKind::CacheIo | ProxyErrorKind::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        ProxyErrorKind::SourceChanged => StatusCode::PRECONDITION_FAILED,
+        ProxyErrorKind::Cancelled => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    (
+        status,
+        [("x-proxy-error", error.kind.code())],
+        format!("error: {:#}", err),
+    )
+        .into_response()
+}
+
 /// HEAD /stream/{session_id} — return headers only.
 async fn head_handler(
     State(sessions): State<SessionMap>,
@@ -263,7 +298,7 @@ async fn head_handler(
     };
 
     let total = session.content_length();
-    let content_type = session.content_type().to_string();
+    let content_type = session.content_type();
 
     let mut resp_headers = HeaderMap::new();
     resp_headers.insert(header::CONTENT_TYPE, content_type.parse().unwrap());
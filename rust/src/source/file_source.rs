@@ -0,0 +1,91 @@
+use std::io::SeekFrom;
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::sync::Mutex;
+
+use super::traits::{MediaSource, SourceInfo};
+use crate::error::{ProxyError, ProxyErrorKind};
+
+/// Local file backend (`file://` URIs, SMB/NFS mounts, downloaded media).
+pub struct FileSource {
+    path: PathBuf,
+    file: Mutex<Option<File>>,
+}
+
+impl FileSource {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            file: Mutex::new(None),
+        }
+    }
+}
+
+/// Type a read failure: a missing file is `NotFound`, anything else is
+/// treated like a dropped connection, since mounts come and go.
+fn read_error(e: std::io::Error) -> anyhow::Error {
+    let kind = match e.kind() {
+        std::io::ErrorKind::NotFound => ProxyErrorKind::NotFound,
+        _ => ProxyErrorKind::Network,
+    };
+    ProxyError::new(kind, e.to_string()).into()
+}
+
+#[async_trait]
+impl MediaSource for FileSource {
+    async fn probe(&self) -> Result<SourceInfo> {
+        let meta = tokio::fs::metadata(&self.path).await.map_err(read_error)?;
+        if !meta.is_file() {
+            return Err(ProxyError::new(
+                ProxyErrorKind::NotFound,
+                format!("not a regular file: {}", self.path.display()),
+            )
+            .into());
+        }
+        let last_modified = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs().to_string());
+        Ok(SourceInfo {
+            content_length: meta.len(),
+            content_type: "application/octet-stream".to_string(),
+            supports_range: true,
+            etag: None,
+            last_modified,
+        })
+    }
+
+    async fn fetch_range(&self, start: u64, end: u64) -> Result<Bytes> {
+        if end < start {
+            return Err(anyhow!("invalid range: start={} end={}", start, end));
+        }
+
+        let mut guard = self.file.lock().await;
+        if guard.is_none() {
+            *guard = Some(File::open(&self.path).await.map_err(read_error)?);
+        }
+        let file = guard.as_mut().expect("file opened above");
+
+        file.seek(SeekFrom::Start(start))
+            .await
+            .map_err(read_error)?;
+        let mut buf = vec![0u8; (end - start + 1) as usize];
+        let mut filled = 0;
+        while filled < buf.len() {
+            let n = file.read(&mut buf[filled..]).await.map_err(read_error)?;
+            if n == 0 {
+                break;
+            }
+            filled += n;
+        }
+        buf.truncate(filled);
+        Ok(Bytes::from(buf))
+    }
+}
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::net::{IpAddr, SocketAddr};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
@@ -9,9 +10,11 @@ use bytes::Bytes;
 use parking_lot::RwLock;
 use reqwest::{Client, RequestBuilder, Url};
 use tokio::sync::Mutex;
+use tokio_stream::StreamExt;
 use tracing::{debug, info, warn};
 
-use super::traits::{MediaSource, SourceInfo};
+use super::traits::{ByteStream, MediaSource, SourceInfo};
+use crate::error::{ProxyError, ProxyErrorKind};
 
 pub struct HttpSource {
     client: Client,
@@ -20,6 +23,8 @@ pub struct HttpSource {
     route_clients: Arc<RwLock<Vec<RouteClient>>>,
     route_init_lock: Arc<Mutex<()>>,
     next_route: AtomicUsize,
+    /// Length and strong ETag seen by `probe`, checked on every range.
+    probed: RwLock<Option<(u64, Option<String>)>>,
 }
 
 #[derive(Clone)]
@@ -37,18 +42,7 @@ impl HttpSource {
             route_clients: Arc::new(RwLock::new(Vec::new())),
             route_init_lock: Arc::new(Mutex::new(())),
             next_route: AtomicUsize::new(0),
-        }
-    }
-
-    /// Update the URL and headers (e.g. after token refresh).
-    pub fn update_auth(&self, new_url: String, new_headers: HashMap<String, String>) {
-        if !new_url.trim().is_empty() {
-            *self.url.write() = new_url;
-            self.route_clients.write().clear();
-            self.next_route.store(0, Ordering::Relaxed);
-        }
-        if !new_headers.is_empty() {
-            *self.headers.write() = new_headers;
+            probed: RwLock::new(None),
         }
     }
 
@@ -71,28 +65,70 @@ impl HttpSource {
         req
     }
 
-    fn route_count(&self) -> usize {
-        let routes = self.route_clients.read();
-        if routes.is_empty() {
-            1
-        } else {
-            routes.len()
+    /// Send a ranged GET and check the status; the body is left unread.
+    async fn send_range(&self, start: u64, end: u64) -> Result<reqwest::Response> {
+        let range = format!("bytes={}-{}", start, end);
+        if self.route_clients.read().is_empty() {
+            let _ = self.ensure_route_clients().await;
+        }
+        let (client, route_ip) = self.pick_client();
+        if let Some(ip) = route_ip {
+            debug!("http fetch via ip={} range={}", ip, range);
         }
+        let resp = self
+            .build_request_with_client(&client, Some(&range))
+            .send()
+            .await
+            .map_err(|e| ProxyError::new(ProxyErrorKind::Network, e.to_string()))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let err = status_error(&resp);
+            warn!(
+                "http fetch failed status={} range={} kind={}",
+                status.as_u16(),
+                range,
+                err.kind.code()
+            );
+            return Err(err.into());
+        }
+        check_range(&resp, start, end)?;
+        self.check_unchanged(&resp)?;
+        Ok(resp)
     }
 
-    pub async fn effective_concurrency(&self, configured: u32) -> u32 {
-        if let Err(e) = self.ensure_route_clients().await {
-            warn!("init route clients failed: {}", e);
+    /// Fail with `SourceChanged` if a range response describes a different
+    /// file than the one probed.
+    fn check_unchanged(&self, resp: &reqwest::Response) -> Result<()> {
+        let Some((length, etag)) = self.probed.read().clone() else {
+            return Ok(());
+        };
+        let total = resp
+            .headers()
+            .get("content-range")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.rsplit('/').next())
+            .and_then(|v| v.parse::<u64>().ok());
+        if let Some(total) = total {
+            if total != length {
+                return Err(ProxyError::new(
+                    ProxyErrorKind::SourceChanged,
+                    format!("length changed from {} to {}", length, total),
+                )
+                .into());
+            }
         }
-        let by_ip = self.route_count() as u32;
-        let effective = configured.min(8).min(by_ip.max(1));
-        if effective != configured {
-            info!(
-                "downloader concurrency adjusted by ip pool: configured={} effective={} ips={}",
-                configured, effective, by_ip
-            );
+        let now = strong_etag(resp.headers());
+        if let (Some(before), Some(now)) = (etag, now) {
+            if before != now {
+                return Err(ProxyError::new(
+                    ProxyErrorKind::SourceChanged,
+                    format!("etag changed from {} to {}", before, now),
+                )
+                .into());
+            }
         }
-        effective
+        Ok(())
     }
 
     fn pick_client(&self) -> (Client, Option<IpAddr>) {
@@ -170,17 +206,19 @@ impl MediaSource for HttpSource {
         let resp = self
             .build_request_with_client(&self.client, Some("bytes=0-0"))
             .send()
-            .await?;
+            .await
+            .map_err(|e| ProxyError::new(ProxyErrorKind::Network, e.to_string()))?;
 
         let status = resp.status();
         debug!("http probe status={}", status.as_u16());
-        if status.as_u16() == 401 || status.as_u16() == 403 || status.as_u16() == 412 {
-            warn!("http probe auth rejected status={}", status.as_u16());
-            return Err(anyhow!("auth_rejected: HTTP {}", status.as_u16()));
-        }
         if !status.is_success() {
-            warn!("http probe failed status={}", status.as_u16());
-            return Err(anyhow!("probe failed: HTTP {}", status.as_u16()));
+            let err = status_error(&resp);
+            warn!(
+                "http probe failed status={} kind={}",
+                status.as_u16(),
+                err.kind.code()
+            );
+            return Err(err.into());
         }
 
         // Parse Content-Range: bytes 0-0/<total>
@@ -207,6 +245,16 @@ impl MediaSource for HttpSource {
             .unwrap_or("application/octet-stream")
             .to_string();
 
+        let header_string = |name: &str| {
+            resp.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let etag = header_string("etag");
+        let last_modified = header_string("last-modified");
+        *self.probed.write() = Some((content_length, strong_etag(resp.headers())));
+
         if let Err(e) = self.ensure_route_clients().await {
             warn!("prepare route clients failed: {}", e);
         }
@@ -215,46 +263,153 @@ impl MediaSource for HttpSource {
             content_length,
             content_type,
             supports_range,
+            etag,
+            last_modified,
         })
     }
 
     async fn fetch_range(&self, start: u64, end: u64) -> Result<Bytes> {
-        let range = format!("bytes={}-{}", start, end);
-        if self.route_clients.read().is_empty() {
-            let _ = self.ensure_route_clients().await;
+        let resp = self.send_range(start, end).await?;
+        let bytes = resp
+            .bytes()
+            .await
+            .map_err(|e| ProxyError::new(ProxyErrorKind::Network, e.to_string()))?;
+        Ok(bytes)
+    }
+
+    async fn fetch_range_stream(&self, start: u64, end: u64) -> Result<ByteStream> {
+        let resp = self.send_range(start, end).await?;
+        Ok(Box::pin(resp.bytes_stream().map(|piece| {
+            piece.map_err(|e| ProxyError::new(ProxyErrorKind::Network, e.to_string()).into())
+        })))
+    }
+
+    async fn refresh_auth(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Update the URL and headers (e.g. after token refresh).
+    fn update_auth(&self, new_url: String, new_headers: HashMap<String, String>) {
+        if !new_url.trim().is_empty() {
+            *self.url.write() = new_url;
+            self.route_clients.write().clear();
+            self.next_route.store(0, Ordering::Relaxed);
         }
-        let (client, route_ip) = self.pick_client();
-        if let Some(ip) = route_ip {
-            debug!("http fetch via ip={} range={}", ip, range);
+        if !new_headers.is_empty() {
+            *self.headers.write() = new_headers;
         }
-        let resp = self
-            .build_request_with_client(&client, Some(&range))
-            .send()
-            .await?;
+    }
 
-        let status = resp.status();
-        if status.as_u16() == 401 || status.as_u16() == 403 || status.as_u16() == 412 {
-            warn!(
-                "http fetch auth rejected status={} range={}",
-                status.as_u16(),
-                range
-            );
-            return Err(anyhow!("auth_rejected: HTTP {}", status.as_u16()));
+    fn host_key(&self) -> Option<String> {
+        let url = Url::parse(&self.url.read()).ok()?;
+        let host = url.host_str()?;
+        Some(match url.port_or_known_default() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        })
+    }
+
+    fn route_count(&self) -> usize {
+        self.route_clients.read().len().max(1)
+    }
+
+    /// Upper bound for the adaptive worker count. Connections are spread
+    /// over the resolved IPs, several per IP if the controller finds that
+    /// faster, so the IP count does not cap it.
+    async fn effective_concurrency(&self, configured: u32) -> u32 {
+        if let Err(e) = self.ensure_route_clients().await {
+            warn!("init route clients failed: {}", e);
         }
-        if !status.is_success() {
-            warn!(
-                "http fetch failed status={} range={}",
-                status.as_u16(),
-                range
+        let effective = configured.min(8);
+        if effective != configured {
+            info!(
+                "downloader concurrency capped: configured={} effective={}",
+                configured, effective
             );
-            return Err(anyhow!("fetch_range failed: HTTP {}", status.as_u16()));
         }
+        effective
+    }
+}
 
-        let bytes = resp.bytes().await?;
-        Ok(bytes)
+/// The ETag header if it is a strong validator; weak ones may differ
+/// between CDN edges for the same bytes.
+fn strong_etag(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    headers
+        .get("etag")
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.starts_with("W/"))
+        .map(str::to_string)
+}
+
+/// Fail with `SourceChanged` unless a response is the `206` for the range
+/// asked for; a `200` full body or a range starting elsewhere would land at
+/// the wrong offset in the cache.
+fn check_range(resp: &reqwest::Response, start: u64, end: u64) -> Result<()> {
+    let range = resp
+        .headers()
+        .get("content-range")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("bytes "))
+        .and_then(|v| v.split('/').next())
+        .and_then(|v| v.split_once('-'))
+        .and_then(|(s, e)| Some((s.parse::<u64>().ok()?, e.parse::<u64>().ok()?)));
+    match range {
+        Some((s, e)) if resp.status().as_u16() == 206 && s == start && e <= end => Ok(()),
+        _ => Err(ProxyError::new(
+            ProxyErrorKind::SourceChanged,
+            format!(
+                "range {}-{} answered with status {} content-range {:?}",
+                start,
+                end,
+                resp.status().as_u16(),
+                resp.headers().get("content-range")
+            ),
+        )
+        .into()),
     }
+}
 
-    async fn refresh_auth(&self) -> Result<()> {
-        Ok(())
+/// Typed error for a non-success response, with its `Retry-After`.
+fn status_error(resp: &reqwest::Response) -> ProxyError {
+    let retry_after = resp
+        .headers()
+        .get("retry-after")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| parse_retry_after(v, SystemTime::now()));
+    ProxyError::from_status(resp.status().as_u16()).with_retry_after(retry_after)
+}
+
+/// Parse a `Retry-After` value: delay seconds or an IMF-fixdate such as
+/// `Sun, 06 Nov 1994 08:49:37 GMT`. A date in the past means no wait.
+pub fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
     }
+    let fields: Vec<&str> = value.split_whitespace().collect();
+    let [_, day, month, year, time, "GMT"] = fields[..] else {
+        return None;
+    };
+    let month = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ]
+    .iter()
+    .position(|&m| m == month)? as i64
+        + 1;
+    let day: i64 = day.parse().ok()?;
+    let year: i64 = year.parse().ok()?;
+    let mut hms = time.splitn(3, ':').map(|v| v.parse::<i64>().ok());
+    let (h, m, sec) = (hms.next()??, hms.next()??, hms.next()??);
+
+    // Days since 1970-01-01 in the proleptic Gregorian calendar.
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    let at = u64::try_from(days * 86_400 + h * 3600 + m * 60 + sec).ok()?;
+    let now = now.duration_since(UNIX_EPOCH).ok()?.as_secs();
+    Some(Duration::from_secs(at.saturating_sub(now)))
 }
@@ -1,10 +1,12 @@
-use super::traits::{MediaSource, SourceInfo};
+use super::traits::{ByteStream, MediaSource, SourceInfo};
 use anyhow::Result;
 use async_trait::async_trait;
 use bytes::Bytes;
+use std::collections::HashMap;
 use std::sync::Arc;
 
-/// Decorator that maps byte ranges to an inner file within an ISO/UDF container.
+/// Decorator that maps byte ranges to an inner file within an ISO/UDF container
+/// (or any other fixed sub-range, such as a stored zip entry).
 pub struct IsoMediaSource {
     inner: Arc<dyn MediaSource>,
     file_offset: u64,
@@ -35,6 +37,8 @@ impl MediaSource for IsoMediaSource {
             content_length: self.file_length,
             content_type: self.content_type.clone(),
             supports_range: true,
+            etag: None,
+            last_modified: None,
         })
     }
 
@@ -44,9 +48,31 @@ impl MediaSource for IsoMediaSource {
             .await
     }
 
+    async fn fetch_range_stream(&self, start: u64, end: u64) -> Result<ByteStream> {
+        self.inner
+            .fetch_range_stream(self.file_offset + start, self.file_offset + end)
+            .await
+    }
+
     async fn refresh_auth(&self) -> Result<()> {
         self.inner.refresh_auth().await
     }
+
+    fn host_key(&self) -> Option<String> {
+        self.inner.host_key()
+    }
+
+    fn route_count(&self) -> usize {
+        self.inner.route_count()
+    }
+
+    fn update_auth(&self, new_url: String, new_headers: HashMap<String, String>) {
+        self.inner.update_auth(new_url, new_headers);
+    }
+
+    async fn effective_concurrency(&self, configured: u32) -> u32 {
+        self.inner.effective_concurrency(configured).await
+    }
 }
 
 /// Auto-detect ISO and wrap if needed. Currently a stub — full UDF parsing for later.
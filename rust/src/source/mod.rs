@@ -1,5 +1,9 @@
 // Data source abstraction — pluggable backends for HTTP, ISO, and future sources.
 
+pub mod file_source;
+pub mod ftp_source;
 pub mod http_source;
 pub mod iso_source;
+pub mod registry;
 pub mod traits;
+pub mod zip_source;
@@ -0,0 +1,502 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use parking_lot::RwLock;
+use percent_encoding::percent_decode_str;
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use tracing::debug;
+
+use super::file_source::FileSource;
+use super::ftp_source::FtpSource;
+use super::http_source::HttpSource;
+use super::iso_source::IsoMediaSource;
+use super::traits::MediaSource;
+use super::zip_source::wrap_zip_entry;
+use crate::error::{ProxyError, ProxyErrorKind};
+
+/// Description of where a session's bytes come from.
+///
+/// `uri` selects the backend by scheme (`https://…`, `file:///…`,
+/// `webdav://…`, `ftp://…`, `alist://…`, and `magnet:?…` once a gateway is
+/// set). Decorators are prefixed with `+`, outermost first, and take their
+/// parameters from the URI fragment, e.g.
+/// `iso+https://host/disc.iso#offset=1048576&length=734003200`. With more
+/// than one decorator, each key names its layer: `#zip.entry=…&iso.offset=…`.
+#[derive(Debug, Clone, Default)]
+pub struct SourceDescriptor {
+    pub uri: String,
+    pub headers: HashMap<String, String>,
+}
+
+/// Builds a base source for a URI whose scheme it was registered under.
+#[async_trait]
+pub trait SourceFactory: Send + Sync {
+    async fn create(
+        &self,
+        uri: &str,
+        headers: &HashMap<String, String>,
+    ) -> Result<Arc<dyn MediaSource>>;
+}
+
+/// Wraps an already-built source, e.g. to expose a file inside an archive.
+#[async_trait]
+pub trait SourceDecorator: Send + Sync {
+    async fn wrap(
+        &self,
+        inner: Arc<dyn MediaSource>,
+        params: &HashMap<String, String>,
+    ) -> Result<Arc<dyn MediaSource>>;
+}
+
+/// Scheme → factory table used by `create_session`.
+pub struct SourceRegistry {
+    factories: RwLock<HashMap<String, Arc<dyn SourceFactory>>>,
+    decorators: RwLock<HashMap<String, Arc<dyn SourceDecorator>>>,
+}
+
+impl SourceRegistry {
+    /// An empty registry. Most callers want [`SourceRegistry::with_defaults`].
+    pub fn new() -> Self {
+        Self {
+            factories: RwLock::new(HashMap::new()),
+            decorators: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Registry with the built-in backends: `http(s)`, `webdav(s)`, `file`,
+    /// `ftp`, `alist(s)`, and the `iso+` / `zip+` decorators. `magnet` needs
+    /// a gateway the app does not ship, so it is registered separately.
+    pub fn with_defaults() -> Self {
+        let registry = Self::new();
+        let http: Arc<dyn SourceFactory> = Arc::new(HttpFactory);
+        for scheme in ["http", "https", "webdav", "webdavs"] {
+            registry.register_factory(scheme, http.clone());
+        }
+        let alist: Arc<dyn SourceFactory> = Arc::new(AlistFactory::default());
+        for scheme in ["alist", "alists"] {
+            registry.register_factory(scheme, alist.clone());
+        }
+        registry.register_factory("file", Arc::new(FileFactory));
+        registry.register_factory("ftp", Arc::new(FtpFactory));
+        registry.register_decorator("iso", Arc::new(IsoDecorator));
+        registry.register_decorator("zip", Arc::new(ZipDecorator));
+        registry
+    }
+
+    /// Process-wide registry. Embedders add their own schemes here (such as
+    /// `magnet` with their gateway) before creating sessions.
+    pub fn global() -> &'static SourceRegistry {
+        static GLOBAL: OnceLock<SourceRegistry> = OnceLock::new();
+        GLOBAL.get_or_init(SourceRegistry::with_defaults)
+    }
+
+    /// Register (or replace) the factory for a base scheme.
+    pub fn register_factory(&self, scheme: &str, factory: Arc<dyn SourceFactory>) {
+        self.factories
+            .write()
+            .insert(scheme.to_ascii_lowercase(), factory);
+    }
+
+    /// Remove the factory for a base scheme, if any.
+    pub fn unregister_factory(&self, scheme: &str) {
+        self.factories.write().remove(&scheme.to_ascii_lowercase());
+    }
+
+    /// Register (or replace) a decorator usable as a `<name>+` prefix.
+    pub fn register_decorator(&self, name: &str, decorator: Arc<dyn SourceDecorator>) {
+        self.decorators
+            .write()
+            .insert(name.to_ascii_lowercase(), decorator);
+    }
+
+    /// Build the source stack described by `descriptor`.
+    pub async fn resolve(&self, descriptor: &SourceDescriptor) -> Result<Arc<dyn MediaSource>> {
+        // `magnet:?xt=…` has no authority, so split on the first `:` and
+        // keep whatever follows (including any `//`) for the factory.
+        let (scheme, rest) = descriptor
+            .uri
+            .split_once(':')
+            .filter(|(scheme, _)| {
+                !scheme.is_empty()
+                    && scheme
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+            })
+            .ok_or_else(|| anyhow!("source uri has no scheme: {}", descriptor.uri))?;
+        let scheme = scheme.to_ascii_lowercase();
+        let mut layers: Vec<&str> = scheme.split('+').collect();
+        let base_scheme = layers.pop().unwrap_or_default();
+
+        // Decorator parameters live in the fragment, which is not part of
+        // the base resource. Plain URIs keep their fragment untouched.
+        let (base_rest, params) = if layers.is_empty() {
+            (rest, HashMap::new())
+        } else {
+            match rest.split_once('#') {
+                Some((r, fragment)) => (r, parse_params(fragment)),
+                None => (rest, HashMap::new()),
+            }
+        };
+        let params = layer_params(&layers, params)?;
+        let base_uri = format!("{}:{}", base_scheme, base_rest);
+
+        let factory = self
+            .factories
+            .read()
+            .get(base_scheme)
+            .cloned()
+            .ok_or_else(|| anyhow!("no source registered for scheme: {}", base_scheme))?;
+        let mut source = factory.create(&base_uri, &descriptor.headers).await?;
+
+        // Apply decorators innermost (rightmost) first.
+        for (name, params) in layers.iter().zip(&params).rev() {
+            let decorator = self
+                .decorators
+                .read()
+                .get(*name)
+                .cloned()
+                .ok_or_else(|| anyhow!("no source decorator registered for: {}+", name))?;
+            debug!("wrapping source with decorator {}+", name);
+            source = decorator.wrap(source, params).await?;
+        }
+
+        Ok(source)
+    }
+}
+
+impl Default for SourceRegistry {
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
+
+/// Parse `k=v&k2=v2` decorator parameters. Values are percent-decoded, so
+/// entry names may contain `&`, `#` or non-ASCII characters.
+fn parse_params(fragment: &str) -> HashMap<String, String> {
+    let decode = |s: &str| percent_decode_str(s).decode_utf8_lossy().into_owned();
+    fragment
+        .split('&')
+        .filter(|kv| !kv.is_empty())
+        .map(|kv| match kv.split_once('=') {
+            Some((k, v)) => (decode(k), decode(v)),
+            None => (decode(kv), String::new()),
+        })
+        .collect()
+}
+
+/// Split fragment parameters between decorator layers. A single decorator
+/// takes plain keys; a stack needs `<name>.<key>` so no two layers read the
+/// same value.
+fn layer_params(
+    layers: &[&str],
+    params: HashMap<String, String>,
+) -> Result<Vec<HashMap<String, String>>> {
+    if layers.len() == 1 {
+        return Ok(vec![params]);
+    }
+    for (i, name) in layers.iter().enumerate() {
+        if layers[..i].contains(name) {
+            return Err(anyhow!("source decorator {}+ is stacked twice", name));
+        }
+    }
+    let mut split = vec![HashMap::new(); layers.len()];
+    for (key, value) in params {
+        let layer = key
+            .split_once('.')
+            .and_then(|(name, key)| Some((layers.iter().position(|l| *l == name)?, key)));
+        let Some((i, key)) = layer else {
+            return Err(anyhow!(
+                "decorator parameter {} must name its layer, e.g. {}.{}",
+                key,
+                layers[0],
+                key
+            ));
+        };
+        split[i].insert(key.to_string(), value);
+    }
+    Ok(split)
+}
+
+fn parse_u64_param(params: &HashMap<String, String>, key: &str) -> Result<Option<u64>> {
+    params
+        .get(key)
+        .map(|v| {
+            v.parse::<u64>()
+                .map_err(|_| anyhow!("invalid {} parameter: {}", key, v))
+        })
+        .transpose()
+}
+
+struct HttpFactory;
+
+#[async_trait]
+impl SourceFactory for HttpFactory {
+    async fn create(
+        &self,
+        uri: &str,
+        headers: &HashMap<String, String>,
+    ) -> Result<Arc<dyn MediaSource>> {
+        // WebDAV serves file bodies over plain GET with Range support.
+        let url = if let Some(rest) = uri.strip_prefix("webdavs://") {
+            format!("https://{}", rest)
+        } else if let Some(rest) = uri.strip_prefix("webdav://") {
+            format!("http://{}", rest)
+        } else {
+            uri.to_string()
+        };
+        Ok(Arc::new(HttpSource::new(url, headers.clone())))
+    }
+}
+
+struct FileFactory;
+
+#[async_trait]
+impl SourceFactory for FileFactory {
+    async fn create(
+        &self,
+        uri: &str,
+        _headers: &HashMap<String, String>,
+    ) -> Result<Arc<dyn MediaSource>> {
+        let path: PathBuf = Url::parse(uri)
+            .map_err(|e| anyhow!("invalid file uri: {}", e))?
+            .to_file_path()
+            .map_err(|_| anyhow!("file uri is not a local path: {}", uri))?;
+        Ok(Arc::new(FileSource::new(path)))
+    }
+}
+
+/// `ftp://[user[:pass]@]host[:port]/path`; anonymous login without a user.
+struct FtpFactory;
+
+#[async_trait]
+impl SourceFactory for FtpFactory {
+    async fn create(
+        &self,
+        uri: &str,
+        _headers: &HashMap<String, String>,
+    ) -> Result<Arc<dyn MediaSource>> {
+        Ok(Arc::new(FtpSource::new(uri)?))
+    }
+}
+
+/// `alist://host/path` (`alists://` for HTTPS): an AList server path. The
+/// `Authorization` header is sent to `/api/fs/get`, and playback uses the
+/// returned raw URL, or the signed `/d/` download link when there is none.
+#[derive(Default)]
+struct AlistFactory {
+    client: Client,
+}
+
+#[derive(Deserialize)]
+struct AlistReply {
+    code: i64,
+    #[serde(default)]
+    message: String,
+    data: Option<AlistFile>,
+}
+
+#[derive(Deserialize)]
+struct AlistFile {
+    #[serde(default)]
+    raw_url: String,
+    #[serde(default)]
+    sign: String,
+}
+
+#[async_trait]
+impl SourceFactory for AlistFactory {
+    async fn create(
+        &self,
+        uri: &str,
+        headers: &HashMap<String, String>,
+    ) -> Result<Arc<dyn MediaSource>> {
+        let url = Url::parse(uri).map_err(|e| anyhow!("invalid alist uri: {}", e))?;
+        let http_scheme = if url.scheme() == "alists" {
+            "https"
+        } else {
+            "http"
+        };
+        let host = url
+            .host_str()
+            .ok_or_else(|| anyhow!("alist uri has no host: {}", uri))?;
+        let base = match url.port() {
+            Some(port) => format!("{}://{}:{}", http_scheme, host, port),
+            None => format!("{}://{}", http_scheme, host),
+        };
+        let path = percent_decode_str(url.path())
+            .decode_utf8_lossy()
+            .into_owned();
+
+        let mut req = self
+            .client
+            .post(format!("{}/api/fs/get", base))
+            .header("Content-Type", "application/json")
+            .body(serde_json::json!({ "path": path, "password": "" }).to_string());
+        for (k, v) in headers {
+            req = req.header(k.as_str(), v.as_str());
+        }
+        let resp = req.send().await?;
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(ProxyError::from_status(status.as_u16()).into());
+        }
+        let reply: AlistReply = serde_json::from_str(&resp.text().await?)
+            .map_err(|e| anyhow!("invalid alist reply: {}", e))?;
+        let file = match (reply.code, reply.data) {
+            (200, Some(file)) => file,
+            (code, _) => {
+                let kind = match code {
+                    401 | 403 => ProxyErrorKind::AuthExpired,
+                    404 => ProxyErrorKind::NotFound,
+                    _ => ProxyErrorKind::Upstream5xx,
+                };
+                let detail = format!("alist {}: {}", code, reply.message);
+                return Err(ProxyError::new(kind, detail).into());
+            }
+        };
+
+        // The raw URL usually points at the storage backend, which must not
+        // see the AList token; the `/d/` link is served by AList itself.
+        if !file.raw_url.is_empty() {
+            debug!("alist {} resolved to raw url", path);
+            return Ok(Arc::new(HttpSource::new(file.raw_url, HashMap::new())));
+        }
+        let mut download = Url::parse(&format!("{}/d", base))?;
+        download
+            .path_segments_mut()
+            .map_err(|_| anyhow!("invalid alist base: {}", base))?
+            .extend(path.split('/').filter(|s| !s.is_empty()));
+        if !file.sign.is_empty() {
+            download.query_pairs_mut().append_pair("sign", &file.sign);
+        }
+        Ok(Arc::new(HttpSource::new(
+            download.to_string(),
+            headers.clone(),
+        )))
+    }
+}
+
+/// `magnet:?xt=urn:btih:…`: streamed through a TorrServer-compatible gateway,
+/// which fetches pieces on demand and serves the file over ranged HTTP.
+/// `#index=N` picks the file inside the torrent (1-based, default 1).
+pub struct MagnetFactory {
+    gateway: String,
+}
+
+impl MagnetFactory {
+    pub fn new(gateway: impl Into<String>) -> Self {
+        Self {
+            gateway: gateway.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl SourceFactory for MagnetFactory {
+    async fn create(
+        &self,
+        uri: &str,
+        headers: &HashMap<String, String>,
+    ) -> Result<Arc<dyn MediaSource>> {
+        let (link, fragment) = uri.split_once('#').unwrap_or((uri, ""));
+        let index = parse_u64_param(&parse_params(fragment), "index")?.unwrap_or(1);
+        let mut url = Url::parse(&format!("{}/stream", self.gateway.trim_end_matches('/')))
+            .map_err(|e| anyhow!("invalid magnet gateway: {}", e))?;
+        url.query_pairs_mut()
+            .append_pair("link", link)
+            .append_pair("index", &index.to_string())
+            .append_key_only("play");
+        Ok(Arc::new(HttpSource::new(url.to_string(), headers.clone())))
+    }
+}
+
+/// `iso+`: expose a file inside a disc image at the `offset` / `length`
+/// given in the fragment, e.g. as listed by the app's ISO browser.
+struct IsoDecorator;
+
+#[async_trait]
+impl SourceDecorator for IsoDecorator {
+    async fn wrap(
+        &self,
+        inner: Arc<dyn MediaSource>,
+        params: &HashMap<String, String>,
+    ) -> Result<Arc<dyn MediaSource>> {
+        let offset = parse_u64_param(params, "offset")?;
+        let length = parse_u64_param(params, "length")?;
+        match (offset, length) {
+            (Some(offset), Some(length)) => {
+                let content_type = params
+                    .get("type")
+                    .cloned()
+                    .unwrap_or_else(|| "application/octet-stream".to_string());
+                Ok(Arc::new(IsoMediaSource::new(
+                    inner,
+                    offset,
+                    length,
+                    content_type,
+                )))
+            }
+            _ => Err(anyhow!("iso+ source needs both offset and length")),
+        }
+    }
+}
+
+/// `zip+`: expose a stored entry of a zip archive (`#entry=<path>`).
+struct ZipDecorator;
+
+#[async_trait]
+impl SourceDecorator for ZipDecorator {
+    async fn wrap(
+        &self,
+        inner: Arc<dyn MediaSource>,
+        params: &HashMap<String, String>,
+    ) -> Result<Arc<dyn MediaSource>> {
+        wrap_zip_entry(inner, params).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_params() {
+        let params = parse_params("offset=10&length=20&flag");
+        assert_eq!(params.get("offset").map(String::as_str), Some("10"));
+        assert_eq!(params.get("length").map(String::as_str), Some("20"));
+        assert_eq!(params.get("flag").map(String::as_str), Some(""));
+    }
+
+    #[test]
+    fn test_parse_params_percent_decodes() {
+        let params = parse_params("entry=%E5%89%A7%E9%9B%86/ep%2001%26extra.mkv");
+        assert_eq!(
+            params.get("entry").map(String::as_str),
+            Some("剧集/ep 01&extra.mkv")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_scheme_rejected() {
+        let registry = SourceRegistry::with_defaults();
+        let descriptor = SourceDescriptor {
+            uri: "gopher://abc".to_string(),
+            headers: HashMap::new(),
+        };
+        assert!(registry.resolve(&descriptor).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_decorator_rejected() {
+        let registry = SourceRegistry::with_defaults();
+        let descriptor = SourceDescriptor {
+            uri: "rar+https://example.com/a.rar#entry=a.mkv".to_string(),
+            headers: HashMap::new(),
+        };
+        assert!(registry.resolve(&descriptor).await.is_err());
+    }
+}
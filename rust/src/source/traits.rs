@@ -1,18 +1,58 @@
+use std::collections::HashMap;
+use std::pin::Pin;
+
 use anyhow::Result;
 use async_trait::async_trait;
 use bytes::Bytes;
+use tokio_stream::Stream;
+
+/// Body pieces of a ranged fetch, in order, as they arrive.
+pub type ByteStream = Pin<Box<dyn Stream<Item = Result<Bytes>> + Send>>;
 
 pub struct SourceInfo {
     pub content_length: u64,
     pub content_type: String,
     pub supports_range: bool,
+    /// Strong or weak entity tag, used to validate a persisted cache.
+    pub etag: Option<String>,
+    /// `Last-Modified` value (or file mtime), used alongside `etag`.
+    pub last_modified: Option<String>,
 }
 
 #[async_trait]
 pub trait MediaSource: Send + Sync {
     async fn probe(&self) -> Result<SourceInfo>;
     async fn fetch_range(&self, start: u64, end: u64) -> Result<Bytes>;
+
+    /// Like `fetch_range`, but yields the body while it is still arriving.
+    /// Sources that cannot stream return the whole range as one piece.
+    async fn fetch_range_stream(&self, start: u64, end: u64) -> Result<ByteStream> {
+        let data = self.fetch_range(start, end).await?;
+        Ok(Box::pin(tokio_stream::once(Ok(data))))
+    }
+
     async fn refresh_auth(&self) -> Result<()> {
         Ok(())
     }
+
+    /// Replace credentials (e.g. a re-signed URL or fresh cookies). Sources
+    /// without credentials ignore this; decorators forward it inward.
+    fn update_auth(&self, _new_url: String, _new_headers: HashMap<String, String>) {}
+
+    /// Key under which learned transfer settings are remembered across
+    /// sessions, usually the upstream host. `None` disables the memory.
+    fn host_key(&self) -> Option<String> {
+        None
+    }
+
+    /// Distinct upstream routes (e.g. resolved IPs) that successive requests
+    /// rotate over.
+    fn route_count(&self) -> usize {
+        1
+    }
+
+    /// Number of parallel fetches this source can usefully serve.
+    async fn effective_concurrency(&self, configured: u32) -> u32 {
+        configured
+    }
 }
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+
+use super::iso_source::IsoMediaSource;
+use super::traits::MediaSource;
+
+/// End-of-central-directory record signature.
+const EOCD_SIGNATURE: u32 = 0x0605_4b50;
+/// Central directory file header signature.
+const CDFH_SIGNATURE: u32 = 0x0201_4b50;
+/// Local file header signature.
+const LFH_SIGNATURE: u32 = 0x0403_4b50;
+/// EOCD is 22 bytes plus an optional comment of up to 64 KB.
+const EOCD_SEARCH_WINDOW: u64 = 22 + 0xFFFF;
+
+/// Location of a stored (uncompressed) entry inside a zip archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZipEntry {
+    pub name: String,
+    pub data_offset: u64,
+    pub size: u64,
+}
+
+fn le_u16(buf: &[u8], pos: usize) -> u16 {
+    u16::from_le_bytes([buf[pos], buf[pos + 1]])
+}
+
+fn le_u32(buf: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes([buf[pos], buf[pos + 1], buf[pos + 2], buf[pos + 3]])
+}
+
+/// Locate `entry_name` in the archive served by `source`.
+///
+/// Only stored entries can be exposed as a byte range; deflated entries
+/// would need the whole stream decompressed and are rejected.
+pub async fn find_zip_entry(source: &dyn MediaSource, entry_name: &str) -> Result<ZipEntry> {
+    let info = source.probe().await?;
+    let total = info.content_length;
+    if total < 22 {
+        return Err(anyhow!("zip archive too small ({} bytes)", total));
+    }
+
+    let tail_start = total.saturating_sub(EOCD_SEARCH_WINDOW);
+    let tail = source.fetch_range(tail_start, total - 1).await?;
+    let eocd = (0..tail.len().saturating_sub(21))
+        .rev()
+        .find(|&i| le_u32(&tail, i) == EOCD_SIGNATURE)
+        .ok_or_else(|| anyhow!("zip end-of-central-directory not found"))?;
+
+    let entries = le_u16(&tail, eocd + 10) as usize;
+    let cd_size = le_u32(&tail, eocd + 12) as u64;
+    let cd_offset = le_u32(&tail, eocd + 16) as u64;
+    if cd_size == 0xFFFF_FFFF || cd_offset == 0xFFFF_FFFF {
+        return Err(anyhow!("zip64 archives are not supported"));
+    }
+
+    let cd = source
+        .fetch_range(cd_offset, cd_offset + cd_size.max(1) - 1)
+        .await?;
+    let mut pos = 0usize;
+    for _ in 0..entries {
+        if pos + 46 > cd.len() || le_u32(&cd, pos) != CDFH_SIGNATURE {
+            break;
+        }
+        let method = le_u16(&cd, pos + 10);
+        let compressed = le_u32(&cd, pos + 20) as u64;
+        let name_len = le_u16(&cd, pos + 28) as usize;
+        let extra_len = le_u16(&cd, pos + 30) as usize;
+        let comment_len = le_u16(&cd, pos + 32) as usize;
+        let local_offset = le_u32(&cd, pos + 42) as u64;
+        let name_end = (pos + 46 + name_len).min(cd.len());
+        let name = String::from_utf8_lossy(&cd[pos + 46..name_end]).to_string();
+
+        if name == entry_name {
+            if method != 0 {
+                return Err(anyhow!(
+                    "zip entry {} is compressed (method {}), only stored entries can be streamed",
+                    name,
+                    method
+                ));
+            }
+            let lfh = source.fetch_range(local_offset, local_offset + 29).await?;
+            if lfh.len() < 30 || le_u32(&lfh, 0) != LFH_SIGNATURE {
+                return Err(anyhow!("zip local header for {} is corrupt", name));
+            }
+            let data_offset = local_offset + 30 + le_u16(&lfh, 26) as u64 + le_u16(&lfh, 28) as u64;
+            return Ok(ZipEntry {
+                name,
+                data_offset,
+                size: compressed,
+            });
+        }
+
+        pos += 46 + name_len + extra_len + comment_len;
+    }
+
+    Err(anyhow!("zip entry not found: {}", entry_name))
+}
+
+/// Expose the entry named by `params["entry"]` as its own source.
+pub async fn wrap_zip_entry(
+    inner: Arc<dyn MediaSource>,
+    params: &HashMap<String, String>,
+) -> Result<Arc<dyn MediaSource>> {
+    let entry_name = params
+        .get("entry")
+        .ok_or_else(|| anyhow!("zip+ source requires an #entry=<path> parameter"))?;
+    let entry = find_zip_entry(inner.as_ref(), entry_name).await?;
+    let content_type = params
+        .get("type")
+        .cloned()
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+    Ok(Arc::new(IsoMediaSource::new(
+        inner,
+        entry.data_offset,
+        entry.size,
+        content_type,
+    )))
+}
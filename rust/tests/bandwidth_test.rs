@@ -0,0 +1,139 @@
+// Bandwidth caps: background downloads are paced, urgent ones borrow.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use rust_lib_ma_palyer::engine::cache::{CacheValidator, DiskCache};
+use rust_lib_ma_palyer::engine::downloader::Downloader;
+use rust_lib_ma_palyer::engine::stats::StatsCollector;
+use rust_lib_ma_palyer::engine::store::CacheBackend;
+use rust_lib_ma_palyer::source::traits::{ByteStream, MediaSource, SourceInfo};
+
+const MB: u64 = 1024 * 1024;
+const CONTENT_LENGTH: u64 = 8 * MB;
+const PIECE: u64 = 64 * 1024;
+
+fn content(start: u64, end_inclusive: u64) -> Bytes {
+    (start..=end_inclusive).map(|i| (i % 233) as u8).collect()
+}
+
+/// Serves every range at once, in 64 KB pieces.
+struct FastSource;
+
+#[async_trait]
+impl MediaSource for FastSource {
+    async fn probe(&self) -> Result<SourceInfo> {
+        Ok(SourceInfo {
+            content_length: CONTENT_LENGTH,
+            content_type: "video/mp4".to_string(),
+            supports_range: true,
+            etag: None,
+            last_modified: None,
+        })
+    }
+
+    async fn fetch_range(&self, start: u64, end: u64) -> Result<Bytes> {
+        Ok(content(start, end))
+    }
+
+    async fn fetch_range_stream(&self, start: u64, end: u64) -> Result<ByteStream> {
+        let pieces: Vec<Result<Bytes>> = (start..=end)
+            .step_by(PIECE as usize)
+            .map(|at| Ok(content(at, (at + PIECE - 1).min(end))))
+            .collect();
+        Ok(Box::pin(tokio_stream::iter(pieces)))
+    }
+}
+
+fn setup() -> (Arc<DiskCache>, Downloader) {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = Arc::new(
+        DiskCache::open_with_backend(
+            dir.path(),
+            "bandwidth",
+            CONTENT_LENGTH,
+            MB,
+            &CacheValidator::default(),
+            CacheBackend::Memory,
+        )
+        .unwrap(),
+    );
+    let downloader = Downloader::new(
+        Arc::new(FastSource),
+        cache.clone(),
+        3,
+        Arc::new(StatsCollector::new()),
+    );
+    (cache, downloader)
+}
+
+#[tokio::test]
+async fn test_session_cap_paces_background_downloads() {
+    let (cache, downloader) = setup();
+    downloader.set_bandwidth_limit(MB);
+    assert_eq!(downloader.bandwidth_limit(), MB);
+
+    let started = Instant::now();
+    downloader.prefetch_range(0, 2);
+    for i in 0..2 {
+        let ready = tokio::time::timeout(Duration::from_secs(10), downloader.wait_for_chunk(i))
+            .await
+            .unwrap();
+        assert!(ready.is_ok());
+    }
+    // 2 MB at 1 MB/s with an empty bucket: the last piece is paid for
+    // after roughly two seconds.
+    assert!(started.elapsed() >= Duration::from_millis(1500));
+    assert_eq!(cache.read_chunk(1).unwrap(), content(MB, 2 * MB - 1));
+
+    downloader.shutdown();
+}
+
+#[tokio::test]
+async fn test_urgent_chunk_borrows_beyond_cap() {
+    let (_cache, downloader) = setup();
+    downloader.set_bandwidth_limit(256 * 1024);
+
+    let started = Instant::now();
+    downloader.start_urgent_prefetch(3);
+    let ready = tokio::time::timeout(Duration::from_secs(2), downloader.wait_for_chunk(3))
+        .await
+        .expect("urgent chunk should not wait for the cap");
+    assert!(ready.is_ok());
+    assert!(started.elapsed() < Duration::from_secs(2));
+
+    // Lifting the cap releases paced background work promptly.
+    downloader.prefetch_range(5, 6);
+    downloader.set_bandwidth_limit(0);
+    let ready = tokio::time::timeout(Duration::from_secs(2), downloader.wait_for_chunk(5))
+        .await
+        .unwrap();
+    assert!(ready.is_ok());
+
+    downloader.shutdown();
+}
+
+#[tokio::test]
+async fn test_merged_chunk_promoted_mid_write_stops_pacing() {
+    let (cache, downloader) = setup();
+    downloader.set_bandwidth_limit(256 * 1024);
+
+    // Background chunks share one paced request; chunk 0 is being written
+    // when playback asks for it.
+    downloader.prefetch_range(0, 3);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let started = Instant::now();
+    downloader.start_urgent_prefetch(0);
+    let ready = tokio::time::timeout(Duration::from_secs(2), downloader.wait_for_chunk(0))
+        .await
+        .expect("promoted member should stop waiting for the cap");
+    assert!(ready.is_ok());
+    assert!(started.elapsed() < Duration::from_secs(2));
+    assert_eq!(cache.read_chunk(0).unwrap(), content(0, MB - 1));
+
+    downloader.shutdown();
+}
@@ -0,0 +1,117 @@
+// Container bitrate detection: MP4 mvhd, Matroska Info/Duration and
+// MPEG-TS PCR spans.
+
+mod common;
+
+use rust_lib_ma_palyer::detect::bitrate::{estimate_bitrate, mkv_duration, mp4_duration};
+
+use common::{mp4_box, MemorySource};
+
+fn mvhd_v0(timescale: u32, duration: u32) -> Vec<u8> {
+    let mut body = vec![0u8; 100];
+    body[12..16].copy_from_slice(&timescale.to_be_bytes());
+    body[16..20].copy_from_slice(&duration.to_be_bytes());
+    mp4_box(b"mvhd", &body)
+}
+
+#[tokio::test]
+async fn test_mp4_bitrate_from_tail_moov() {
+    let mut file = mp4_box(b"ftyp", b"isom\0\0\0\0");
+    file.extend(mp4_box(b"mdat", &vec![0u8; 400_000]));
+    let moov = mp4_box(b"moov", &mvhd_v0(1000, 100_000));
+    assert_eq!(mp4_duration(&moov), Some(100.0));
+    file.extend(moov);
+
+    let len = file.len() as u64;
+    let rate = estimate_bitrate(&MemorySource(file.into()), len)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!((rate - len as f64 / 100.0).abs() < 1e-6);
+}
+
+/// EBML element with a one-byte size.
+fn ebml(id: &[u8], body: &[u8]) -> Vec<u8> {
+    assert!(body.len() < 0x7F);
+    let mut out = id.to_vec();
+    out.push(0x80 | body.len() as u8);
+    out.extend_from_slice(body);
+    out
+}
+
+fn mkv_header(children: &[Vec<u8>]) -> Vec<u8> {
+    let mut file = ebml(&[0x1A, 0x45, 0xDF, 0xA3], &ebml(&[0x42, 0x82], b"webm"));
+    // Segment of unknown size, as live muxers write it.
+    file.extend_from_slice(&[
+        0x18, 0x53, 0x80, 0x67, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    ]);
+    for child in children {
+        file.extend_from_slice(child);
+    }
+    file
+}
+
+#[tokio::test]
+async fn test_mkv_duration_from_segment_info() {
+    let seek_head = ebml(&[0x11, 0x4D, 0x9B, 0x74], &[0u8; 12]);
+    let mut info = ebml(&[0x2A, 0xD7, 0xB1], &1_000_000u32.to_be_bytes());
+    info.extend(ebml(&[0x44, 0x89], &90_000f64.to_be_bytes()));
+    let info = ebml(&[0x15, 0x49, 0xA9, 0x66], &info);
+    let mut file = mkv_header(&[seek_head, info]);
+    assert_eq!(mkv_duration(&file), Some(90.0));
+
+    file.resize(900_000, 0);
+    let rate = estimate_bitrate(&MemorySource(file.into()), 900_000)
+        .await
+        .unwrap();
+    assert_eq!(rate, Some(10_000.0));
+
+    // Media data before Info: no duration to be had from the header.
+    let cluster = ebml(&[0x1F, 0x43, 0xB6, 0x75], &[0u8; 4]);
+    assert_eq!(mkv_duration(&mkv_header(&[cluster])), None);
+}
+
+fn ts_packet(pcr_secs: Option<f64>) -> Vec<u8> {
+    let mut p = vec![0xFFu8; 188];
+    p[0] = 0x47;
+    p[1] = 0x01;
+    p[2] = 0x00;
+    match pcr_secs {
+        Some(secs) => {
+            let pcr = (secs * 27_000_000.0) as u64;
+            let (base, ext) = (pcr / 300, pcr % 300);
+            p[3] = 0x20;
+            p[4] = 183;
+            p[5] = 0x10;
+            p[6] = (base >> 25) as u8;
+            p[7] = (base >> 17) as u8;
+            p[8] = (base >> 9) as u8;
+            p[9] = (base >> 1) as u8;
+            p[10] = (((base & 1) << 7) as u8) | 0x7E | ((ext >> 8) as u8);
+            p[11] = ext as u8;
+        }
+        None => p[3] = 0x10,
+    }
+    p
+}
+
+#[tokio::test]
+async fn test_ts_bitrate_from_pcr_span() {
+    let packets = 2000;
+    let mut file = Vec::new();
+    for k in 0..packets {
+        let pcr = match k {
+            0 => Some(10.0),
+            k if k == packets - 1 => Some(20.0),
+            _ => None,
+        };
+        file.extend(ts_packet(pcr));
+    }
+    let len = file.len() as u64;
+    let rate = estimate_bitrate(&MemorySource(file.into()), len)
+        .await
+        .unwrap()
+        .unwrap();
+    let expected = ((packets - 1) * 188) as f64 / 10.0;
+    assert!((rate - expected).abs() < 1.0, "{} vs {}", rate, expected);
+}
@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use rust_lib_ma_palyer::config::CONCURRENCY_STATE_FILE;
+use rust_lib_ma_palyer::engine::cache::{CacheValidator, DiskCache};
+use rust_lib_ma_palyer::engine::cache_manager::CacheManager;
+
+const MB: u64 = 1024 * 1024;
+
+/// Fill every chunk of a fresh cache so the sidecar records `len` bytes.
+fn fill_cache(dir: &std::path::Path, key: &str, len: u64) {
+    let cache = DiskCache::open(dir, key, len, MB, &CacheValidator::default()).unwrap();
+    for i in 0..cache.total_chunks() {
+        let data = vec![0u8; cache.chunk_len(i)];
+        cache.put_chunk(i, &data).unwrap();
+    }
+}
+
+#[test]
+fn test_startup_scan_removes_orphans() {
+    let dir = tempfile::tempdir().unwrap();
+    fill_cache(dir.path(), "kept", 2 * MB);
+    std::fs::write(dir.path().join("crashed.cache"), b"partial").unwrap();
+    std::fs::write(dir.path().join("lonely.json"), b"{}").unwrap();
+    std::fs::write(dir.path().join("kept.json.tmp"), b"").unwrap();
+    std::fs::create_dir(dir.path().join("stray.chunks")).unwrap();
+    std::fs::write(dir.path().join("stray.chunks/00000000.bin"), b"x").unwrap();
+
+    let manager = CacheManager::new(dir.path(), 0);
+    assert_eq!(manager.startup_scan().unwrap(), 4);
+
+    assert!(dir.path().join("kept.cache").exists());
+    assert!(dir.path().join("kept.json").exists());
+    assert!(!dir.path().join("crashed.cache").exists());
+    assert!(!dir.path().join("lonely.json").exists());
+    assert!(!dir.path().join("stray.chunks").exists());
+    assert_eq!(manager.total_usage(), 2 * MB);
+}
+
+#[test]
+fn test_startup_scan_keeps_learned_concurrency() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = dir.path().join(CONCURRENCY_STATE_FILE);
+    std::fs::write(&state, br#"{"host.example:443":3}"#).unwrap();
+
+    let manager = CacheManager::new(dir.path(), 0);
+    assert_eq!(manager.startup_scan().unwrap(), 0);
+    assert!(state.exists());
+    assert!(manager.entries().is_empty());
+}
+
+#[test]
+fn test_acquire_evicts_least_recently_used() {
+    let dir = tempfile::tempdir().unwrap();
+    fill_cache(dir.path(), "oldest", 3 * MB);
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    fill_cache(dir.path(), "middle", 3 * MB);
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    fill_cache(dir.path(), "newest", 3 * MB);
+
+    let manager = Arc::new(CacheManager::new(dir.path(), 10 * MB));
+    assert_eq!(manager.total_usage(), 9 * MB);
+
+    // Keep "oldest" in use so it must be skipped.
+    let _active = manager.acquire("oldest", 0);
+    let _lease = manager.acquire("incoming", 3 * MB);
+
+    let keys: Vec<String> = manager.entries().into_iter().map(|e| e.key).collect();
+    assert!(keys.contains(&"oldest".to_string()));
+    assert!(!keys.contains(&"middle".to_string()));
+    assert!(keys.contains(&"newest".to_string()));
+    assert!(!dir.path().join("middle.cache").exists());
+}
+
+#[test]
+fn test_pinned_entries_survive_eviction_and_clear() {
+    let dir = tempfile::tempdir().unwrap();
+    fill_cache(dir.path(), "pinned", 4 * MB);
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    fill_cache(dir.path(), "plain", 4 * MB);
+
+    let manager = Arc::new(CacheManager::new(dir.path(), 6 * MB));
+    manager.set_pinned("pinned", true).unwrap();
+
+    // "pinned" is the least recently used but must be skipped.
+    let _lease = manager.acquire("incoming", 2 * MB);
+    let keys: Vec<String> = manager.entries().into_iter().map(|e| e.key).collect();
+    assert_eq!(keys, vec!["pinned".to_string()]);
+
+    assert_eq!(manager.clear(false), 0);
+    assert_eq!(manager.clear(true), 1);
+    assert!(manager.entries().is_empty());
+}
+
+#[test]
+fn test_label_and_pin_persist_in_sidecar() {
+    let dir = tempfile::tempdir().unwrap();
+    {
+        let cache = DiskCache::open(
+            dir.path(),
+            "labelled",
+            2 * MB,
+            MB,
+            &CacheValidator::default(),
+        )
+        .unwrap();
+        cache.set_label(Some("fid-42".to_string()), Some("Episode 1".to_string()));
+        cache.set_pinned(true);
+    }
+
+    let manager = CacheManager::new(dir.path(), 0);
+    let entry = manager.entries().pop().unwrap();
+    assert_eq!(entry.file_key.as_deref(), Some("fid-42"));
+    assert_eq!(entry.title.as_deref(), Some("Episode 1"));
+    assert!(entry.pinned);
+}
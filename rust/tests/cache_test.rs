@@ -1,4 +1,15 @@
-use rust_lib_ma_palyer::engine::cache::DiskCache;
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use rust_lib_ma_palyer::config::EngineConfig;
+use rust_lib_ma_palyer::engine::cache::{cache_meta_path, CacheMeta, CacheValidator, DiskCache};
+use rust_lib_ma_palyer::engine::cache_manager::CacheManager;
+use rust_lib_ma_palyer::engine::session::ProxySession;
+use rust_lib_ma_palyer::engine::store::CacheBackend;
+
+use common::OpaqueSource;
 
 const MB: u64 = 1024 * 1024;
 
@@ -66,3 +77,435 @@ fn test_disk_cache_last_chunk_shorter() {
     let read_back = cache.read_chunk(2).unwrap();
     assert_eq!(read_back, data);
 }
+
+#[test]
+fn test_disk_cache_resumes_after_reopen() {
+    let dir = tempfile::tempdir().unwrap();
+    let validator = CacheValidator {
+        etag: Some("\"v1\"".to_string()),
+        last_modified: None,
+    };
+
+    {
+        let cache = DiskCache::open(dir.path(), "warm", 5 * MB, 2 * MB, &validator).unwrap();
+        cache.put_chunk(0, &vec![0x11u8; 2 * MB as usize]).unwrap();
+        cache.put_chunk(2, &vec![0x22u8; MB as usize]).unwrap();
+    }
+
+    let cache = DiskCache::open(dir.path(), "warm", 5 * MB, 2 * MB, &validator).unwrap();
+    assert!(cache.has_chunk(0));
+    assert!(!cache.has_chunk(1));
+    assert!(cache.has_chunk(2));
+    assert_eq!(cache.cached_bytes(), 3 * MB);
+    assert_eq!(cache.read_chunk(2).unwrap(), vec![0x22u8; MB as usize]);
+}
+
+#[test]
+fn test_disk_cache_invalidated_on_validator_change() {
+    let dir = tempfile::tempdir().unwrap();
+    let v1 = CacheValidator {
+        etag: Some("\"v1\"".to_string()),
+        last_modified: None,
+    };
+    let v2 = CacheValidator {
+        etag: Some("\"v2\"".to_string()),
+        last_modified: None,
+    };
+
+    {
+        let cache = DiskCache::open(dir.path(), "stale", 4 * MB, 2 * MB, &v1).unwrap();
+        cache.put_chunk(0, &vec![0x33u8; 2 * MB as usize]).unwrap();
+    }
+
+    // Different ETag: previous chunks must not be served.
+    let cache = DiskCache::open(dir.path(), "stale", 4 * MB, 2 * MB, &v2).unwrap();
+    assert!(!cache.has_chunk(0));
+    assert_eq!(cache.cached_bytes(), 0);
+    drop(cache);
+
+    // Different length: also discarded.
+    let cache = DiskCache::open(dir.path(), "stale", 6 * MB, 2 * MB, &v2).unwrap();
+    assert!(!cache.has_chunk(0));
+}
+
+#[test]
+fn test_disk_cache_budget_evicts_far_behind_playback() {
+    let dir = tempfile::tempdir().unwrap();
+    // 40 MB file, 2 MB chunks, budget of 16 MB (8 chunks).
+    let cache = DiskCache::new(dir.path(), "budget", 40 * MB, 2 * MB).unwrap();
+    cache.set_byte_budget(16 * MB);
+
+    for i in 0..20 {
+        cache.set_playback_offset(i as u64 * 2 * MB);
+        cache.put_chunk(i, &vec![i as u8; 2 * MB as usize]).unwrap();
+        assert!(cache.cached_bytes() <= 16 * MB);
+    }
+
+    // The oldest chunks were dropped, the most recent ones kept.
+    assert!(!cache.has_chunk(0));
+    assert!(!cache.has_chunk(5));
+    assert!(cache.has_chunk(19));
+    assert!(cache.has_chunk(18));
+    assert_eq!(cache.cached_bytes(), 16 * MB);
+    assert_eq!(cache.read_chunk(19).unwrap(), vec![19u8; 2 * MB as usize]);
+
+    // Evicted chunks no longer occupy disk blocks.
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let meta = std::fs::metadata(dir.path().join("budget.cache")).unwrap();
+        assert!(meta.blocks() * 512 <= 18 * MB);
+    }
+}
+
+#[test]
+fn test_budget_end_chunk_leaves_room_for_kept_chunks() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = DiskCache::new(dir.path(), "horizon", 40 * MB, 2 * MB).unwrap();
+    assert_eq!(cache.budget_end_chunk(), None);
+
+    // 16 MB budget, 6 MB of it kept behind playback chunk 10.
+    cache.set_byte_budget(16 * MB);
+    for i in 7..10 {
+        cache.put_chunk(i, &vec![i as u8; 2 * MB as usize]).unwrap();
+    }
+    cache.set_playback_offset(10 * 2 * MB);
+    assert_eq!(cache.budget_end_chunk(), Some(15));
+
+    // A budget below one chunk still lets the playback chunk through.
+    cache.set_byte_budget(MB);
+    assert_eq!(cache.budget_end_chunk(), Some(11));
+}
+
+#[tokio::test]
+async fn test_prefetch_stops_at_session_budget() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = Arc::new(OpaqueSource::new(64 * MB));
+    let session = ProxySession::new(
+        "budgeted".to_string(),
+        source.clone(),
+        Arc::new(CacheManager::new(dir.path(), 0)),
+        &EngineConfig {
+            chunk_size: 2 * MB,
+            max_session_cache_bytes: 8 * MB,
+            ..EngineConfig::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    session.serve_range(0, 2 * MB).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    // Four chunks fit; prefetching more would only evict them again.
+    let starts = source.starts.lock().clone();
+    assert!(
+        starts.iter().all(|&s| !(8 * MB..56 * MB).contains(&s)),
+        "{:?}",
+        starts
+    );
+    session.shutdown();
+}
+
+#[test]
+fn test_chunk_file_backend_reads_across_chunks_and_resumes() {
+    let dir = tempfile::tempdir().unwrap();
+    let validator = CacheValidator::default();
+    let open = || {
+        DiskCache::open_with_backend(
+            dir.path(),
+            "files",
+            5 * MB,
+            2 * MB,
+            &validator,
+            CacheBackend::ChunkFiles,
+        )
+        .unwrap()
+    };
+
+    {
+        let cache = open();
+        assert_eq!(cache.backend(), CacheBackend::ChunkFiles);
+        cache.put_chunk(0, &vec![0x44u8; 2 * MB as usize]).unwrap();
+        cache.put_chunk(1, &vec![0x55u8; 2 * MB as usize]).unwrap();
+
+        // A range spanning the chunk boundary is stitched from two files.
+        let data = cache.read_range(2 * MB - 2, 2 * MB + 2).unwrap();
+        assert_eq!(data, vec![0x44, 0x44, 0x55, 0x55]);
+    }
+    assert!(!dir.path().join("files.cache").exists());
+    assert!(dir.path().join("files.chunks").is_dir());
+
+    let cache = open();
+    assert!(cache.has_chunk(0));
+    assert!(cache.has_chunk(1));
+    assert_eq!(cache.cached_bytes(), 4 * MB);
+}
+
+#[test]
+fn test_chunk_file_backend_budget_removes_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = DiskCache::open_with_backend(
+        dir.path(),
+        "files_budget",
+        40 * MB,
+        2 * MB,
+        &CacheValidator::default(),
+        CacheBackend::ChunkFiles,
+    )
+    .unwrap();
+    cache.set_byte_budget(16 * MB);
+
+    for i in 0..20 {
+        cache.set_playback_offset(i as u64 * 2 * MB);
+        cache.put_chunk(i, &vec![i as u8; 2 * MB as usize]).unwrap();
+    }
+
+    let files = std::fs::read_dir(dir.path().join("files_budget.chunks"))
+        .unwrap()
+        .count();
+    assert_eq!(files, 8);
+    assert_eq!(cache.read_chunk(19).unwrap(), vec![19u8; 2 * MB as usize]);
+}
+
+#[test]
+fn test_memory_backend_is_bounded_and_not_persisted() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = DiskCache::open_with_backend(
+        dir.path(),
+        "clip",
+        40 * MB,
+        2 * MB,
+        &CacheValidator::default(),
+        CacheBackend::Memory,
+    )
+    .unwrap();
+    // Without a session budget the store's own capacity applies.
+    assert!(cache.byte_budget() > 0);
+    cache.set_byte_budget(16 * MB);
+    assert_eq!(cache.byte_budget(), 16 * MB);
+
+    for i in 0..20 {
+        cache.set_playback_offset(i as u64 * 2 * MB);
+        cache.put_chunk(i, &vec![i as u8; 2 * MB as usize]).unwrap();
+        assert!(cache.cached_bytes() <= 16 * MB);
+    }
+    assert!(!cache.has_chunk(0));
+    assert_eq!(cache.read_chunk(19).unwrap(), vec![19u8; 2 * MB as usize]);
+    drop(cache);
+
+    // Nothing touches the disk.
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn test_backend_switch_discards_other_backend_data() {
+    let dir = tempfile::tempdir().unwrap();
+    let validator = CacheValidator::default();
+    {
+        let cache = DiskCache::open_with_backend(
+            dir.path(),
+            "switch",
+            4 * MB,
+            2 * MB,
+            &validator,
+            CacheBackend::Mmap,
+        )
+        .unwrap();
+        cache.put_chunk(0, &vec![0x66u8; 2 * MB as usize]).unwrap();
+    }
+
+    let cache = DiskCache::open_with_backend(
+        dir.path(),
+        "switch",
+        4 * MB,
+        2 * MB,
+        &validator,
+        CacheBackend::ChunkFiles,
+    )
+    .unwrap();
+    assert!(!cache.has_chunk(0));
+    assert!(!dir.path().join("switch.cache").exists());
+}
+
+#[test]
+fn test_read_bytes_views_survive_eviction() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = DiskCache::new(dir.path(), "views", 48 * MB, 2 * MB).unwrap();
+    cache.set_byte_budget(16 * MB);
+
+    cache.put_chunk(0, &vec![0x77u8; 2 * MB as usize]).unwrap();
+    let view = cache.read_bytes(MB, 2 * MB).unwrap();
+    assert_eq!(view.len(), MB as usize);
+
+    // Chunk 0 is evicted while the view is still held.
+    for i in 1..20 {
+        cache.set_playback_offset(i as u64 * 2 * MB);
+        cache.put_chunk(i, &vec![i as u8; 2 * MB as usize]).unwrap();
+    }
+    assert!(!cache.has_chunk(0));
+    assert!(cache.read_bytes(0, MB).is_none());
+    assert!(view.iter().all(|&b| b == 0x77));
+    drop(view);
+
+    // Storage is given back on the next write once no views remain.
+    cache.set_playback_offset(20 * 2 * MB);
+    cache.put_chunk(20, &vec![20u8; 2 * MB as usize]).unwrap();
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let meta = std::fs::metadata(dir.path().join("views.cache")).unwrap();
+        assert!(meta.blocks() * 512 <= 18 * MB);
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_view_holds_back_only_its_own_chunk() {
+    use std::os::unix::fs::MetadataExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let cache = DiskCache::new(dir.path(), "held", 48 * MB, 2 * MB).unwrap();
+    cache.set_byte_budget(16 * MB);
+    let allocated = || {
+        std::fs::metadata(dir.path().join("held.cache"))
+            .unwrap()
+            .blocks()
+            * 512
+    };
+
+    cache.put_chunk(0, &vec![0x55u8; 2 * MB as usize]).unwrap();
+    let view = cache.read_bytes(0, MB).unwrap();
+
+    // Other evicted chunks are punched right away while the view is alive;
+    // only chunk 0 stays allocated.
+    for i in 1..20 {
+        cache.set_playback_offset(i as u64 * 2 * MB);
+        cache.put_chunk(i, &vec![i as u8; 2 * MB as usize]).unwrap();
+    }
+    assert!(!cache.has_chunk(0));
+    assert!(allocated() <= 20 * MB);
+    assert!(view.iter().all(|&b| b == 0x55));
+
+    // Dropping the last view releases chunk 0 without another write.
+    drop(view);
+    assert!(allocated() <= 18 * MB);
+}
+
+#[test]
+fn test_partial_writes_are_readable_before_completion() {
+    for backend in [
+        CacheBackend::Mmap,
+        CacheBackend::ChunkFiles,
+        CacheBackend::Memory,
+    ] {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = DiskCache::open_with_backend(
+            dir.path(),
+            "partial",
+            5 * MB,
+            2 * MB,
+            &CacheValidator::default(),
+            backend,
+        )
+        .unwrap();
+        let chunk: Vec<u8> = (0..2 * MB).map(|i| (i % 253) as u8).collect();
+
+        assert_eq!(
+            cache.write_partial(1, 0, &chunk[..MB as usize]).unwrap(),
+            MB
+        );
+        assert_eq!(cache.available_in_chunk(1), MB);
+        assert!(!cache.has_chunk(1));
+
+        // Bytes below the high-water mark are served, the rest is not.
+        let head = cache.read_bytes(2 * MB + 10, 2 * MB + 20).unwrap();
+        assert_eq!(&head[..], &chunk[10..20], "{:?}", backend);
+        assert!(cache.read_bytes(2 * MB, 3 * MB + 1).is_none());
+
+        // Gaps are rejected; finishing early is rejected.
+        assert!(cache.write_partial(1, MB + 1, &[0]).is_err());
+        assert!(cache.finish_chunk(1).is_err());
+
+        cache.write_partial(1, MB, &chunk[MB as usize..]).unwrap();
+        cache.finish_chunk(1).unwrap();
+        assert!(cache.has_chunk(1));
+        assert_eq!(cache.read_chunk(1).unwrap(), chunk, "{:?}", backend);
+        assert_eq!(cache.cached_bytes(), 2 * MB);
+    }
+}
+
+#[test]
+fn test_partial_chunk_resumes_after_reopen() {
+    for backend in [CacheBackend::Mmap, CacheBackend::ChunkFiles] {
+        let dir = tempfile::tempdir().unwrap();
+        let chunk: Vec<u8> = (0..2 * MB).map(|i| (i % 249) as u8).collect();
+        {
+            let cache = DiskCache::open_with_backend(
+                dir.path(),
+                "resume",
+                5 * MB,
+                2 * MB,
+                &CacheValidator::default(),
+                backend,
+            )
+            .unwrap();
+            cache.write_partial(1, 0, &chunk[..MB as usize]).unwrap();
+        }
+
+        let cache = DiskCache::open_with_backend(
+            dir.path(),
+            "resume",
+            5 * MB,
+            2 * MB,
+            &CacheValidator::default(),
+            backend,
+        )
+        .unwrap();
+        assert_eq!(cache.available_in_chunk(1), MB, "{:?}", backend);
+        assert!(!cache.has_chunk(1));
+        let head = cache.read_bytes(2 * MB, 2 * MB + 16).unwrap();
+        assert_eq!(&head[..], &chunk[..16]);
+
+        // Only the missing suffix needs writing.
+        cache.write_partial(1, MB, &chunk[MB as usize..]).unwrap();
+        cache.finish_chunk(1).unwrap();
+        assert_eq!(cache.read_chunk(1).unwrap(), chunk, "{:?}", backend);
+    }
+}
+
+#[test]
+fn test_mmap_resume_skips_chunks_without_data() {
+    let dir = tempfile::tempdir().unwrap();
+    let open = || {
+        DiskCache::open(
+            dir.path(),
+            "holes",
+            8 * MB,
+            2 * MB,
+            &CacheValidator::default(),
+        )
+    };
+    {
+        let cache = open().unwrap();
+        cache.put_chunk(0, &vec![0x66u8; 2 * MB as usize]).unwrap();
+        cache
+            .write_partial(2, 0, &vec![0x77u8; MB as usize])
+            .unwrap();
+        cache.persist_meta().unwrap();
+    }
+
+    // A sidecar listing chunks whose bytes never reached the data file.
+    let meta_path = cache_meta_path(dir.path(), "holes");
+    let mut meta = CacheMeta::load(&meta_path).unwrap();
+    meta.downloaded_chunks.push(1);
+    meta.partial_chunks.insert(3, MB);
+    meta.store(&meta_path).unwrap();
+
+    let cache = open().unwrap();
+    assert!(cache.has_chunk(0));
+    assert!(!cache.has_chunk(1));
+    assert_eq!(cache.available_in_chunk(2), MB);
+    assert_eq!(cache.available_in_chunk(3), 0);
+    assert_eq!(cache.cached_bytes(), 2 * MB);
+}
@@ -0,0 +1,81 @@
+// Fixtures shared by the integration tests. Each test crate uses only some
+// of them.
+#![allow(dead_code)]
+
+use anyhow::Result;
+use async_trait::async_trait;
+use bytes::Bytes;
+use parking_lot::Mutex;
+
+use rust_lib_ma_palyer::source::traits::{ByteStream, MediaSource, SourceInfo};
+
+/// A whole file held in memory.
+pub struct MemorySource(pub Bytes);
+
+#[async_trait]
+impl MediaSource for MemorySource {
+    async fn probe(&self) -> Result<SourceInfo> {
+        Ok(SourceInfo {
+            content_length: self.0.len() as u64,
+            content_type: "application/octet-stream".to_string(),
+            supports_range: true,
+            etag: None,
+            last_modified: None,
+        })
+    }
+
+    async fn fetch_range(&self, start: u64, end: u64) -> Result<Bytes> {
+        Ok(self.0.slice(start as usize..=end as usize))
+    }
+}
+
+/// ISO BMFF box with a 32-bit size.
+pub fn mp4_box(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut out = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(body);
+    out
+}
+
+/// Content of no known container; records the start of every range.
+pub struct OpaqueSource {
+    pub starts: Mutex<Vec<u64>>,
+    content_length: u64,
+}
+
+impl OpaqueSource {
+    pub fn new(content_length: u64) -> Self {
+        Self {
+            starts: Mutex::new(Vec::new()),
+            content_length,
+        }
+    }
+}
+
+/// The bytes `OpaqueSource` serves for a range; never a container magic.
+pub fn content(start: u64, end_inclusive: u64) -> Bytes {
+    (start..=end_inclusive).map(|i| (i % 7) as u8 + 1).collect()
+}
+
+#[async_trait]
+impl MediaSource for OpaqueSource {
+    async fn probe(&self) -> Result<SourceInfo> {
+        Ok(SourceInfo {
+            content_length: self.content_length,
+            content_type: "application/octet-stream".to_string(),
+            supports_range: true,
+            etag: None,
+            last_modified: None,
+        })
+    }
+
+    async fn fetch_range(&self, start: u64, end: u64) -> Result<Bytes> {
+        self.starts.lock().push(start);
+        Ok(content(start, end))
+    }
+
+    async fn fetch_range_stream(&self, start: u64, end: u64) -> Result<ByteStream> {
+        self.starts.lock().push(start);
+        Ok(Box::pin(tokio_stream::once(Ok(content(start, end)))))
+    }
+}
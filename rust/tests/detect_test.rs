@@ -63,3 +63,63 @@ fn test_find_moov_not_present() {
     let result = find_moov_box(&header);
     assert_eq!(result, None);
 }
+
+/// Sparse MP4: box headers at fixed offsets, zeros elsewhere. Records the
+/// length of every fetch.
+struct SparseMp4 {
+    boxes: Vec<(u64, u64, [u8; 4])>,
+    content_length: u64,
+    fetches: parking_lot::Mutex<Vec<u64>>,
+}
+
+#[async_trait::async_trait]
+impl rust_lib_ma_palyer::source::traits::MediaSource for SparseMp4 {
+    async fn probe(&self) -> anyhow::Result<rust_lib_ma_palyer::source::traits::SourceInfo> {
+        unimplemented!()
+    }
+
+    async fn fetch_range(&self, start: u64, end: u64) -> anyhow::Result<bytes::Bytes> {
+        self.fetches.lock().push(end - start + 1);
+        let mut out = vec![0u8; (end - start + 1) as usize];
+        for &(offset, size, kind) in &self.boxes {
+            let mut header = (size as u32).to_be_bytes().to_vec();
+            header.extend_from_slice(&kind);
+            for (k, byte) in header.into_iter().enumerate() {
+                let at = offset + k as u64;
+                if (start..=end).contains(&at) {
+                    out[(at - start) as usize] = byte;
+                }
+            }
+        }
+        Ok(out.into())
+    }
+}
+
+#[tokio::test]
+async fn test_warmup_walks_atoms_to_large_tail_moov() {
+    use rust_lib_ma_palyer::engine::warmup::compute_warmup_ranges;
+
+    const MB: u64 = 1024 * 1024;
+    let mdat = 50 * MB;
+    let moov_at = 16 + mdat;
+    let moov = 9 * MB;
+    let free_at = moov_at + moov;
+    let source = SparseMp4 {
+        boxes: vec![
+            (0, 16, *b"ftyp"),
+            (16, mdat, *b"mdat"),
+            (moov_at, moov, *b"moov"),
+            (free_at, MB, *b"free"),
+        ],
+        content_length: free_at + MB,
+        fetches: parking_lot::Mutex::new(Vec::new()),
+    };
+
+    let ranges = compute_warmup_ranges(&source, source.content_length, 2 * MB, false)
+        .await
+        .unwrap();
+    assert_eq!(ranges, vec![(0, 2 * MB - 1), (moov_at, moov_at + moov - 1)]);
+    // Past the head probe only box headers were fetched.
+    let fetches = source.fetches.lock().clone();
+    assert!(fetches[1..].iter().all(|&len| len <= 16), "{:?}", fetches);
+}
@@ -0,0 +1,198 @@
+// Fragmented MP4: detection, sidx and mfra fragment indexes, warmup of the
+// trailing mfra and seeks from fragment starts.
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use rust_lib_ma_palyer::config::EngineConfig;
+use rust_lib_ma_palyer::detect::container::{detect_container, ContainerFormat};
+use rust_lib_ma_palyer::detect::fmp4::{parse_mfra, parse_sidx, Fragment};
+use rust_lib_ma_palyer::engine::cache_manager::CacheManager;
+use rust_lib_ma_palyer::engine::session::ProxySession;
+use rust_lib_ma_palyer::engine::warmup::compute_warmup_ranges;
+
+use common::{mp4_box, MemorySource};
+
+/// Full box with the given version and 32-bit fields.
+fn full_box(kind: &[u8; 4], version: u8, fields: &[u32]) -> Vec<u8> {
+    let mut body = vec![version, 0, 0, 0];
+    for f in fields {
+        body.extend_from_slice(&f.to_be_bytes());
+    }
+    mp4_box(kind, &body)
+}
+
+/// Init segment: one video track at 90 kHz with empty sample tables.
+fn init_segment(fragmented: bool) -> Vec<u8> {
+    let stbl = [
+        full_box(b"stts", 0, &[0]),
+        full_box(b"stsc", 0, &[0]),
+        full_box(b"stsz", 0, &[0, 0]),
+        full_box(b"stco", 0, &[0]),
+    ]
+    .concat();
+    let mut hdlr = full_box(b"hdlr", 0, &[0]);
+    hdlr.extend_from_slice(b"vide");
+    hdlr[3] += 4;
+    let mdia = [
+        full_box(b"mdhd", 0, &[0, 0, 90_000, 0, 0]),
+        hdlr,
+        mp4_box(b"minf", &mp4_box(b"stbl", &stbl)),
+    ]
+    .concat();
+    let trak = mp4_box(
+        b"trak",
+        &[
+            full_box(b"tkhd", 0, &[0, 0, 1, 0, 0]),
+            mp4_box(b"mdia", &mdia),
+        ]
+        .concat(),
+    );
+    let mut moov = [full_box(b"mvhd", 0, &[0, 0, 1000, 0]), trak].concat();
+    if fragmented {
+        moov.extend(mp4_box(b"mvex", &full_box(b"trex", 0, &[1, 1, 0, 0, 0])));
+    }
+    [mp4_box(b"ftyp", b"iso6\0\0\0\0"), mp4_box(b"moov", &moov)].concat()
+}
+
+fn sidx(sizes: &[u32], duration: u32) -> Vec<u8> {
+    let mut fields = vec![1, 1000, 0, 0, sizes.len() as u32];
+    for &size in sizes {
+        fields.extend_from_slice(&[size, duration, 0x9000_0000]);
+    }
+    full_box(b"sidx", 0, &fields)
+}
+
+/// `mfra` for track 1 with (time, moof offset) entries, then `mfro`.
+fn mfra(entries: &[(u64, u64)]) -> Vec<u8> {
+    let mut tfra = vec![1u8, 0, 0, 0];
+    tfra.extend_from_slice(&1u32.to_be_bytes());
+    tfra.extend_from_slice(&0u32.to_be_bytes());
+    tfra.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+    for &(time, moof) in entries {
+        tfra.extend_from_slice(&time.to_be_bytes());
+        tfra.extend_from_slice(&moof.to_be_bytes());
+        tfra.extend_from_slice(&[1, 1, 1]);
+    }
+    let tfra = mp4_box(b"tfra", &tfra);
+    let size = (tfra.len() + 8 + 16) as u32;
+    mp4_box(b"mfra", &[tfra, full_box(b"mfro", 0, &[size])].concat())
+}
+
+#[test]
+fn test_detect_fragmented_mp4() {
+    assert_eq!(
+        detect_container(&init_segment(true)),
+        ContainerFormat::FragmentedMp4
+    );
+    assert_eq!(detect_container(&init_segment(false)), ContainerFormat::Mp4);
+
+    let mut with_sidx = init_segment(false);
+    with_sidx.extend(sidx(&[1000], 2000));
+    assert_eq!(detect_container(&with_sidx), ContainerFormat::FragmentedMp4);
+}
+
+#[test]
+fn test_sidx_fragment_offsets_and_duration() {
+    let sidx = sidx(&[1000, 2000, 3000], 2000);
+    let index = parse_sidx(&sidx, 500).unwrap();
+    let end = 500 + sidx.len() as u64;
+    assert_eq!(
+        index.fragments,
+        vec![
+            Fragment {
+                time_secs: 0.0,
+                offset: end
+            },
+            Fragment {
+                time_secs: 2.0,
+                offset: end + 1000
+            },
+            Fragment {
+                time_secs: 4.0,
+                offset: end + 3000
+            },
+        ]
+    );
+    assert_eq!(index.duration_secs, Some(6.0));
+}
+
+#[test]
+fn test_mfra_fragment_offsets() {
+    let mfra = mfra(&[(0, 5000), (45_000, 5000), (90_000, 9000)]);
+    let index = parse_mfra(&mfra, 1, 90_000).unwrap();
+    assert_eq!(
+        index.fragments,
+        vec![
+            Fragment {
+                time_secs: 0.0,
+                offset: 5000
+            },
+            Fragment {
+                time_secs: 1.0,
+                offset: 9000
+            },
+        ]
+    );
+    assert!(parse_mfra(&mfra, 2, 90_000).is_err());
+}
+
+/// Init segment, 100 KB fragments at 0/2/4 s, then `mfra`. Returns the file
+/// and the fragment offsets.
+fn fragmented_file() -> (Vec<u8>, Vec<u64>) {
+    let mut file = init_segment(true);
+    let mut offsets = Vec::new();
+    for _ in 0..3 {
+        offsets.push(file.len() as u64);
+        file.extend(mp4_box(b"moof", &[0u8; 8]));
+        file.extend(mp4_box(b"mdat", &vec![0u8; 100_000]));
+    }
+    let entries: Vec<(u64, u64)> = offsets
+        .iter()
+        .enumerate()
+        .map(|(k, &offset)| (k as u64 * 180_000, offset))
+        .collect();
+    file.extend(mfra(&entries));
+    (file, offsets)
+}
+
+#[tokio::test]
+async fn test_warmup_fetches_trailing_mfra() {
+    let (file, _) = fragmented_file();
+    let len = file.len() as u64;
+    let mfra_len = mfra(&[(0, 0), (0, 0), (0, 0)]).len() as u64;
+    let ranges = compute_warmup_ranges(&MemorySource(file.into()), len, 64 * 1024, false)
+        .await
+        .unwrap();
+    assert_eq!(ranges, vec![(0, 64 * 1024 - 1), (len - mfra_len, len - 1)]);
+}
+
+#[tokio::test]
+async fn test_session_seeks_from_fragment_start() {
+    let (file, _) = fragmented_file();
+    let dir = tempfile::tempdir().unwrap();
+    let session = ProxySession::new(
+        "fragmented".to_string(),
+        Arc::new(MemorySource(file.into())),
+        Arc::new(CacheManager::new(dir.path(), 0)),
+        &EngineConfig {
+            chunk_size: 64 * 1024,
+            max_concurrency: 4,
+            ..EngineConfig::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let mut keyframe = None;
+    for _ in 0..50 {
+        keyframe = session.prepare_seek(3.0);
+        if keyframe.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(keyframe, Some(2.0));
+}
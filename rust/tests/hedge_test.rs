@@ -0,0 +1,177 @@
+// Tail-latency guards: hedged requests for stalled urgent chunks and the
+// split first chunk.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use bytes::Bytes;
+use parking_lot::Mutex;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+use rust_lib_ma_palyer::engine::cache::{CacheValidator, DiskCache};
+use rust_lib_ma_palyer::engine::downloader::Downloader;
+use rust_lib_ma_palyer::engine::stats::StatsCollector;
+use rust_lib_ma_palyer::engine::store::CacheBackend;
+use rust_lib_ma_palyer::source::traits::{ByteStream, MediaSource, SourceInfo};
+
+const MB: u64 = 1024 * 1024;
+const CONTENT_LENGTH: u64 = 8 * MB;
+const FIRST_PIECE: u64 = 64 * 1024;
+
+fn content(start: u64, end_inclusive: u64) -> Bytes {
+    (start..=end_inclusive).map(|i| (i % 239) as u8).collect()
+}
+
+/// The first streamed request stalls after a small piece; later requests
+/// and whole-range fetches are served at once. Records every request.
+struct FlakyRouteSource {
+    routes: usize,
+    streams: Mutex<Vec<(u64, u64)>>,
+    fetches: Mutex<Vec<u64>>,
+}
+
+impl FlakyRouteSource {
+    fn new(routes: usize) -> Arc<Self> {
+        Arc::new(Self {
+            routes,
+            streams: Mutex::new(Vec::new()),
+            fetches: Mutex::new(Vec::new()),
+        })
+    }
+}
+
+#[async_trait]
+impl MediaSource for FlakyRouteSource {
+    async fn probe(&self) -> Result<SourceInfo> {
+        Ok(SourceInfo {
+            content_length: CONTENT_LENGTH,
+            content_type: "video/mp4".to_string(),
+            supports_range: true,
+            etag: None,
+            last_modified: None,
+        })
+    }
+
+    async fn fetch_range(&self, start: u64, end: u64) -> Result<Bytes> {
+        self.fetches.lock().push(start);
+        Ok(content(start, end))
+    }
+
+    async fn fetch_range_stream(&self, start: u64, end: u64) -> Result<ByteStream> {
+        let first = {
+            let mut streams = self.streams.lock();
+            streams.push((start, end));
+            streams.len() == 1
+        };
+        if !first {
+            return Ok(Box::pin(tokio_stream::once(Ok(content(start, end)))));
+        }
+        let (tx, rx) = mpsc::channel(2);
+        tokio::spawn(async move {
+            if tx
+                .send(Ok(content(start, start + FIRST_PIECE - 1)))
+                .await
+                .is_err()
+            {
+                return;
+            }
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            let _ = tx.send(Ok(content(start + FIRST_PIECE, end))).await;
+        });
+        Ok(Box::pin(ReceiverStream::new(rx)))
+    }
+
+    fn route_count(&self) -> usize {
+        self.routes
+    }
+}
+
+fn memory_cache(dir: &std::path::Path) -> Arc<DiskCache> {
+    Arc::new(
+        DiskCache::open_with_backend(
+            dir,
+            "hedge",
+            CONTENT_LENGTH,
+            2 * MB,
+            &CacheValidator::default(),
+            CacheBackend::Memory,
+        )
+        .unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn test_stalled_urgent_chunk_is_hedged() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = memory_cache(dir.path());
+    let source = FlakyRouteSource::new(1);
+    let downloader = Downloader::new(
+        source.clone(),
+        cache.clone(),
+        4,
+        Arc::new(StatsCollector::new()),
+    );
+
+    downloader.start_urgent_prefetch(1);
+    let ready = tokio::time::timeout(Duration::from_secs(5), downloader.wait_for_chunk(1))
+        .await
+        .expect("hedge should finish the chunk long before the stall ends");
+    assert!(ready.is_ok());
+    assert_eq!(
+        *source.streams.lock(),
+        vec![(2 * MB, 4 * MB - 1), (2 * MB + FIRST_PIECE, 4 * MB - 1)]
+    );
+    assert_eq!(cache.read_chunk(1).unwrap(), content(2 * MB, 4 * MB - 1));
+
+    downloader.shutdown();
+}
+
+#[tokio::test]
+async fn test_background_chunk_is_not_hedged() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = memory_cache(dir.path());
+    let source = FlakyRouteSource::new(1);
+    let downloader = Downloader::new(
+        source.clone(),
+        cache.clone(),
+        4,
+        Arc::new(StatsCollector::new()),
+    );
+
+    downloader.start_prefetch(2);
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+    assert_eq!(source.streams.lock().len(), 1);
+    assert_eq!(cache.available_in_chunk(2), FIRST_PIECE);
+
+    downloader.shutdown();
+}
+
+#[tokio::test]
+async fn test_first_chunk_split_across_routes() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = memory_cache(dir.path());
+    let source = FlakyRouteSource::new(4);
+    let downloader = Downloader::new(
+        source.clone(),
+        cache.clone(),
+        4,
+        Arc::new(StatsCollector::new()),
+    );
+
+    downloader.start_warmup(0);
+    let ready = tokio::time::timeout(Duration::from_secs(5), downloader.wait_for_chunk(0))
+        .await
+        .unwrap();
+    assert!(ready.is_ok());
+
+    let mut fetches = source.fetches.lock().clone();
+    fetches.sort_unstable();
+    assert_eq!(fetches, vec![0, MB / 2, MB, 3 * MB / 2]);
+    assert!(source.streams.lock().is_empty());
+    assert_eq!(cache.read_chunk(0).unwrap(), content(0, 2 * MB - 1));
+
+    downloader.shutdown();
+}
@@ -8,7 +8,7 @@ use axum::routing::get;
 use axum::Router;
 use tokio::net::TcpListener;
 
-use rust_lib_ma_palyer::source::http_source::HttpSource;
+use rust_lib_ma_palyer::source::http_source::{parse_retry_after, HttpSource};
 use rust_lib_ma_palyer::source::traits::MediaSource;
 
 const TEST_SIZE: usize = 1024 * 1024; // 1 MB
@@ -93,3 +93,92 @@ async fn test_http_source_fetch_range() {
         assert_eq!(data[i as usize], i);
     }
 }
+
+#[test]
+fn test_parse_retry_after() {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let now = UNIX_EPOCH + Duration::from_secs(784_111_717); // Sun, 06 Nov 1994 08:48:37 GMT
+    assert_eq!(
+        parse_retry_after("120", now),
+        Some(Duration::from_secs(120))
+    );
+    assert_eq!(
+        parse_retry_after("Sun, 06 Nov 1994 08:49:37 GMT", now),
+        Some(Duration::from_secs(60))
+    );
+    assert_eq!(
+        parse_retry_after("Sun, 06 Nov 1994 08:00:00 GMT", now),
+        Some(Duration::ZERO)
+    );
+    assert_eq!(parse_retry_after("soon", now), None);
+}
+
+#[tokio::test]
+async fn test_http_source_rate_limited_carries_retry_after() {
+    use rust_lib_ma_palyer::error::{ProxyError, ProxyErrorKind};
+
+    let app = Router::new().route(
+        "/busy",
+        get(|| async {
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, "7")],
+                "slow down",
+            )
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let source = HttpSource::new(format!("http://{}/busy", addr), HashMap::new());
+    let err = source.fetch_range(0, 99).await.unwrap_err();
+    let err = ProxyError::classify(&err);
+    assert_eq!(err.kind, ProxyErrorKind::RateLimited);
+    assert_eq!(err.retry_after, Some(std::time::Duration::from_secs(7)));
+}
+
+#[tokio::test]
+async fn test_http_source_rejects_ignored_range() {
+    use rust_lib_ma_palyer::error::{ProxyError, ProxyErrorKind};
+
+    let app = Router::new()
+        .route(
+            "/whole",
+            get(|| async { (StatusCode::OK, vec![7u8; 1000]) }),
+        )
+        .route(
+            "/shifted",
+            get(|| async {
+                (
+                    StatusCode::PARTIAL_CONTENT,
+                    [(header::CONTENT_RANGE, "bytes 0-99/1000")],
+                    vec![7u8; 100],
+                )
+            }),
+        );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    for path in ["whole", "shifted"] {
+        let source = HttpSource::new(format!("http://{}/{}", addr, path), HashMap::new());
+        let err = source.fetch_range(100, 199).await.unwrap_err();
+        assert_eq!(
+            ProxyError::classify(&err).kind,
+            ProxyErrorKind::SourceChanged,
+            "{}",
+            path
+        );
+        assert!(
+            source.fetch_range_stream(100, 199).await.is_err(),
+            "{}",
+            path
+        );
+    }
+}
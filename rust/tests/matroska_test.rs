@@ -0,0 +1,183 @@
+// Matroska layout: SeekHead-located Info/Tracks/Cues, warmup of the tail
+// Cues, and cue points as the session's seek index.
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use rust_lib_ma_palyer::config::EngineConfig;
+use rust_lib_ma_palyer::detect::matroska::{
+    parse_cues, parse_info, segment_layout, video_tracks, CuePoint,
+};
+use rust_lib_ma_palyer::engine::cache_manager::CacheManager;
+use rust_lib_ma_palyer::engine::session::ProxySession;
+use rust_lib_ma_palyer::engine::warmup::compute_warmup_ranges;
+
+use common::MemorySource;
+
+const CLUSTER_BYTES: usize = 100_000;
+
+/// EBML element with an 8-byte size.
+fn ebml(id: &[u8], body: &[u8]) -> Vec<u8> {
+    let mut out = id.to_vec();
+    out.push(0x01);
+    out.extend_from_slice(&(body.len() as u64).to_be_bytes()[1..]);
+    out.extend_from_slice(body);
+    out
+}
+
+fn uint(id: &[u8], value: u64) -> Vec<u8> {
+    ebml(id, &value.to_be_bytes())
+}
+
+const INFO: [u8; 4] = [0x15, 0x49, 0xA9, 0x66];
+const TRACKS: [u8; 4] = [0x16, 0x54, 0xAE, 0x6B];
+const CUES: [u8; 4] = [0x1C, 0x53, 0xBB, 0x6B];
+
+fn seek_head(info: u64, tracks: u64, cues: u64) -> Vec<u8> {
+    let seek = |id: &[u8], pos: u64| {
+        ebml(
+            &[0x4D, 0xBB],
+            &[ebml(&[0x53, 0xAB], id), uint(&[0x53, 0xAC], pos)].concat(),
+        )
+    };
+    ebml(
+        &[0x11, 0x4D, 0x9B, 0x74],
+        &[seek(&INFO, info), seek(&TRACKS, tracks), seek(&CUES, cues)].concat(),
+    )
+}
+
+fn cue(time: u64, track: u64, cluster: u64) -> Vec<u8> {
+    let positions = ebml(
+        &[0xB7],
+        &[uint(&[0xF7], track), uint(&[0xF1], cluster)].concat(),
+    );
+    ebml(&[0xBB], &[uint(&[0xB3], time), positions].concat())
+}
+
+/// A 30 s file: audio track 1, video track 2, three clusters and Cues at
+/// the end. Returns the bytes and the Segment's data start.
+fn mkv_file() -> (Vec<u8>, u64) {
+    let mut file = ebml(&[0x1A, 0x45, 0xDF, 0xA3], &ebml(&[0x42, 0x82], b"webm"));
+    file.extend_from_slice(&[
+        0x18, 0x53, 0x80, 0x67, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    ]);
+    let data_start = file.len() as u64;
+
+    let info = ebml(
+        &INFO,
+        &[
+            uint(&[0x2A, 0xD7, 0xB1], 1_000_000),
+            ebml(&[0x44, 0x89], &30_000f64.to_be_bytes()),
+        ]
+        .concat(),
+    );
+    let track = |number: u64, kind: u64| {
+        ebml(
+            &[0xAE],
+            &[uint(&[0xD7], number), uint(&[0x83], kind)].concat(),
+        )
+    };
+    let tracks = ebml(&TRACKS, &[track(1, 2), track(2, 1)].concat());
+    let cluster = ebml(&[0x1F, 0x43, 0xB6, 0x75], &vec![0u8; CLUSTER_BYTES]);
+
+    // Segment-relative positions; the SeekHead's own size does not depend
+    // on the values.
+    let head_len = seek_head(0, 0, 0).len() as u64;
+    let info_at = head_len;
+    let tracks_at = info_at + info.len() as u64;
+    let cluster_at = tracks_at + tracks.len() as u64;
+    let clusters: Vec<u64> = (0..3)
+        .map(|k| cluster_at + k * cluster.len() as u64)
+        .collect();
+    let cues_at = cluster_at + 3 * cluster.len() as u64;
+    let cues = ebml(
+        &CUES,
+        &[
+            cue(0, 2, clusters[0]),
+            cue(5_000, 1, clusters[0]),
+            cue(10_000, 2, clusters[1]),
+            cue(20_000, 2, clusters[2]),
+        ]
+        .concat(),
+    );
+
+    file.extend(seek_head(info_at, tracks_at, cues_at));
+    file.extend(info);
+    file.extend(tracks);
+    for _ in 0..3 {
+        file.extend_from_slice(&cluster);
+    }
+    file.extend(cues);
+    (file, data_start)
+}
+
+#[test]
+fn test_seek_head_locates_metadata_and_cues() {
+    let (file, data_start) = mkv_file();
+    let layout = segment_layout(&file).unwrap();
+    assert_eq!(layout.data_start, data_start);
+
+    let info = parse_info(&file[layout.info.unwrap() as usize..]).unwrap();
+    assert_eq!(info.timestamp_scale, 1_000_000);
+    assert_eq!(info.duration_secs, Some(30.0));
+    assert_eq!(
+        video_tracks(&file[layout.tracks.unwrap() as usize..]),
+        vec![2]
+    );
+
+    let cues = parse_cues(
+        &file[layout.cues.unwrap() as usize..],
+        data_start,
+        1_000_000,
+    );
+    assert_eq!(cues.len(), 4);
+    assert_eq!(
+        cues[2],
+        CuePoint {
+            time_secs: 10.0,
+            track: 2,
+            cluster_offset: cues[0].cluster_offset + (CLUSTER_BYTES as u64 + 12),
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_warmup_fetches_tail_cues() {
+    let (file, _) = mkv_file();
+    let cues_at = segment_layout(&file).unwrap().cues.unwrap();
+    let len = file.len() as u64;
+    let ranges = compute_warmup_ranges(&MemorySource(file.into()), len, 64 * 1024, false)
+        .await
+        .unwrap();
+    assert_eq!(ranges, vec![(0, 64 * 1024 - 1), (cues_at, len - 1)]);
+}
+
+#[tokio::test]
+async fn test_session_seeks_from_cue_point() {
+    let (file, _) = mkv_file();
+    let dir = tempfile::tempdir().unwrap();
+    let session = ProxySession::new(
+        "cued".to_string(),
+        Arc::new(MemorySource(file.into())),
+        Arc::new(CacheManager::new(dir.path(), 0)),
+        &EngineConfig {
+            chunk_size: 64 * 1024,
+            max_concurrency: 4,
+            ..EngineConfig::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let mut keyframe = None;
+    for _ in 0..50 {
+        keyframe = session.prepare_seek(15.0);
+        if keyframe.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(keyframe, Some(10.0));
+}
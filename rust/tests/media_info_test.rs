@@ -0,0 +1,462 @@
+// Media info probe: MP4 sample descriptions, Matroska Tracks, TS program
+// tables, AVI stream headers, FLV tags, and a probe through a session.
+
+mod common;
+
+use std::sync::Arc;
+
+use rust_lib_ma_palyer::config::EngineConfig;
+use rust_lib_ma_palyer::detect::container::ContainerFormat;
+use rust_lib_ma_palyer::detect::media_info::{
+    avi_media_info, flv_media_info, mkv_media_info, mp4_media_info, ts_media_info, AudioTrack,
+    SubtitleTrack, VideoTrack,
+};
+use rust_lib_ma_palyer::engine::cache_manager::CacheManager;
+use rust_lib_ma_palyer::engine::session::ProxySession;
+
+use common::{mp4_box, MemorySource};
+
+/// Version 0 full box with 32-bit fields.
+fn full_box(kind: &[u8; 4], fields: &[u32]) -> Vec<u8> {
+    let mut body = vec![0u8; 4];
+    for f in fields {
+        body.extend_from_slice(&f.to_be_bytes());
+    }
+    mp4_box(kind, &body)
+}
+
+/// A `trak` with one sample entry and uniform decode deltas.
+fn trak(handler: &[u8; 4], language: u16, entry: Vec<u8>, timescale: u32, delta: u32) -> Vec<u8> {
+    let mut mdhd = vec![0u8; 12];
+    mdhd.extend(timescale.to_be_bytes());
+    mdhd.extend(0u32.to_be_bytes());
+    mdhd.extend(language.to_be_bytes());
+    mdhd.extend([0u8; 2]);
+    let mut hdlr = vec![0u8; 8];
+    hdlr.extend(handler);
+    hdlr.extend([0u8; 12]);
+    let mut stsd = vec![0u8; 4];
+    stsd.extend(1u32.to_be_bytes());
+    stsd.extend(entry);
+    let stbl = [mp4_box(b"stsd", &stsd), full_box(b"stts", &[1, 240, delta])].concat();
+    let minf = mp4_box(b"stbl", &stbl);
+    let mdia = [
+        mp4_box(b"mdhd", &mdhd),
+        mp4_box(b"hdlr", &hdlr),
+        mp4_box(b"minf", &minf),
+    ]
+    .concat();
+    mp4_box(b"trak", &mp4_box(b"mdia", &mdia))
+}
+
+fn hdr_hevc_entry() -> Vec<u8> {
+    let mut body = vec![0u8; 78];
+    body[24..26].copy_from_slice(&3840u16.to_be_bytes());
+    body[26..28].copy_from_slice(&2160u16.to_be_bytes());
+    let mut hvcc = vec![0u8; 23];
+    hvcc[0] = 1;
+    hvcc[1] = 0x02; // Main 10
+    hvcc[17] = 0xF8 | 2; // bitDepthLumaMinus8
+    body.extend(mp4_box(b"hvcC", &hvcc));
+    // nclx: BT.2020 primaries, PQ transfer, BT.2020 matrix.
+    body.extend(mp4_box(
+        b"colr",
+        &[b"nclx".as_slice(), &[0, 9, 0, 16, 0, 9, 0]].concat(),
+    ));
+    body.extend(mp4_box(b"dvcC", &[1, 0, 0x10, 0x35, 0, 0, 0, 0]));
+    mp4_box(b"hvc1", &body)
+}
+
+fn aac_entry(channels: u16, rate: u32) -> Vec<u8> {
+    let mut body = vec![0u8; 28];
+    body[16..18].copy_from_slice(&channels.to_be_bytes());
+    body[24..28].copy_from_slice(&(rate << 16).to_be_bytes());
+    // ES_Descriptor holding a DecoderConfigDescriptor for MPEG-4 audio.
+    let esds = [0, 0, 0, 0, 0x03, 8, 0, 1, 0, 0x04, 3, 0x40, 0x15, 0].to_vec();
+    body.extend(mp4_box(b"esds", &esds));
+    mp4_box(b"mp4a", &body)
+}
+
+fn packed_language(code: &[u8; 3]) -> u16 {
+    code.iter().fold(0u16, |v, &c| (v << 5) | (c - 0x60) as u16)
+}
+
+#[test]
+fn test_mp4_tracks_codecs_and_hdr() {
+    let moov = mp4_box(
+        b"moov",
+        &[
+            full_box(b"mvhd", &[0, 0, 1000, 10_010]),
+            trak(
+                b"vide",
+                packed_language(b"und"),
+                hdr_hevc_entry(),
+                24000,
+                1001,
+            ),
+            trak(
+                b"soun",
+                packed_language(b"jpn"),
+                aac_entry(6, 48000),
+                48000,
+                1024,
+            ),
+            trak(
+                b"sbtl",
+                packed_language(b"eng"),
+                mp4_box(b"tx3g", &[0u8; 8]),
+                1000,
+                1,
+            ),
+        ]
+        .concat(),
+    );
+    let media = mp4_media_info(&moov, ContainerFormat::Mp4).unwrap();
+
+    assert_eq!(media.duration_secs, Some(10.01));
+    let video = &media.video[0];
+    assert_eq!(video.codec, "hevc");
+    assert_eq!(video.profile.as_deref(), Some("Main 10"));
+    assert_eq!((video.width, video.height), (3840, 2160));
+    assert_eq!(video.bit_depth, Some(10));
+    assert!((video.frame_rate.unwrap() - 23.976).abs() < 0.001);
+    assert!(video.hdr && video.dolby_vision);
+    assert_eq!(
+        media.audio,
+        vec![AudioTrack {
+            codec: "aac".to_string(),
+            channels: Some(6),
+            sample_rate: Some(48000),
+            language: Some("jpn".to_string()),
+            title: None,
+        }]
+    );
+    assert_eq!(media.subtitles[0].codec, "mov_text");
+    assert_eq!(media.subtitles[0].language.as_deref(), Some("eng"));
+}
+
+fn ebml(id: &[u8], body: &[u8]) -> Vec<u8> {
+    let mut out = id.to_vec();
+    out.push(0x01);
+    out.extend_from_slice(&(body.len() as u64).to_be_bytes()[1..]);
+    out.extend_from_slice(body);
+    out
+}
+
+fn uint(id: &[u8], value: u64) -> Vec<u8> {
+    ebml(id, &value.to_be_bytes())
+}
+
+fn mkv_info(duration_ms: f64) -> Vec<u8> {
+    ebml(
+        &[0x15, 0x49, 0xA9, 0x66],
+        &[
+            uint(&[0x2A, 0xD7, 0xB1], 1_000_000),
+            ebml(&[0x44, 0x89], &duration_ms.to_be_bytes()),
+        ]
+        .concat(),
+    )
+}
+
+fn mkv_tracks() -> Vec<u8> {
+    let mut hvcc = vec![0u8; 23];
+    hvcc[1] = 0x02;
+    hvcc[17] = 0xFA;
+    let video = ebml(
+        &[0xAE],
+        &[
+            uint(&[0xD7], 1),
+            uint(&[0x83], 1),
+            ebml(&[0x86], b"V_MPEGH/ISO/HEVC"),
+            ebml(&[0x63, 0xA2], &hvcc),
+            uint(&[0x23, 0xE3, 0x83], 41_708_333),
+            ebml(
+                &[0xE0],
+                &[
+                    uint(&[0xB0], 1920),
+                    uint(&[0xBA], 1080),
+                    ebml(&[0x55, 0xB0], &uint(&[0x55, 0xBA], 18)),
+                ]
+                .concat(),
+            ),
+            ebml(
+                &[0x41, 0xE4],
+                &uint(&[0x41, 0xE7], u32::from_be_bytes(*b"dvvC") as u64),
+            ),
+        ]
+        .concat(),
+    );
+    let audio = ebml(
+        &[0xAE],
+        &[
+            uint(&[0xD7], 2),
+            uint(&[0x83], 2),
+            ebml(&[0x86], b"A_EAC3"),
+            ebml(&[0x22, 0xB5, 0x9C], b"ger"),
+            ebml(&[0x53, 0x6E], b"Director"),
+            ebml(
+                &[0xE1],
+                &[uint(&[0x9F], 6), ebml(&[0xB5], &48000f64.to_be_bytes())].concat(),
+            ),
+        ]
+        .concat(),
+    );
+    let subtitle = ebml(
+        &[0xAE],
+        &[
+            uint(&[0xD7], 3),
+            uint(&[0x83], 17),
+            ebml(&[0x86], b"S_HDMV/PGS"),
+            uint(&[0x55, 0xAA], 1),
+        ]
+        .concat(),
+    );
+    ebml(
+        &[0x16, 0x54, 0xAE, 0x6B],
+        &[video, audio, subtitle].concat(),
+    )
+}
+
+#[test]
+fn test_mkv_tracks_codecs_and_hdr() {
+    let info = mkv_info(5000.0);
+    let tracks = mkv_tracks();
+    let media = mkv_media_info(Some(&info), Some(&tracks));
+
+    assert_eq!(media.duration_secs, Some(5.0));
+    let video = &media.video[0];
+    assert_eq!(video.codec, "hevc");
+    assert_eq!(video.profile.as_deref(), Some("Main 10"));
+    assert_eq!((video.width, video.height), (1920, 1080));
+    assert_eq!(video.bit_depth, Some(10));
+    assert!((video.frame_rate.unwrap() - 23.976).abs() < 0.001);
+    assert!(video.hdr && video.dolby_vision);
+    assert_eq!(
+        media.audio,
+        vec![AudioTrack {
+            codec: "eac3".to_string(),
+            channels: Some(6),
+            sample_rate: Some(48000),
+            language: Some("ger".to_string()),
+            title: Some("Director".to_string()),
+        }]
+    );
+    // No Language element means English.
+    assert_eq!(
+        media.subtitles,
+        vec![SubtitleTrack {
+            codec: "pgs".to_string(),
+            language: Some("eng".to_string()),
+            title: None,
+            forced: true,
+        }]
+    );
+}
+
+/// A TS packet starting a PSI section on `pid`.
+fn psi_packet(pid: u16, table_id: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = vec![0x47, 0x40 | (pid >> 8) as u8, pid as u8, 0x10, 0x00];
+    let length = 5 + body.len() + 4;
+    packet.extend([table_id, 0xB0 | (length >> 8) as u8, length as u8]);
+    packet.extend([0, 1, 0xC1, 0, 0]);
+    packet.extend(body);
+    packet.extend([0u8; 4]); // CRC
+    packet.resize(188, 0xFF);
+    packet
+}
+
+fn ts_head() -> Vec<u8> {
+    let pat = psi_packet(0, 0x00, &[0, 1, 0xE1, 0x00]);
+    let streams = [
+        vec![0xE1, 0x01, 0xF0, 0x00], // PCR PID, no program info
+        vec![0x1B, 0xE1, 0x01, 0xF0, 0x00],
+        vec![
+            0x06, 0xE1, 0x02, 0xF0, 9, 0x6A, 1, 0, 0x0A, 4, b'f', b'r', b'e', 0,
+        ],
+        vec![
+            0x06, 0xE1, 0x03, 0xF0, 10, 0x59, 8, b'e', b'n', b'g', 0x10, 0, 1, 0, 1,
+        ],
+    ]
+    .concat();
+    let pmt = psi_packet(0x100, 0x02, &streams);
+    [pat, pmt].concat()
+}
+
+#[test]
+fn test_ts_streams_from_program_tables() {
+    let expect = |media: rust_lib_ma_palyer::detect::media_info::ProbedMedia| {
+        assert_eq!(media.video[0].codec, "h264");
+        assert_eq!(media.audio[0].codec, "ac3");
+        assert_eq!(media.audio[0].language.as_deref(), Some("fre"));
+        assert_eq!(media.subtitles[0].codec, "dvb_subtitle");
+        assert_eq!(media.subtitles[0].language.as_deref(), Some("eng"));
+    };
+    expect(ts_media_info(&ts_head(), ContainerFormat::TransportStream));
+
+    let m2ts: Vec<u8> = ts_head()
+        .chunks(188)
+        .flat_map(|packet| [[0u8; 4].as_slice(), packet].concat())
+        .collect();
+    expect(ts_media_info(&m2ts, ContainerFormat::M2ts));
+}
+
+fn riff_chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut out = id.to_vec();
+    out.extend((body.len() as u32).to_le_bytes());
+    out.extend(body);
+    out
+}
+
+fn le_fields(fields: &[u32], len: usize) -> Vec<u8> {
+    let mut out: Vec<u8> = fields.iter().flat_map(|f| f.to_le_bytes()).collect();
+    out.resize(len, 0);
+    out
+}
+
+#[test]
+fn test_avi_streams_from_header_list() {
+    let avih = le_fields(&[40_000, 0, 0, 0, 250, 0, 2, 0, 640, 360], 56);
+    let mut vids = b"vidsXVID".to_vec();
+    vids.extend(le_fields(&[0, 0, 0, 1, 25, 0, 250], 48));
+    let bih = le_fields(
+        &[
+            40,
+            640,
+            (-360i32) as u32,
+            0x0018_0001,
+            u32::from_le_bytes(*b"XVID"),
+        ],
+        40,
+    );
+    let mut auds = b"auds\0\0\0\0".to_vec();
+    auds.extend(le_fields(&[], 48));
+    let wave = le_fields(&[0x0002_0055, 44100, 176_400, 0x0010_0004], 18);
+
+    let hdrl = [
+        b"hdrl".to_vec(),
+        riff_chunk(b"avih", &avih),
+        riff_chunk(
+            b"LIST",
+            &[
+                b"strl".to_vec(),
+                riff_chunk(b"strh", &vids),
+                riff_chunk(b"strf", &bih),
+            ]
+            .concat(),
+        ),
+        riff_chunk(
+            b"LIST",
+            &[
+                b"strl".to_vec(),
+                riff_chunk(b"strh", &auds),
+                riff_chunk(b"strf", &wave),
+                riff_chunk(b"strn", b"Commentary\0"),
+            ]
+            .concat(),
+        ),
+    ]
+    .concat();
+    let head = [b"RIFF\0\0\0\0AVI ".to_vec(), riff_chunk(b"LIST", &hdrl)].concat();
+    let media = avi_media_info(&head);
+
+    assert_eq!(media.duration_secs, Some(10.0));
+    assert_eq!(
+        media.video,
+        vec![VideoTrack {
+            codec: "mpeg4".to_string(),
+            width: 640,
+            height: 360,
+            frame_rate: Some(25.0),
+            ..VideoTrack::default()
+        }]
+    );
+    assert_eq!(media.audio[0].codec, "mp3");
+    assert_eq!(media.audio[0].channels, Some(2));
+    assert_eq!(media.audio[0].sample_rate, Some(44100));
+    assert_eq!(media.audio[0].title.as_deref(), Some("Commentary"));
+}
+
+fn flv_tag(kind: u8, data: &[u8]) -> Vec<u8> {
+    let mut out = vec![kind];
+    out.extend(&(data.len() as u32).to_be_bytes()[1..]);
+    out.extend([0u8; 7]);
+    out.extend(data);
+    out.extend(((data.len() + 11) as u32).to_be_bytes());
+    out
+}
+
+fn amf_property(key: &str, value: f64) -> Vec<u8> {
+    let mut out = (key.len() as u16).to_be_bytes().to_vec();
+    out.extend(key.as_bytes());
+    out.push(0x00);
+    out.extend(value.to_be_bytes());
+    out
+}
+
+#[test]
+fn test_flv_codecs_from_first_tags() {
+    let mut meta = vec![0x02, 0, 10];
+    meta.extend(b"onMetaData");
+    meta.extend([0x08, 0, 0, 0, 4]);
+    for (key, value) in [
+        ("duration", 12.5),
+        ("width", 1280.0),
+        ("height", 720.0),
+        ("framerate", 30.0),
+    ] {
+        meta.extend(amf_property(key, value));
+    }
+    meta.extend([0, 0, 9]);
+    // AVC sequence header with a High profile avcC; AAC LC 48 kHz stereo.
+    let video = [0x17, 0x00, 0, 0, 0, 1, 100, 0, 40, 0xFF, 0xE0, 0];
+    let audio = [0xAF, 0x00, 0x11, 0x90];
+
+    let head = [
+        b"FLV\x01\x05\0\0\0\x09\0\0\0\0".to_vec(),
+        flv_tag(18, &meta),
+        flv_tag(9, &video),
+        flv_tag(8, &audio),
+    ]
+    .concat();
+    let media = flv_media_info(&head);
+
+    assert_eq!(media.duration_secs, Some(12.5));
+    let video = &media.video[0];
+    assert_eq!(video.codec, "h264");
+    assert_eq!(video.profile.as_deref(), Some("High"));
+    assert_eq!((video.width, video.height), (1280, 720));
+    assert_eq!(video.frame_rate, Some(30.0));
+    assert_eq!(media.audio[0].codec, "aac");
+    assert_eq!(media.audio[0].sample_rate, Some(48000));
+    assert_eq!(media.audio[0].channels, Some(2));
+}
+
+#[tokio::test]
+async fn test_session_probes_media_info() {
+    let header = ebml(&[0x1A, 0x45, 0xDF, 0xA3], &ebml(&[0x42, 0x82], b"matroska"));
+    let segment = ebml(
+        &[0x18, 0x53, 0x80, 0x67],
+        &[mkv_info(8000.0), mkv_tracks(), vec![0u8; 200_000]].concat(),
+    );
+    let file = [header, segment].concat();
+    let dir = tempfile::tempdir().unwrap();
+    let session = ProxySession::new(
+        "probe".to_string(),
+        Arc::new(MemorySource(file.into())),
+        Arc::new(CacheManager::new(dir.path(), 0)),
+        &EngineConfig {
+            chunk_size: 64 * 1024,
+            max_concurrency: 4,
+            ..EngineConfig::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let media = session.media_info().await.unwrap();
+    assert_eq!(media.container, ContainerFormat::Matroska);
+    assert_eq!(media.duration_secs, Some(8.0));
+    assert_eq!(media.video.len(), 1);
+    assert_eq!(media.audio.len(), 1);
+    assert_eq!(media.subtitles.len(), 1);
+}
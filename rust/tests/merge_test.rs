@@ -0,0 +1,288 @@
+// Merged upstream requests: consecutive background chunks share one ranged
+// GET and each chunk is released as soon as its bytes are in.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use bytes::Bytes;
+use parking_lot::Mutex;
+use tokio::sync::{mpsc, Semaphore};
+use tokio_stream::wrappers::ReceiverStream;
+
+use rust_lib_ma_palyer::engine::cache::{CacheValidator, DiskCache};
+use rust_lib_ma_palyer::engine::downloader::Downloader;
+use rust_lib_ma_palyer::engine::host_limiter::host_limiter;
+use rust_lib_ma_palyer::engine::stats::StatsCollector;
+use rust_lib_ma_palyer::engine::store::CacheBackend;
+use rust_lib_ma_palyer::error::{ProxyError, ProxyErrorKind};
+use rust_lib_ma_palyer::source::traits::{ByteStream, MediaSource, SourceInfo};
+
+const MB: u64 = 1024 * 1024;
+const CONTENT_LENGTH: u64 = 16 * MB;
+
+fn content(start: u64, end_inclusive: u64) -> Bytes {
+    (start..=end_inclusive).map(|i| (i % 241) as u8).collect()
+}
+
+/// Chunk 0 waits for the gate. Multi-chunk ranges arrive one chunk per
+/// piece; with `stall`, nothing follows the first chunk.
+struct RangeSource {
+    ranges: Mutex<Vec<(u64, u64)>>,
+    gate: Semaphore,
+    stall: bool,
+    host: Option<&'static str>,
+}
+
+impl RangeSource {
+    fn new(stall: bool) -> Arc<Self> {
+        Arc::new(Self {
+            ranges: Mutex::new(Vec::new()),
+            gate: Semaphore::new(0),
+            stall,
+            host: None,
+        })
+    }
+}
+
+#[async_trait]
+impl MediaSource for RangeSource {
+    async fn probe(&self) -> Result<SourceInfo> {
+        Ok(SourceInfo {
+            content_length: CONTENT_LENGTH,
+            content_type: "video/mp4".to_string(),
+            supports_range: true,
+            etag: None,
+            last_modified: None,
+        })
+    }
+
+    async fn fetch_range(&self, start: u64, end: u64) -> Result<Bytes> {
+        Ok(content(start, end))
+    }
+
+    async fn fetch_range_stream(&self, start: u64, end: u64) -> Result<ByteStream> {
+        self.ranges.lock().push((start, end));
+        if start == 0 {
+            let _permit = self.gate.acquire().await?;
+        }
+        let stall = self.stall;
+        let (tx, rx) = mpsc::channel(4);
+        tokio::spawn(async move {
+            let mut at = start;
+            while at <= end {
+                let piece_end = (at + MB - 1).min(end);
+                if tx.send(Ok(content(at, piece_end))).await.is_err() {
+                    return;
+                }
+                if stall {
+                    tokio::time::sleep(Duration::from_secs(30)).await;
+                }
+                at = piece_end + 1;
+            }
+        });
+        Ok(Box::pin(ReceiverStream::new(rx)))
+    }
+
+    fn host_key(&self) -> Option<String> {
+        self.host.map(str::to_string)
+    }
+}
+
+fn setup(source: Arc<RangeSource>) -> (Arc<DiskCache>, Downloader) {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = Arc::new(
+        DiskCache::open_with_backend(
+            dir.path(),
+            "merge",
+            CONTENT_LENGTH,
+            MB,
+            &CacheValidator::default(),
+            CacheBackend::Memory,
+        )
+        .unwrap(),
+    );
+    // One background worker plus the reserved urgent ones.
+    let downloader = Downloader::new(source, cache.clone(), 3, Arc::new(StatsCollector::new()));
+    (cache, downloader)
+}
+
+async fn wait_requested(source: &RangeSource, start: u64) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !source.ranges.lock().iter().any(|&(s, _)| s == start) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_adjacent_background_chunks_share_one_request() {
+    let source = RangeSource::new(false);
+    let (cache, downloader) = setup(source.clone());
+
+    downloader.start_prefetch(0);
+    wait_requested(&source, 0).await;
+    downloader.prefetch_range(1, 7);
+    source.gate.add_permits(1);
+
+    for i in 0..7 {
+        let ready = tokio::time::timeout(Duration::from_secs(5), downloader.wait_for_chunk(i))
+            .await
+            .unwrap();
+        assert!(ready.is_ok(), "chunk {} not downloaded", i);
+    }
+    assert_eq!(
+        *source.ranges.lock(),
+        vec![(0, MB - 1), (MB, 5 * MB - 1), (5 * MB, 7 * MB - 1)]
+    );
+    for i in 0..7u64 {
+        assert_eq!(
+            cache.read_chunk(i as usize).unwrap(),
+            content(i * MB, (i + 1) * MB - 1)
+        );
+    }
+
+    downloader.shutdown();
+}
+
+#[tokio::test]
+async fn test_merged_chunk_released_before_request_finishes() {
+    let source = RangeSource::new(true);
+    let (cache, downloader) = setup(source.clone());
+
+    downloader.start_prefetch(0);
+    wait_requested(&source, 0).await;
+    downloader.prefetch_range(3, 6);
+    source.gate.add_permits(1);
+    wait_requested(&source, 3 * MB).await;
+
+    let ready = tokio::time::timeout(Duration::from_secs(5), downloader.wait_for_chunk(3))
+        .await
+        .unwrap();
+    assert!(ready.is_ok());
+    assert!(!cache.has_chunk(4));
+
+    // Shutdown releases waiters of chunks still inside the stalled request.
+    downloader.shutdown();
+    let ready = tokio::time::timeout(Duration::from_secs(5), downloader.wait_for_chunk(5))
+        .await
+        .unwrap();
+    assert_eq!(ready.unwrap_err().kind, ProxyErrorKind::Cancelled);
+}
+
+#[tokio::test]
+async fn test_merged_chunk_requested_urgently_mid_run() {
+    let source = RangeSource::new(true);
+    let (cache, downloader) = setup(source.clone());
+
+    downloader.start_prefetch(0);
+    wait_requested(&source, 0).await;
+    downloader.prefetch_range(3, 6);
+    source.gate.add_permits(1);
+    wait_requested(&source, 3 * MB).await;
+    tokio::time::timeout(Duration::from_secs(5), downloader.wait_for_chunk(3))
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Chunk 5 is still inside the stalled merged request; asking for it
+    // cuts it from the run and fetches it on its own at once.
+    downloader.start_urgent_prefetch(5);
+    let ready = tokio::time::timeout(Duration::from_secs(5), downloader.wait_for_chunk(5))
+        .await
+        .unwrap();
+    assert!(ready.is_ok());
+    assert_eq!(cache.read_chunk(5).unwrap(), content(5 * MB, 6 * MB - 1));
+    assert!(source.ranges.lock().contains(&(5 * MB, 6 * MB - 1)));
+    assert!(!cache.has_chunk(4));
+
+    downloader.shutdown();
+}
+
+#[tokio::test]
+async fn test_urgent_member_does_not_wait_out_host_pause() {
+    const HOST: &str = "paused.merge.test:443";
+    let source = Arc::new(RangeSource {
+        ranges: Mutex::new(Vec::new()),
+        gate: Semaphore::new(0),
+        stall: false,
+        host: Some(HOST),
+    });
+    let (_cache, downloader) = setup(source.clone());
+
+    // Keep the background worker busy while the host gets paused, so
+    // chunks 2..=4 are merged into one request that waits out the pause.
+    downloader.start_prefetch(0);
+    wait_requested(&source, 0).await;
+    host_limiter(HOST).record_failure(
+        &ProxyError::from_status(429).with_retry_after(Some(Duration::from_secs(60))),
+        Instant::now(),
+    );
+    downloader.prefetch_range(2, 5);
+    source.gate.add_permits(1);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!source.ranges.lock().iter().any(|&(s, _)| s == 2 * MB));
+
+    downloader.start_urgent_prefetch(2);
+    let ready = tokio::time::timeout(Duration::from_secs(5), downloader.wait_for_chunk(2))
+        .await
+        .unwrap();
+    assert!(ready.is_ok());
+    assert!(source.ranges.lock().contains(&(2 * MB, 3 * MB - 1)));
+
+    downloader.shutdown();
+}
+
+#[tokio::test]
+async fn test_merged_request_rejects_ignored_range() {
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use axum::Router;
+    use rust_lib_ma_palyer::source::http_source::HttpSource;
+
+    // Answers every range with the whole file.
+    let app = Router::new().route(
+        "/file",
+        get(|| async { (StatusCode::OK, content(0, CONTENT_LENGTH - 1)) }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let dir = tempfile::tempdir().unwrap();
+    let cache = Arc::new(
+        DiskCache::open_with_backend(
+            dir.path(),
+            "ignored",
+            CONTENT_LENGTH,
+            MB,
+            &CacheValidator::default(),
+            CacheBackend::Memory,
+        )
+        .unwrap(),
+    );
+    let source = Arc::new(HttpSource::new(
+        format!("http://{}/file", addr),
+        Default::default(),
+    ));
+    let downloader = Downloader::new(source, cache.clone(), 3, Arc::new(StatsCollector::new()));
+
+    downloader.prefetch_range(0, 7);
+    for i in 0..7 {
+        let result = tokio::time::timeout(Duration::from_secs(5), downloader.wait_for_chunk(i))
+            .await
+            .unwrap();
+        assert_eq!(
+            result.unwrap_err().kind,
+            ProxyErrorKind::SourceChanged,
+            "chunk {}",
+            i
+        );
+        assert_eq!(cache.available_in_chunk(i), 0, "chunk {}", i);
+    }
+}
@@ -0,0 +1,172 @@
+// MP4 sample tables: keyframe offsets from stts/stss/stsc/stsz/stco/co64,
+// and a session using them for a keyframe-accurate seek.
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use rust_lib_ma_palyer::config::EngineConfig;
+use rust_lib_ma_palyer::detect::mp4::parse_moov;
+use rust_lib_ma_palyer::engine::cache_manager::CacheManager;
+use rust_lib_ma_palyer::engine::session::ProxySession;
+
+use common::{mp4_box, MemorySource};
+
+/// Version 0 full box with 32-bit fields.
+fn full_box(kind: &[u8; 4], fields: &[u32]) -> Vec<u8> {
+    let mut body = vec![0u8; 4];
+    for f in fields {
+        body.extend_from_slice(&f.to_be_bytes());
+    }
+    mp4_box(kind, &body)
+}
+
+fn trak(track_id: u32, handler: &[u8; 4], timescale: u32, stbl: Vec<u8>) -> Vec<u8> {
+    let tkhd = full_box(b"tkhd", &[0, 0, track_id, 0, 0]);
+    let mdhd = full_box(b"mdhd", &[0, 0, timescale, 0, 0]);
+    let mut hdlr = full_box(b"hdlr", &[0]);
+    hdlr.extend_from_slice(handler);
+    hdlr[3] += 4;
+    let minf = mp4_box(b"minf", &mp4_box(b"stbl", &stbl));
+    let mdia = mp4_box(b"mdia", &[mdhd, hdlr, minf].concat());
+    mp4_box(b"trak", &[tkhd, mdia].concat())
+}
+
+/// 30 frames at 0.5 s, keyframes every 10, five 1000-byte frames per chunk.
+fn video_trak() -> Vec<u8> {
+    let stbl = [
+        full_box(b"stts", &[1, 30, 45_000]),
+        full_box(b"stss", &[3, 1, 11, 21]),
+        full_box(b"stsc", &[1, 1, 5, 1]),
+        full_box(b"stsz", &[[0, 30].as_slice(), &[1000; 30]].concat()),
+        full_box(
+            b"stco",
+            &[6, 100_000, 110_000, 120_000, 130_000, 140_000, 150_000],
+        ),
+    ]
+    .concat();
+    trak(1, b"vide", 90_000, stbl)
+}
+
+/// 100 AAC frames, all sync, ten 500-byte frames per 64-bit-offset chunk.
+fn audio_trak() -> Vec<u8> {
+    let mut co64 = vec![0u8, 0, 0, 0, 0, 0, 0, 10];
+    for c in 0..10u64 {
+        co64.extend_from_slice(&(200_000 + c * 5000).to_be_bytes());
+    }
+    let stbl = [
+        full_box(b"stts", &[1, 100, 1024]),
+        full_box(b"stsc", &[1, 1, 10, 1]),
+        full_box(b"stsz", &[500, 100]),
+        mp4_box(b"co64", &co64),
+    ]
+    .concat();
+    trak(2, b"soun", 48_000, stbl)
+}
+
+fn moov() -> Vec<u8> {
+    let mvhd = full_box(b"mvhd", &[0, 0, 1000, 15_000]);
+    mp4_box(b"moov", &[mvhd, video_trak(), audio_trak()].concat())
+}
+
+#[test]
+fn test_keyframe_offsets_from_sample_tables() {
+    let index = parse_moov(&moov()).unwrap();
+    assert_eq!(index.duration_secs, Some(15.0));
+    assert_eq!(index.tracks.len(), 2);
+
+    let video = index.primary_track().unwrap();
+    assert_eq!(video.track_id, 1);
+    assert_eq!(video.sample_count, 30);
+    let keyframes: Vec<(f64, u64)> = video
+        .keyframes
+        .iter()
+        .map(|k| (k.time_secs, k.offset))
+        .collect();
+    assert_eq!(
+        keyframes,
+        vec![(0.0, 100_000), (5.0, 120_000), (10.0, 140_000)]
+    );
+
+    // Every audio frame is a sync sample; the index keeps one per second.
+    let audio = &index.tracks[1];
+    let offsets: Vec<u64> = audio.keyframes.iter().map(|k| k.offset).collect();
+    assert_eq!(offsets, vec![200_000, 223_500, 247_000]);
+}
+
+#[test]
+fn test_truncated_moov_is_an_error() {
+    let moov = moov();
+    assert!(parse_moov(&moov[..moov.len() - 10]).is_err());
+}
+
+#[test]
+fn test_sample_count_beyond_chunk_tables_is_rejected() {
+    // Fixed-size samples claiming far more than two 3-sample chunks hold.
+    let bogus = trak(
+        3,
+        b"vide",
+        90_000,
+        [
+            full_box(b"stts", &[1, u32::MAX, 3000]),
+            full_box(b"stsc", &[1, 1, 3, 1]),
+            full_box(b"stsz", &[100, u32::MAX]),
+            full_box(b"stco", &[2, 1000, 2000]),
+        ]
+        .concat(),
+    );
+    let mvhd = full_box(b"mvhd", &[0, 0, 1000, 15_000]);
+    let moov = mp4_box(b"moov", &[mvhd, bogus, video_trak()].concat());
+    let index = parse_moov(&moov).unwrap();
+    let ids: Vec<u32> = index.tracks.iter().map(|t| t.track_id).collect();
+    assert_eq!(ids, vec![1]);
+}
+
+#[test]
+fn test_sample_offsets_stop_on_overflow() {
+    let mut co64 = vec![0u8, 0, 0, 0, 0, 0, 0, 1];
+    co64.extend_from_slice(&(u64::MAX - 10).to_be_bytes());
+    let stbl = [
+        full_box(b"stts", &[1, 4, 1024]),
+        full_box(b"stsc", &[1, 1, 4, 1]),
+        full_box(b"stsz", &[u32::MAX, 4]),
+        mp4_box(b"co64", &co64),
+    ]
+    .concat();
+    let mvhd = full_box(b"mvhd", &[0, 0, 1000, 15_000]);
+    let moov = mp4_box(b"moov", &[mvhd, trak(4, b"soun", 48_000, stbl)].concat());
+    let index = parse_moov(&moov).unwrap();
+    assert_eq!(index.tracks[0].keyframes.len(), 1);
+}
+
+#[tokio::test]
+async fn test_session_seeks_from_indexed_keyframe() {
+    let mut file = mp4_box(b"ftyp", b"isom\0\0\0\0");
+    file.extend(mp4_box(b"mdat", &vec![0u8; 300_000 - file.len() - 8]));
+    file.extend(moov());
+
+    let dir = tempfile::tempdir().unwrap();
+    let session = ProxySession::new(
+        "indexed".to_string(),
+        Arc::new(MemorySource(file.into())),
+        Arc::new(CacheManager::new(dir.path(), 0)),
+        &EngineConfig {
+            chunk_size: 64 * 1024,
+            max_concurrency: 4,
+            ..EngineConfig::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let mut keyframe = None;
+    for _ in 0..50 {
+        keyframe = session.prepare_seek(7.0);
+        if keyframe.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(keyframe, Some(5.0));
+}
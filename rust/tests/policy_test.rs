@@ -0,0 +1,55 @@
+// Data-saver policy: no speculative tail warmup, a short prefetch horizon
+// and a per-period byte counter.
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use rust_lib_ma_palyer::config::{EngineConfig, DATA_SAVER_PREFETCH_CHUNKS};
+use rust_lib_ma_palyer::engine::cache_manager::CacheManager;
+use rust_lib_ma_palyer::engine::policy::{current_period, set_policy, NetworkPolicy};
+use rust_lib_ma_palyer::engine::session::ProxySession;
+
+use common::{content, OpaqueSource};
+
+const MB: u64 = 1024 * 1024;
+const CONTENT_LENGTH: u64 = 64 * MB;
+
+#[tokio::test]
+async fn test_data_saver_limits_speculation() {
+    set_policy(NetworkPolicy::DataSaver);
+
+    let dir = tempfile::tempdir().unwrap();
+    let source = Arc::new(OpaqueSource::new(CONTENT_LENGTH));
+    let chunk_size = 2 * MB;
+    let session = ProxySession::new(
+        "saver".to_string(),
+        source.clone(),
+        Arc::new(CacheManager::new(dir.path(), 0)),
+        &EngineConfig {
+            chunk_size,
+            max_concurrency: 4,
+            ..EngineConfig::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let data = session.serve_range(0, 1024).await.unwrap();
+    assert_eq!(data, content(0, 1023));
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let horizon = (1 + DATA_SAVER_PREFETCH_CHUNKS) * chunk_size;
+    let starts = source.starts.lock().clone();
+    assert!(
+        starts.iter().all(|&s| s < horizon),
+        "fetched beyond the data-saver horizon: {:?}",
+        starts
+    );
+    assert!(current_period().downloaded_bytes >= chunk_size);
+
+    let ended = set_policy(NetworkPolicy::Normal);
+    assert_eq!(ended.policy, NetworkPolicy::DataSaver);
+    assert_eq!(current_period().downloaded_bytes, 0);
+}
@@ -0,0 +1,69 @@
+// Player position hints: reported media time steers prefetch and the
+// buffered-ahead stat instead of the last request offset.
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use rust_lib_ma_palyer::config::EngineConfig;
+use rust_lib_ma_palyer::engine::cache_manager::CacheManager;
+use rust_lib_ma_palyer::engine::position::{PlaybackHint, TimeIndex};
+use rust_lib_ma_palyer::engine::session::ProxySession;
+
+use common::OpaqueSource;
+
+const MB: u64 = 1024 * 1024;
+const CONTENT_LENGTH: u64 = 64 * MB;
+
+#[tokio::test]
+async fn test_position_hints_drive_prefetch_and_buffered_stat() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = Arc::new(OpaqueSource::new(CONTENT_LENGTH));
+    let chunk_size = 2 * MB;
+    let session = ProxySession::new(
+        "hinted".to_string(),
+        source.clone(),
+        Arc::new(CacheManager::new(dir.path(), 0)),
+        &EngineConfig {
+            chunk_size,
+            max_concurrency: 4,
+            ..EngineConfig::default()
+        },
+    )
+    .await
+    .unwrap();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    source.starts.lock().clear();
+
+    // 640 s of media at 0.1 MB/s: 100 s is at 10 MB, 200 s at 20 MB and the
+    // 120 s buffer horizon past 200 s at 32 MB.
+    session.set_time_index(TimeIndex::new(vec![(0.0, 0), (640.0, CONTENT_LENGTH)]));
+    session.report_playback(PlaybackHint {
+        position_secs: 100.0,
+        buffer_end_secs: 200.0,
+        rate: 1.0,
+    });
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let starts = source.starts.lock().clone();
+    assert!(
+        starts.contains(&(20 * MB)),
+        "no prefetch at the buffer end: {:?}",
+        starts
+    );
+    assert!(
+        starts.iter().all(|&s| (20 * MB..32 * MB).contains(&s)),
+        "prefetch outside the hinted window: {:?}",
+        starts
+    );
+
+    // Buffered bytes count from the reported position, not a request offset.
+    assert_eq!(session.snapshot().buffered_bytes_ahead, 0);
+    session.report_playback(PlaybackHint {
+        position_secs: 210.0,
+        buffer_end_secs: 220.0,
+        rate: 1.0,
+    });
+    assert!(session.snapshot().buffered_bytes_ahead >= 10 * MB);
+}
@@ -0,0 +1,247 @@
+// Progressive delivery: bytes of a chunk reach the player before the chunk
+// finishes downloading.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+use rust_lib_ma_palyer::config::EngineConfig;
+use rust_lib_ma_palyer::engine::cache_manager::CacheManager;
+use rust_lib_ma_palyer::engine::session::ProxySession;
+use rust_lib_ma_palyer::error::{ProxyError, ProxyErrorKind};
+use rust_lib_ma_palyer::source::traits::{ByteStream, MediaSource, SourceInfo};
+
+const MB: u64 = 1024 * 1024;
+const CONTENT_LENGTH: u64 = 16 * MB;
+const FIRST_PIECE: u64 = 128 * 1024;
+
+fn byte_at(i: u64) -> u8 {
+    (i % 251) as u8
+}
+
+fn content(start: u64, end_inclusive: u64) -> Bytes {
+    (start..=end_inclusive).map(byte_at).collect()
+}
+
+/// Streams the first 128 KB of every range at once, then stalls for a
+/// long time before sending the rest.
+struct StallingSource;
+
+#[async_trait]
+impl MediaSource for StallingSource {
+    async fn probe(&self) -> Result<SourceInfo> {
+        Ok(SourceInfo {
+            content_length: CONTENT_LENGTH,
+            content_type: "video/mp4".to_string(),
+            supports_range: true,
+            etag: None,
+            last_modified: None,
+        })
+    }
+
+    async fn fetch_range(&self, start: u64, end: u64) -> Result<Bytes> {
+        Ok(content(start, end))
+    }
+
+    async fn fetch_range_stream(&self, start: u64, end: u64) -> Result<ByteStream> {
+        let (tx, rx) = mpsc::channel(2);
+        tokio::spawn(async move {
+            let split = (start + FIRST_PIECE).min(end + 1);
+            if tx.send(Ok(content(start, split - 1))).await.is_err() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            if split <= end {
+                let _ = tx.send(Ok(content(split, end))).await;
+            }
+        });
+        Ok(Box::pin(ReceiverStream::new(rx)))
+    }
+}
+
+#[tokio::test]
+async fn test_first_bytes_arrive_before_chunk_completes() {
+    let dir = tempfile::tempdir().unwrap();
+    let session = Arc::new(
+        ProxySession::new(
+            "progressive".to_string(),
+            Arc::new(StallingSource),
+            Arc::new(CacheManager::new(dir.path(), 0)),
+            &EngineConfig {
+                chunk_size: 2 * MB,
+                max_concurrency: 4,
+                ..EngineConfig::default()
+            },
+        )
+        .await
+        .unwrap(),
+    );
+
+    // Cold read in the middle of chunk 2.
+    let start = 4 * MB + 100;
+    let mut rx = session.serve_range_stream(start, 6 * MB).unwrap();
+
+    let first = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .expect("first bytes should not wait for the whole chunk")
+        .unwrap()
+        .unwrap();
+    assert_eq!(first.len() as u64, FIRST_PIECE - 100);
+    assert_eq!(first, content(start, 4 * MB + FIRST_PIECE - 1));
+
+    session.shutdown();
+}
+
+/// Cuts the first response for every range in half and records the ranges
+/// it was asked for.
+struct TruncatingSource {
+    requests: parking_lot::Mutex<Vec<(u64, u64)>>,
+}
+
+#[async_trait]
+impl MediaSource for TruncatingSource {
+    async fn probe(&self) -> Result<SourceInfo> {
+        StallingSource.probe().await
+    }
+
+    async fn fetch_range(&self, start: u64, end: u64) -> Result<Bytes> {
+        Ok(content(start, end))
+    }
+
+    async fn fetch_range_stream(&self, start: u64, end: u64) -> Result<ByteStream> {
+        let first = {
+            let mut requests = self.requests.lock();
+            let first = !requests.iter().any(|&(_, e)| e == end);
+            requests.push((start, end));
+            first
+        };
+        let body = if first {
+            content(start, start + (end - start) / 2)
+        } else {
+            content(start, end)
+        };
+        Ok(Box::pin(tokio_stream::once(Ok(body))))
+    }
+}
+
+#[tokio::test]
+async fn test_short_response_resumes_from_missing_suffix() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = Arc::new(TruncatingSource {
+        requests: parking_lot::Mutex::new(Vec::new()),
+    });
+    let session = ProxySession::new(
+        "truncated".to_string(),
+        source.clone(),
+        Arc::new(CacheManager::new(dir.path(), 0)),
+        &EngineConfig {
+            chunk_size: 2 * MB,
+            max_concurrency: 2,
+            ..EngineConfig::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let bytes = tokio::time::timeout(Duration::from_secs(10), session.serve_range(4 * MB, 6 * MB))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(bytes, content(4 * MB, 6 * MB - 1));
+
+    let requests: Vec<_> = source
+        .requests
+        .lock()
+        .iter()
+        .copied()
+        .filter(|&(_, end)| end == 6 * MB - 1)
+        .collect();
+    assert_eq!(requests.len(), 2, "{:?}", requests);
+    assert_eq!(requests[0].0, 4 * MB);
+    assert_eq!(requests[1].0, 5 * MB);
+
+    session.shutdown();
+}
+
+/// Cuts the first response for the range ending at `end` in half, rejects
+/// the resume as a changed source, then serves it whole.
+struct MismatchingSource {
+    end: u64,
+    requests: parking_lot::Mutex<Vec<u64>>,
+}
+
+#[async_trait]
+impl MediaSource for MismatchingSource {
+    async fn probe(&self) -> Result<SourceInfo> {
+        StallingSource.probe().await
+    }
+
+    async fn fetch_range(&self, start: u64, end: u64) -> Result<Bytes> {
+        Ok(content(start, end))
+    }
+
+    async fn fetch_range_stream(&self, start: u64, end: u64) -> Result<ByteStream> {
+        let seen = if end == self.end {
+            let mut requests = self.requests.lock();
+            requests.push(start);
+            requests.len()
+        } else {
+            0
+        };
+        let body = match seen {
+            1 => content(start, start + (end - start) / 2),
+            2 => {
+                return Err(ProxyError::new(
+                    ProxyErrorKind::SourceChanged,
+                    "content-range does not match",
+                )
+                .into())
+            }
+            _ => content(start, end),
+        };
+        Ok(Box::pin(tokio_stream::once(Ok(body))))
+    }
+}
+
+#[tokio::test]
+async fn test_mismatched_resume_restarts_chunk() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = Arc::new(MismatchingSource {
+        end: 6 * MB - 1,
+        requests: parking_lot::Mutex::new(Vec::new()),
+    });
+    let session = ProxySession::new(
+        "mismatched".to_string(),
+        source.clone(),
+        Arc::new(CacheManager::new(dir.path(), 0)),
+        &EngineConfig {
+            chunk_size: 2 * MB,
+            max_concurrency: 2,
+            ..EngineConfig::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let first = tokio::time::timeout(Duration::from_secs(10), session.serve_range(4 * MB, 6 * MB))
+        .await
+        .unwrap();
+    assert!(first.is_err());
+
+    let bytes = tokio::time::timeout(Duration::from_secs(10), session.serve_range(4 * MB, 6 * MB))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(bytes, content(4 * MB, 6 * MB - 1));
+
+    // The half kept from the cut-off response was dropped with the mismatch.
+    let requests = source.requests.lock().clone();
+    assert_eq!(requests, vec![4 * MB, 5 * MB, 4 * MB]);
+
+    session.shutdown();
+}
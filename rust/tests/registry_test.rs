@@ -0,0 +1,489 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::Query;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+use rust_lib_ma_palyer::error::{ProxyError, ProxyErrorKind};
+use rust_lib_ma_palyer::source::registry::{MagnetFactory, SourceDescriptor, SourceRegistry};
+
+fn file_uri(path: &Path) -> String {
+    reqwest::Url::from_file_path(path).unwrap().to_string()
+}
+
+/// Build a single-entry zip archive with the entry stored (method 0).
+fn stored_zip(name: &str, data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    // Local file header.
+    out.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+    out.extend_from_slice(&[20, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    out.extend_from_slice(&0u32.to_le_bytes()); // crc (unchecked)
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out.extend_from_slice(name.as_bytes());
+    out.extend_from_slice(data);
+
+    // Central directory.
+    let cd_offset = out.len() as u32;
+    out.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+    out.extend_from_slice(&[20, 0, 20, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    out.extend_from_slice(&0u32.to_le_bytes());
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    out.extend_from_slice(&[0u8; 12]);
+    out.extend_from_slice(&0u32.to_le_bytes()); // local header offset
+    out.extend_from_slice(name.as_bytes());
+    let cd_size = out.len() as u32 - cd_offset;
+
+    // End of central directory.
+    out.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    out.extend_from_slice(&[0, 0, 0, 0, 1, 0, 1, 0]);
+    out.extend_from_slice(&cd_size.to_le_bytes());
+    out.extend_from_slice(&cd_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out
+}
+
+#[tokio::test]
+async fn test_file_source_resolves() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("movie.bin");
+    let content: Vec<u8> = (0..4096u32).map(|i| (i % 251) as u8).collect();
+    std::fs::write(&path, &content).unwrap();
+
+    let source = SourceRegistry::with_defaults()
+        .resolve(&SourceDescriptor {
+            uri: file_uri(&path),
+            headers: HashMap::new(),
+        })
+        .await
+        .unwrap();
+
+    let info = source.probe().await.unwrap();
+    assert_eq!(info.content_length, 4096);
+    let data = source.fetch_range(100, 199).await.unwrap();
+    assert_eq!(&data[..], &content[100..200]);
+}
+
+#[tokio::test]
+async fn test_file_source_errors_are_typed() {
+    let dir = tempfile::tempdir().unwrap();
+    let registry = SourceRegistry::with_defaults();
+    let resolve = |path: &Path| {
+        let descriptor = SourceDescriptor {
+            uri: file_uri(path),
+            headers: HashMap::new(),
+        };
+        let registry = &registry;
+        async move { registry.resolve(&descriptor).await }
+    };
+
+    // A missing file is not a cache failure.
+    let missing = resolve(&dir.path().join("missing.bin")).await.unwrap();
+    let err = missing.probe().await.err().unwrap();
+    assert_eq!(ProxyError::classify(&err).kind, ProxyErrorKind::NotFound);
+    let err = missing.fetch_range(0, 9).await.err().unwrap();
+    assert_eq!(ProxyError::classify(&err).kind, ProxyErrorKind::NotFound);
+
+    let folder = resolve(dir.path()).await.unwrap();
+    let err = folder.probe().await.err().unwrap();
+    assert_eq!(ProxyError::classify(&err).kind, ProxyErrorKind::NotFound);
+}
+
+#[tokio::test]
+async fn test_iso_decorator_over_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("disc.iso");
+    let content: Vec<u8> = (0..8192u32).map(|i| (i % 253) as u8).collect();
+    std::fs::write(&path, &content).unwrap();
+
+    let uri = format!(
+        "iso+{}#offset=1000&length=500&type=video/mp2t",
+        file_uri(&path)
+    );
+    let source = SourceRegistry::with_defaults()
+        .resolve(&SourceDescriptor {
+            uri,
+            headers: HashMap::new(),
+        })
+        .await
+        .unwrap();
+
+    let info = source.probe().await.unwrap();
+    assert_eq!(info.content_length, 500);
+    assert_eq!(info.content_type, "video/mp2t");
+    let data = source.fetch_range(0, 9).await.unwrap();
+    assert_eq!(&data[..], &content[1000..1010]);
+}
+
+#[tokio::test]
+async fn test_zip_decorator_over_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pack.zip");
+    let entry: Vec<u8> = (0..3000u32).map(|i| (i % 241) as u8).collect();
+    std::fs::write(&path, stored_zip("video/ep01.mkv", &entry)).unwrap();
+
+    let uri = format!("zip+{}#entry=video/ep01.mkv", file_uri(&path));
+    let source = SourceRegistry::with_defaults()
+        .resolve(&SourceDescriptor {
+            uri,
+            headers: HashMap::new(),
+        })
+        .await
+        .unwrap();
+
+    let info = source.probe().await.unwrap();
+    assert_eq!(info.content_length, 3000);
+    let data = source.fetch_range(2990, 2999).await.unwrap();
+    assert_eq!(&data[..], &entry[2990..3000]);
+}
+
+#[tokio::test]
+async fn test_stacked_decorators_take_their_own_params() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pack.zip");
+    let entry: Vec<u8> = (0..3000u32).map(|i| (i % 241) as u8).collect();
+    std::fs::write(&path, stored_zip("disc.iso", &entry)).unwrap();
+    let registry = SourceRegistry::with_defaults();
+
+    let uri = format!(
+        "iso+zip+{}#zip.entry=disc.iso&iso.offset=1000&iso.length=500",
+        file_uri(&path)
+    );
+    let source = registry
+        .resolve(&SourceDescriptor {
+            uri,
+            headers: HashMap::new(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(source.probe().await.unwrap().content_length, 500);
+    let data = source.fetch_range(0, 9).await.unwrap();
+    assert_eq!(&data[..], &entry[1000..1010]);
+
+    // Plain keys would be read by both layers.
+    let ambiguous = format!(
+        "iso+zip+{}#entry=disc.iso&offset=1000&length=500",
+        file_uri(&path)
+    );
+    let result = registry
+        .resolve(&SourceDescriptor {
+            uri: ambiguous,
+            headers: HashMap::new(),
+        })
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_zip_entry_name_is_percent_decoded() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pack.zip");
+    let entry: Vec<u8> = (0..1000u32).map(|i| (i % 239) as u8).collect();
+    std::fs::write(&path, stored_zip("第1集 & more.mkv", &entry)).unwrap();
+
+    let uri = format!(
+        "zip+{}#entry=%E7%AC%AC1%E9%9B%86%20%26%20more.mkv",
+        file_uri(&path)
+    );
+    let source = SourceRegistry::with_defaults()
+        .resolve(&SourceDescriptor {
+            uri,
+            headers: HashMap::new(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(source.probe().await.unwrap().content_length, 1000);
+}
+
+#[tokio::test]
+async fn test_iso_decorator_requires_range() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("disc.iso");
+    std::fs::write(&path, vec![0u8; 4096]).unwrap();
+
+    let result = SourceRegistry::with_defaults()
+        .resolve(&SourceDescriptor {
+            uri: format!("iso+{}", file_uri(&path)),
+            headers: HashMap::new(),
+        })
+        .await;
+    assert!(result.is_err());
+}
+
+/// Minimal FTP server: login, SIZE, PASV, REST and RETR over `content`.
+/// Also returns the number of control connections accepted so far.
+async fn start_ftp_server(content: Vec<u8>) -> (SocketAddr, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let content = Arc::new(content);
+    let connections = Arc::new(AtomicUsize::new(0));
+    let accepted = connections.clone();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            accepted.fetch_add(1, Ordering::SeqCst);
+            let content = content.clone();
+            tokio::spawn(async move {
+                let (read, mut write) = stream.into_split();
+                let mut lines = BufReader::new(read).lines();
+                let mut data: Option<TcpListener> = None;
+                let mut rest = 0usize;
+                write.write_all(b"220 ready\r\n").await.unwrap();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    let (cmd, arg) = line.split_once(' ').unwrap_or((line.as_str(), ""));
+                    let reply = match cmd {
+                        "USER" => "331 password please".to_string(),
+                        "PASS" if arg == "secret" => "230 welcome".to_string(),
+                        "PASS" => "530 login incorrect".to_string(),
+                        "TYPE" => "200 ok".to_string(),
+                        "SIZE" if arg == "/media/movie.mkv" => format!("213 {}", content.len()),
+                        "SIZE" => "550 no such file".to_string(),
+                        "MDTM" => "213 20260101120000".to_string(),
+                        "REST" => {
+                            rest = arg.parse().unwrap();
+                            "350 restarting".to_string()
+                        }
+                        "PASV" => {
+                            let l = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                            let port = l.local_addr().unwrap().port();
+                            data = Some(l);
+                            format!(
+                                "227 Entering Passive Mode (127,0,0,1,{},{}).",
+                                port >> 8,
+                                port & 0xFF
+                            )
+                        }
+                        "RETR" => {
+                            write.write_all(b"150 sending\r\n").await.unwrap();
+                            let (mut conn, _) = data.take().unwrap().accept().await.unwrap();
+                            let _ = conn.write_all(&content[rest..]).await;
+                            drop(conn);
+                            "226 done".to_string()
+                        }
+                        "QUIT" => {
+                            let _ = write.write_all(b"221 bye\r\n").await;
+                            break;
+                        }
+                        _ => "502 not implemented".to_string(),
+                    };
+                    if write
+                        .write_all(format!("{}\r\n", reply).as_bytes())
+                        .await
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+    (addr, connections)
+}
+
+#[tokio::test]
+async fn test_ftp_source_resolves() {
+    let content: Vec<u8> = (0..50_000u32).map(|i| (i % 247) as u8).collect();
+    let (addr, connections) = start_ftp_server(content.clone()).await;
+
+    let source = SourceRegistry::with_defaults()
+        .resolve(&SourceDescriptor {
+            uri: format!("ftp://me:secret@{}/media/movie.mkv", addr),
+            headers: HashMap::new(),
+        })
+        .await
+        .unwrap();
+
+    let info = source.probe().await.unwrap();
+    assert_eq!(info.content_length, 50_000);
+    assert_eq!(info.last_modified.as_deref(), Some("20260101120000"));
+    let data = source.fetch_range(40_000, 40_999).await.unwrap();
+    assert_eq!(&data[..], &content[40_000..41_000]);
+
+    // Later fetches reuse the logged-in connection once the aborted
+    // transfer is acknowledged.
+    for at in [10_000u64, 20_000] {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let data = source.fetch_range(at, at + 99).await.unwrap();
+        assert_eq!(&data[..], &content[at as usize..at as usize + 100]);
+    }
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+
+    // A transfer ending before the range does is an error, not a short read.
+    let err = source.fetch_range(49_990, 50_009).await.err().unwrap();
+    assert_eq!(ProxyError::classify(&err).kind, ProxyErrorKind::Network);
+}
+
+#[tokio::test]
+async fn test_ftp_errors_are_typed() {
+    let (addr, _) = start_ftp_server(vec![0u8; 10]).await;
+    let registry = SourceRegistry::with_defaults();
+    let kind_of = |uri: String| {
+        let registry = &registry;
+        async move {
+            let source = registry
+                .resolve(&SourceDescriptor {
+                    uri,
+                    headers: HashMap::new(),
+                })
+                .await
+                .unwrap();
+            let err = source.probe().await.err().unwrap();
+            err.downcast_ref::<ProxyError>().map(|e| e.kind)
+        }
+    };
+
+    assert_eq!(
+        kind_of(format!("ftp://me:wrong@{}/media/movie.mkv", addr)).await,
+        Some(ProxyErrorKind::AuthExpired)
+    );
+    assert_eq!(
+        kind_of(format!("ftp://me:secret@{}/missing.mkv", addr)).await,
+        Some(ProxyErrorKind::NotFound)
+    );
+}
+
+/// Serves `/api/fs/get` (AList) and `/stream` (magnet gateway) in front of
+/// a ranged `/raw` file.
+async fn start_gateway(content: Vec<u8>) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let content = Arc::new(content);
+    let raw = {
+        let content = content.clone();
+        move |headers: HeaderMap| {
+            let content = content.clone();
+            async move { ranged(&content, &headers) }
+        }
+    };
+    let stream = {
+        let content = content.clone();
+        move |Query(query): Query<HashMap<String, String>>, headers: HeaderMap| {
+            let content = content.clone();
+            async move {
+                let expected = query.get("link").map(String::as_str)
+                    == Some("magnet:?xt=urn:btih:abcdef&dn=Movie")
+                    && query.get("index").map(String::as_str) == Some("2")
+                    && query.contains_key("play");
+                if !expected {
+                    return StatusCode::BAD_REQUEST.into_response();
+                }
+                ranged(&content, &headers)
+            }
+        }
+    };
+    let fs_get = move |headers: HeaderMap, Json(body): Json<serde_json::Value>| async move {
+        if headers.get("Authorization").and_then(|v| v.to_str().ok()) != Some("token") {
+            return Json(serde_json::json!({ "code": 401, "message": "token is invalid" }));
+        }
+        if body["path"] != "/movies/第1集.mkv" {
+            return Json(serde_json::json!({ "code": 404, "message": "object not found" }));
+        }
+        Json(serde_json::json!({
+            "code": 200,
+            "message": "success",
+            "data": { "raw_url": format!("http://{}/raw", addr), "sign": "" },
+        }))
+    };
+    let app = Router::new()
+        .route("/raw", get(raw))
+        .route("/stream", get(stream))
+        .route("/api/fs/get", post(fs_get));
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    addr
+}
+
+fn ranged(content: &[u8], headers: &HeaderMap) -> axum::response::Response {
+    let total = content.len();
+    let (start, end) = headers
+        .get("Range")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("bytes="))
+        .and_then(|v| v.split_once('-'))
+        .map(|(s, e)| {
+            let start: usize = s.parse().unwrap_or(0);
+            let end: usize = e.parse().unwrap_or(total - 1);
+            (start, end.min(total - 1))
+        })
+        .unwrap_or((0, total - 1));
+    (
+        StatusCode::PARTIAL_CONTENT,
+        [
+            (header::CONTENT_TYPE, "video/x-matroska".to_string()),
+            (
+                header::CONTENT_RANGE,
+                format!("bytes {}-{}/{}", start, end, total),
+            ),
+        ],
+        content[start..=end].to_vec(),
+    )
+        .into_response()
+}
+
+#[tokio::test]
+async fn test_alist_resolves_raw_url() {
+    let content: Vec<u8> = (0..20_000u32).map(|i| (i % 233) as u8).collect();
+    let addr = start_gateway(content.clone()).await;
+    let registry = SourceRegistry::with_defaults();
+
+    let headers = HashMap::from([("Authorization".to_string(), "token".to_string())]);
+    let source = registry
+        .resolve(&SourceDescriptor {
+            uri: format!("alist://{}/movies/%E7%AC%AC1%E9%9B%86.mkv", addr),
+            headers: headers.clone(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(source.probe().await.unwrap().content_length, 20_000);
+    let data = source.fetch_range(100, 199).await.unwrap();
+    assert_eq!(&data[..], &content[100..200]);
+
+    let err = registry
+        .resolve(&SourceDescriptor {
+            uri: format!("alist://{}/movies/missing.mkv", addr),
+            headers,
+        })
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.downcast_ref::<ProxyError>().map(|e| e.kind),
+        Some(ProxyErrorKind::NotFound)
+    );
+}
+
+#[tokio::test]
+async fn test_magnet_streams_through_gateway() {
+    let content: Vec<u8> = (0..20_000u32).map(|i| (i % 229) as u8).collect();
+    let addr = start_gateway(content.clone()).await;
+    let registry = SourceRegistry::with_defaults();
+    let descriptor = SourceDescriptor {
+        uri: "magnet:?xt=urn:btih:abcdef&dn=Movie#index=2".to_string(),
+        headers: HashMap::new(),
+    };
+    // No gateway is assumed until one is configured.
+    assert!(registry.resolve(&descriptor).await.is_err());
+    registry.register_factory(
+        "magnet",
+        Arc::new(MagnetFactory::new(format!("http://{}", addr))),
+    );
+
+    let source = registry.resolve(&descriptor).await.unwrap();
+    assert_eq!(source.probe().await.unwrap().content_length, 20_000);
+    let data = source.fetch_range(5000, 5099).await.unwrap();
+    assert_eq!(&data[..], &content[5000..5100]);
+}
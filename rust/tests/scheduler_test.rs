@@ -0,0 +1,167 @@
+// Download scheduling against a simulated source: deadline order, urgent
+// bypass and reprioritisation on seek.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use bytes::Bytes;
+use parking_lot::Mutex;
+use tokio::sync::Semaphore;
+
+use rust_lib_ma_palyer::engine::cache::{CacheValidator, DiskCache};
+use rust_lib_ma_palyer::engine::downloader::Downloader;
+use rust_lib_ma_palyer::engine::stats::StatsCollector;
+use rust_lib_ma_palyer::engine::store::CacheBackend;
+use rust_lib_ma_palyer::error::ProxyErrorKind;
+use rust_lib_ma_palyer::source::traits::{ByteStream, MediaSource, SourceInfo};
+
+const MB: u64 = 1024 * 1024;
+const CONTENT_LENGTH: u64 = 40 * MB;
+
+/// Serves every range immediately except chunk 0, which waits for the gate.
+/// Records the chunk index of each request in arrival order.
+struct GatedSource {
+    requests: Mutex<Vec<u64>>,
+    gate: Semaphore,
+}
+
+#[async_trait]
+impl MediaSource for GatedSource {
+    async fn probe(&self) -> Result<SourceInfo> {
+        Ok(SourceInfo {
+            content_length: CONTENT_LENGTH,
+            content_type: "video/mp4".to_string(),
+            supports_range: true,
+            etag: None,
+            last_modified: None,
+        })
+    }
+
+    async fn fetch_range(&self, start: u64, end: u64) -> Result<Bytes> {
+        self.requests.lock().push(start / MB);
+        if start == 0 {
+            let _permit = self.gate.acquire().await?;
+        }
+        Ok(vec![(start / MB) as u8; (end - start + 1) as usize].into())
+    }
+
+    async fn fetch_range_stream(&self, start: u64, end: u64) -> Result<ByteStream> {
+        let body = self.fetch_range(start, end).await?;
+        Ok(Box::pin(tokio_stream::once(Ok(body))))
+    }
+}
+
+async fn wait_until(mut done: impl FnMut() -> bool) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !done() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("condition not reached in time");
+}
+
+#[tokio::test]
+async fn test_deadline_order_urgent_bypass_and_seek_reorder() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = Arc::new(
+        DiskCache::open_with_backend(
+            dir.path(),
+            "sched",
+            CONTENT_LENGTH,
+            MB,
+            &CacheValidator::default(),
+            CacheBackend::Memory,
+        )
+        .unwrap(),
+    );
+    let source = Arc::new(GatedSource {
+        requests: Mutex::new(Vec::new()),
+        gate: Semaphore::new(0),
+    });
+    // One background worker plus the reserved urgent ones.
+    let downloader = Downloader::new(
+        source.clone(),
+        cache.clone(),
+        3,
+        Arc::new(StatsCollector::new()),
+    );
+
+    // Chunk 0 occupies the only background worker.
+    downloader.start_prefetch(0);
+    wait_until(|| source.requests.lock().contains(&0)).await;
+
+    for i in [10, 5, 30, 8] {
+        downloader.start_prefetch(i);
+    }
+
+    // Urgent work does not wait behind background transfers.
+    downloader.start_urgent_prefetch(20);
+    let ready = tokio::time::timeout(Duration::from_secs(5), downloader.wait_for_chunk(20))
+        .await
+        .unwrap();
+    assert!(ready.is_ok());
+    assert!(!cache.has_chunk(5));
+
+    // Seek past chunks 5 and 8: they drop to the back instead of being
+    // cancelled.
+    downloader.set_playback(9 * MB, MB);
+    source.gate.add_permits(1);
+
+    for i in [0, 5, 8, 10, 30] {
+        let ready = tokio::time::timeout(Duration::from_secs(5), downloader.wait_for_chunk(i))
+            .await
+            .unwrap();
+        assert!(ready.is_ok(), "chunk {} not downloaded", i);
+    }
+    assert_eq!(*source.requests.lock(), vec![0, 20, 10, 30, 5, 8]);
+    assert_eq!(cache.read_chunk(30).unwrap()[0], 30);
+
+    downloader.shutdown();
+}
+
+#[tokio::test]
+async fn test_shutdown_releases_queued_waiters() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = Arc::new(
+        DiskCache::open_with_backend(
+            dir.path(),
+            "sched-shutdown",
+            CONTENT_LENGTH,
+            MB,
+            &CacheValidator::default(),
+            CacheBackend::Memory,
+        )
+        .unwrap(),
+    );
+    let source = Arc::new(GatedSource {
+        requests: Mutex::new(Vec::new()),
+        gate: Semaphore::new(0),
+    });
+    let downloader = Arc::new(Downloader::new(
+        source.clone(),
+        cache,
+        3,
+        Arc::new(StatsCollector::new()),
+    ));
+
+    downloader.start_prefetch(0);
+    wait_until(|| source.requests.lock().contains(&0)).await;
+    downloader.start_prefetch(12);
+
+    let waiter = {
+        let downloader = downloader.clone();
+        tokio::spawn(async move { downloader.wait_for_chunk(12).await })
+    };
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    downloader.shutdown();
+
+    let ready = tokio::time::timeout(Duration::from_secs(5), waiter)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(ready.unwrap_err().kind, ProxyErrorKind::Cancelled);
+    assert!(!source.requests.lock().contains(&12));
+}
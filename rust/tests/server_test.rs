@@ -13,8 +13,12 @@ use axum::{
 use parking_lot::RwLock;
 use tokio::net::TcpListener;
 
+use rust_lib_ma_palyer::config::EngineConfig;
+use rust_lib_ma_palyer::engine::cache_manager::CacheManager;
 use rust_lib_ma_palyer::engine::session::ProxySession;
+use rust_lib_ma_palyer::error::ProxyErrorKind;
 use rust_lib_ma_palyer::server::handler::{ProxyServer, SessionMap};
+use rust_lib_ma_palyer::source::http_source::HttpSource;
 
 const CONTENT_SIZE: usize = 10 * 1024 * 1024; // 10 MB
 
@@ -95,11 +99,13 @@ async fn test_proxy_server() {
     let tmp_dir = tempfile::tempdir().unwrap();
     let session = ProxySession::new(
         "test-session".to_string(),
-        upstream_url,
-        HashMap::new(),
-        tmp_dir.path().to_str().unwrap(),
-        2 * 1024 * 1024, // 2 MB chunks
-        4,
+        Arc::new(HttpSource::new(upstream_url, HashMap::new())),
+        Arc::new(CacheManager::new(tmp_dir.path(), 0)),
+        &EngineConfig {
+            chunk_size: 2 * 1024 * 1024, // 2 MB chunks
+            max_concurrency: 4,
+            ..EngineConfig::default()
+        },
     )
     .await
     .unwrap();
@@ -146,3 +152,69 @@ async fn test_proxy_server() {
     // Cleanup.
     server.shutdown();
 }
+
+/// Upstream whose credentials only cover the first 4 MB.
+async fn expiring_upstream_handler(req: Request) -> axum::response::Response {
+    let end = req
+        .headers()
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.rsplit('-').next())
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(u64::MAX);
+    if end >= 4 * 1024 * 1024 {
+        return (StatusCode::FORBIDDEN, "signature expired").into_response();
+    }
+    fake_upstream_handler(req).await.into_response()
+}
+
+#[tokio::test]
+async fn test_proxy_reports_typed_upstream_error() {
+    let upstream_app = Router::new().route("/file", get(expiring_upstream_handler));
+    let upstream_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let upstream_port = upstream_listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        axum::serve(upstream_listener, upstream_app).await.ok();
+    });
+    let upstream_url = format!("http://127.0.0.1:{}/file", upstream_port);
+
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let session = Arc::new(
+        ProxySession::new(
+            "expiring".to_string(),
+            Arc::new(HttpSource::new(upstream_url, HashMap::new())),
+            Arc::new(CacheManager::new(tmp_dir.path(), 0)),
+            &EngineConfig {
+                chunk_size: 2 * 1024 * 1024,
+                max_concurrency: 4,
+                ..EngineConfig::default()
+            },
+        )
+        .await
+        .unwrap(),
+    );
+    let sessions: SessionMap = Arc::new(RwLock::new(HashMap::new()));
+    sessions
+        .write()
+        .insert("expiring".to_string(), session.clone());
+    let server = ProxyServer::start(sessions).await.unwrap();
+
+    let resp = reqwest::Client::new()
+        .get(server.url_for_session("expiring"))
+        .header("Range", "bytes=5242880-5243903")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 401);
+    assert_eq!(resp.headers()["x-proxy-error"], "auth_expired");
+    assert_eq!(
+        session.last_error().map(|e| e.kind),
+        Some(ProxyErrorKind::AuthExpired)
+    );
+
+    // Fresh credentials clear the reported error.
+    session.update_auth(String::new(), HashMap::new());
+    assert!(session.last_error().is_none());
+
+    server.shutdown();
+}